@@ -0,0 +1,17 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+#![no_main]
+
+use cargo_rdme::{
+    extract_doc_from_source_str, source_includes_readme, source_out_dir_doc_include,
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|source: &str| {
+    let _ = extract_doc_from_source_str(source);
+    let _ = source_includes_readme(source);
+    let _ = source_out_dir_doc_include(source);
+});
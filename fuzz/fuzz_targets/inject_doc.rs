@@ -0,0 +1,37 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+#![no_main]
+
+use cargo_rdme::{
+    fix_readme, freeze_readme, inject_doc_in_readme, readme_has_markers, region_content_hashes,
+    Doc, FixContext, InjectOptions, Readme,
+};
+use libfuzzer_sys::fuzz_target;
+
+// The input is the README and the documentation, separated by a nul byte.
+fuzz_target!(|input: &str| {
+    let (readme, doc) = input.split_once('\0').unwrap_or((input, ""));
+    let readme = Readme::from_str(readme);
+    let doc = Doc::from_str(doc);
+
+    let _ = readme_has_markers(&readme);
+    let _ = region_content_hashes(&readme);
+    let _ = freeze_readme(&readme, "fuzz", "1.0.0");
+    let _ = fix_readme(&readme, FixContext::new("fuzz", "1.0.0"));
+
+    let opts = InjectOptions::default().with_content_hash(true);
+
+    if let Ok(new_readme) = inject_doc_in_readme(&readme, &doc, &opts) {
+        // Injecting the same documentation again must not change the README.
+        let again = inject_doc_in_readme(&new_readme.readme, &doc, &opts)
+            .expect("failed to inject the documentation in a generated README");
+
+        assert_eq!(again.readme.as_string(), new_readme.readme.as_string());
+    }
+
+    let opts = InjectOptions::default().with_heading_base_level(2).with_heading("Documentation");
+    let _ = inject_doc_in_readme(&readme, &doc, &opts);
+});
@@ -0,0 +1,31 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+#![no_main]
+
+use cargo_rdme::transform::{
+    DetailsAction, DocTransform, DocTransformDetails, DocTransformEmoji,
+    DocTransformFormatTables, DocTransformHeadingNumbering, DocTransformMath,
+    DocTransformMermaid, DocTransformRustMarkdownTag, DocTransformRustRemoveComments,
+    EmojiConversion, MathAction, MermaidAction,
+};
+use cargo_rdme::Doc;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    let doc = Doc::from_str(input);
+
+    let _ = DocTransformRustRemoveComments::new().transform(&doc);
+    let _ = DocTransformRustMarkdownTag::new().transform(&doc);
+    let _ = DocTransformFormatTables::new().transform(&doc);
+    let _ = DocTransformHeadingNumbering::new().transform(&doc);
+    let _ = DocTransformDetails::new(DetailsAction::Unwrap).transform(&doc);
+    let _ = DocTransformDetails::new(DetailsAction::Drop).transform(&doc);
+    let _ = DocTransformEmoji::new(EmojiConversion::ShortcodeToUnicode).transform(&doc);
+    let _ = DocTransformEmoji::new(EmojiConversion::UnicodeToShortcode).transform(&doc);
+    let _ = DocTransformMath::new(MathAction::Code, |_| ()).transform(&doc);
+    let _ = DocTransformMath::new(MathAction::Image, |_| ()).transform(&doc);
+    let _ = DocTransformMermaid::new(MermaidAction::Image).transform(&doc);
+});
@@ -0,0 +1,55 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+#![cfg_attr(feature = "fatal-warnings", deny(warnings))]
+
+//! `mdbook-rdme` is an [mdBook](https://rust-lang.github.io/mdBook/) preprocessor that injects
+//! the crate’s documentation in the chapters of a book, between the same markers cargo rdme uses
+//! in the README, so that the book and the README have a single source of truth.
+//!
+//! Add this to the `book.toml` of the book:
+//!
+//! ```toml
+//! [preprocessor.rdme]
+//! # The manifest of the crate, relative to the book.  By default it is found from the book
+//! # directory.
+//! manifest-path = "../Cargo.toml"
+//! # If the manifest is a workspace, the package whose documentation is injected.
+//! workspace-project = "foo"
+//! # Base heading level of the injected documentation.
+//! heading-base-level = 1
+//! ```
+//!
+//! The configuration of cargo rdme, e.g. `.cargo-rdme.toml`, applies to the book as well.
+
+use std::path::PathBuf;
+use std::process::{Command, ExitCode};
+
+/// The `cargo-rdme` executable, which is installed next to this one.
+fn cargo_rdme() -> PathBuf {
+    let file_name = format!("cargo-rdme{}", std::env::consts::EXE_SUFFIX);
+
+    std::env::current_exe()
+        .ok()
+        .map(|exe| exe.with_file_name(&file_name))
+        .filter(|cargo_rdme| cargo_rdme.is_file())
+        .unwrap_or_else(|| PathBuf::from(file_name))
+}
+
+/// Runs `cargo rdme mdbook`, which does the work, so that the book gets the same configuration
+/// and transformations as the README.  The book is passed through standard input and output.
+fn main() -> ExitCode {
+    let status =
+        Command::new(cargo_rdme()).arg("mdbook").args(std::env::args_os().skip(1)).status();
+
+    match status {
+        Ok(status) if status.success() => ExitCode::SUCCESS,
+        Ok(_) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("mdbook-rdme: error: failed to run cargo-rdme: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
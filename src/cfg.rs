@@ -0,0 +1,213 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+//! Evaluation of `cfg` predicates, such as `all(feature = "std", not(feature = "nightly"))`, for
+//! the `cfg_attr` doc attributes and the `rdme:if` directives of the crate documentation.
+
+use std::collections::{BTreeMap, BTreeSet};
+use syn::punctuated::Punctuated;
+use syn::{Expr, ExprLit, Lit, Meta, Token};
+
+/// The features of the package to enable, with the same meaning as the cargo flags of the same
+/// name.
+#[derive(PartialEq, Eq, Clone, Default, Debug)]
+#[non_exhaustive]
+pub struct FeatureSelection {
+    pub features: Vec<String>,
+    pub all_features: bool,
+    pub no_default_features: bool,
+}
+
+impl FeatureSelection {
+    /// Sets `--features`, the features to enable in addition to the default ones.
+    #[must_use]
+    pub fn with_features(mut self, features: Vec<String>) -> FeatureSelection {
+        self.features = features;
+        self
+    }
+
+    /// Sets `--all-features`, which enables every feature of the package.
+    #[must_use]
+    pub fn with_all_features(mut self, all_features: bool) -> FeatureSelection {
+        self.all_features = all_features;
+        self
+    }
+
+    /// Sets `--no-default-features`, which disables the `default` feature.
+    #[must_use]
+    pub fn with_no_default_features(mut self, no_default_features: bool) -> FeatureSelection {
+        self.no_default_features = no_default_features;
+        self
+    }
+
+    /// The features enabled by this selection, given the features of the package (the `[features]`
+    /// table of its manifest).  This includes the features enabled by other features.
+    #[must_use]
+    pub fn enabled_features(
+        &self,
+        package_features: &BTreeMap<String, Vec<String>>,
+    ) -> BTreeSet<String> {
+        let mut pending: Vec<&str> = match self.all_features {
+            true => package_features.keys().map(String::as_str).collect(),
+            false => self.features.iter().map(String::as_str).collect(),
+        };
+
+        if !self.no_default_features && package_features.contains_key("default") {
+            pending.push("default");
+        }
+
+        let mut enabled = BTreeSet::new();
+
+        while let Some(feature) = pending.pop() {
+            if !enabled.insert(feature.to_owned()) {
+                continue;
+            }
+
+            for item in package_features.get(feature).into_iter().flatten() {
+                match item.split_once('/') {
+                    // `dep:foo` enables the optional dependency `foo`, not a feature.
+                    None if item.starts_with("dep:") => (),
+                    None => pending.push(item),
+                    // `foo/bar` enables the feature `bar` of the dependency `foo`, and `foo` itself
+                    // if it is optional, which is a feature unless `dep:foo` is used somewhere.
+                    // With `foo?/bar` the dependency is not enabled.
+                    Some((dependency, _)) if !dependency.ends_with('?') => {
+                        if package_features.contains_key(dependency) {
+                            pending.push(dependency);
+                        }
+                    }
+                    Some(_) => (),
+                }
+            }
+        }
+
+        enabled
+    }
+}
+
+/// The configuration `cfg` predicates are evaluated against.  The crate is not built, so only
+/// `doc` and the enabled features are set: any other option, e.g. `unix` or `test`, is false.
+#[derive(PartialEq, Eq, Clone, Default, Debug)]
+pub struct CfgContext {
+    features: BTreeSet<String>,
+}
+
+impl CfgContext {
+    #[must_use]
+    pub fn new(features: BTreeSet<String>) -> CfgContext {
+        CfgContext { features }
+    }
+
+    /// Evaluates a predicate, e.g. `feature = "std"`.  Returns `None` if it is not a valid
+    /// predicate.
+    #[must_use]
+    pub fn evaluate(&self, predicate: &str) -> Option<bool> {
+        let meta: Meta = syn::parse_str(predicate).ok()?;
+
+        self.evaluate_meta(&meta)
+    }
+
+    pub(crate) fn evaluate_meta(&self, meta: &Meta) -> Option<bool> {
+        match meta {
+            Meta::Path(path) => Some(path.is_ident("doc")),
+            Meta::NameValue(name_value) => match &name_value.value {
+                Expr::Lit(ExprLit { lit: Lit::Str(value), .. }) => Some(
+                    name_value.path.is_ident("feature") && self.features.contains(&value.value()),
+                ),
+                _ => None,
+            },
+            Meta::List(list) => {
+                let predicates =
+                    list.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated).ok()?;
+                let values: Vec<bool> =
+                    predicates.iter().map(|p| self.evaluate_meta(p)).collect::<Option<_>>()?;
+
+                if list.path.is_ident("all") {
+                    Some(values.into_iter().all(|v| v))
+                } else if list.path.is_ident("any") {
+                    Some(values.into_iter().any(|v| v))
+                } else if list.path.is_ident("not") {
+                    match values.as_slice() {
+                        [value] => Some(!value),
+                        _ => None,
+                    }
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn package_features() -> BTreeMap<String, Vec<String>> {
+        [
+            ("default", vec!["std"]),
+            ("std", vec!["alloc", "serde?/std"]),
+            ("alloc", vec![]),
+            ("serde", vec!["dep:serde"]),
+            ("json", vec!["serde/derive", "dep:serde_json"]),
+            ("nightly", vec![]),
+        ]
+        .into_iter()
+        .map(|(name, items)| (name.to_owned(), items.into_iter().map(ToOwned::to_owned).collect()))
+        .collect()
+    }
+
+    fn features(names: &[&str]) -> BTreeSet<String> {
+        names.iter().map(|&name| name.to_owned()).collect()
+    }
+
+    #[test]
+    fn test_enabled_features() {
+        let package_features = package_features();
+
+        assert_eq!(
+            FeatureSelection::default().enabled_features(&package_features),
+            features(&["alloc", "default", "std"])
+        );
+
+        let selection = FeatureSelection {
+            features: vec!["json".to_owned()],
+            all_features: false,
+            no_default_features: true,
+        };
+
+        assert_eq!(selection.enabled_features(&package_features), features(&["json", "serde"]));
+
+        let selection = FeatureSelection { all_features: true, ..FeatureSelection::default() };
+
+        assert_eq!(
+            selection.enabled_features(&package_features),
+            features(&["alloc", "default", "json", "nightly", "serde", "std"])
+        );
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let cfg = CfgContext::new(features(&["std"]));
+
+        assert_eq!(cfg.evaluate("doc"), Some(true));
+        assert_eq!(cfg.evaluate("unix"), Some(false));
+        assert_eq!(cfg.evaluate(r#"feature = "std""#), Some(true));
+        assert_eq!(cfg.evaluate(r#"feature = "nightly""#), Some(false));
+        assert_eq!(cfg.evaluate(r#"target_os = "linux""#), Some(false));
+        assert_eq!(
+            cfg.evaluate(r#"all(doc, feature = "std", not(feature = "nightly"))"#),
+            Some(true)
+        );
+        assert_eq!(cfg.evaluate(r#"any(feature = "nightly", test)"#), Some(false));
+        assert_eq!(cfg.evaluate("all()"), Some(true));
+        assert_eq!(cfg.evaluate("any()"), Some(false));
+        assert_eq!(cfg.evaluate("not(doc, test)"), None);
+        assert_eq!(cfg.evaluate("foo(doc)"), None);
+        assert_eq!(cfg.evaluate("feature = 1"), None);
+        assert_eq!(cfg.evaluate("feature ="), None);
+    }
+}
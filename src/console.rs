@@ -3,18 +3,163 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/.
  */
 
+use crate::explain::DiagnosticCode;
+use std::cell::RefCell;
 use std::fmt::Display;
 use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
 use termcolor::ColorChoice;
 use termcolor::WriteColor;
 use termcolor::{ColorSpec, StandardStream};
 
 pub use termcolor::Color;
 
+/// Number of warnings printed so far.
+static WARNING_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+pub fn warning_count() -> usize {
+    WARNING_COUNT.load(Ordering::Relaxed)
+}
+
+pub fn count_warning() {
+    WARNING_COUNT.fetch_add(1, Ordering::Relaxed);
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Level {
+    Warning,
+    Error,
+}
+
+impl Level {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Level::Warning => "warning",
+            Level::Error => "error",
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            Level::Warning => Color::Yellow,
+            Level::Error => Color::Red,
+        }
+    }
+}
+
+/// A warning or an error, as it was emitted.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct EmittedDiagnostic {
+    pub level: Level,
+    pub code: Option<DiagnosticCode>,
+    pub message: String,
+    /// The file the diagnostic is about, if any.
+    pub path: Option<PathBuf>,
+}
+
+/// The warnings and errors printed so far.
+static DIAGNOSTICS: Mutex<Vec<EmittedDiagnostic>> = Mutex::new(Vec::new());
+
+/// The warnings and errors printed so far, in the order they were printed.
+pub fn diagnostics() -> Vec<EmittedDiagnostic> {
+    DIAGNOSTICS.lock().map(|diagnostics| diagnostics.clone()).unwrap_or_default()
+}
+
+enum Output {
+    Stdout(String),
+    Stderr(EmittedDiagnostic),
+}
+
+thread_local! {
+    /// The output printed by the current thread, if it is being captured.
+    static CAPTURED: RefCell<Option<Vec<Output>>> = const { RefCell::new(None) };
+}
+
+/// Output captured by [`capture`], to print later.
+pub struct CapturedOutput {
+    output: Vec<Output>,
+}
+
+impl CapturedOutput {
+    pub fn print(self) {
+        for output in self.output {
+            match output {
+                Output::Stdout(message) => print_stdout(message),
+                Output::Stderr(diagnostic) => print_stderr(diagnostic),
+            }
+        }
+    }
+}
+
+/// Runs `f`, capturing what it prints instead of printing it.  This lets the output of work done
+/// in parallel be printed in a deterministic order.
+pub fn capture<R>(f: impl FnOnce() -> R) -> (R, CapturedOutput) {
+    let previous = CAPTURED.with(|captured| captured.replace(Some(Vec::new())));
+    let result = f();
+    let output = CAPTURED.with(|captured| captured.replace(previous)).unwrap_or_default();
+
+    (result, CapturedOutput { output })
+}
+
+/// Adds the output to the captured output of the current thread.  Returns it back if the output
+/// is not being captured.
+fn try_capture(output: Output) -> Option<Output> {
+    CAPTURED.with(|captured| match captured.borrow_mut().as_mut() {
+        Some(captured) => {
+            captured.push(output);
+            None
+        }
+        None => Some(output),
+    })
+}
+
 fn is_stderr_terminal() -> bool {
     std::io::stderr().is_terminal()
 }
 
+/// A line on stderr with how many items were processed so far, redrawn in place.
+pub struct Progress {
+    /// What is being processed, e.g. `packages`.
+    items: &'static str,
+    total: usize,
+    visible: bool,
+}
+
+impl Progress {
+    /// The progress is only shown if `enabled` and stderr is a terminal, since a line that is
+    /// redrawn in place is just noise in a log.
+    pub fn new(items: &'static str, total: usize, enabled: bool) -> Progress {
+        Progress { items, total, visible: enabled && is_stderr_terminal() }
+    }
+
+    pub fn show(&self, processed: usize) {
+        if self.visible {
+            let mut stderr = std::io::stderr().lock();
+            let remaining = self.total - processed;
+
+            // Errors writing the progress are not worth failing for.
+            let _ = write!(
+                stderr,
+                "\r\x1b[2K{processed}/{} {} processed, {remaining} remaining",
+                self.total, self.items
+            );
+            let _ = stderr.flush();
+        }
+    }
+
+    /// Erases the progress line, so that something else can be printed.
+    pub fn clear(&self) {
+        if self.visible {
+            let mut stderr = std::io::stderr().lock();
+
+            let _ = write!(stderr, "\r\x1b[2K");
+            let _ = stderr.flush();
+        }
+    }
+}
+
 fn print_color(
     stream: &mut StandardStream,
     level: impl Display,
@@ -41,41 +186,121 @@ fn print_nocolor(
     stream.flush()
 }
 
-pub fn print_stderr(level: impl Display, message: impl Display, color: Color) {
+pub fn print_stdout(message: impl Display) {
+    if let Some(Output::Stdout(message)) = try_capture(Output::Stdout(message.to_string())) {
+        println!("{message}");
+    }
+}
+
+/// Prints the warning or error, and records it for [`diagnostics`].
+pub fn print_stderr(diagnostic: EmittedDiagnostic) {
+    let Some(Output::Stderr(diagnostic)) = try_capture(Output::Stderr(diagnostic)) else {
+        return;
+    };
+    let level = match diagnostic.code {
+        Some(code) => format!("{}[{code}]", diagnostic.level.as_str()),
+        None => diagnostic.level.as_str().to_owned(),
+    };
+    let color = diagnostic.level.color();
     let mut stream = StandardStream::stderr(ColorChoice::Auto);
 
     match is_stderr_terminal() {
-        true => {
-            print_color(&mut stream, level, message, color).expect("error writing to stderr");
-        }
-        false => {
-            print_nocolor(&mut stream, level, message).expect("error writing to stderr");
-        }
+        true => print_color(&mut stream, level, &diagnostic.message, color),
+        false => print_nocolor(&mut stream, level, &diagnostic.message),
+    }
+    .expect("error writing to stderr");
+
+    // Captured output is recorded when it is finally printed, so that the order is the same.
+    if let Ok(mut diagnostics) = DIAGNOSTICS.lock() {
+        diagnostics.push(diagnostic);
     }
 }
 
+/// Prints a warning or an error.  This is what [`print_error!`] and [`print_warning!`] expand to.
+pub fn emit(
+    level: Level,
+    code: Option<DiagnosticCode>,
+    path: Option<&Path>,
+    message: impl Into<String>,
+) {
+    if level == Level::Warning {
+        count_warning();
+    }
+
+    print_stderr(EmittedDiagnostic {
+        level,
+        code,
+        message: message.into(),
+        path: path.map(Path::to_path_buf),
+    });
+}
+
+// The file a diagnostic is about can be given with `path:`, and its code with `code:`, so that they
+// are recorded for the report of `--report`.
 macro_rules! print_error {
+    (code: $code:expr, path: $path:expr, $f:literal, $($arg:tt)*) => {{
+        $crate::console::emit(
+            $crate::console::Level::Error,
+            ::std::option::Option::Some($code),
+            ::std::option::Option::Some(::std::path::Path::new(&$path)),
+            ::std::format!($f, $($arg)*),
+        );
+    }};
+    (path: $path:expr, $f:literal, $($arg:tt)*) => {{
+        $crate::console::emit(
+            $crate::console::Level::Error,
+            ::std::option::Option::None,
+            ::std::option::Option::Some(::std::path::Path::new(&$path)),
+            ::std::format!($f, $($arg)*),
+        );
+    }};
     ($f:literal, $($arg:tt)*) => {{
-        $crate::console::print_stderr(
-            "error",
+        $crate::console::emit(
+            $crate::console::Level::Error,
+            ::std::option::Option::None,
+            ::std::option::Option::None,
             ::std::format!($f, $($arg)*),
-            $crate::console::Color::Red,
         );
     }};
 }
 
 macro_rules! print_warning {
+    (code: $code:expr, path: $path:expr, $f:literal, $($arg:tt)*) => {{
+        $crate::console::emit(
+            $crate::console::Level::Warning,
+            ::std::option::Option::Some($code),
+            ::std::option::Option::Some(::std::path::Path::new(&$path)),
+            ::std::format!($f, $($arg)*),
+        );
+    }};
+    (code: $code:expr, $f:literal, $($arg:tt)*) => {{
+        $crate::console::emit(
+            $crate::console::Level::Warning,
+            ::std::option::Option::Some($code),
+            ::std::option::Option::None,
+            ::std::format!($f, $($arg)*),
+        );
+    }};
+    (path: $path:expr, $f:literal, $($arg:tt)*) => {{
+        $crate::console::emit(
+            $crate::console::Level::Warning,
+            ::std::option::Option::None,
+            ::std::option::Option::Some(::std::path::Path::new(&$path)),
+            ::std::format!($f, $($arg)*),
+        );
+    }};
     ($f:literal, $($arg:tt)*) => {{
-        $crate::console::print_stderr(
-            "warning",
+        $crate::console::emit(
+            $crate::console::Level::Warning,
+            ::std::option::Option::None,
+            ::std::option::Option::None,
             ::std::format!($f, $($arg)*),
-            $crate::console::Color::Yellow,
         );
     }};
 }
 
 macro_rules! print_info {
     ($f:literal, $($arg:tt)*) => {{
-        println!($f, $($arg)*);
+        $crate::console::print_stdout(::std::format!($f, $($arg)*));
     }};
 }
@@ -0,0 +1,342 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::explain::DiagnosticCode;
+use std::fmt::Write;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Number of lines to show before and after the line of the snippet.
+const CONTEXT_LINES: usize = 2;
+
+/// A region of a file to show along with an error message.
+#[derive(Debug)]
+pub struct Snippet {
+    path: PathBuf,
+    source: String,
+    /// Byte range of the source that is highlighted.
+    span: Range<usize>,
+    label: String,
+}
+
+impl Snippet {
+    pub fn new(
+        path: impl AsRef<Path>,
+        source: impl Into<String>,
+        span: Range<usize>,
+        label: impl Into<String>,
+    ) -> Snippet {
+        Snippet {
+            path: path.as_ref().to_path_buf(),
+            source: source.into(),
+            span,
+            label: label.into(),
+        }
+    }
+
+    /// Creates a snippet that highlights the given line (starting at 1).
+    pub fn from_line(
+        path: impl AsRef<Path>,
+        source: impl Into<String>,
+        line_number: usize,
+        label: impl Into<String>,
+    ) -> Option<Snippet> {
+        let source = source.into();
+        let span = line_spans(&source).nth(line_number.checked_sub(1)?).map(|line| {
+            let line_str = &source[line.clone()];
+            let start = line.start + (line_str.len() - line_str.trim_start().len());
+
+            start..(line.start + line_str.trim_end().len()).max(start)
+        })?;
+
+        Some(Snippet::new(path, source, span, label))
+    }
+
+    /// Creates a snippet that highlights the line where the `key` of a toml file is defined.  The key
+    /// can also be a table header, such as `[entrypoint]`.
+    pub fn from_toml_key(
+        path: impl AsRef<Path>,
+        source: impl Into<String>,
+        key: &str,
+        label: impl Into<String>,
+    ) -> Option<Snippet> {
+        let source = source.into();
+        let line_number = source.lines().position(|line| {
+            let line = line.trim();
+
+            match key.starts_with('[') {
+                true => line == key,
+                false => {
+                    line.strip_prefix(key).is_some_and(|rest| rest.trim_start().starts_with('='))
+                }
+            }
+        })?;
+
+        Snippet::from_line(path, source, line_number + 1, label)
+    }
+
+    fn render(&self, out: &mut String) {
+        let lines: Vec<Range<usize>> = line_spans(&self.source).collect();
+        let start = self.span.start.min(self.source.len());
+        let line_index = lines
+            .iter()
+            .position(|line| start <= line.end)
+            .unwrap_or(lines.len().saturating_sub(1));
+        let Some(line) = lines.get(line_index) else {
+            return;
+        };
+        // The span can start past the end of its line, e.g. at the end of the file.
+        let start = start.clamp(line.start, line.end);
+
+        let column = self.source[line.start..start].chars().count();
+        let span_end = self.span.end.clamp(start, line.end);
+        let width = self.source[start..span_end].chars().count().max(1);
+
+        let first_line = line_index.saturating_sub(CONTEXT_LINES);
+        let last_line = (line_index + CONTEXT_LINES).min(lines.len() - 1);
+        let gutter_width = (last_line + 1).to_string().len();
+        let empty_gutter = " ".repeat(gutter_width);
+
+        let _ = writeln!(
+            out,
+            "{empty_gutter}--> {}:{}:{}",
+            self.path.display(),
+            line_index + 1,
+            column + 1
+        );
+        let _ = writeln!(out, "{empty_gutter} |");
+
+        for (i, l) in lines.iter().enumerate().take(last_line + 1).skip(first_line) {
+            let text = &self.source[l.clone()];
+
+            let _ = match text.is_empty() {
+                true => writeln!(out, "{:>gutter_width$} |", i + 1),
+                false => writeln!(out, "{:>gutter_width$} | {text}", i + 1),
+            };
+
+            if i == line_index {
+                let _ = writeln!(
+                    out,
+                    "{empty_gutter} | {}{} {}",
+                    " ".repeat(column),
+                    "^".repeat(width),
+                    self.label
+                );
+            }
+        }
+
+        let _ = writeln!(out, "{empty_gutter} |");
+    }
+}
+
+/// Byte ranges of every line of `source`, not including the line terminator.
+fn line_spans(source: &str) -> impl Iterator<Item = Range<usize>> + '_ {
+    let mut offset = 0;
+
+    source.split_inclusive('\n').map(move |line| {
+        let start = offset;
+        let content = line.strip_suffix('\n').unwrap_or(line);
+        let content = content.strip_suffix('\r').unwrap_or(content);
+
+        offset += line.len();
+
+        start..(start + content.len())
+    })
+}
+
+/// An error to report to the user, optionally with a snippet of the file that caused it and a
+/// hint on how to fix it.
+#[derive(Debug)]
+pub struct Diagnostic {
+    message: String,
+    code: Option<DiagnosticCode>,
+    snippet: Option<Snippet>,
+    help: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn new(message: impl Into<String>) -> Diagnostic {
+        Diagnostic { message: message.into(), code: None, snippet: None, help: None }
+    }
+
+    pub fn with_code(mut self, code: Option<DiagnosticCode>) -> Diagnostic {
+        self.code = code;
+        self
+    }
+
+    pub fn with_snippet(mut self, snippet: Option<Snippet>) -> Diagnostic {
+        self.snippet = snippet;
+        self
+    }
+
+    pub fn with_help(mut self, help: impl Into<String>) -> Diagnostic {
+        self.help = Some(help.into());
+        self
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn code(&self) -> Option<DiagnosticCode> {
+        self.code
+    }
+
+    /// The file of the snippet, if any.
+    pub fn path(&self) -> Option<&Path> {
+        self.snippet.as_ref().map(|snippet| snippet.path.as_path())
+    }
+
+    /// Renders everything in the diagnostic except the message.
+    pub fn render_details(&self) -> String {
+        let mut out = String::new();
+
+        if let Some(snippet) = &self.snippet {
+            snippet.render(&mut out);
+        }
+
+        if let Some(help) = &self.help {
+            let mut lines = help.lines();
+
+            if let Some(first_line) = lines.next() {
+                let _ = writeln!(out, "help: {first_line}");
+            }
+
+            for line in lines {
+                let _ = writeln!(out, "      {line}");
+            }
+        }
+
+        if let Some(code) = self.code {
+            let _ = writeln!(out, "for more information, try `cargo rdme explain {code}`");
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_render_snippet_from_line() {
+        let source = indoc! { "
+            # My crate
+
+            <!-- cargo-rdme start -->
+
+            Hope you enjoy!
+            "
+        };
+
+        let snippet =
+            Snippet::from_line("README.md", source, 3, "this start marker is never closed");
+        let diagnostic = Diagnostic::new("unmatched start marker")
+            .with_snippet(snippet)
+            .with_help("add a `<!-- cargo-rdme end -->` marker");
+
+        let expected = indoc! { "
+             --> README.md:3:1
+              |
+            1 | # My crate
+            2 |
+            3 | <!-- cargo-rdme start -->
+              | ^^^^^^^^^^^^^^^^^^^^^^^^^ this start marker is never closed
+            4 |
+            5 | Hope you enjoy!
+              |
+            help: add a `<!-- cargo-rdme end -->` marker
+            "
+        };
+
+        assert_eq!(diagnostic.render_details(), expected);
+    }
+
+    #[test]
+    fn test_render_snippet_from_toml_key() {
+        let source = indoc! { r#"
+            readme-path = "README.md"
+
+            [entrypoint]
+            type = "bin"
+            "#
+        };
+
+        let snippet = Snippet::from_toml_key(".cargo-rdme.toml", source, "type", "invalid type");
+        let diagnostic = Diagnostic::new("invalid entrypoint table").with_snippet(snippet);
+
+        let expected = indoc! { r#"
+             --> .cargo-rdme.toml:4:1
+              |
+            2 |
+            3 | [entrypoint]
+            4 | type = "bin"
+              | ^^^^^^^^^^^^ invalid type
+              |
+            "#
+        };
+
+        assert_eq!(diagnostic.render_details(), expected);
+
+        assert!(Snippet::from_toml_key(".cargo-rdme.toml", source, "[entrypoint]", "").is_some());
+        assert!(Snippet::from_toml_key(".cargo-rdme.toml", source, "bin-name", "").is_none());
+    }
+
+    #[test]
+    fn test_render_code() {
+        let diagnostic = Diagnostic::new("README is not up to date")
+            .with_code(Some(DiagnosticCode::ReadmeMismatch))
+            .with_help("run `cargo rdme`");
+
+        let expected = indoc! { "
+            help: run `cargo rdme`
+            for more information, try `cargo rdme explain RDME0004`
+            "
+        };
+
+        assert_eq!(diagnostic.render_details(), expected);
+    }
+
+    #[test]
+    fn test_render_snippet_span() {
+        let source = "heading-base-level = \"three\"\n";
+
+        let snippet = Snippet::new(".cargo-rdme.toml", source, 21..28, "expected an integer");
+        let diagnostic = Diagnostic::new("invalid field").with_snippet(Some(snippet));
+
+        let expected = indoc! { r#"
+             --> .cargo-rdme.toml:1:22
+              |
+            1 | heading-base-level = "three"
+              |                      ^^^^^^^ expected an integer
+              |
+            "#
+        };
+
+        assert_eq!(diagnostic.render_details(), expected);
+    }
+
+    #[test]
+    fn test_render_snippet_span_at_end_of_file() {
+        let source = "a = [1,\n";
+
+        let snippet = Snippet::new(".cargo-rdme.toml", source, 8..8, "invalid array");
+        let diagnostic = Diagnostic::new("invalid toml").with_snippet(Some(snippet));
+
+        let expected = indoc! { "
+             --> .cargo-rdme.toml:1:8
+              |
+            1 | a = [1,
+              |        ^ invalid array
+              |
+            "
+        };
+
+        assert_eq!(diagnostic.render_details(), expected);
+    }
+}
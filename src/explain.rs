@@ -0,0 +1,380 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+//! Stable codes of the errors and warnings we report, and their explanations, as printed by
+//! `cargo rdme explain <code>`.
+
+use indoc::indoc;
+use std::fmt::{Display, Formatter, Write};
+use std::str::FromStr;
+
+/// A diagnostic code.  Codes are never reused or renumbered, so they can be referred to in
+/// scripts and documentation.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum DiagnosticCode {
+    NoReadmeFile,
+    NoRustdoc,
+    UnresolvedIntralink,
+    ReadmeMismatch,
+    ReadmeUncommittedChanges,
+    ReadmeHandEdited,
+    HandEditsMergeConflict,
+    InvalidMarkers,
+    InvalidConfig,
+    MathConverted,
+    CheckHasWarnings,
+    StrictWarnings,
+    NoBuildScriptOutDir,
+    HeadingCase,
+}
+
+impl DiagnosticCode {
+    pub const ALL: [DiagnosticCode; 14] = [
+        DiagnosticCode::NoReadmeFile,
+        DiagnosticCode::NoRustdoc,
+        DiagnosticCode::UnresolvedIntralink,
+        DiagnosticCode::ReadmeMismatch,
+        DiagnosticCode::ReadmeUncommittedChanges,
+        DiagnosticCode::ReadmeHandEdited,
+        DiagnosticCode::HandEditsMergeConflict,
+        DiagnosticCode::InvalidMarkers,
+        DiagnosticCode::InvalidConfig,
+        DiagnosticCode::MathConverted,
+        DiagnosticCode::CheckHasWarnings,
+        DiagnosticCode::StrictWarnings,
+        DiagnosticCode::NoBuildScriptOutDir,
+        DiagnosticCode::HeadingCase,
+    ];
+
+    pub fn number(self) -> usize {
+        DiagnosticCode::ALL.iter().position(|&code| code == self).expect("code not in `ALL`") + 1
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            DiagnosticCode::NoReadmeFile => "README file not found",
+            DiagnosticCode::NoRustdoc => "crate-level documentation not found",
+            DiagnosticCode::UnresolvedIntralink => "unresolved intra-doc link",
+            DiagnosticCode::ReadmeMismatch => "README is not up to date",
+            DiagnosticCode::ReadmeUncommittedChanges => "README has uncommitted changes",
+            DiagnosticCode::ReadmeHandEdited => "generated documentation was edited by hand",
+            DiagnosticCode::HandEditsMergeConflict => "hand edits conflict with the documentation",
+            DiagnosticCode::InvalidMarkers => "invalid cargo-rdme markers in the README",
+            DiagnosticCode::InvalidConfig => "invalid configuration",
+            DiagnosticCode::MathConverted => "math converted to code or images",
+            DiagnosticCode::CheckHasWarnings => "warnings were emitted in check mode",
+            DiagnosticCode::StrictWarnings => "warnings were emitted in strict mode",
+            DiagnosticCode::NoBuildScriptOutDir => "output directory of the build script not found",
+            DiagnosticCode::HeadingCase => "heading not in the expected case",
+        }
+    }
+
+    #[allow(clippy::too_many_lines)]
+    pub fn explanation(self) -> &'static str {
+        match self {
+            DiagnosticCode::NoReadmeFile => indoc! { r#"
+                cargo rdme could not find the README to update.
+
+                The README is the file set in the `readme` field of the package in `Cargo.toml`,
+                or `README.md` in the package directory if that field is not set.  The file must
+                already exist, since cargo rdme only updates the part of the README between its
+                markers.
+
+                Possible fixes:
+
+                  * Create the README with a marker where the documentation should go:
+
+                        # My crate
+
+                        <!-- cargo-rdme -->
+
+                  * Point cargo rdme to the README with `--readme-path <path>`, or in
+                    `.cargo-rdme.toml`:
+
+                        readme-path = "docs/README.md"
+                "#
+            },
+            DiagnosticCode::NoRustdoc => indoc! { r#"
+                The crate's entrypoint (e.g. `src/lib.rs`) has no crate-level documentation,
+                i.e. no `//!` comments or `#![doc = "…"]` attributes.
+
+                This is common in proc-macro crates or when the crate root is mostly behind
+                `#[cfg]`, where the documentation lives in another file.
+
+                Possible fixes:
+
+                  * Document the crate in its entrypoint:
+
+                        //! My crate does things.
+
+                  * Point cargo rdme to the file with the documentation with
+                    `--doc-source <path>`, or in `.cargo-rdme.toml`:
+
+                        doc-source = "docs/crate.md"
+
+                  * Select another entrypoint, e.g. a binary, in `.cargo-rdme.toml`:
+
+                        [entrypoint]
+                        type = "bin"
+
+                  * If the package is not meant to have documentation, use
+                    `--allow-missing-docs` (or `allow-missing-docs = true` in `.cargo-rdme.toml`)
+                    so that cargo rdme does nothing instead of failing.
+                "#
+            },
+            DiagnosticCode::UnresolvedIntralink => indoc! { r#"
+                An intra-doc link of the documentation (e.g. [`Foo`](crate::Foo)) points to an
+                item cargo rdme could not find.  The link is removed from the README, but its text
+                is kept.
+
+                cargo rdme looks for the items of the crate in its source, so items that are
+                generated by macros, or re-exported from other crates, cannot be resolved.  This
+                warning is also emitted if the file of a module is not found.
+
+                Possible fixes:
+
+                  * Check that the path of the link is correct, e.g. `crate::foo::Bar` rather than
+                    `crate::Bar`.
+
+                  * Use a regular link instead:
+
+                        [`Foo`](https://docs.rs/my-crate/latest/my_crate/struct.Foo.html)
+
+                  * Strip every intralink from the README with `--intralinks-strip-links`, or in
+                    `.cargo-rdme.toml`:
+
+                        [intralinks]
+                        strip-links = true
+                "#
+            },
+            DiagnosticCode::ReadmeMismatch => indoc! { r#"
+                In check mode (`--check`) cargo rdme does not update the README, but fails if the
+                README does not match the crate's documentation.
+
+                This means the documentation changed since the README was last generated.
+
+                Possible fix:
+
+                  * Update the README by running `cargo rdme`, and commit it.
+                "#
+            },
+            DiagnosticCode::ReadmeUncommittedChanges => indoc! { r#"
+                cargo rdme refuses to update a README that has uncommitted changes, since those
+                changes would be lost.
+
+                Possible fixes:
+
+                  * Commit or stash the changes of the README and run cargo rdme again.
+
+                  * Overwrite the README anyway with `cargo rdme --force`.
+                "#
+            },
+            DiagnosticCode::ReadmeHandEdited => indoc! { r#"
+                The end marker of the README has a hash of the generated documentation (the
+                `content-hash` option), and the documentation in the README no longer matches it.
+                This means someone edited the generated documentation by hand, and updating the
+                README would discard those edits.
+
+                Possible fixes:
+
+                  * Move the edits to the crate's documentation, and run `cargo rdme --force`.
+
+                  * Discard the edits with `cargo rdme --force`.
+
+                  * Merge the edits with the new documentation, in `.cargo-rdme.toml`:
+
+                        merge-hand-edits = true
+                "#
+            },
+            DiagnosticCode::HandEditsMergeConflict => indoc! { r#"
+                With `merge-hand-edits` cargo rdme merges the hand edits of the generated
+                documentation with the new documentation.  This failed, either because the edits
+                change the same lines as the new documentation, or because the previously
+                generated README, which is the base of the merge, is not available.
+
+                Possible fixes:
+
+                  * Move the edits to the crate's documentation, and run `cargo rdme --force`.
+
+                  * Discard the edits with `cargo rdme --force`.
+                "#
+            },
+            DiagnosticCode::InvalidMarkers => indoc! { r#"
+                The markers of the README that delimit the documentation are not balanced: there
+                is a start marker without an end marker, or an end marker without a start marker.
+
+                The documentation goes either at a single marker:
+
+                    <!-- cargo-rdme -->
+
+                or between a start and an end marker:
+
+                    <!-- cargo-rdme start -->
+                    <!-- cargo-rdme end -->
+
+                Possible fix:
+
+                  * Add the missing marker, or remove the unmatched one.
+                "#
+            },
+            DiagnosticCode::InvalidConfig => indoc! { r#"
+                The configuration, in `.cargo-rdme.toml`, `[package.metadata.rdme]`, or
+                `[workspace.metadata.rdme]`, is not valid: it is not valid toml, or a field has
+                a value of the wrong type.
+
+                Possible fixes:
+
+                  * Fix the field shown in the error.  The documentation of every field is in the
+                    README of cargo rdme.
+
+                  * Validate the configuration with the JSON schema printed by
+                    `cargo rdme config-schema`.
+                "#
+            },
+            DiagnosticCode::MathConverted => indoc! { r#"
+                The `math` option converted math (`$…$`, `$$…$$`, or ```` ```math ```` blocks) to
+                code or images, which do not look the same as the math in the documentation.  This
+                warning lists the lines of the documentation with math, so that you can check them.
+
+                This warning only fails cargo rdme in strict mode.  It also catches text that looks
+                like math but is not, e.g. `costs $5 or $6`.
+
+                Possible fixes:
+
+                  * Escape dollar signs that are not math: `costs \$5 or \$6`.
+
+                  * Keep the math as it is in `.cargo-rdme.toml`:
+
+                        math = "keep"
+                "#
+            },
+            DiagnosticCode::CheckHasWarnings => indoc! { r#"
+                In check mode (`--check`) the README is up to date, but warnings were emitted
+                while generating the documentation, e.g. unresolved intra-doc links.
+
+                Possible fixes:
+
+                  * Fix the warnings shown above.  Run `cargo rdme explain <code>` for the codes
+                    of those warnings.
+
+                  * Do not fail on warnings with `cargo rdme --check --no-fail-on-warnings`.
+                "#
+            },
+            DiagnosticCode::StrictWarnings => indoc! { r#"
+                In strict mode (`--strict`, or `strict = true` in the configuration) every warning
+                is an error, and the README is not updated.  Packages of a workspace that are
+                skipped are also errors.
+
+                Possible fixes:
+
+                  * Fix the warnings shown above.  Run `cargo rdme explain <code>` for the codes
+                    of those warnings.
+
+                  * Disable strict mode.
+                "#
+            },
+            DiagnosticCode::NoBuildScriptOutDir => indoc! { r#"
+                The `doc-source` option refers to `$OUT_DIR`, the output directory of the build
+                script, but that directory is not known.  cargo rdme only knows it if it runs the
+                build script, which requires the `build-script` option, and a package with a build
+                script.
+
+                Possible fix:
+
+                  * Enable the `build-script` option in `.cargo-rdme.toml`:
+
+                        build-script = true
+                        doc-source = "$OUT_DIR/docs.md"
+                "#
+            },
+            DiagnosticCode::HeadingCase => indoc! { r#"
+                The `heading-case` option checks the capitalization of the headings of the README,
+                and this heading is not in the expected case: sentence case (“Getting started”),
+                title case (“Getting Started”), or, with `consistent`, the case of most headings.
+                Headings written by hand and headings of the crate’s documentation often differ.
+
+                Headings whose case cannot be told, such as “Usage” or “Using Tokio”, are never
+                reported.
+
+                Possible fixes:
+
+                  * Change the capitalization of the heading, in the README or in the crate’s
+                    documentation, depending on where the heading comes from.
+
+                  * Pick another case in `.cargo-rdme.toml`:
+
+                        heading-case = "title"
+                "#
+            },
+        }
+    }
+}
+
+impl Display for DiagnosticCode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "RDME{:04}", self.number())
+    }
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub struct UnknownDiagnosticCode;
+
+impl FromStr for DiagnosticCode {
+    type Err = UnknownDiagnosticCode;
+
+    /// Parses codes such as `RDME0003`.  The prefix and the leading zeros are optional, so `3`
+    /// also works.
+    fn from_str(s: &str) -> Result<DiagnosticCode, UnknownDiagnosticCode> {
+        let number =
+            s.get(..4).filter(|prefix| prefix.eq_ignore_ascii_case("rdme")).map_or(s, |_| &s[4..]);
+        let number: usize = number.parse().map_err(|_| UnknownDiagnosticCode)?;
+
+        number
+            .checked_sub(1)
+            .and_then(|i| DiagnosticCode::ALL.get(i))
+            .copied()
+            .ok_or(UnknownDiagnosticCode)
+    }
+}
+
+/// The text printed by `cargo rdme explain <code>`.
+pub fn explain(code: DiagnosticCode) -> String {
+    format!("{code}: {}\n\n{}", code.title(), code.explanation())
+}
+
+/// The text printed by `cargo rdme explain` without a code.
+pub fn list_codes() -> String {
+    DiagnosticCode::ALL.iter().fold(String::new(), |mut list, code| {
+        let _ = writeln!(list, "{code}  {}", code.title());
+        list
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_diagnostic_code_from_str() {
+        assert_eq!("RDME0003".parse(), Ok(DiagnosticCode::UnresolvedIntralink));
+        assert_eq!("rdme0003".parse(), Ok(DiagnosticCode::UnresolvedIntralink));
+        assert_eq!("3".parse(), Ok(DiagnosticCode::UnresolvedIntralink));
+        assert_eq!("RDME0000".parse::<DiagnosticCode>(), Err(UnknownDiagnosticCode));
+        assert_eq!("RDME9999".parse::<DiagnosticCode>(), Err(UnknownDiagnosticCode));
+        assert_eq!("E0003".parse::<DiagnosticCode>(), Err(UnknownDiagnosticCode));
+    }
+
+    #[test]
+    fn test_diagnostic_code_round_trip() {
+        for code in DiagnosticCode::ALL {
+            assert_eq!(code.to_string().parse(), Ok(code));
+            assert!(code.explanation().ends_with('\n'));
+        }
+
+        assert_eq!(DiagnosticCode::NoReadmeFile.to_string(), "RDME0001");
+        assert_eq!(DiagnosticCode::NoBuildScriptOutDir.to_string(), "RDME0013");
+    }
+}
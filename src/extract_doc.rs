@@ -3,66 +3,131 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/.
  */
 
+#[cfg(feature = "rust-source")]
+use crate::cfg::CfgContext;
+#[cfg(feature = "rust-source")]
 use crate::markdown::Markdown;
+#[cfg(feature = "rust-source")]
 use crate::Doc;
-use std::path::{Path, PathBuf};
+#[cfg(feature = "rust-source")]
+use std::path::Path;
+use std::path::PathBuf;
+#[cfg(feature = "rust-source")]
 use syn::Expr;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum ExtractDocError {
     #[error("cannot open source file \"{0}\"")]
     ErrorReadingSourceFile(PathBuf),
+    #[cfg(feature = "rust-source")]
     #[error("cannot parse source file: {0}")]
     ErrorParsingSourceFile(syn::Error),
+    #[error("cannot open markdown file \"{0}\"")]
+    ErrorReadingMarkdownFile(PathBuf),
 }
 
+#[cfg(feature = "rust-source")]
 pub fn extract_doc_from_source_file(
     file_path: impl AsRef<Path>,
+) -> Result<Option<Doc>, ExtractDocError> {
+    extract_doc_from_source_file_with_cfg(file_path, None)
+}
+
+/// Like [`extract_doc_from_source_file`], but also extracts the documentation of
+/// `#![cfg_attr(<predicate>, doc = "…")]` attributes whose predicate holds in `cfg`.
+#[cfg(feature = "rust-source")]
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(file = %file_path.as_ref().display()))
+)]
+pub fn extract_doc_from_source_file_with_cfg(
+    file_path: impl AsRef<Path>,
+    cfg: Option<&CfgContext>,
 ) -> Result<Option<Doc>, ExtractDocError> {
     let source: String = std::fs::read_to_string(file_path.as_ref())
         .map_err(|_| ExtractDocError::ErrorReadingSourceFile(file_path.as_ref().to_path_buf()))?;
 
-    extract_doc_from_source_str(&source)
+    extract_doc_from_source_str_with_cfg(&source, cfg)
 }
 
+#[cfg(feature = "rust-source")]
 pub fn extract_doc_from_source_str(source: &str) -> Result<Option<Doc>, ExtractDocError> {
-    use syn::{parse_str, ExprLit, Lit, Meta, MetaNameValue};
+    extract_doc_from_source_str_with_cfg(source, None)
+}
 
-    let ast: syn::File = parse_str(source).map_err(ExtractDocError::ErrorParsingSourceFile)?;
+/// The doc strings of a `doc` attribute, or of the `doc` attributes of a `cfg_attr` attribute if
+/// its predicate holds in `cfg`.
+#[cfg(feature = "rust-source")]
+fn doc_attr_strings(meta: &syn::Meta, cfg: Option<&CfgContext>) -> Vec<String> {
+    use syn::punctuated::Punctuated;
+    use syn::{ExprLit, Lit, Meta, MetaNameValue, Token};
+
+    match meta {
+        Meta::NameValue(MetaNameValue {
+            path,
+            value: Expr::Lit(ExprLit { lit: Lit::Str(lstr), .. }),
+            ..
+        }) if path.is_ident("doc") => vec![lstr.value()],
+        Meta::List(list) if list.path.is_ident("cfg_attr") => {
+            let Some(cfg) = cfg else {
+                return Vec::new();
+            };
+            let Ok(args) = list.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+            else {
+                return Vec::new();
+            };
+            let mut args = args.iter();
+
+            match args.next().and_then(|predicate| cfg.evaluate_meta(predicate)) {
+                Some(true) => args.flat_map(|attr| doc_attr_strings(attr, Some(cfg))).collect(),
+                Some(false) | None => Vec::new(),
+            }
+        }
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(feature = "rust-source")]
+pub fn extract_doc_from_source_str_with_cfg(
+    source: &str,
+    cfg: Option<&CfgContext>,
+) -> Result<Option<Doc>, ExtractDocError> {
+    use syn::{parse_str, AttrStyle};
+
+    // Source files saved with `\r\n` line terminators must not leave a `\r` in the doc strings,
+    // e.g. in multiline comments or in string literals that span lines.
+    let source = source.replace("\r\n", "\n");
+    let ast: syn::File = parse_str(&source).map_err(ExtractDocError::ErrorParsingSourceFile)?;
     let mut lines: Vec<String> = Vec::with_capacity(1024);
 
     for attr in &ast.attrs {
-        if Doc::is_toplevel_doc(attr) {
-            if let Meta::NameValue(MetaNameValue {
-                value: Expr::Lit(ExprLit { lit: Lit::Str(lstr), .. }),
-                ..
-            }) = &attr.meta
-            {
-                let string: String = lstr.value();
-
-                match string.lines().count() {
-                    0 => lines.push(String::new()),
-                    1 => {
-                        let line =
-                            string.strip_prefix(' ').map(ToOwned::to_owned).unwrap_or(string);
-                        lines.push(line);
-                    }
-
-                    // Multiline comment.
-                    _ => {
-                        fn empty_line(str: &str) -> bool {
-                            str.chars().all(char::is_whitespace)
-                        }
+        if !matches!(attr.style, AttrStyle::Inner(_)) {
+            continue;
+        }
 
-                        let comment_lines = string
-                            .lines()
-                            .enumerate()
-                            .filter(|(i, l)| !(*i == 0 && empty_line(l)))
-                            .map(|(_, l)| l.to_owned());
+        for string in doc_attr_strings(&attr.meta, cfg) {
+            match string.lines().count() {
+                0 => lines.push(String::new()),
+                1 => {
+                    let line = string.strip_prefix(' ').map(ToOwned::to_owned).unwrap_or(string);
+                    lines.push(line);
+                }
 
-                        lines.extend(comment_lines);
+                // Multiline comment.
+                _ => {
+                    fn empty_line(str: &str) -> bool {
+                        str.chars().all(char::is_whitespace)
                     }
+
+                    let comment_lines = string
+                        .lines()
+                        .enumerate()
+                        .filter(|(i, l)| !(*i == 0 && empty_line(l)))
+                        .map(|(_, l)| l.to_owned());
+
+                    lines.extend(comment_lines);
                 }
             }
         }
@@ -74,7 +139,256 @@ pub fn extract_doc_from_source_str(source: &str) -> Result<Option<Doc>, ExtractD
     }
 }
 
-#[cfg(test)]
+/// Line numbers (starting at 1) of the source lines each line of the crate documentation comes
+/// from, as extracted by [`extract_doc_from_source_str`].  This is `None` if the source has doc
+/// attributes we cannot map to lines, e.g. `#![doc = include_str!(…)]`.
+#[cfg(feature = "rust-source")]
+pub fn doc_line_numbers(source: &str) -> Option<Vec<usize>> {
+    let source_lines: Vec<&str> = source.lines().collect();
+    let mut line_numbers = Vec::new();
+    let mut i = 0;
+
+    while i < source_lines.len() {
+        let line = source_lines[i].trim_start();
+
+        if line.starts_with("//!") {
+            line_numbers.push(i + 1);
+        } else if let Some(rest) = line.strip_prefix("/*!") {
+            let start = i;
+            let mut content = rest.to_owned();
+
+            while !content.contains("*/") && i + 1 < source_lines.len() {
+                i += 1;
+                content.push('\n');
+                content.push_str(source_lines[i]);
+            }
+
+            let content = &content[..content.find("*/")?];
+            let count = content.lines().count();
+
+            match count {
+                0 | 1 => line_numbers.push(start + 1),
+                _ => line_numbers.extend(
+                    content
+                        .lines()
+                        .enumerate()
+                        .filter(|(j, l)| !(*j == 0 && l.trim().is_empty()))
+                        .map(|(j, _)| start + j + 1),
+                ),
+            }
+        } else if line.starts_with("#![doc") {
+            let attr = line.strip_prefix("#![doc")?.trim_start();
+
+            // The list form, e.g. `#![doc(html_logo_url = "…")]`, has settings, not documentation.
+            if attr.starts_with('(') {
+                i += 1;
+                continue;
+            }
+
+            let value = attr.strip_prefix('=')?.trim_start();
+
+            // Only string literals in a single line map to a single documentation line.
+            if !value.starts_with('"') || value.contains("\\n") {
+                return None;
+            }
+
+            line_numbers.push(i + 1);
+        }
+
+        i += 1;
+    }
+
+    Some(line_numbers)
+}
+
+/// Whether the crate documentation of the source file is the README, included with something like
+/// `#![doc = include_str!("../README.md")]`.
+#[cfg(feature = "rust-source")]
+pub fn source_includes_readme(source: &str) -> Result<bool, ExtractDocError> {
+    use syn::{parse_str, AttrStyle, ExprMacro, Meta, MetaList, MetaNameValue};
+
+    fn mentions_readme(tokens: &str) -> bool {
+        tokens.to_lowercase().contains("readme")
+    }
+
+    let ast: syn::File = parse_str(source).map_err(ExtractDocError::ErrorParsingSourceFile)?;
+
+    let includes =
+        ast.attrs.iter().filter(|attr| matches!(attr.style, AttrStyle::Inner(_))).any(|attr| {
+            match &attr.meta {
+                Meta::NameValue(MetaNameValue {
+                    path,
+                    value: Expr::Macro(ExprMacro { mac, .. }),
+                    ..
+                }) if path.is_ident("doc") && mac.path.is_ident("include_str") => {
+                    mentions_readme(&mac.tokens.to_string())
+                }
+                // E.g. `#![cfg_attr(doc, doc = include_str!("../README.md"))]`.
+                Meta::List(MetaList { path, tokens, .. }) if path.is_ident("cfg_attr") => {
+                    let tokens = tokens.to_string();
+
+                    tokens.contains("include_str") && mentions_readme(&tokens)
+                }
+                _ => false,
+            }
+        });
+
+    Ok(includes)
+}
+
+/// The file in the build script output directory (`OUT_DIR`) that is the crate documentation of
+/// the source file, included with something like
+/// `#![doc = include_str!(concat!(env!("OUT_DIR"), "/docs.md"))]`.  The returned path is relative
+/// to `OUT_DIR`.
+#[cfg(feature = "rust-source")]
+pub fn source_out_dir_doc_include(source: &str) -> Result<Option<PathBuf>, ExtractDocError> {
+    use syn::parse::Parser;
+    use syn::punctuated::Punctuated;
+    use syn::{parse_str, AttrStyle, ExprLit, ExprMacro, Lit, LitStr, Meta, MetaNameValue, Token};
+
+    let ast: syn::File = parse_str(source).map_err(ExtractDocError::ErrorParsingSourceFile)?;
+
+    let out_dir_file = |concat_args: &Punctuated<Expr, Token![,]>| -> Option<PathBuf> {
+        let mut args = concat_args.iter();
+
+        match args.next()? {
+            Expr::Macro(ExprMacro { mac, .. })
+                if mac.path.is_ident("env")
+                    && mac.parse_body::<LitStr>().is_ok_and(|var| var.value() == "OUT_DIR") => {}
+            _ => return None,
+        }
+
+        let file: Option<String> = args
+            .map(|arg| match arg {
+                Expr::Lit(ExprLit { lit: Lit::Str(lstr), .. }) => Some(lstr.value()),
+                _ => None,
+            })
+            .collect();
+
+        file.map(|file| PathBuf::from(file.trim_start_matches(['/', '\\'])))
+    };
+
+    let file = ast.attrs.iter().filter(|attr| matches!(attr.style, AttrStyle::Inner(_))).find_map(
+        |attr| match &attr.meta {
+            Meta::NameValue(MetaNameValue {
+                path,
+                value: Expr::Macro(ExprMacro { mac: include_str, .. }),
+                ..
+            }) if path.is_ident("doc") && include_str.path.is_ident("include_str") => {
+                let concat = include_str.parse_body::<ExprMacro>().ok()?.mac;
+
+                match concat.path.is_ident("concat") {
+                    true => Punctuated::<Expr, Token![,]>::parse_terminated
+                        .parse2(concat.tokens)
+                        .ok()
+                        .and_then(|args| out_dir_file(&args)),
+                    false => None,
+                }
+            }
+            _ => None,
+        },
+    );
+
+    Ok(file)
+}
+
+/// The file to extract the crate documentation from instead of the source file, declared in the
+/// source file with a comment like `// cargo-rdme: doc-source = "docs/crate.md"`.  This is useful
+/// when the crate documentation of the entrypoint is behind `#[cfg]` facades, and keeps that
+/// configuration next to the code.  The returned path is relative to the package directory, like
+/// the `doc-source` option.
+#[cfg(feature = "rust-source")]
+#[must_use]
+pub fn source_doc_source_pragma(source: &str) -> Option<PathBuf> {
+    source.lines().find_map(|line| {
+        let comment = line.trim().strip_prefix("//")?;
+
+        // Doc comments are documentation, not pragmas.
+        if comment.starts_with(['/', '!']) {
+            return None;
+        }
+
+        let value = comment
+            .trim()
+            .strip_prefix("cargo-rdme:")?
+            .trim()
+            .strip_prefix("doc-source")?
+            .trim()
+            .strip_prefix('=')?
+            .trim();
+        let path = value.strip_prefix('"')?.strip_suffix('"')?;
+
+        Some(PathBuf::from(path))
+    })
+}
+
+/// The settings of the crate given in the list form of the `doc` attribute, e.g.
+/// `#![doc(html_logo_url = "…")]`.  These are not documentation: settings cargo rdme has no use
+/// for, like `#![doc(test(attr(deny(warnings))))]`, are ignored.
+#[derive(PartialEq, Eq, Clone, Default, Debug)]
+#[non_exhaustive]
+pub struct DocMeta {
+    /// The root of the rustdoc output where the documentation of the crate is published.
+    pub html_root_url: Option<String>,
+    /// The logo rustdoc shows in the documentation of the crate.
+    pub html_logo_url: Option<String>,
+    pub html_favicon_url: Option<String>,
+    pub html_playground_url: Option<String>,
+    pub issue_tracker_base_url: Option<String>,
+}
+
+/// The settings of the `#![doc(…)]` attributes of the crate.  If a setting is given more than
+/// once the first one is used.
+#[cfg(feature = "rust-source")]
+pub fn source_doc_meta(source: &str) -> Result<DocMeta, ExtractDocError> {
+    use syn::punctuated::Punctuated;
+    use syn::{parse_str, AttrStyle, ExprLit, Lit, Meta, MetaNameValue, Token};
+
+    let ast: syn::File = parse_str(source).map_err(ExtractDocError::ErrorParsingSourceFile)?;
+    let mut doc_meta = DocMeta::default();
+
+    let settings = ast
+        .attrs
+        .iter()
+        .filter(|attr| matches!(attr.style, AttrStyle::Inner(_)) && attr.path().is_ident("doc"))
+        .filter_map(|attr| {
+            attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated).ok()
+        })
+        .flatten();
+
+    for meta in settings {
+        let Meta::NameValue(MetaNameValue {
+            path,
+            value: Expr::Lit(ExprLit { lit: Lit::Str(lstr), .. }),
+            ..
+        }) = meta
+        else {
+            // Settings without a value, like `test(…)` or `html_no_source`.
+            continue;
+        };
+        let setting = match path.get_ident().map(ToString::to_string).as_deref() {
+            Some("html_root_url") => &mut doc_meta.html_root_url,
+            Some("html_logo_url") => &mut doc_meta.html_logo_url,
+            Some("html_favicon_url") => &mut doc_meta.html_favicon_url,
+            Some("html_playground_url") => &mut doc_meta.html_playground_url,
+            Some("issue_tracker_base_url") => &mut doc_meta.issue_tracker_base_url,
+            _ => continue,
+        };
+
+        setting.get_or_insert_with(|| lstr.value());
+    }
+
+    Ok(doc_meta)
+}
+
+/// The `html_root_url` of the crate, declared with `#![doc(html_root_url = "...")]`.  This is the
+/// root of the rustdoc output where the documentation of the crate is published.
+#[cfg(feature = "rust-source")]
+pub fn source_html_root_url(source: &str) -> Result<Option<String>, ExtractDocError> {
+    Ok(source_doc_meta(source)?.html_root_url)
+}
+
+#[cfg(all(test, feature = "rust-source"))]
 mod tests {
     use super::*;
     use indoc::indoc;
@@ -122,6 +436,16 @@ mod tests {
         assert_eq!(lines, expected);
     }
 
+    #[test]
+    fn test_doc_from_source_str_crlf() {
+        let str = "//! # Foo\r\n//!\r\n//! Some text.\r\n\r\n/*!\r\nMore text.\r\n*/\r\n\r\n\
+                   #![doc = \"Multi\r\nline\"]\r\n\r\nstruct Nothing {}\r\n";
+
+        let doc = extract_doc_from_source_str(str).unwrap().unwrap();
+
+        assert_eq!(doc.as_string(), "# Foo\n\nSome text.\nMore text.\nMulti\nline\n");
+    }
+
     #[test]
     fn test_doc_from_source_str_multi_line_comment() {
         let str = indoc! { r#"
@@ -179,4 +503,172 @@ mod tests {
 
         assert_eq!(lines, expected);
     }
+
+    #[test]
+    fn test_doc_from_source_str_cfg_attr() {
+        let str = indoc! { r#"
+            //! This is the doc for the crate.
+            #![cfg_attr(feature = "std", doc = "", doc = "Uses the standard library.")]
+            #![cfg_attr(not(feature = "std"), doc = "Does not use the standard library.")]
+            #![cfg_attr(doc, cfg_attr(feature = "std", doc = "Nested."))]
+            #![cfg_attr(not(feature = "std"), no_std)]
+
+            struct Nothing {}
+            "#
+        };
+
+        let doc = extract_doc_from_source_str(str).unwrap().unwrap();
+
+        assert_eq!(doc.as_string(), "This is the doc for the crate.\n");
+
+        let cfg = CfgContext::new(["std".to_owned()].into_iter().collect());
+        let doc = extract_doc_from_source_str_with_cfg(str, Some(&cfg)).unwrap().unwrap();
+        let lines: Vec<&str> = doc.lines().collect();
+
+        let expected =
+            vec!["This is the doc for the crate.", "", "Uses the standard library.", "Nested."];
+
+        assert_eq!(lines, expected);
+    }
+
+    #[test]
+    fn test_source_includes_readme() {
+        let str = indoc! { r#"
+            #![doc = include_str!("../README.md")]
+
+            struct Nothing {}
+            "#
+        };
+
+        assert!(source_includes_readme(str).unwrap());
+
+        let str = indoc! { r#"
+            #![cfg_attr(doc, doc = include_str!("../Readme.md"))]
+            "#
+        };
+
+        assert!(source_includes_readme(str).unwrap());
+
+        let str = indoc! { r#"
+            #![doc = include_str!("../docs/crate.md")]
+            //! See the [README](README.md).
+            "#
+        };
+
+        assert!(!source_includes_readme(str).unwrap());
+    }
+
+    #[test]
+    fn test_source_html_root_url() {
+        let str = indoc! { r#"
+            #![doc(html_logo_url = "https://example.com/logo.png")]
+            #![doc(html_favicon_url = "https://example.com/favicon.ico", html_root_url = "https://docs.rs/foo/1.2.3")]
+            //! The crate.
+            "#
+        };
+
+        assert_eq!(
+            source_html_root_url(str).unwrap().as_deref(),
+            Some("https://docs.rs/foo/1.2.3")
+        );
+
+        let str = indoc! { r#"
+            #![doc = "The crate."]
+            #[doc(html_root_url = "https://example.com")]
+            struct Nothing {}
+            "#
+        };
+
+        assert_eq!(source_html_root_url(str).unwrap(), None);
+    }
+
+    #[test]
+    fn test_source_doc_meta() {
+        let str = indoc! { r#"
+            #![doc(test(attr(deny(warnings))), html_no_source)]
+            #![doc(html_logo_url = "https://example.com/logo.png")]
+            #![doc(
+                html_favicon_url = "https://example.com/favicon.ico",
+                html_root_url = "https://docs.rs/foo/1.2.3",
+            )]
+            #![doc(html_logo_url = "https://example.com/other-logo.png", html_foo = "bar")]
+            //! The crate.
+            "#
+        };
+        let expected = DocMeta {
+            html_root_url: Some("https://docs.rs/foo/1.2.3".to_owned()),
+            html_logo_url: Some("https://example.com/logo.png".to_owned()),
+            html_favicon_url: Some("https://example.com/favicon.ico".to_owned()),
+            html_playground_url: None,
+            issue_tracker_base_url: None,
+        };
+
+        assert_eq!(source_doc_meta(str).unwrap(), expected);
+        assert_eq!(source_doc_meta("//! The crate.\n").unwrap(), DocMeta::default());
+    }
+
+    #[test]
+    fn test_doc_meta_attributes_are_not_documentation() {
+        let str = indoc! { r#"
+            #![doc(test(attr(deny(warnings))))]
+            #![doc(html_logo_url = "https://example.com/logo.png")]
+            //! The crate.
+            #![doc = "More of it."]
+            "#
+        };
+
+        let doc = extract_doc_from_source_str(str).unwrap().unwrap();
+
+        assert_eq!(doc.as_string(), "The crate.\nMore of it.\n");
+        assert_eq!(doc_line_numbers(str), Some(vec![3, 4]));
+    }
+
+    #[test]
+    fn test_source_out_dir_doc_include() {
+        let str = indoc! { r#"
+            #![doc = include_str!(concat!(env!("OUT_DIR"), "/docs/", "crate.md"))]
+
+            struct Nothing {}
+            "#
+        };
+
+        assert_eq!(source_out_dir_doc_include(str).unwrap(), Some(PathBuf::from("docs/crate.md")));
+
+        let str = indoc! { r#"
+            #![doc = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/README.md"))]
+            "#
+        };
+
+        assert_eq!(source_out_dir_doc_include(str).unwrap(), None);
+
+        let str = indoc! { r#"
+            #![doc = include_str!("../README.md")]
+            "#
+        };
+
+        assert_eq!(source_out_dir_doc_include(str).unwrap(), None);
+    }
+
+    #[test]
+    fn test_source_doc_source_pragma() {
+        let str = indoc! { r#"
+            //! Only part of the documentation.
+
+            // cargo-rdme: doc-source = "docs/README-source.md"
+            #![cfg_attr(feature = "nightly", doc = include_str!("../docs/nightly.md"))]
+            "#
+        };
+
+        assert_eq!(source_doc_source_pragma(str), Some(PathBuf::from("docs/README-source.md")));
+
+        let str = indoc! { r#"
+            //! Use `// cargo-rdme: doc-source = "docs/crate.md"` to change the documentation.
+            /// cargo-rdme: doc-source = "docs/crate.md"
+            // cargo-rdme: doc-source = docs/crate.md
+            struct Nothing {}
+            "#
+        };
+
+        assert_eq!(source_doc_source_pragma(str), None);
+    }
 }
@@ -0,0 +1,344 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+//! Machine-applicable fixes of common issues of READMEs, as applied by `--fix`.
+
+use crate::freeze::{code_fence, freeze_dependency_line};
+use crate::inject_doc::generated_regions;
+use crate::{Doc, Readme};
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[non_exhaustive]
+pub enum Lint {
+    /// The documentation starts with the same title as the README.
+    DuplicateTitle,
+    /// Lines indented with tabs, which renderers expand inconsistently.
+    Tabs,
+    TrailingWhitespace,
+    /// Code blocks with a language alias that not every renderer highlights, e.g. `rs`.
+    FenceLanguage,
+    /// Dependency snippets in toml code blocks with an outdated version of the crate.
+    OutdatedVersion,
+}
+
+impl Display for Lint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let description = match self {
+            Lint::DuplicateTitle => "duplicate title",
+            Lint::Tabs => "tab indentation",
+            Lint::TrailingWhitespace => "trailing whitespace",
+            Lint::FenceLanguage => "code block language",
+            Lint::OutdatedVersion => "outdated version of the crate",
+        };
+
+        f.write_str(description)
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[non_exhaustive]
+pub struct Fix {
+    pub lint: Lint,
+    /// Line where the fix was applied (starting at 1), in the text before the fixes.
+    pub line_number: usize,
+}
+
+/// The crate being documented, used to fix outdated dependency snippets.
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub struct FixContext<'a> {
+    pub package_name: &'a str,
+    pub version: &'a str,
+}
+
+impl<'a> FixContext<'a> {
+    #[must_use]
+    pub fn new(package_name: &'a str, version: &'a str) -> FixContext<'a> {
+        FixContext { package_name, version }
+    }
+}
+
+/// Fixes the documentation before it is injected in `readme`.
+#[must_use]
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+pub fn fix_doc(doc: &Doc, readme: &Readme, context: FixContext<'_>) -> (Doc, Vec<Fix>) {
+    let (source, mut fixes) = fix_lines(doc.as_string(), &[], context);
+    let source = match readme_title(readme) {
+        Some(title) => match remove_title(&source, &title) {
+            Some(source) => {
+                fixes.insert(0, Fix { lint: Lint::DuplicateTitle, line_number: 1 });
+                source
+            }
+            None => source,
+        },
+        None => source,
+    };
+
+    (Doc::from_str(source), fixes)
+}
+
+/// Fixes the README, except for the regions generated by cargo rdme, which are replaced by the
+/// (fixed) documentation.
+#[must_use]
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+pub fn fix_readme(readme: &Readme, context: FixContext<'_>) -> (Readme, Vec<Fix>) {
+    let (source, fixes) = fix_lines(readme.as_string(), &generated_regions(readme), context);
+
+    (Readme::from_str(source), fixes)
+}
+
+/// Text of the first level one heading of the README, if it comes before the documentation.
+fn readme_title(readme: &Readme) -> Option<String> {
+    use pulldown_cmark::{Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+
+    let first_region_start = generated_regions(readme).first().map(|region| region.start);
+    let mut title: Option<String> = None;
+
+    for (event, range) in Parser::new_ext(readme.as_string(), Options::all()).into_offset_iter() {
+        if first_region_start.is_some_and(|start| range.start >= start) {
+            return None;
+        }
+
+        match event {
+            Event::Start(Tag::Heading { level: HeadingLevel::H1, .. }) => {
+                title = Some(String::new());
+            }
+            Event::Text(text) | Event::Code(text) => {
+                if let Some(title) = &mut title {
+                    title.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::Heading(HeadingLevel::H1)) => {
+                return title.filter(|_| first_region_start.is_some());
+            }
+            _ => (),
+        }
+    }
+
+    None
+}
+
+/// Removes the heading at the start of the documentation if its text is `title`, along with the
+/// blank lines that follow it.
+fn remove_title(source: &str, title: &str) -> Option<String> {
+    let mut rest_start = 0;
+    let first_line = source.split_inclusive('\n').find(|line| {
+        rest_start += line.len();
+        !line.trim().is_empty()
+    })?;
+    let text = first_line.trim().strip_prefix('#')?.trim_start_matches('#').trim();
+
+    match text.eq_ignore_ascii_case(title.trim()) {
+        true => Some(source[rest_start..].trim_start_matches(['\r', '\n']).to_owned()),
+        false => None,
+    }
+}
+
+fn fix_fence_language(fence_line: &str, fence_str: &str) -> Option<String> {
+    let indent = &fence_line[..fence_line.len() - fence_line.trim_start().len()];
+    let info = fence_line.trim()[fence_str.len()..].trim_start();
+    let language_len = info.find(|c: char| c == ',' || c.is_whitespace()).unwrap_or(info.len());
+    let (language, rest) = info.split_at(language_len);
+
+    let fixed = match language.to_ascii_lowercase().as_str() {
+        "rs" => "rust",
+        "yml" => "yaml",
+        "sh" | "bash" | "shell" | "console" | "rust" | "toml" | "yaml" | "json" | "text" => {
+            // Only the case of these is wrong, if anything.
+            return (language != language.to_ascii_lowercase())
+                .then(|| format!("{indent}{fence_str}{}{rest}", language.to_ascii_lowercase()));
+        }
+        _ => return None,
+    };
+
+    Some(format!("{indent}{fence_str}{fixed}{rest}"))
+}
+
+/// Whether the line ends with a hard line break, i.e. two or more spaces.
+fn is_hard_break(line: &str) -> bool {
+    !line.trim().is_empty() && line.ends_with("  ")
+}
+
+fn fix_lines(source: &str, skip: &[Range<usize>], context: FixContext<'_>) -> (String, Vec<Fix>) {
+    let mut fixed = String::with_capacity(source.len());
+    let mut fixes = Vec::new();
+    // The fence string of the code block we are in, and whether it is a toml code block.
+    let mut fence: Option<(String, bool)> = None;
+    let mut offset = 0;
+    let lines: Vec<&str> = source.split_inclusive('\n').collect();
+
+    for (i, line_with_terminator) in lines.iter().enumerate() {
+        let line_start = offset;
+
+        offset += line_with_terminator.len();
+
+        if skip.iter().any(|region| region.contains(&line_start)) {
+            fixed.push_str(line_with_terminator);
+            continue;
+        }
+
+        let line_number = i + 1;
+        let content_len = line_with_terminator.trim_end_matches(['\r', '\n']).len();
+        let (line, terminator) = line_with_terminator.split_at(content_len);
+        let mut line = line.to_owned();
+        let mut fix = |lint: Lint| fixes.push(Fix { lint, line_number });
+
+        match &fence {
+            None => {
+                if let Some(fence_str) = code_fence(line.trim_start()) {
+                    let fence_str = fence_str.to_owned();
+
+                    if let Some(fence_line) = fix_fence_language(&line, &fence_str) {
+                        line = fence_line;
+                        fix(Lint::FenceLanguage);
+                    }
+
+                    let info = line.trim()[fence_str.len()..].trim();
+                    let is_toml = info.split(',').next().is_some_and(|tag| tag.trim() == "toml");
+
+                    fence = Some((fence_str, is_toml));
+                } else if line.starts_with('\t') {
+                    let tabs = line.len() - line.trim_start_matches('\t').len();
+
+                    line = format!("{}{}", "    ".repeat(tabs), &line[tabs..]);
+                    fix(Lint::Tabs);
+                }
+            }
+            Some((fence_str, is_toml)) => {
+                let trimmed = line.trim();
+
+                if trimmed.starts_with(fence_str.as_str())
+                    && trimmed.chars().all(|c| fence_str.starts_with(c))
+                {
+                    fence = None;
+                } else if *is_toml {
+                    let frozen =
+                        freeze_dependency_line(&line, context.package_name, context.version);
+
+                    if frozen != line {
+                        line = frozen;
+                        fix(Lint::OutdatedVersion);
+                    }
+                }
+            }
+        }
+
+        let keep_trailing_spaces = fence.is_none()
+            && is_hard_break(&line)
+            && lines.get(i + 1).is_some_and(|next| !next.trim().is_empty());
+
+        if line.len() != line.trim_end().len() && !keep_trailing_spaces {
+            line.truncate(line.trim_end().len());
+            fix(Lint::TrailingWhitespace);
+        }
+
+        fixed.push_str(&line);
+        fixed.push_str(terminator);
+    }
+
+    (fixed, fixes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    const CONTEXT: FixContext<'static> = FixContext { package_name: "my-crate", version: "1.2.3" };
+
+    #[test]
+    fn test_fix_readme() {
+        let readme = "# My crate \n\
+                      \n\
+                      \tIndented with a tab.\n\
+                      Hard  \n\
+                      break.\n\
+                      \n\
+                      ```Rust\n\
+                      let a = 1; \n\
+                      ```\n\
+                      \n\
+                      ```toml\n\
+                      my-crate = \"0.3\"\n\
+                      ```\n\
+                      \n\
+                      <!-- cargo-rdme start -->\n\
+                      \n\
+                      Generated \n\
+                      \n\
+                      <!-- cargo-rdme end -->\n\
+                      \n\
+                      ~~~rs,ignore\n\
+                      ~~~\n";
+
+        let expected = "# My crate\n\
+                        \n\
+                        \x20   Indented with a tab.\n\
+                        Hard  \n\
+                        break.\n\
+                        \n\
+                        ```rust\n\
+                        let a = 1;\n\
+                        ```\n\
+                        \n\
+                        ```toml\n\
+                        my-crate = \"1.2\"\n\
+                        ```\n\
+                        \n\
+                        <!-- cargo-rdme start -->\n\
+                        \n\
+                        Generated \n\
+                        \n\
+                        <!-- cargo-rdme end -->\n\
+                        \n\
+                        ~~~rust,ignore\n\
+                        ~~~\n";
+
+        let (fixed, fixes) = fix_readme(&Readme::from_str(readme), CONTEXT);
+
+        assert_eq!(fixed.as_string(), expected);
+        assert_eq!(
+            fixes,
+            vec![
+                Fix { lint: Lint::TrailingWhitespace, line_number: 1 },
+                Fix { lint: Lint::Tabs, line_number: 3 },
+                Fix { lint: Lint::FenceLanguage, line_number: 7 },
+                Fix { lint: Lint::TrailingWhitespace, line_number: 8 },
+                Fix { lint: Lint::OutdatedVersion, line_number: 12 },
+                Fix { lint: Lint::FenceLanguage, line_number: 21 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fix_doc_duplicate_title() {
+        let readme = indoc! { "
+            # My crate
+
+            <!-- cargo-rdme -->
+            "
+        };
+        let doc = indoc! { "
+            # My Crate
+
+            It does things.
+            "
+        };
+
+        let (fixed, fixes) = fix_doc(&Doc::from_str(doc), &Readme::from_str(readme), CONTEXT);
+
+        assert_eq!(fixed.as_string(), "It does things.\n");
+        assert_eq!(fixes, vec![Fix { lint: Lint::DuplicateTitle, line_number: 1 }]);
+
+        let (fixed, fixes) =
+            fix_doc(&Doc::from_str("# Other\n"), &Readme::from_str(readme), CONTEXT);
+
+        assert_eq!(fixed.as_string(), "# Other\n");
+        assert!(fixes.is_empty());
+    }
+}
@@ -0,0 +1,190 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+//! Regions of the README maintained by other tools, between marker comments such as
+//! `<!-- START doctoc -->` and `<!-- END doctoc -->`, or `<!-- ALL-CONTRIBUTORS-LIST:START -->` and
+//! `<!-- ALL-CONTRIBUTORS-LIST:END -->`.  cargo rdme never injects inside them, so its own markers
+//! found there are ignored.
+
+use crate::Readme;
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// A region of the README maintained by another tool.
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[non_exhaustive]
+pub struct ForeignRegion {
+    /// Name of the tool, as in its markers, e.g. `doctoc`.
+    pub tool: String,
+    /// Line of the start marker (starting at 1).
+    pub line_number: usize,
+    /// Byte range of the region, from the start of the start marker to the end of the end marker.
+    pub range: Range<usize>,
+}
+
+/// A cargo rdme marker inside the region of another tool, which is ignored.
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[non_exhaustive]
+pub struct ForeignRegionOverlap {
+    /// Line of the cargo rdme marker (starting at 1).
+    pub line_number: usize,
+    pub region: ForeignRegion,
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+enum ForeignMarker {
+    Start,
+    End,
+}
+
+/// Parses the marker of another tool, returning its kind and the name of the tool.
+fn foreign_marker(html: &str) -> Option<(ForeignMarker, String)> {
+    let comment = html.trim().strip_prefix("<!--")?.strip_suffix("-->")?.trim();
+    let mut words = comment.split_whitespace();
+    let first = words.next()?;
+
+    let (marker, tool) = match first {
+        "BEGIN" | "START" => (ForeignMarker::Start, words.next()?),
+        "END" => (ForeignMarker::End, words.next()?),
+        _ => match first.rsplit_once(':')? {
+            (tool, "START" | "BEGIN") => (ForeignMarker::Start, tool),
+            (tool, "END") => (ForeignMarker::End, tool),
+            _ => return None,
+        },
+    };
+    let tool = tool.to_lowercase();
+
+    match tool.is_empty() || tool == "cargo-rdme" {
+        true => None,
+        false => Some((marker, tool)),
+    }
+}
+
+/// The regions of the README maintained by other tools.  Markers in code blocks, or without their
+/// matching marker, are ignored.
+pub(crate) fn foreign_regions_of(source: &str) -> Vec<ForeignRegion> {
+    use pulldown_cmark::{Event, Options, Parser};
+
+    let mut open: HashMap<String, (usize, usize)> = HashMap::new();
+    let mut regions = Vec::new();
+
+    for (event, range) in Parser::new_ext(source, Options::all()).into_offset_iter() {
+        let Event::Html(html) = event else {
+            continue;
+        };
+        let line_start = source[..range.start].rfind('\n').map_or(0, |i| i + 1);
+
+        if !source[line_start..range.start].trim().is_empty() {
+            continue;
+        }
+
+        match foreign_marker(&html) {
+            Some((ForeignMarker::Start, tool)) => {
+                let line_number = source[..line_start].matches('\n').count() + 1;
+
+                open.entry(tool).or_insert((line_start, line_number));
+            }
+            Some((ForeignMarker::End, tool)) => {
+                if let Some((start, line_number)) = open.remove(&tool) {
+                    let line_end = source[range.start..]
+                        .find('\n')
+                        .map_or(source.len(), |i| range.start + i + 1);
+
+                    regions.push(ForeignRegion { tool, line_number, range: start..line_end });
+                }
+            }
+            None => (),
+        }
+    }
+
+    regions.sort_by_key(|region| region.range.start);
+
+    regions
+}
+
+/// The regions of the README maintained by other tools.
+#[must_use]
+pub fn foreign_regions(readme: &Readme) -> Vec<ForeignRegion> {
+    foreign_regions_of(readme.as_string())
+}
+
+/// The cargo rdme markers of the README inside the regions of other tools, which are ignored.
+#[must_use]
+pub fn foreign_region_overlaps(readme: &Readme) -> Vec<ForeignRegionOverlap> {
+    use pulldown_cmark::{Event, Options, Parser};
+
+    let source = readme.as_string();
+    let regions = foreign_regions_of(source);
+
+    if regions.is_empty() {
+        return Vec::new();
+    }
+
+    Parser::new_ext(source, Options::all())
+        .into_offset_iter()
+        .filter_map(|(event, range)| match event {
+            Event::Html(html) if crate::inject_doc::is_marker(html.trim()) => {
+                let region = regions.iter().find(|r| r.range.contains(&range.start))?;
+                let line_number = source[..range.start].matches('\n').count() + 1;
+
+                Some(ForeignRegionOverlap { line_number, region: region.clone() })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_foreign_marker() {
+        assert_eq!(
+            foreign_marker("<!-- START doctoc generated TOC please keep comment here -->"),
+            Some((ForeignMarker::Start, "doctoc".to_owned()))
+        );
+        assert_eq!(
+            foreign_marker("<!-- END mktoc -->"),
+            Some((ForeignMarker::End, "mktoc".to_owned()))
+        );
+        assert_eq!(
+            foreign_marker("<!-- ALL-CONTRIBUTORS-LIST:START - Do not remove -->"),
+            Some((ForeignMarker::Start, "all-contributors-list".to_owned()))
+        );
+        assert_eq!(foreign_marker("<!-- cargo-rdme start -->"), None);
+        assert_eq!(foreign_marker("<!-- start of the introduction -->"), None);
+        assert_eq!(foreign_marker("<!-- END -->"), None);
+    }
+
+    #[test]
+    fn test_foreign_region_overlaps() {
+        let readme = indoc! { r#"
+            # Foo
+
+            <!-- BEGIN mktoc -->
+            - [Foo](#foo)
+            <!-- cargo-rdme -->
+            <!-- END mktoc -->
+
+            ```markdown
+            <!-- BEGIN other -->
+            ```
+
+            <!-- cargo-rdme -->
+            "#
+        };
+        let readme = Readme::from_str(readme);
+        let region = ForeignRegion { tool: "mktoc".to_owned(), line_number: 3, range: 7..81 };
+
+        assert_eq!(foreign_regions(&readme), vec![region.clone()]);
+        assert_eq!(
+            foreign_region_overlaps(&readme),
+            vec![ForeignRegionOverlap { line_number: 5, region }]
+        );
+    }
+}
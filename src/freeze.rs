@@ -0,0 +1,169 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+//! Pins the README to a specific version of the crate, which is useful when releasing a new
+//! version of the crate.
+
+use crate::Readme;
+
+/// Rewrites the README so that it refers to `version` of the crate:
+///
+/// * Links to the `latest` version of the crate documentation in docs.rs will point to `version`.
+/// * Dependency snippets in toml code blocks (e.g. `mycrate = "1.2"`) will use `version`, keeping
+///   the same number of version components.
+#[must_use]
+pub fn freeze_readme(readme: &Readme, package_name: &str, version: &str) -> Readme {
+    let latest_url = format!("https://docs.rs/{package_name}/latest/");
+    let frozen_url = format!("https://docs.rs/{package_name}/{version}/");
+
+    let mut new_readme = String::with_capacity(readme.as_string().len());
+    let mut fence: Option<(&str, bool)> = None;
+
+    for line in readme.as_string().split_inclusive('\n') {
+        let trimmed = line.trim_start();
+
+        let line = match fence {
+            None => {
+                if let Some(fence_str) = code_fence(trimmed) {
+                    let info = trimmed[fence_str.len()..].trim();
+                    let is_toml = info.split(',').next().is_some_and(|tag| tag.trim() == "toml");
+
+                    fence = Some((fence_str, is_toml));
+                }
+
+                line.replace(&latest_url, &frozen_url)
+            }
+            Some((fence_str, is_toml)) => {
+                if trimmed.trim_end().starts_with(fence_str)
+                    && trimmed.trim_end().chars().all(|c| fence_str.starts_with(c))
+                {
+                    fence = None;
+                }
+
+                match is_toml {
+                    true => freeze_dependency_line(line, package_name, version),
+                    false => line.to_owned(),
+                }
+            }
+        };
+
+        new_readme.push_str(&line);
+    }
+
+    Readme::from_str(new_readme)
+}
+
+pub fn code_fence(line: &str) -> Option<&str> {
+    ["```", "~~~"].into_iter().find(|fence| line.starts_with(fence)).map(|fence| {
+        let c = fence.chars().next().unwrap();
+        let len = line.chars().take_while(|&d| d == c).count();
+
+        &line[..len]
+    })
+}
+
+/// Replaces the version in `<package_name> = "<version>"` or
+/// `<package_name> = { version = "<version>", ... }`.
+pub fn freeze_dependency_line(line: &str, package_name: &str, version: &str) -> String {
+    let Some(rest) = line.trim_start().strip_prefix(package_name) else {
+        return line.to_owned();
+    };
+    let Some(value) = rest.trim_start().strip_prefix('=') else {
+        return line.to_owned();
+    };
+
+    let value_start = line.len() - value.len();
+    let version_start = match value.trim_start().strip_prefix('{') {
+        None => value_start,
+        Some(table) => match table.find("version") {
+            Some(i) => line.len() - table.len() + i + "version".len(),
+            None => return line.to_owned(),
+        },
+    };
+
+    let Some(quote_start) = line[version_start..].find('"').map(|i| version_start + i + 1) else {
+        return line.to_owned();
+    };
+    let Some(quote_end) = line[quote_start..].find('"').map(|i| quote_start + i) else {
+        return line.to_owned();
+    };
+
+    let old_version = &line[quote_start..quote_end];
+    let new_version = same_precision(old_version, version);
+
+    format!("{}{}{}", &line[..quote_start], new_version, &line[quote_end..])
+}
+
+/// Truncates `version` to the same number of components of `reference`, e.g. `1.2` and `3.4.5`
+/// results in `3.4`.  Version requirement operators of `reference` (e.g. `^` or `~`) are kept.
+fn same_precision(reference: &str, version: &str) -> String {
+    let operator_len =
+        reference.len() - reference.trim_start_matches(|c: char| !c.is_ascii_digit()).len();
+    let (operator, reference) = reference.split_at(operator_len);
+    let components = reference.split('.').count();
+    let version: Vec<&str> = version.split('.').take(components).collect();
+
+    format!("{operator}{}", version.join("."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_freeze_readme() {
+        let readme = indoc! { r#"
+            # My crate
+
+            See the [docs](https://docs.rs/my-crate/latest/my_crate/) and the
+            [docs of another crate](https://docs.rs/other/latest/other/).
+
+            ```toml
+            [dependencies]
+            my-crate = "0.3"
+            my-crate-derive = "0.3"
+            other = "1.0.0"
+            ```
+
+            ```toml
+            my-crate = { version = "~0.3.1", features = ["serde"] }
+            ```
+
+            ```
+            my-crate = "0.3"
+            ```
+            "#
+        };
+
+        let expected = indoc! { r#"
+            # My crate
+
+            See the [docs](https://docs.rs/my-crate/1.2.3/my_crate/) and the
+            [docs of another crate](https://docs.rs/other/latest/other/).
+
+            ```toml
+            [dependencies]
+            my-crate = "1.2"
+            my-crate-derive = "0.3"
+            other = "1.0.0"
+            ```
+
+            ```toml
+            my-crate = { version = "~1.2.3", features = ["serde"] }
+            ```
+
+            ```
+            my-crate = "0.3"
+            ```
+            "#
+        };
+
+        let frozen = freeze_readme(&Readme::from_str(readme), "my-crate", "1.2.3");
+
+        assert_eq!(frozen.as_string(), expected);
+    }
+}
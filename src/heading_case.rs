@@ -0,0 +1,204 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+//! Lint of the capitalization of the headings of the README, which often differs between the
+//! injected documentation and the sections written by hand.
+
+use crate::Readme;
+use std::fmt::{Display, Formatter};
+
+/// Words that are not capitalized in title case, unless they are the first word.
+const MINOR_WORDS: [&str; 19] = [
+    "a", "an", "and", "as", "at", "but", "by", "for", "from", "in", "into", "nor", "of", "on",
+    "or", "the", "to", "vs", "with",
+];
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[non_exhaustive]
+pub enum HeadingCase {
+    /// Only the first word is capitalized, e.g. “Getting started”.
+    Sentence,
+    /// Every word except minor words is capitalized, e.g. “Getting Started”.
+    Title,
+}
+
+impl Display for HeadingCase {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HeadingCase::Sentence => f.write_str("sentence case"),
+            HeadingCase::Title => f.write_str("title case"),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[non_exhaustive]
+pub struct HeadingCaseMismatch {
+    /// Line of the heading in the README (starting at 1).
+    pub line_number: usize,
+    /// The text of the heading.
+    pub heading: String,
+    /// The case the heading should be in.
+    pub expected: HeadingCase,
+}
+
+/// The case of a heading, if it can be told.  Only words after the first one that are not minor
+/// words are considered, and words with other capital letters, like “GitHub” or “README”, are
+/// ignored since they are written the same way in both cases, as is inline code.  A single
+/// capitalized word is not enough to tell the heading is in title case, since it can be a name, as
+/// in “Using Tokio”.
+fn heading_case(heading: &str) -> Option<HeadingCase> {
+    let text: Vec<&str> = heading.split('`').step_by(2).collect();
+    let words = text
+        .iter()
+        .flat_map(|text| text.split_whitespace())
+        .skip(1)
+        .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric()))
+        .filter(|word| !MINOR_WORDS.contains(&word.to_lowercase().as_str()))
+        .filter(|word| word.chars().all(char::is_alphabetic))
+        .filter(|word| word.chars().skip(1).all(char::is_lowercase));
+    let (mut capitalized, mut lowercase) = (0, 0);
+
+    for word in words {
+        match word.starts_with(char::is_uppercase) {
+            true => capitalized += 1,
+            false => lowercase += 1,
+        }
+    }
+
+    match (capitalized, lowercase) {
+        (_, 1..) => Some(HeadingCase::Sentence),
+        (2.., 0) => Some(HeadingCase::Title),
+        _ => None,
+    }
+}
+
+/// The headings of the README, with their line numbers.
+fn headings(readme: &Readme) -> Vec<(usize, String)> {
+    use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+
+    let source = readme.as_string();
+    let mut headings: Vec<(usize, String)> = Vec::new();
+    let mut in_heading = false;
+
+    for (event, range) in Parser::new_ext(source, Options::all()).into_offset_iter() {
+        match event {
+            Event::Start(Tag::Heading { .. }) => {
+                let line_number = 1 + source[..range.start].matches('\n').count();
+
+                headings.push((line_number, String::new()));
+                in_heading = true;
+            }
+            Event::End(TagEnd::Heading(_)) => in_heading = false,
+            Event::Text(text) if in_heading => {
+                if let Some((_, heading)) = headings.last_mut() {
+                    heading.push_str(&text);
+                }
+            }
+            Event::Code(code) if in_heading => {
+                if let Some((_, heading)) = headings.last_mut() {
+                    heading.push('`');
+                    heading.push_str(&code);
+                    heading.push('`');
+                }
+            }
+            _ => (),
+        }
+    }
+
+    headings
+}
+
+/// Finds the headings of the README that are not in the `expected` case.  If no case is expected
+/// the headings must be consistent: the case of most headings is expected of every heading.
+#[must_use]
+pub fn heading_case_mismatches(
+    readme: &Readme,
+    expected: Option<HeadingCase>,
+) -> Vec<HeadingCaseMismatch> {
+    let headings: Vec<(usize, String, HeadingCase)> = headings(readme)
+        .into_iter()
+        .filter_map(|(line_number, heading)| {
+            let case = heading_case(&heading)?;
+
+            Some((line_number, heading, case))
+        })
+        .collect();
+
+    let expected = match expected {
+        Some(expected) => expected,
+        None => {
+            let title_count = headings.iter().filter(|(_, _, case)| *case == HeadingCase::Title);
+            let title_count = title_count.count();
+            let sentence_count = headings.len() - title_count;
+
+            match title_count.cmp(&sentence_count) {
+                std::cmp::Ordering::Less => HeadingCase::Sentence,
+                std::cmp::Ordering::Greater => HeadingCase::Title,
+                std::cmp::Ordering::Equal => return Vec::new(),
+            }
+        }
+    };
+
+    headings
+        .into_iter()
+        .filter(|(_, _, case)| *case != expected)
+        .map(|(line_number, heading, _)| HeadingCaseMismatch { line_number, heading, expected })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_heading_case() {
+        assert_eq!(heading_case("Getting started"), Some(HeadingCase::Sentence));
+        assert_eq!(heading_case("Getting Started"), None);
+        assert_eq!(heading_case("Getting Started With Cargo"), Some(HeadingCase::Title));
+        assert_eq!(heading_case("The Art of the Deal"), Some(HeadingCase::Title));
+        assert_eq!(heading_case("Using Tokio"), None);
+        assert_eq!(heading_case("Using the GitHub API"), None);
+        assert_eq!(heading_case("Usage"), None);
+        assert_eq!(heading_case("Mixed Case in some headings"), Some(HeadingCase::Sentence));
+        assert_eq!(heading_case("The `Foo` Type"), None);
+    }
+
+    #[test]
+    fn test_heading_case_mismatches() {
+        let readme = indoc! { "
+            # My crate
+
+            ## Getting started
+
+            ## How It Works
+
+            ## Feature flags
+
+            ## The `foo` function and its friends
+            "
+        };
+        let readme = Readme::from_str(readme);
+        let mismatch = |line_number: usize, heading: &str, expected: HeadingCase| {
+            HeadingCaseMismatch { line_number, heading: heading.to_owned(), expected }
+        };
+
+        assert_eq!(
+            heading_case_mismatches(&readme, None),
+            vec![mismatch(5, "How It Works", HeadingCase::Sentence)]
+        );
+        assert_eq!(
+            heading_case_mismatches(&readme, Some(HeadingCase::Title)),
+            vec![
+                mismatch(1, "My crate", HeadingCase::Title),
+                mismatch(3, "Getting started", HeadingCase::Title),
+                mismatch(7, "Feature flags", HeadingCase::Title),
+                mismatch(9, "The `foo` function and its friends", HeadingCase::Title),
+            ]
+        );
+    }
+}
@@ -0,0 +1,193 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+//! The history of the READMEs written by cargo rdme, kept in `target/cargo-rdme/history/`, so that
+//! `cargo rdme undo` can restore the READMEs written by the last run.
+//!
+//! Each write of a README is a line of the `log` file, with the run it belongs to, the hashes of
+//! the README before and after, and its path.  The contents are kept in files named after their
+//! hash.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+/// Packages processed in parallel record their writes concurrently.
+static LOG_LOCK: Mutex<()> = Mutex::new(());
+
+/// Identifies the current run in the log.
+fn run_id() -> &'static str {
+    static RUN_ID: OnceLock<String> = OnceLock::new();
+
+    RUN_ID.get_or_init(|| {
+        let since_epoch =
+            std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default();
+
+        format!(
+            "{}.{:09}-{}",
+            since_epoch.as_secs(),
+            since_epoch.subsec_nanos(),
+            std::process::id()
+        )
+    })
+}
+
+/// FNV-1a hash of the exact content, which is stable across rust versions and platforms.
+pub fn content_hash(content: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    content.iter().fold(OFFSET_BASIS, |hash, &byte| (hash ^ u64::from(byte)).wrapping_mul(PRIME))
+}
+
+/// A write of a README.
+#[derive(PartialEq, Eq, Debug)]
+pub struct HistoryEntry {
+    run_id: String,
+    pub readme_path: PathBuf,
+    pub old_hash: u64,
+    pub new_hash: u64,
+}
+
+impl HistoryEntry {
+    fn parse(line: &str) -> Option<HistoryEntry> {
+        let mut fields = line.splitn(4, '\t');
+        let run_id = fields.next()?.to_owned();
+        let old_hash = u64::from_str_radix(fields.next()?, 16).ok()?;
+        let new_hash = u64::from_str_radix(fields.next()?, 16).ok()?;
+        let readme_path = PathBuf::from(fields.next()?);
+
+        Some(HistoryEntry { run_id, readme_path, old_hash, new_hash })
+    }
+
+    fn to_line(&self) -> String {
+        format!(
+            "{}\t{:016x}\t{:016x}\t{}\n",
+            self.run_id,
+            self.old_hash,
+            self.new_hash,
+            self.readme_path.display()
+        )
+    }
+}
+
+pub struct History {
+    dir: PathBuf,
+}
+
+impl History {
+    pub fn new(target_directory: &Path) -> History {
+        History { dir: target_directory.join("cargo-rdme").join("history") }
+    }
+
+    fn log_path(&self) -> PathBuf {
+        self.dir.join("log")
+    }
+
+    fn content_path(&self, hash: u64) -> PathBuf {
+        self.dir.join(format!("{hash:016x}"))
+    }
+
+    fn entries(&self) -> std::io::Result<Vec<HistoryEntry>> {
+        match std::fs::read_to_string(self.log_path()) {
+            Ok(log) => Ok(log.lines().filter_map(HistoryEntry::parse).collect()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Records that the README was overwritten, with its content before and after.
+    pub fn record(&self, readme_path: &Path, old: &[u8], new: &[u8]) -> std::io::Result<()> {
+        let entry = HistoryEntry {
+            run_id: run_id().to_owned(),
+            readme_path: std::env::current_dir()?.join(readme_path),
+            old_hash: content_hash(old),
+            new_hash: content_hash(new),
+        };
+        let _lock = LOG_LOCK.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::write(self.content_path(entry.old_hash), old)?;
+        std::fs::write(self.content_path(entry.new_hash), new)?;
+
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.log_path())?
+            .write_all(entry.to_line().as_bytes())
+    }
+
+    /// The writes of the last run recorded, in the order they were made.
+    pub fn last_run(&self) -> std::io::Result<Vec<HistoryEntry>> {
+        let mut entries = self.entries()?;
+        let Some(last_run_id) = entries.last().map(|entry| entry.run_id.clone()) else {
+            return Ok(Vec::new());
+        };
+
+        entries.retain(|entry| entry.run_id == last_run_id);
+
+        Ok(entries)
+    }
+
+    pub fn content(&self, hash: u64) -> std::io::Result<Vec<u8>> {
+        std::fs::read(self.content_path(hash))
+    }
+
+    /// Removes the last run from the history, once it was undone, so that undoing again goes back
+    /// to the run before it.  The contents no longer referenced by the log are removed too.
+    pub fn forget_last_run(&self) -> std::io::Result<()> {
+        let mut entries = self.entries()?;
+        let Some(last_run_id) = entries.last().map(|entry| entry.run_id.clone()) else {
+            return Ok(());
+        };
+        let (forgotten, kept): (Vec<HistoryEntry>, Vec<HistoryEntry>) =
+            entries.drain(..).partition(|entry| entry.run_id == last_run_id);
+        let log: String = kept.iter().map(HistoryEntry::to_line).collect();
+
+        std::fs::write(self.log_path(), log)?;
+
+        for entry in forgotten {
+            for hash in [entry.old_hash, entry.new_hash] {
+                let referenced = kept.iter().any(|e| e.old_hash == hash || e.new_hash == hash);
+
+                if !referenced {
+                    std::fs::remove_file(self.content_path(hash)).or_else(|e| match e.kind() {
+                        std::io::ErrorKind::NotFound => Ok(()),
+                        _ => Err(e),
+                    })?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_history_entry_line() {
+        let entry = HistoryEntry {
+            run_id: "1700000000.000000001-42".to_owned(),
+            readme_path: PathBuf::from("/foo/README with\ttab.md"),
+            old_hash: content_hash(b"old"),
+            new_hash: 0x0123,
+        };
+        let line = entry.to_line();
+
+        assert!(line.ends_with("\t0000000000000123\t/foo/README with\ttab.md\n"));
+        assert_eq!(HistoryEntry::parse(line.trim_end_matches('\n')), Some(entry));
+        assert_eq!(HistoryEntry::parse("garbage"), None);
+    }
+
+    #[test]
+    fn test_content_hash() {
+        assert_eq!(content_hash(b""), 0xcbf2_9ce4_8422_2325);
+        assert_ne!(content_hash(b"# Foo\n"), content_hash(b"# Foo\r\n"));
+    }
+}
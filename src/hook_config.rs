@@ -0,0 +1,46 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+//! Configuration to run `cargo rdme --check` from other tools, as printed by
+//! `cargo rdme hook-config`.  These rely on the arguments and exit codes of the check mode, which
+//! are stable.
+
+use crate::options::HookConfigOpt;
+use indoc::indoc;
+
+/// The hooks of the pre-commit framework, which is also in the root of the repository so that the
+/// repository can be used directly in a `.pre-commit-config.yaml`.
+const PRE_COMMIT_HOOKS: &str = include_str!("../.pre-commit-hooks.yaml");
+
+const GITLAB_CI: &str = indoc! { "
+    # Checks that the README is up to date with the crate's documentation.  Add this job to your
+    # `.gitlab-ci.yml`.
+    cargo-rdme:
+      stage: test
+      image: rust:latest
+      script:
+        - cargo install cargo-rdme
+        - cargo rdme --check
+    "
+};
+
+#[must_use]
+pub fn hook_config(hook_config: HookConfigOpt) -> &'static str {
+    match hook_config {
+        HookConfigOpt::PreCommit => PRE_COMMIT_HOOKS,
+        HookConfigOpt::GitLabCi => GITLAB_CI,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hook_config_runs_check() {
+        assert!(hook_config(HookConfigOpt::PreCommit).contains("entry: cargo-rdme --check\n"));
+        assert!(hook_config(HookConfigOpt::GitLabCi).contains("- cargo rdme --check\n"));
+    }
+}
@@ -3,13 +3,22 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/.
  */
 
+use crate::foreign_regions::foreign_regions_of;
+use crate::freeze::code_fence;
+use crate::transform::setext_to_atx;
 use crate::utils::{ItemOrOther, MarkdownItemIterator, Span};
-use crate::{Doc, Readme};
+use crate::{content_line_terminator, Doc, LineTerminator, Readme};
+use std::fmt::Write;
+use std::io::BufRead;
+use std::ops::Range;
 use thiserror::Error;
 
 pub const MARKER_RDME: &str = "<!-- cargo-rdme -->";
 const MARKER_RDME_START: &str = "<!-- cargo-rdme start -->";
 const MARKER_RDME_END: &str = "<!-- cargo-rdme end -->";
+const MARKER_RDME_END_HASH_PREFIX: &str = "<!-- cargo-rdme end hash:";
+/// Replaces the `<!--` of markers found in the documentation.
+const MARKER_ESCAPE: &str = "<!-- escaped:";
 
 #[derive(PartialEq, Eq, Clone, Debug)]
 struct Heading<'a> {
@@ -17,6 +26,39 @@ struct Heading<'a> {
     text: &'a str,
 }
 
+/// Hash of the content between the markers.  The content is normalized so that the hash doesn't
+/// depend on line terminators or surrounding blank lines.
+pub fn hash_content(content: &str) -> u64 {
+    // FNV-1a, which is stable across rust versions and platforms, unlike the std hashers.
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    content.trim().lines().enumerate().fold(OFFSET_BASIS, |hash, (i, line)| {
+        let separator: &[u8] = if i == 0 { &[] } else { b"\n" };
+
+        separator
+            .iter()
+            .chain(line.as_bytes())
+            .fold(hash, |hash, &byte| (hash ^ u64::from(byte)).wrapping_mul(PRIME))
+    })
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+enum MarkerEnd {
+    Plain,
+    WithContentHash(u64),
+}
+
+fn parse_marker_end(line: &str) -> Option<MarkerEnd> {
+    if line == MARKER_RDME_END {
+        return Some(MarkerEnd::Plain);
+    }
+
+    let hash = line.strip_prefix(MARKER_RDME_END_HASH_PREFIX)?.strip_suffix("-->")?.trim();
+
+    u64::from_str_radix(hash, 16).ok().map(MarkerEnd::WithContentHash)
+}
+
 #[derive(PartialEq, Eq, Clone, Debug)]
 enum ReadmeLine<'a> {
     Heading(Heading<'a>, Span),
@@ -25,14 +67,21 @@ enum ReadmeLine<'a> {
     MarkerCargoRdmeEnd(Span),
 }
 
-fn readme_line_iterator(readme: &Readme) -> MarkdownItemIterator<ReadmeLine> {
+fn is_line_start(source: &str, start: usize) -> bool {
+    start == 0 || source[0..start].chars().rev().find(|&c| c != ' ') == Some('\n')
+}
+
+fn readme_line_iterator(readme: &Readme) -> MarkdownItemIterator<'_, ReadmeLine<'_>> {
     use pulldown_cmark::{Event, Options, Parser, Tag};
 
     let source = readme.as_string();
     let parser = Parser::new_ext(source, Options::all());
 
-    let is_line_start =
-        |start| start == 0 || source[0..start].chars().rev().find(|&c| c != ' ') == Some('\n');
+    let foreign_regions = foreign_regions_of(source);
+    // Markers inside the regions of other tools are not ours to replace.
+    let is_line_start = move |start| {
+        is_line_start(source, start) && !foreign_regions.iter().any(|r| r.range.contains(&start))
+    };
     let mut depth = 0;
 
     let iter = parser.into_offset_iter().filter_map(move |(event, range)| match event {
@@ -54,7 +103,7 @@ fn readme_line_iterator(readme: &Readme) -> MarkdownItemIterator<ReadmeLine> {
                     depth += 1;
                     Some((range.clone().into(), ReadmeLine::MarkerCargoRdmeStart(range.into())))
                 }
-                MARKER_RDME_END if depth <= 1 => {
+                line if depth <= 1 && parse_marker_end(line).is_some() => {
                     depth -= 1;
                     Some((range.clone().into(), ReadmeLine::MarkerCargoRdmeEnd(range.into())))
                 }
@@ -62,7 +111,7 @@ fn readme_line_iterator(readme: &Readme) -> MarkdownItemIterator<ReadmeLine> {
                     depth += 1;
                     None
                 }
-                MARKER_RDME_END => {
+                line if parse_marker_end(line).is_some() => {
                     depth -= 1;
                     None
                 }
@@ -75,7 +124,7 @@ fn readme_line_iterator(readme: &Readme) -> MarkdownItemIterator<ReadmeLine> {
     MarkdownItemIterator::new(source, iter)
 }
 
-fn doc_heading_iterator(doc: &Doc) -> MarkdownItemIterator<Heading> {
+fn doc_heading_iterator(doc: &Doc) -> MarkdownItemIterator<'_, Heading<'_>> {
     use pulldown_cmark::{Event, Options, Parser, Tag};
 
     let source = doc.as_string();
@@ -93,11 +142,27 @@ fn doc_heading_iterator(doc: &Doc) -> MarkdownItemIterator<Heading> {
 }
 
 #[derive(Error, Eq, PartialEq, Debug)]
+#[non_exhaustive]
 pub enum InjectDocError {
     #[error("unexpected end marker at line {line_number}")]
     UnexpectedMarkerCargoRdmeEnd { line_number: usize },
-    #[error("unmatched start marker")]
-    UnmatchedMarkerCargoRdmeStart,
+    #[error("unmatched start marker at line {line_number}")]
+    UnmatchedMarkerCargoRdmeStart { line_number: usize },
+    /// This is a bug: injecting the documentation must never change the README outside of the
+    /// markers.
+    #[error("the README would change outside the markers, at line {line_number}")]
+    ChangedOutsideMarkers { line_number: usize },
+}
+
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum InjectDocStreamError {
+    #[error("failed to read the README: {0}")]
+    ErrorReading(std::io::Error),
+    #[error("failed to write the README: {0}")]
+    ErrorWriting(std::io::Error),
+    #[error(transparent)]
+    InjectDoc(#[from] InjectDocError),
 }
 
 fn bump_heading_level(doc: &Doc, level_bump: u8) -> Doc {
@@ -105,10 +170,17 @@ fn bump_heading_level(doc: &Doc, level_bump: u8) -> Doc {
 
     for item in doc_heading_iterator(doc).complete() {
         match item {
-            ItemOrOther::Item(Heading { text, .. }) => {
-                (0..level_bump).for_each(|_| new_doc.push('#'));
-                new_doc.push_str(text);
+            // Setext headings cannot go beyond level two, so they become ATX headings.
+            ItemOrOther::Item(Heading { level, text }) if level_bump > 0 => {
+                match setext_to_atx(text, usize::from(level + level_bump)) {
+                    Some(atx) => new_doc.push_str(&atx),
+                    None => {
+                        (0..level_bump).for_each(|_| new_doc.push('#'));
+                        new_doc.push_str(text);
+                    }
+                }
             }
+            ItemOrOther::Item(Heading { text, .. }) => new_doc.push_str(text),
             ItemOrOther::Other(other) => {
                 new_doc.push_str(other);
             }
@@ -118,18 +190,328 @@ fn bump_heading_level(doc: &Doc, level_bump: u8) -> Doc {
     Doc::from_str(new_doc)
 }
 
+/// Adds a top level heading to the documentation.  The documentation headings are nested under the
+/// new heading.
+pub fn prepend_heading(doc: &Doc, heading: &str) -> Doc {
+    let doc = bump_heading_level(doc, 1);
+    let mut new_doc = String::with_capacity(doc.as_string().len() + heading.len() + 4);
+
+    new_doc.push_str("# ");
+    new_doc.push_str(heading);
+    new_doc.push_str("\n\n");
+    new_doc.push_str(doc.as_string());
+
+    Doc::from_str(new_doc)
+}
+
+#[non_exhaustive]
 pub struct NewReadme {
     pub readme: Readme,
     /// Weather the README had a cargo-rdme marker or not.
     pub had_marker: bool,
+    /// Line numbers of the start markers whose content doesn't match the hash in their end marker,
+    /// i.e. the generated content was edited by hand.
+    pub hand_edited_regions: Vec<usize>,
 }
 
-pub fn inject_doc_in_readme(
+/// The content hash carried by an end marker of a README, and the hash of the actual content
+/// between the markers.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[non_exhaustive]
+pub struct RegionContentHash {
+    pub marker_hash: u64,
+    pub content_hash: u64,
+}
+
+impl RegionContentHash {
+    /// Whether the content is the same as when it was generated.
+    #[must_use]
+    pub fn is_pristine(&self) -> bool {
+        self.marker_hash == self.content_hash
+    }
+}
+
+/// Whether the README has cargo rdme markers.
+#[must_use]
+pub fn readme_has_markers(readme: &Readme) -> bool {
+    readme_line_iterator(readme).items().any(|item| {
+        matches!(item, ReadmeLine::MarkerCargoRdme(_) | ReadmeLine::MarkerCargoRdmeStart(_))
+    })
+}
+
+/// Returns the content hashes of the regions of the README whose end marker carries a hash.
+#[must_use]
+pub fn region_content_hashes(readme: &Readme) -> Vec<RegionContentHash> {
+    let source = readme.as_string();
+    let mut content_start: Option<usize> = None;
+    let mut hashes = Vec::new();
+
+    for item in readme_line_iterator(readme).items() {
+        match item {
+            ReadmeLine::MarkerCargoRdmeStart(span) => content_start = Some(span.end),
+            ReadmeLine::MarkerCargoRdmeEnd(span) => {
+                let marker = source[span.start..span.end].trim();
+
+                if let (Some(start), Some(MarkerEnd::WithContentHash(marker_hash))) =
+                    (content_start.take(), parse_marker_end(marker))
+                {
+                    let content_hash = hash_content(&source[start..span.start]);
+
+                    hashes.push(RegionContentHash { marker_hash, content_hash });
+                }
+            }
+            ReadmeLine::Heading(..) | ReadmeLine::MarkerCargoRdme(_) => (),
+        }
+    }
+
+    hashes
+}
+
+/// Byte ranges of the regions of the README generated by cargo rdme, from the start of the start
+/// marker to the end of the end marker.
+#[must_use]
+pub fn generated_regions(readme: &Readme) -> Vec<Range<usize>> {
+    let mut region_start: Option<usize> = None;
+    let mut regions = Vec::new();
+
+    for item in readme_line_iterator(readme).items() {
+        match item {
+            ReadmeLine::MarkerCargoRdmeStart(span) => region_start = Some(span.start),
+            ReadmeLine::MarkerCargoRdmeEnd(span) => {
+                if let Some(start) = region_start.take() {
+                    regions.push(start..span.end);
+                }
+            }
+            ReadmeLine::MarkerCargoRdme(span) => regions.push(span.start..span.end),
+            ReadmeLine::Heading(..) => (),
+        }
+    }
+
+    regions
+}
+
+/// Closes the fenced code block the documentation ends in, if any.  Rustdoc closes it implicitly,
+/// but in the README it would swallow the end marker, and everything after it.
+fn close_code_block(doc: &Doc) -> Option<Doc> {
+    use pulldown_cmark::{CodeBlockKind, Event, Options, Parser, Tag};
+
+    let source = doc.as_string();
+    let (_, block) =
+        Parser::new_ext(source, Options::all()).into_offset_iter().find(|(event, range)| {
+            matches!(event, Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(_))))
+                && range.end == source.len()
+        })?;
+    let block = &source[block];
+    let fence = code_fence(block.trim_start())?;
+    let fence_char = fence.chars().next()?;
+    let closed = block.trim_end().lines().skip(1).last().is_some_and(|line| {
+        let line = line.trim();
+
+        line.len() >= fence.len() && line.chars().all(|c| c == fence_char)
+    });
+
+    match closed {
+        true => None,
+        false => {
+            let terminator = if source.ends_with('\n') { "" } else { "\n" };
+
+            Some(Doc::from_str(format!("{source}{terminator}{fence}\n")))
+        }
+    }
+}
+
+pub(crate) fn is_marker(line: &str) -> bool {
+    line == MARKER_RDME || line == MARKER_RDME_START || parse_marker_end(line).is_some()
+}
+
+/// Escapes the cargo rdme markers in the documentation (e.g. of a crate that documents how to use
+/// cargo rdme), so that they are not taken for the boundaries of the generated region in later runs.
+/// Markers in code are left untouched, since they are not markers.
+fn escape_markers(doc: &Doc) -> Option<Doc> {
+    use pulldown_cmark::{Event, Options, Parser};
+
+    let source = doc.as_string();
+    let markers: Vec<usize> = Parser::new_ext(source, Options::all())
+        .into_offset_iter()
+        .filter_map(|(event, range)| match event {
+            Event::Html(html) if is_line_start(source, range.start) && is_marker(html.trim()) => {
+                source[range.clone()].find("<!--").map(|i| range.start + i)
+            }
+            _ => None,
+        })
+        .collect();
+
+    if markers.is_empty() {
+        return None;
+    }
+
+    let mut escaped = String::with_capacity(source.len() + markers.len() * MARKER_ESCAPE.len());
+    let mut last = 0;
+
+    for start in markers {
+        escaped.push_str(&source[last..start]);
+        escaped.push_str(MARKER_ESCAPE);
+        last = start + "<!--".len();
+    }
+
+    escaped.push_str(&source[last..]);
+
+    Some(Doc::from_str(escaped))
+}
+
+/// The parts of the README outside the regions generated by cargo rdme, with their byte offsets.
+fn outside_generated_regions(readme: &Readme) -> Vec<(usize, &str)> {
+    let source = readme.as_string();
+    let mut parts = Vec::new();
+    let mut last = 0;
+
+    for region in generated_regions(readme) {
+        parts.push((last, &source[last..region.start]));
+        last = region.end;
+    }
+
+    parts.push((last, &source[last..]));
+
+    parts
+}
+
+/// Returns the line of `readme` where `new_readme` first differs from it outside the generated
+/// regions, if anywhere.
+fn first_change_outside_markers(readme: &Readme, new_readme: &Readme) -> Option<usize> {
+    let source = readme.as_string();
+    let parts = outside_generated_regions(readme);
+    let new_parts = outside_generated_regions(new_readme);
+
+    let offset = (0..parts.len().max(new_parts.len())).find_map(|i| {
+        match (parts.get(i), new_parts.get(i)) {
+            (Some((start, part)), Some((_, new_part))) => {
+                let common = part.bytes().zip(new_part.bytes()).take_while(|(a, b)| a == b).count();
+
+                (part != new_part).then_some(start + common)
+            }
+            (Some((start, _)), None) => Some(*start),
+            (None, _) => Some(source.len()),
+        }
+    })?;
+
+    Some(1 + source[..offset].matches('\n').count())
+}
+
+/// The documentation as it is injected in the README: with the heading, if any, and without
+/// anything that would leak out of the generated region.
+fn doc_to_inject(doc: &Doc, heading: Option<&str>) -> Doc {
+    let doc_with_heading: Option<Doc> = heading.map(|heading| prepend_heading(doc, heading));
+    let doc: &Doc = doc_with_heading.as_ref().unwrap_or(doc);
+    let escaped_doc: Option<Doc> = escape_markers(doc);
+    let doc: &Doc = escaped_doc.as_ref().unwrap_or(doc);
+    let closed_doc: Option<Doc> = close_code_block(doc);
+
+    closed_doc.unwrap_or_else(|| doc.clone())
+}
+
+/// The level of the first heading of the README with text `title`, and the byte range of its
+/// content, from the line after the heading to the next heading of the same or a higher level.
+fn readme_section(readme: &Readme, title: &str) -> Option<(u8, Range<usize>)> {
+    use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+
+    let source = readme.as_string();
+    let line_start = |offset: usize| source[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let mut heading: Option<(u8, String)> = None;
+    let mut section: Option<(u8, usize)> = None;
+
+    for (event, range) in Parser::new_ext(source, Options::all()).into_offset_iter() {
+        match event {
+            Event::Start(Tag::Heading { level, .. }) => match section {
+                Some((section_level, content_start)) if level as u8 <= section_level => {
+                    return Some((section_level, content_start..line_start(range.start)));
+                }
+                Some(_) => (),
+                None => heading = Some((level as u8, String::new())),
+            },
+            Event::Text(text) | Event::Code(text) => {
+                if let Some((_, heading_text)) = &mut heading {
+                    heading_text.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                if let Some((level, heading_text)) = heading.take() {
+                    if heading_text.trim() == title.trim() {
+                        let last = range.end.saturating_sub(1);
+                        let content_start =
+                            source[last..].find('\n').map_or(source.len(), |i| last + i + 1);
+
+                        section = Some((level, content_start));
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    section.map(|(level, content_start)| (level, content_start..source.len()))
+}
+
+/// Injects the documentation in the README section with heading `section`, replacing its content,
+/// for READMEs without markers.  The headings of the documentation are nested under the section
+/// heading, unless `heading_base_level` is given.
+///
+/// If the README has no such section it is returned unchanged, with `had_marker` unset.
+#[must_use]
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+pub fn inject_doc_in_readme_section(
     readme: &Readme,
     doc: &Doc,
+    section: &str,
     heading_base_level: Option<u8>,
+    heading: Option<&str>,
+) -> NewReadme {
+    let source = readme.as_string();
+    let Some((section_level, content)) = readme_section(readme, section) else {
+        return NewReadme {
+            readme: readme.clone(),
+            had_marker: false,
+            hand_edited_regions: Vec::new(),
+        };
+    };
+    let doc = bump_heading_level(
+        &doc_to_inject(doc, heading),
+        heading_base_level.unwrap_or(section_level),
+    );
+    let mut new_readme = String::with_capacity(source.len() + doc.as_string().len() + 2);
+
+    new_readme.push_str(&source[..content.start]);
+    new_readme.push('\n');
+    doc.lines().for_each(|line| {
+        new_readme.push_str(line);
+        new_readme.push('\n');
+    });
+
+    if content.end < source.len() {
+        new_readme.push('\n');
+    }
+
+    new_readme.push_str(&source[content.end..]);
+
+    NewReadme {
+        readme: Readme::from_str(new_readme),
+        had_marker: true,
+        hand_edited_regions: Vec::new(),
+    }
+}
+
+/// Injects the documentation in the README.  If [`InjectOptions::content_hash`] is set, the end
+/// marker will carry a hash of the generated content, so that manual edits can be detected in later
+/// runs.
+///
+/// Only the regions between markers are changed: everything outside of them is kept byte for byte,
+/// which is verified before returning the new README.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+pub fn inject_doc_in_readme(
+    readme: &Readme,
+    doc: &Doc,
+    opts: &InjectOptions,
 ) -> Result<NewReadme, InjectDocError> {
-    fn inject(new_readme: &mut String, doc: &Doc) {
+    let inject = |new_readme: &mut String, doc: &Doc| {
         new_readme.push_str(MARKER_RDME_START);
         new_readme.push_str("\n\n");
         doc.lines().for_each(|line| {
@@ -137,64 +519,161 @@ pub fn inject_doc_in_readme(
             new_readme.push('\n');
         });
         new_readme.push('\n');
-        new_readme.push_str(MARKER_RDME_END);
+        match opts.content_hash {
+            true => {
+                let hash = hash_content(doc.as_string());
+                let _ = write!(new_readme, "{MARKER_RDME_END_HASH_PREFIX}{hash:016x} -->");
+            }
+            false => new_readme.push_str(MARKER_RDME_END),
+        }
         new_readme.push('\n');
-    }
+    };
 
+    let doc = &doc_to_inject(doc, opts.heading.as_deref());
     let mut new_readme: String =
         String::with_capacity(readme.as_string().len() + doc.as_string().len() + 1024);
-    let mut inside_markers = false;
+    let line_number = |span: Span| -> usize {
+        1 + readme.as_string()[0..span.start].chars().filter(|&c| c == '\n').count()
+    };
+
+    // Line number of the start marker and where the content starts, if we are inside the markers.
+    let mut inside_markers: Option<(usize, usize)> = None;
     let mut last_heading_level: u8 = 0;
     let mut had_marker = false;
+    let mut hand_edited_regions = Vec::new();
 
     for item in readme_line_iterator(readme).complete() {
         match (inside_markers, item) {
-            (true, ItemOrOther::Item(ReadmeLine::MarkerCargoRdmeEnd(_))) => {
-                inside_markers = false;
+            (
+                Some((line_number, content_start)),
+                ItemOrOther::Item(ReadmeLine::MarkerCargoRdmeEnd(span)),
+            ) => {
+                let source = readme.as_string();
+                let marker = source[span.start..span.end].trim();
+                let content = &source[content_start..span.start];
+
+                if let Some(MarkerEnd::WithContentHash(hash)) = parse_marker_end(marker) {
+                    if hash != hash_content(content) {
+                        hand_edited_regions.push(line_number);
+                    }
+                }
+
+                inside_markers = None;
             }
-            (true, _) => (),
+            (Some(_), _) => (),
 
-            (false, ItemOrOther::Item(ReadmeLine::MarkerCargoRdmeEnd(span))) => {
-                let line_number =
-                    1 + readme.as_string()[0..span.start].chars().filter(|&c| c == '\n').count();
+            (None, ItemOrOther::Item(ReadmeLine::MarkerCargoRdmeEnd(span))) => {
+                let line_number = line_number(span);
 
                 return Err(InjectDocError::UnexpectedMarkerCargoRdmeEnd { line_number });
             }
-            (false, ItemOrOther::Item(ReadmeLine::Heading(Heading { level, text }, _))) => {
+            (None, ItemOrOther::Item(ReadmeLine::Heading(Heading { level, text }, _))) => {
                 new_readme.push_str(text);
                 last_heading_level = level;
             }
-            (false, ItemOrOther::Other(other)) => new_readme.push_str(other),
-            (false, ItemOrOther::Item(ReadmeLine::MarkerCargoRdme(_))) => {
-                let level_bump = heading_base_level.unwrap_or(last_heading_level);
+            (None, ItemOrOther::Other(other)) => new_readme.push_str(other),
+            (None, ItemOrOther::Item(ReadmeLine::MarkerCargoRdme(_))) => {
+                let level_bump = opts.heading_base_level.unwrap_or(last_heading_level);
                 let doc = bump_heading_level(doc, level_bump);
                 inject(&mut new_readme, &doc);
                 had_marker = true;
             }
-            (false, ItemOrOther::Item(ReadmeLine::MarkerCargoRdmeStart(_))) => {
-                let level_bump = heading_base_level.unwrap_or(last_heading_level);
+            (None, ItemOrOther::Item(ReadmeLine::MarkerCargoRdmeStart(span))) => {
+                let level_bump = opts.heading_base_level.unwrap_or(last_heading_level);
                 let doc = bump_heading_level(doc, level_bump);
                 inject(&mut new_readme, &doc);
-                inside_markers = true;
+                let content_start = span.end;
+                inside_markers = Some((line_number(span), content_start));
                 had_marker = true;
             }
         }
     }
 
     match inside_markers {
-        true => Err(InjectDocError::UnmatchedMarkerCargoRdmeStart),
-        false => {
-            let new_readme = NewReadme { readme: Readme::from_str(new_readme), had_marker };
+        Some((line_number, _)) => {
+            Err(InjectDocError::UnmatchedMarkerCargoRdmeStart { line_number })
+        }
+        None => {
+            let new_readme = Readme::from_str(new_readme);
+
+            if let Some(line_number) = first_change_outside_markers(readme, &new_readme) {
+                return Err(InjectDocError::ChangedOutsideMarkers { line_number });
+            }
 
-            Ok(new_readme)
+            Ok(NewReadme { readme: new_readme, had_marker, hand_edited_regions })
         }
     }
 }
 
+/// Options of [`inject_doc_in_readme()`] and [`inject_doc_stream()`].
+#[derive(Default, Clone, Debug)]
+#[non_exhaustive]
+pub struct InjectOptions {
+    /// The level the headings of the documentation are bumped to.  If unset, it is the level of the
+    /// heading above the markers.
+    pub heading_base_level: Option<u8>,
+    /// A heading to add above the documentation.
+    pub heading: Option<String>,
+    /// Whether the end marker carries a hash of the generated content.
+    pub content_hash: bool,
+}
+
+impl InjectOptions {
+    #[must_use]
+    pub fn with_heading_base_level(mut self, heading_base_level: u8) -> InjectOptions {
+        self.heading_base_level = Some(heading_base_level);
+        self
+    }
+
+    #[must_use]
+    pub fn with_heading(mut self, heading: impl Into<String>) -> InjectOptions {
+        self.heading = Some(heading.into());
+        self
+    }
+
+    #[must_use]
+    pub fn with_content_hash(mut self, content_hash: bool) -> InjectOptions {
+        self.content_hash = content_hash;
+        self
+    }
+}
+
+/// Injects the documentation in the README read from `reader`, and writes the new README to
+/// `writer`.  This allows to process READMEs in memory, without files, e.g. in a server.
+///
+/// The line terminators of the README, and whether it ends with one, are kept.  Note that the whole
+/// README is read before anything is written, since the markers can only be matched in the whole
+/// README.
+///
+/// Returns whether the README had a cargo rdme marker, i.e. whether the documentation was injected.
+pub fn inject_doc_stream(
+    mut reader: impl BufRead,
+    mut writer: impl std::io::Write,
+    doc: &Doc,
+    opts: &InjectOptions,
+) -> Result<bool, InjectDocStreamError> {
+    let mut source = String::new();
+
+    reader.read_to_string(&mut source).map_err(InjectDocStreamError::ErrorReading)?;
+
+    let line_terminator: LineTerminator = content_line_terminator(&source);
+    let final_newline = source.is_empty() || source.ends_with(['\n', '\r']);
+    let new_readme = inject_doc_in_readme(&Readme::from_str(source), doc, opts)?;
+    let mut output = new_readme.readme.to_string(line_terminator);
+
+    if !final_newline {
+        output.truncate(output.trim_end_matches(['\n', '\r']).len());
+    }
+
+    writer.write_all(output.as_bytes()).map_err(InjectDocStreamError::ErrorWriting)?;
+
+    Ok(new_readme.had_marker)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use indoc::indoc;
+    use indoc::{formatdoc, indoc};
     use pretty_assertions::assert_eq;
 
     #[test]
@@ -233,29 +712,273 @@ mod tests {
     }
 
     #[test]
-    fn test_readme_line_iterator_nested() {
-        let str = indoc! { "
-            A
-            <!-- cargo-rdme start -->
-            B
+    fn test_readme_line_iterator_nested() {
+        let str = indoc! { "
+            A
+            <!-- cargo-rdme start -->
+            B
+            <!-- cargo-rdme -->
+            C
+            <!-- cargo-rdme start -->
+            D
+            <!-- cargo-rdme end -->
+            E
+            <!-- cargo-rdme end -->
+            F"
+        };
+
+        let readme = Readme::from_str(str);
+        let mut iter = readme_line_iterator(&readme).items();
+
+        // TODO Replace by `assert_matches!()` once https://github.com/rust-lang/rust/issues/82775
+        // stabilizes.
+        assert!(matches!(iter.next(), Some(ReadmeLine::MarkerCargoRdmeStart(_))));
+        assert!(matches!(iter.next(), Some(ReadmeLine::MarkerCargoRdmeEnd(_))));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn test_readme_line_iterator_code() {
+        let str = indoc! { "
+            ```markdown
+            <!-- cargo-rdme -->
+            ```
+
+            ~~~
+            <!-- cargo-rdme start -->
+            ~~~
+
+                <!-- cargo-rdme end -->
+
+            `<!-- cargo-rdme -->`
+
+            > <!-- cargo-rdme -->
+
+            - <!-- cargo-rdme start -->
+
+            <!-- cargo-rdme start -->
+
+            ````text
+            ```
+            <!-- cargo-rdme end -->
+            ```
+            ````
+
+            <!-- cargo-rdme end -->
+            "
+        };
+
+        let readme = Readme::from_str(str);
+        let mut iter = readme_line_iterator(&readme).items();
+
+        // TODO Replace by `assert_matches!()` once https://github.com/rust-lang/rust/issues/82775
+        // stabilizes.
+        assert!(matches!(iter.next(), Some(ReadmeLine::MarkerCargoRdmeStart(_))));
+        assert!(matches!(iter.next(), Some(ReadmeLine::MarkerCargoRdmeEnd(_))));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn test_inject_doc_markers_in_code() {
+        let readme_str = indoc! { r#"
+            # My crate
+
+            <!-- cargo-rdme -->
+            "#
+        };
+        let doc_str = indoc! { r#"
+            Add a marker to your README:
+
+            ```markdown
+            <!-- cargo-rdme start -->
+            <!-- cargo-rdme end -->
+            ```
+
+            The marker can be `<!-- cargo-rdme -->` too.
+
+            ```rust
+            let unterminated = "code block";
+            "#
+        };
+
+        let expected = indoc! { r#"
+            # My crate
+
+            <!-- cargo-rdme start -->
+
+            Add a marker to your README:
+
+            ```markdown
+            <!-- cargo-rdme start -->
+            <!-- cargo-rdme end -->
+            ```
+
+            The marker can be `<!-- cargo-rdme -->` too.
+
+            ```rust
+            let unterminated = "code block";
+            ```
+
+            <!-- cargo-rdme end -->
+            "#
+        };
+
+        let doc = Doc::from_str(doc_str);
+        let new_readme =
+            inject_doc_in_readme(&Readme::from_str(readme_str), &doc, &InjectOptions::default())
+                .unwrap();
+
+        assert_eq!(new_readme.readme.as_string(), expected);
+
+        let again =
+            inject_doc_in_readme(&new_readme.readme, &doc, &InjectOptions::default()).unwrap();
+
+        assert_eq!(again.readme.as_string(), expected);
+        assert_eq!(generated_regions(&again.readme).len(), 1);
+    }
+
+    #[test]
+    fn test_inject_doc_markers_in_block_quote() {
+        let readme_str = indoc! { r#"
+            # My crate
+
+            > Add a marker to your README:
+            >
+            > <!-- cargo-rdme -->
+            >
+            > > <!-- cargo-rdme start -->
+            > > <!-- cargo-rdme end -->
+
+            <!-- cargo-rdme -->
+            "#
+        };
+        let doc_str = indoc! { r#"
+            > Quoted documentation.
+            "#
+        };
+
+        let expected = indoc! { r#"
+            # My crate
+
+            > Add a marker to your README:
+            >
+            > <!-- cargo-rdme -->
+            >
+            > > <!-- cargo-rdme start -->
+            > > <!-- cargo-rdme end -->
+
+            <!-- cargo-rdme start -->
+
+            > Quoted documentation.
+
+            <!-- cargo-rdme end -->
+            "#
+        };
+
+        let doc = Doc::from_str(doc_str);
+        let new_readme =
+            inject_doc_in_readme(&Readme::from_str(readme_str), &doc, &InjectOptions::default())
+                .unwrap();
+
+        assert_eq!(new_readme.readme.as_string(), expected);
+
+        let again =
+            inject_doc_in_readme(&new_readme.readme, &doc, &InjectOptions::default()).unwrap();
+
+        assert_eq!(again.readme.as_string(), expected);
+        assert_eq!(generated_regions(&again.readme).len(), 1);
+    }
+
+    #[test]
+    fn test_inject_doc_escape_markers() {
+        let readme_str = indoc! { r#"
+            # My crate
+
+            <!-- cargo-rdme -->
+
+            The end.
+            "#
+        };
+        let doc_str = indoc! { r#"
+            Documentation with markers:
+
+            <!-- cargo-rdme start -->
+
+            <!-- cargo-rdme end -->
+
+            ```markdown
+            <!-- cargo-rdme -->
+            ```
+            "#
+        };
+
+        let expected = indoc! { r#"
+            # My crate
+
+            <!-- cargo-rdme start -->
+
+            Documentation with markers:
+
+            <!-- escaped: cargo-rdme start -->
+
+            <!-- escaped: cargo-rdme end -->
+
+            ```markdown
+            <!-- cargo-rdme -->
+            ```
+
+            <!-- cargo-rdme end -->
+
+            The end.
+            "#
+        };
+
+        let doc = Doc::from_str(doc_str);
+        let new_readme =
+            inject_doc_in_readme(&Readme::from_str(readme_str), &doc, &InjectOptions::default())
+                .unwrap();
+
+        assert_eq!(new_readme.readme.as_string(), expected);
+
+        let again =
+            inject_doc_in_readme(&new_readme.readme, &doc, &InjectOptions::default()).unwrap();
+
+        assert_eq!(again.readme.as_string(), expected);
+    }
+
+    #[test]
+    fn test_inject_doc_markers_in_foreign_region() {
+        let readme_str = indoc! { r#"
+            # The crate
+
+            <!-- START doctoc -->
             <!-- cargo-rdme -->
-            C
+            <!-- END doctoc -->
+
+            <!-- cargo-rdme -->
+            "#
+        };
+        let expected = indoc! { r#"
+            # The crate
+
+            <!-- START doctoc -->
+            <!-- cargo-rdme -->
+            <!-- END doctoc -->
+
             <!-- cargo-rdme start -->
-            D
-            <!-- cargo-rdme end -->
-            E
+
+            Foo.
+
             <!-- cargo-rdme end -->
-            F"
+            "#
         };
 
-        let readme = Readme::from_str(str);
-        let mut iter = readme_line_iterator(&readme).items();
+        let readme = Readme::from_str(readme_str);
+        let doc = Doc::from_str("Foo.\n");
 
-        // TODO Replace by `assert_matches!()` once https://github.com/rust-lang/rust/issues/82775
-        // stabilizes.
-        assert!(matches!(iter.next(), Some(ReadmeLine::MarkerCargoRdmeStart(_))));
-        assert!(matches!(iter.next(), Some(ReadmeLine::MarkerCargoRdmeEnd(_))));
-        assert_eq!(iter.next(), None);
+        let new_readme = inject_doc_in_readme(&readme, &doc, &InjectOptions::default()).unwrap();
+
+        assert_eq!(new_readme.readme.as_string(), expected);
     }
 
     #[test]
@@ -297,7 +1020,7 @@ mod tests {
         let readme = Readme::from_str(readme_str);
         let doc = Doc::from_str(doc_str);
 
-        let new_readme = inject_doc_in_readme(&readme, &doc, None).unwrap();
+        let new_readme = inject_doc_in_readme(&readme, &doc, &InjectOptions::default()).unwrap();
 
         assert_eq!(new_readme.readme.markdown.as_string(), expected);
         assert!(new_readme.had_marker);
@@ -347,7 +1070,7 @@ mod tests {
         let readme = Readme::from_str(readme_str);
         let doc = Doc::from_str(doc_str);
 
-        let new_readme = inject_doc_in_readme(&readme, &doc, None).unwrap();
+        let new_readme = inject_doc_in_readme(&readme, &doc, &InjectOptions::default()).unwrap();
 
         assert_eq!(new_readme.readme.markdown.as_string(), expected);
         assert!(new_readme.had_marker);
@@ -374,9 +1097,12 @@ mod tests {
         let readme = Readme::from_str(readme_str);
         let doc = Doc::from_str(doc_str);
 
-        let result = inject_doc_in_readme(&readme, &doc, None);
+        let result = inject_doc_in_readme(&readme, &doc, &InjectOptions::default());
 
-        assert_eq!(result.err(), Some(InjectDocError::UnmatchedMarkerCargoRdmeStart));
+        assert_eq!(
+            result.err(),
+            Some(InjectDocError::UnmatchedMarkerCargoRdmeStart { line_number: 5 })
+        );
     }
 
     #[test]
@@ -400,7 +1126,7 @@ mod tests {
         let readme = Readme::from_str(readme_str);
         let doc = Doc::from_str(doc_str);
 
-        let result = inject_doc_in_readme(&readme, &doc, None);
+        let result = inject_doc_in_readme(&readme, &doc, &InjectOptions::default());
 
         assert_eq!(
             result.err(),
@@ -440,6 +1166,62 @@ mod tests {
         assert_eq!(new_readme.markdown.as_string(), expected);
     }
 
+    #[test]
+    fn test_bump_heading_level_setext() {
+        let doc_str = indoc! { r#"
+            Foo
+            ===
+            Lorem ipsum.
+
+            Bar
+            ---
+            Dolor sit amet.
+            "#
+        };
+        let doc = Doc::from_str(doc_str);
+
+        assert_eq!(bump_heading_level(&doc, 0).markdown.as_string(), doc_str);
+
+        let expected = indoc! { r#"
+            ## Foo
+            Lorem ipsum.
+
+            ### Bar
+            Dolor sit amet.
+            "#
+        };
+
+        assert_eq!(bump_heading_level(&doc, 1).markdown.as_string(), expected);
+    }
+
+    #[test]
+    fn test_bump_heading_level_block_quote() {
+        let doc_str = indoc! { r#"
+            > # Note
+            >
+            > > Nested
+            > > ------
+            > >
+            > > ```
+            > > # Not a heading
+            > > ```
+            "#
+        };
+        let expected = indoc! { r#"
+            > ## Note
+            >
+            > > ### Nested
+            > >
+            > > ```
+            > > # Not a heading
+            > > ```
+            "#
+        };
+        let doc = Doc::from_str(doc_str);
+
+        assert_eq!(bump_heading_level(&doc, 1).markdown.as_string(), expected);
+    }
+
     #[test]
     fn test_inject_doc_bump_heading_level() {
         let readme_str = indoc! { r#"
@@ -487,7 +1269,7 @@ mod tests {
         let readme = Readme::from_str(readme_str);
         let doc = Doc::from_str(doc_str);
 
-        let new_readme = inject_doc_in_readme(&readme, &doc, None).unwrap();
+        let new_readme = inject_doc_in_readme(&readme, &doc, &InjectOptions::default()).unwrap();
 
         assert_eq!(new_readme.readme.markdown.as_string(), expected);
         assert!(new_readme.had_marker);
@@ -554,7 +1336,7 @@ mod tests {
         let readme = Readme::from_str(readme_str);
         let doc = Doc::from_str(doc_str);
 
-        let new_readme = inject_doc_in_readme(&readme, &doc, None).unwrap();
+        let new_readme = inject_doc_in_readme(&readme, &doc, &InjectOptions::default()).unwrap();
 
         assert_eq!(new_readme.readme.markdown.as_string(), expected);
         assert!(new_readme.had_marker);
@@ -605,7 +1387,7 @@ mod tests {
         let readme = Readme::from_str(readme_str);
         let doc = Doc::from_str(doc_str);
 
-        let new_readme = inject_doc_in_readme(&readme, &doc, None).unwrap();
+        let new_readme = inject_doc_in_readme(&readme, &doc, &InjectOptions::default()).unwrap();
 
         assert_eq!(new_readme.readme.markdown.as_string(), expected);
         assert!(new_readme.had_marker);
@@ -658,9 +1440,336 @@ mod tests {
         let readme = Readme::from_str(readme_str);
         let doc = Doc::from_str(doc_str);
 
-        let new_readme = inject_doc_in_readme(&readme, &doc, Some(0)).unwrap();
+        let new_readme = inject_doc_in_readme(
+            &readme,
+            &doc,
+            &InjectOptions::default().with_heading_base_level(0),
+        )
+        .unwrap();
+
+        assert_eq!(new_readme.readme.markdown.as_string(), expected);
+        assert!(new_readme.had_marker);
+    }
+
+    #[test]
+    fn test_inject_doc_with_heading() {
+        let readme_str = indoc! { r#"
+            # The crate
+
+            This is a really nice crate.
+
+            <!-- cargo-rdme -->
+
+            Hope you enjoy!
+            "#
+        };
+        let doc_str = indoc! { r#"
+            Lorem ipsum dolor sit amet, consectetur adipiscing elit, sed do eiusmod tempor
+            incididunt ut labore et dolore magna aliqua.
+
+            # Foo
+
+            Aenean dictum in nisi eu rutrum. Suspendisse vulputate tristique turpis eu vestibulum.
+            "#
+        };
+
+        let expected = indoc! { r#"
+            # The crate
+
+            This is a really nice crate.
+
+            <!-- cargo-rdme start -->
+
+            ## Documentation
+
+            Lorem ipsum dolor sit amet, consectetur adipiscing elit, sed do eiusmod tempor
+            incididunt ut labore et dolore magna aliqua.
+
+            ### Foo
+
+            Aenean dictum in nisi eu rutrum. Suspendisse vulputate tristique turpis eu vestibulum.
+
+            <!-- cargo-rdme end -->
+
+            Hope you enjoy!
+            "#
+        };
+
+        let readme = Readme::from_str(readme_str);
+        let doc = Doc::from_str(doc_str);
+
+        let new_readme = inject_doc_in_readme(
+            &readme,
+            &doc,
+            &InjectOptions::default().with_heading("Documentation"),
+        )
+        .unwrap();
 
         assert_eq!(new_readme.readme.markdown.as_string(), expected);
         assert!(new_readme.had_marker);
     }
+
+    #[test]
+    fn test_inject_doc_in_readme_section() {
+        let readme_str = indoc! { r#"
+            # The crate
+
+            This is a really nice crate.
+
+            ## Documentation
+
+            Old documentation.
+
+            ### Old subsection
+
+            ## License
+
+            MIT
+            "#
+        };
+        let doc_str = indoc! { r#"
+            Lorem ipsum dolor sit amet.
+
+            # Foo
+
+            ```rust
+            let x = 1;
+            "#
+        };
+
+        let expected = indoc! { r#"
+            # The crate
+
+            This is a really nice crate.
+
+            ## Documentation
+
+            Lorem ipsum dolor sit amet.
+
+            ### Foo
+
+            ```rust
+            let x = 1;
+            ```
+
+            ## License
+
+            MIT
+            "#
+        };
+
+        let readme = Readme::from_str(readme_str);
+        let doc = Doc::from_str(doc_str);
+
+        let new_readme = inject_doc_in_readme_section(&readme, &doc, "Documentation", None, None);
+
+        assert_eq!(new_readme.readme.as_string(), expected);
+        assert!(new_readme.had_marker);
+
+        // Injecting again changes nothing.
+        let new_readme =
+            inject_doc_in_readme_section(&new_readme.readme, &doc, "Documentation", None, None);
+
+        assert_eq!(new_readme.readme.as_string(), expected);
+
+        let new_readme = inject_doc_in_readme_section(&readme, &doc, "Usage", None, None);
+
+        assert_eq!(new_readme.readme.as_string(), readme_str);
+        assert!(!new_readme.had_marker);
+    }
+
+    #[test]
+    fn test_inject_doc_in_readme_section_last() {
+        let readme = Readme::from_str(
+            "Documentation
+=============
+
+Old.
+",
+        );
+        let doc = Doc::from_str(
+            "# Foo
+
+Bar.
+",
+        );
+
+        let new_readme = inject_doc_in_readme_section(&readme, &doc, "Documentation", None, None);
+
+        assert_eq!(
+            new_readme.readme.as_string(),
+            "Documentation\n=============\n\n## Foo\n\nBar.\n"
+        );
+    }
+
+    #[test]
+    fn test_inject_doc_content_hash() {
+        let readme_str = indoc! { r#"
+            # The crate
+
+            <!-- cargo-rdme -->
+
+            Hope you enjoy!
+            "#
+        };
+        let doc = Doc::from_str("Lorem ipsum dolor sit amet.\n");
+
+        let readme = Readme::from_str(readme_str);
+        let new_readme =
+            inject_doc_in_readme(&readme, &doc, &InjectOptions::default().with_content_hash(true))
+                .unwrap();
+        let hash = hash_content(doc.as_string());
+
+        let expected = formatdoc! { r#"
+            # The crate
+
+            <!-- cargo-rdme start -->
+
+            Lorem ipsum dolor sit amet.
+
+            <!-- cargo-rdme end hash:{hash:016x} -->
+
+            Hope you enjoy!
+            "#
+        };
+
+        assert_eq!(new_readme.readme.markdown.as_string(), expected);
+        assert_eq!(new_readme.hand_edited_regions, Vec::<usize>::new());
+
+        // Running again on the generated README finds no manual edits.
+        let new_readme = inject_doc_in_readme(
+            &new_readme.readme,
+            &doc,
+            &InjectOptions::default().with_content_hash(true),
+        )
+        .unwrap();
+
+        assert_eq!(new_readme.readme.markdown.as_string(), expected);
+        assert_eq!(new_readme.hand_edited_regions, Vec::<usize>::new());
+
+        // Line terminators do not affect the hash.
+        let readme_crlf = Readme::from_str(expected.replace('\n', "\r\n"));
+        let new_readme = inject_doc_in_readme(
+            &readme_crlf,
+            &doc,
+            &InjectOptions::default().with_content_hash(true),
+        )
+        .unwrap();
+
+        assert_eq!(new_readme.hand_edited_regions, Vec::<usize>::new());
+
+        let edited_readme = Readme::from_str(expected.replace("amet.", "amet, consectetur."));
+        let new_readme =
+            inject_doc_in_readme(&edited_readme, &doc, &InjectOptions::default()).unwrap();
+
+        assert_eq!(
+            new_readme.readme.markdown.as_string(),
+            expected.replace(&format!(" hash:{hash:016x}"), "")
+        );
+        assert_eq!(new_readme.hand_edited_regions, vec![3]);
+    }
+
+    #[test]
+    fn test_inject_doc_stream() {
+        let readme = "# The crate\r\n\r\n<!-- cargo-rdme -->\r\n\r\nThe end.";
+        let doc = Doc::from_str("# Usage\n\nUse it.\n");
+        let options =
+            InjectOptions { heading: Some("Documentation".to_owned()), ..InjectOptions::default() };
+        let mut output: Vec<u8> = Vec::new();
+
+        let had_marker = inject_doc_stream(readme.as_bytes(), &mut output, &doc, &options).unwrap();
+
+        let expected = "# The crate\r\n\
+                        \r\n\
+                        <!-- cargo-rdme start -->\r\n\
+                        \r\n\
+                        ## Documentation\r\n\
+                        \r\n\
+                        ### Usage\r\n\
+                        \r\n\
+                        Use it.\r\n\
+                        \r\n\
+                        <!-- cargo-rdme end -->\r\n\
+                        \r\n\
+                        The end.";
+
+        assert!(had_marker);
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+
+        let mut output: Vec<u8> = Vec::new();
+        let had_marker =
+            inject_doc_stream("# The crate\n".as_bytes(), &mut output, &doc, &options).unwrap();
+
+        assert!(!had_marker);
+        assert_eq!(String::from_utf8(output).unwrap(), "# The crate\n");
+    }
+
+    #[test]
+    fn test_first_change_outside_markers() {
+        let readme = Readme::from_str(indoc! { "
+            # My crate
+
+            <!-- cargo-rdme -->
+
+            The end.
+            "
+        });
+        let new_readme = Readme::from_str(indoc! { "
+            # My crate
+
+            <!-- cargo-rdme start -->
+
+            Documentation.
+
+            <!-- cargo-rdme end -->
+
+            The end.
+            "
+        });
+
+        assert_eq!(first_change_outside_markers(&readme, &new_readme), None);
+
+        let new_readme = Readme::from_str(indoc! { "
+            # My crate
+
+            <!-- cargo-rdme start -->
+
+            Documentation.
+
+            <!-- cargo-rdme end -->
+
+            The End.
+            "
+        });
+
+        assert_eq!(first_change_outside_markers(&readme, &new_readme), Some(5));
+
+        let new_readme = Readme::from_str("# My crate\n");
+
+        assert_eq!(first_change_outside_markers(&readme, &new_readme), Some(2));
+    }
+
+    #[test]
+    fn test_readme_has_markers() {
+        let readme = indoc! { r#"
+            # My crate
+
+            ```markdown
+            <!-- cargo-rdme -->
+            ```
+            "#
+        };
+
+        assert!(!readme_has_markers(&Readme::from_str(readme)));
+
+        let readme = indoc! { r#"
+            # My crate
+
+            <!-- cargo-rdme start -->
+            <!-- cargo-rdme end -->
+            "#
+        };
+
+        assert!(readme_has_markers(&Readme::from_str(readme)));
+    }
 }
@@ -6,20 +6,63 @@
 #![cfg_attr(feature = "fatal-warnings", deny(warnings))]
 
 use crate::markdown::{Markdown, MarkdownError};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
 use thiserror::Error;
 
+#[cfg(feature = "rust-source")]
+mod cfg;
 mod extract_doc;
+mod fix;
+mod foreign_regions;
+mod freeze;
+mod heading_case;
 mod inject_doc;
+mod logo;
 mod markdown;
+mod merge;
+mod preview;
+mod source_map;
 pub mod transform;
+mod translation;
+mod usage;
 pub mod utils;
-
-pub use extract_doc::{extract_doc_from_source_file, ExtractDocError};
-pub use inject_doc::{inject_doc_in_readme, InjectDocError, MARKER_RDME};
+mod verify_examples;
+
+#[cfg(feature = "rust-source")]
+pub use cfg::{CfgContext, FeatureSelection};
+#[cfg(feature = "rust-source")]
+pub use extract_doc::{
+    extract_doc_from_source_file, extract_doc_from_source_file_with_cfg,
+    extract_doc_from_source_str, extract_doc_from_source_str_with_cfg, source_doc_meta,
+    source_doc_source_pragma, source_html_root_url, source_includes_readme,
+    source_out_dir_doc_include,
+};
+pub use extract_doc::{DocMeta, ExtractDocError};
+pub use fix::{fix_doc, fix_readme, Fix, FixContext, Lint};
+pub use foreign_regions::{
+    foreign_region_overlaps, foreign_regions, ForeignRegion, ForeignRegionOverlap,
+};
+pub use freeze::freeze_readme;
+pub use heading_case::{heading_case_mismatches, HeadingCase, HeadingCaseMismatch};
+pub use inject_doc::{
+    inject_doc_in_readme, inject_doc_in_readme_section, inject_doc_stream, readme_has_markers,
+    region_content_hashes, InjectDocError, InjectDocStreamError, InjectOptions, NewReadme,
+    RegionContentHash, MARKER_RDME,
+};
+pub use logo::{inject_logo_in_readme, logo_html};
+pub use markdown::LinesMut;
+pub use merge::{merge3, unified_diff, MergeConflicts};
+pub use preview::{preview_html, preview_page, PreviewTarget};
+pub use source_map::DocSourceMap;
+pub use translation::{sync_translation, SyncedTranslation};
+pub use usage::{
+    inject_usage_in_readme, usage_snippet, UsageConfig, UsageStyle, UsageVersion, MARKER_USAGE,
+};
+pub use verify_examples::{doc_examples, DocExample, ExampleFailure, VerifyExamplesError};
 
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum ProjectError {
     #[error("failed to get cargo metadata: {0}")]
     CargoMetadataError(cargo_metadata::Error),
@@ -27,6 +70,10 @@ pub enum ProjectError {
     ProjectHasNoRootPackage,
     #[error("project has no package \"{0}\"")]
     ProjectHasNoPackage(String),
+    #[error("failed to run `cargo check`: {0}")]
+    CargoCheckError(std::io::Error),
+    #[error("`cargo check` failed, so the output of the build script is not available")]
+    CargoCheckFailed,
 }
 
 impl From<cargo_metadata::Error> for ProjectError {
@@ -46,27 +93,206 @@ pub fn find_first_file_in_ancestors(dir_path: impl AsRef<Path>, filename: &str)
     None
 }
 
-#[derive(PartialEq, Eq, Debug)]
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[non_exhaustive]
+pub enum TargetKind {
+    Lib,
+    Bin { name: String },
+    Example { name: String },
+    Test { name: String },
+}
+
+/// A target of a package, such as its library or one of its binaries.
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[non_exhaustive]
+pub struct Target {
+    pub kind: TargetKind,
+    /// The entry file of the target, e.g. `src/lib.rs` or `examples/demo.rs`, as resolved by
+    /// cargo.
+    pub entryfile: PathBuf,
+}
+
+/// A dependency of a package, as resolved by cargo.  If the package has a `Cargo.lock` this is the
+/// version locked there, otherwise it is the version cargo would lock.
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[non_exhaustive]
+pub struct Dependency {
+    /// Name of the crate in the code of the package, e.g. `serde_json`.  This is the new name of
+    /// the dependency if it is renamed.
+    pub name: String,
+    pub package_name: String,
+    pub version: String,
+    /// The version requirement of the dependency in the manifest, e.g. `^1.0`.
+    pub requirement: String,
+    /// The entry file of the library of the dependency.
+    pub entryfile: PathBuf,
+    /// Whether the dependency comes from crates.io, and therefore has its documentation in
+    /// docs.rs.  This is not the case of path and git dependencies, including the ones that patch
+    /// a crates.io dependency.
+    pub published: bool,
+    /// The documentation url of the dependency, as specified by `package.documentation`.
+    pub documentation_url: Option<String>,
+    /// The url of the repository of the dependency, as specified by `package.repository`.
+    pub repository: Option<String>,
+}
+
+impl Dependency {
+    /// The version of the dependency as docs.rs resolves it, taken from the requirement of the
+    /// manifest instead of the version it resolves to, e.g. `1.0` for `^1.0`.  Unlike the resolved
+    /// version, this does not depend on `Cargo.lock` or on the versions published.  Requirements
+    /// docs.rs does not understand give `latest`.
+    #[must_use]
+    pub fn requirement_version(&self) -> String {
+        use cargo_metadata::semver::{Op, VersionReq};
+        use std::fmt::Write;
+
+        let Ok(requirement) = VersionReq::parse(&self.requirement) else {
+            return "latest".to_owned();
+        };
+
+        match requirement.comparators.as_slice() {
+            [comparator] if matches!(comparator.op, Op::Caret | Op::Exact) => {
+                let mut version = comparator.major.to_string();
+
+                for part in [comparator.minor, comparator.patch].into_iter().flatten() {
+                    let _ = write!(version, ".{part}");
+                }
+
+                if !comparator.pre.is_empty() {
+                    let _ = write!(version, "-{}", comparator.pre);
+                }
+
+                version
+            }
+            _ => "latest".to_owned(),
+        }
+    }
+}
+
+/// Flags passed to cargo when it is run to get information about the project, with the same
+/// meaning as the cargo flags of the same name.
+#[derive(PartialEq, Eq, Clone, Copy, Default, Debug)]
+#[non_exhaustive]
+pub struct CargoFlags {
+    /// Require `Cargo.lock` to be up to date.
+    pub locked: bool,
+    /// Like `locked` and `offline` together.
+    pub frozen: bool,
+    /// Do not access the network.
+    pub offline: bool,
+}
+
+impl CargoFlags {
+    /// Sets `--locked`, which fails if the `Cargo.lock` needs to be updated.
+    #[must_use]
+    pub fn with_locked(mut self, locked: bool) -> CargoFlags {
+        self.locked = locked;
+        self
+    }
+
+    /// Sets `--frozen`, which is `--locked` and `--offline` together.
+    #[must_use]
+    pub fn with_frozen(mut self, frozen: bool) -> CargoFlags {
+        self.frozen = frozen;
+        self
+    }
+
+    /// Sets `--offline`, which runs cargo without accessing the network.
+    #[must_use]
+    pub fn with_offline(mut self, offline: bool) -> CargoFlags {
+        self.offline = offline;
+        self
+    }
+
+    fn args(self) -> Vec<String> {
+        [(self.locked, "--locked"), (self.frozen, "--frozen"), (self.offline, "--offline")]
+            .into_iter()
+            .filter(|(enabled, _)| *enabled)
+            .map(|(_, flag)| flag.to_owned())
+            .collect()
+    }
+}
+
+#[derive(PartialEq, Debug)]
 pub struct Project {
+    package_id: cargo_metadata::PackageId,
     package_name: String,
+    version: String,
+    description: Option<String>,
+    repository: Option<String>,
+    license: Option<String>,
+    rust_version: Option<String>,
+    edition: String,
     readme_path: Option<PathBuf>,
+    documentation_url: Option<String>,
     lib_path: Option<PathBuf>,
     bin_path: HashMap<String, PathBuf>,
+    targets: Vec<Target>,
+    dependencies: Vec<Dependency>,
+    features: BTreeMap<String, Vec<String>>,
     directory: PathBuf,
+    target_directory: PathBuf,
+    workspace_metadata: Option<toml::Value>,
+    package_metadata: Option<toml::Value>,
+    cargo_flags: CargoFlags,
 }
 
 impl Project {
     /// Creates a [`Project`] the current directory.  It will search ancestor paths until it finds
     /// the root of the project.
     pub fn from_current_dir() -> Result<Project, ProjectError> {
-        let metadata = Project::get_cargo_metadata()?;
+        Project::load(None, None, CargoFlags::default())
+    }
+
+    /// Creates a [`Project`] from the given `Cargo.toml` file.
+    pub fn from_manifest_path(manifest_path: impl AsRef<Path>) -> Result<Project, ProjectError> {
+        Project::load(Some(manifest_path.as_ref()), None, CargoFlags::default())
+    }
+
+    /// Creates a [`Project`] from the given `Cargo.toml` file, or from the current directory, like
+    /// the other constructors.  If `workspace_project` is given, the project is that member of the
+    /// workspace.  `cargo_flags` are passed to cargo, e.g. to not update `Cargo.lock`.
+    pub fn load(
+        manifest_path: Option<&Path>,
+        workspace_project: Option<&str>,
+        cargo_flags: CargoFlags,
+    ) -> Result<Project, ProjectError> {
+        let metadata = Project::get_cargo_metadata(manifest_path, cargo_flags)?;
+
+        match workspace_project {
+            None => Project::from_metadata(&metadata, cargo_flags),
+            Some(project_name) => {
+                Project::from_workspace_project(&metadata, project_name, cargo_flags)
+            }
+        }
+    }
+
+    fn from_metadata(
+        metadata: &cargo_metadata::Metadata,
+        cargo_flags: CargoFlags,
+    ) -> Result<Project, ProjectError> {
         let package = metadata.root_package().ok_or(ProjectError::ProjectHasNoRootPackage)?;
 
-        Ok(Project::from_package(package))
+        Ok(Project::from_package(metadata, package, cargo_flags))
     }
 
-    fn get_cargo_metadata() -> Result<cargo_metadata::Metadata, ProjectError> {
-        Ok(cargo_metadata::MetadataCommand::new().exec()?)
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(manifest_path = ?manifest_path))
+    )]
+    fn get_cargo_metadata(
+        manifest_path: Option<&Path>,
+        cargo_flags: CargoFlags,
+    ) -> Result<cargo_metadata::Metadata, ProjectError> {
+        let mut command = cargo_metadata::MetadataCommand::new();
+
+        if let Some(manifest_path) = manifest_path {
+            command.manifest_path(manifest_path);
+        }
+
+        command.other_options(cargo_flags.args());
+
+        Ok(command.exec()?)
     }
 
     fn select_package<'a>(
@@ -79,15 +305,52 @@ impl Project {
     }
 
     pub fn from_current_dir_workspace_project(project_name: &str) -> Result<Project, ProjectError> {
-        let metadata = Project::get_cargo_metadata()?;
+        Project::load(None, Some(project_name), CargoFlags::default())
+    }
+
+    /// Creates a [`Project`] for the workspace member `project_name` of the workspace the given
+    /// `Cargo.toml` file belongs to.
+    pub fn from_manifest_path_workspace_project(
+        manifest_path: impl AsRef<Path>,
+        project_name: &str,
+    ) -> Result<Project, ProjectError> {
+        Project::load(Some(manifest_path.as_ref()), Some(project_name), CargoFlags::default())
+    }
+
+    /// Creates a [`Project`] for every member of the workspace of the current directory, or of the
+    /// given `Cargo.toml` file.
+    pub fn workspace_members(
+        manifest_path: Option<&Path>,
+        cargo_flags: CargoFlags,
+    ) -> Result<Vec<Project>, ProjectError> {
+        let metadata = Project::get_cargo_metadata(manifest_path, cargo_flags)?;
+
+        let projects = metadata
+            .packages
+            .iter()
+            .filter(|package| metadata.workspace_members.contains(&package.id))
+            .map(|package| Project::from_package(&metadata, package, cargo_flags))
+            .collect();
+
+        Ok(projects)
+    }
 
-        let package = Project::select_package(&metadata, project_name)
+    fn from_workspace_project(
+        metadata: &cargo_metadata::Metadata,
+        project_name: &str,
+        cargo_flags: CargoFlags,
+    ) -> Result<Project, ProjectError> {
+        let package = Project::select_package(metadata, project_name)
             .ok_or_else(|| ProjectError::ProjectHasNoPackage(project_name.to_owned()))?;
 
-        Ok(Project::from_package(package))
+        Ok(Project::from_package(metadata, package, cargo_flags))
     }
 
-    fn from_package(package: &cargo_metadata::Package) -> Project {
+    fn from_package(
+        metadata: &cargo_metadata::Metadata,
+        package: &cargo_metadata::Package,
+        cargo_flags: CargoFlags,
+    ) -> Project {
         const LIB_CRATE_KINDS: [&str; 6] =
             ["lib", "dylib", "staticlib", "cdylib", "rlib", "proc-macro"];
         let lib_packages: Vec<&cargo_metadata::Target> = package
@@ -103,6 +366,69 @@ impl Project {
         let bin_packages =
             package.targets.iter().filter(|target| target.kind.contains(&"bin".to_owned()));
 
+        let targets = package
+            .targets
+            .iter()
+            .filter_map(|target| {
+                let kind = if target.kind.iter().any(|k| LIB_CRATE_KINDS.contains(&k.as_str())) {
+                    TargetKind::Lib
+                } else if target.kind.iter().any(|k| k == "bin") {
+                    TargetKind::Bin { name: target.name.clone() }
+                } else if target.kind.iter().any(|k| k == "example") {
+                    TargetKind::Example { name: target.name.clone() }
+                } else if target.kind.iter().any(|k| k == "test") {
+                    TargetKind::Test { name: target.name.clone() }
+                } else {
+                    return None;
+                };
+
+                Some(Target { kind, entryfile: target.src_path.clone().into_std_path_buf() })
+            })
+            .collect();
+
+        // Only normal dependencies can be used in the documentation of the crate.
+        let dependencies = metadata
+            .resolve
+            .iter()
+            .flat_map(|resolve| &resolve.nodes)
+            .filter(|node| node.id == package.id)
+            .flat_map(|node| &node.deps)
+            .filter(|dep| {
+                dep.dep_kinds
+                    .iter()
+                    .any(|dep_kind| dep_kind.kind == cargo_metadata::DependencyKind::Normal)
+            })
+            .filter_map(|dep| {
+                let dep_package = metadata.packages.iter().find(|p| p.id == dep.pkg)?;
+                let lib_target = dep_package.targets.iter().find(|target| {
+                    target.kind.iter().any(|k| LIB_CRATE_KINDS.contains(&k.as_str()))
+                })?;
+
+                let requirement = package
+                    .dependencies
+                    .iter()
+                    .find(|d| {
+                        d.kind == cargo_metadata::DependencyKind::Normal
+                            && d.rename.as_ref().unwrap_or(&d.name).replace('-', "_") == dep.name
+                    })
+                    .map_or_else(|| "*".to_owned(), |d| d.req.to_string());
+
+                Some(Dependency {
+                    name: dep.name.clone(),
+                    package_name: dep_package.name.clone(),
+                    version: dep_package.version.to_string(),
+                    requirement,
+                    entryfile: lib_target.src_path.clone().into_std_path_buf(),
+                    published: dep_package
+                        .source
+                        .as_ref()
+                        .is_some_and(cargo_metadata::Source::is_crates_io),
+                    documentation_url: dep_package.documentation.clone(),
+                    repository: dep_package.repository.clone(),
+                })
+            })
+            .collect();
+
         let directory = package
             .manifest_path
             .clone()
@@ -112,13 +438,29 @@ impl Project {
             .to_path_buf();
 
         Project {
+            package_id: package.id.clone(),
             package_name: package.name.clone(),
+            version: package.version.to_string(),
+            description: package.description.clone(),
+            repository: package.repository.clone(),
+            license: package.license.clone(),
+            rust_version: package.rust_version.as_ref().map(ToString::to_string),
+            edition: package.edition.as_str().to_owned(),
             readme_path: package.readme.as_ref().map(|p| p.clone().into_std_path_buf()),
+            documentation_url: package.documentation.clone(),
             lib_path: lib_package.map(|t| t.src_path.clone().into_std_path_buf()),
             bin_path: bin_packages
                 .map(|t| (t.name.clone(), t.src_path.clone().into_std_path_buf()))
                 .collect(),
+            targets,
+            dependencies,
+            features: package.features.clone(),
             directory,
+            target_directory: metadata.target_directory.clone().into_std_path_buf(),
+            // This is `null` if the workspace has no metadata, which fails to convert.
+            workspace_metadata: toml::Value::try_from(&metadata.workspace_metadata).ok(),
+            package_metadata: toml::Value::try_from(&package.metadata).ok(),
+            cargo_flags,
         }
     }
 
@@ -144,6 +486,30 @@ impl Project {
         self.bin_path.get(name).filter(|p| p.is_file()).map(PathBuf::as_path)
     }
 
+    /// The documentable targets of the project: its library, binaries, examples, and integration
+    /// tests, in the order they are defined in the manifest.
+    pub fn targets(&self) -> impl Iterator<Item = &Target> {
+        self.targets.iter()
+    }
+
+    #[must_use]
+    pub fn get_example_entryfile_path(&self, name: &str) -> Option<&Path> {
+        self.targets
+            .iter()
+            .find(|target| matches!(&target.kind, TargetKind::Example { name: n } if n == name))
+            .map(|target| target.entryfile.as_path())
+            .filter(|p| p.is_file())
+    }
+
+    #[must_use]
+    pub fn get_test_entryfile_path(&self, name: &str) -> Option<&Path> {
+        self.targets
+            .iter()
+            .find(|target| matches!(&target.kind, TargetKind::Test { name: n } if n == name))
+            .map(|target| target.entryfile.as_path())
+            .filter(|p| p.is_file())
+    }
+
     #[must_use]
     pub fn get_readme_path(&self) -> Option<PathBuf> {
         self.readme_path
@@ -153,12 +519,135 @@ impl Project {
             .filter(|p| p.is_file())
     }
 
+    /// The dependencies of the package, with the versions they resolve to.
+    #[must_use]
+    pub fn get_dependencies(&self) -> &[Dependency] {
+        &self.dependencies
+    }
+
+    /// The features of the package, from the `[features]` table of its manifest, with the
+    /// features or dependencies each of them enables.
+    #[must_use]
+    pub fn get_features(&self) -> &BTreeMap<String, Vec<String>> {
+        &self.features
+    }
+
     #[must_use]
     pub fn get_package_name(&self) -> &str {
         &self.package_name
     }
+
+    #[must_use]
+    pub fn get_version(&self) -> &str {
+        &self.version
+    }
+
+    /// The description of the package, as specified by `package.description`.
+    #[must_use]
+    pub fn get_description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// The url of the package repository, as specified by `package.repository`.
+    #[must_use]
+    pub fn get_repository(&self) -> Option<&str> {
+        self.repository.as_deref()
+    }
+
+    /// The SPDX license expression of the package, as specified by `package.license`.
+    #[must_use]
+    pub fn get_license(&self) -> Option<&str> {
+        self.license.as_deref()
+    }
+
+    /// The minimum supported rust version, as specified by `package.rust-version`.  It is always
+    /// a full version, e.g. `1.70.0` for `rust-version = "1.70"`.
+    #[must_use]
+    pub fn get_rust_version(&self) -> Option<&str> {
+        self.rust_version.as_deref()
+    }
+
+    /// The edition of the project, as specified by `package.edition`, e.g. `2021`.  This is
+    /// `2015` if the manifest does not specify one, as for cargo.
+    #[must_use]
+    pub fn get_edition(&self) -> &str {
+        &self.edition
+    }
+
+    /// The url of the crate documentation, as specified by `package.documentation`.
+    #[must_use]
+    pub fn get_documentation_url(&self) -> Option<&str> {
+        self.documentation_url.as_deref()
+    }
+
+    /// The directory of the project, i.e. where its `Cargo.toml` is.
+    #[must_use]
+    pub fn get_directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// The directory where cargo places build artifacts, usually `target/`.
+    #[must_use]
+    pub fn get_target_directory(&self) -> &Path {
+        &self.target_directory
+    }
+
+    /// The `[workspace.metadata]` table of the workspace the project belongs to.
+    #[must_use]
+    pub fn get_workspace_metadata(&self) -> Option<&toml::Value> {
+        self.workspace_metadata.as_ref()
+    }
+
+    /// The `[package.metadata]` table of the project.
+    #[must_use]
+    pub fn get_package_metadata(&self) -> Option<&toml::Value> {
+        self.package_metadata.as_ref()
+    }
+
+    /// Runs `cargo check` on the project, which runs its build script, and returns the directory
+    /// where the build script writes its output (`OUT_DIR`).  This is `None` if the project has no
+    /// build script.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(package = %self.package_name))
+    )]
+    pub fn build_script_out_dir(&self) -> Result<Option<PathBuf>, ProjectError> {
+        use cargo_metadata::Message;
+        use std::io::BufReader;
+        use std::process::{Command, Stdio};
+
+        let cargo = std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+        let mut child = Command::new(cargo)
+            .args(["check", "--message-format=json-render-diagnostics", "--manifest-path"])
+            .arg(self.directory.join("Cargo.toml"))
+            .args(["--package", &self.package_name])
+            .args(self.cargo_flags.args())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(ProjectError::CargoCheckError)?;
+
+        let out_dir = child.stdout.take().and_then(|stdout| {
+            Message::parse_stream(BufReader::new(stdout))
+                .filter_map(Result::ok)
+                .filter_map(|message| match message {
+                    Message::BuildScriptExecuted(script)
+                        if script.package_id == self.package_id =>
+                    {
+                        Some(script.out_dir.into_std_path_buf())
+                    }
+                    _ => None,
+                })
+                .last()
+        });
+
+        match child.wait().map_err(ProjectError::CargoCheckError)?.success() {
+            true => Ok(out_dir),
+            false => Err(ProjectError::CargoCheckFailed),
+        }
+    }
 }
 
+#[cfg(feature = "rust-source")]
 fn project_package_name(manifest_path: impl AsRef<Path>) -> Option<String> {
     let str: String = std::fs::read_to_string(&manifest_path).ok()?;
     let toml: toml::Value = toml::from_str(&str).ok()?;
@@ -179,31 +668,100 @@ impl Doc {
         Doc { markdown }
     }
 
+    /// Reads the documentation from a markdown file, such as a file that is included in the crate
+    /// with `#![doc = include_str!("../docs/crate.md")]`.
+    pub fn from_markdown_file(file_path: impl AsRef<Path>) -> Result<Doc, ExtractDocError> {
+        let markdown = Markdown::from_file(file_path.as_ref()).map_err(|_| {
+            ExtractDocError::ErrorReadingMarkdownFile(file_path.as_ref().to_path_buf())
+        })?;
+
+        Ok(Doc { markdown })
+    }
+
+    /// Returns `true` if the documentation has no content.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.as_string().trim().is_empty()
+    }
+
     // TODO implement FromStr when ! type is stable.
     #[allow(clippy::should_implement_trait)]
     pub fn from_str(str: impl Into<String>) -> Doc {
         Doc { markdown: Markdown::from_str(str) }
     }
 
-    fn is_toplevel_doc(attr: &syn::Attribute) -> bool {
-        use syn::token::Not;
-        use syn::AttrStyle;
+    /// Appends `section` to the documentation, nested under a new top level `heading`.
+    #[must_use]
+    pub fn with_section(&self, heading: &str, section: &Doc) -> Doc {
+        let section = inject_doc::prepend_heading(section, heading);
+
+        Doc::concat(&[self.clone(), section], "\n\n")
+    }
+
+    /// Joins documentation from several sources, with `separator` between them, e.g. `"\n\n"` for
+    /// a blank line.  The trailing whitespace before a separator is removed, and empty
+    /// documentation is skipped.
+    #[must_use]
+    pub fn concat(docs: &[Doc], separator: &str) -> Doc {
+        let docs: Vec<&Doc> = docs.iter().filter(|doc| !doc.is_empty()).collect();
+        let mut markdown = String::new();
+
+        for (i, doc) in docs.iter().enumerate() {
+            match i + 1 == docs.len() {
+                true => markdown.push_str(doc.as_string()),
+                false => {
+                    markdown.push_str(doc.as_string().trim_end());
+                    markdown.push_str(separator);
+                }
+            }
+        }
 
-        attr.style == AttrStyle::Inner(Not::default()) && attr.path().is_ident("doc")
+        Doc::from_str(markdown)
+    }
+
+    /// Adds `text` before the documentation, as is, so it must end with the separator to use, e.g.
+    /// a blank line.
+    #[must_use]
+    pub fn prepend(&self, text: &str) -> Doc {
+        Doc::from_str(format!("{text}{}", self.as_string()))
+    }
+
+    /// Adds `text` after the documentation, as is, so it must start with the separator to use, e.g.
+    /// a blank line.
+    #[must_use]
+    pub fn append(&self, text: &str) -> Doc {
+        Doc::from_str(format!("{}{text}", self.as_string()))
     }
 
     pub fn lines(&self) -> impl Iterator<Item = &str> {
         self.markdown.lines()
     }
 
+    #[must_use]
+    pub fn into_lines(self) -> Vec<String> {
+        self.markdown.into_lines()
+    }
+
+    /// Returns the lines for editing.  The changes are applied when the returned value is dropped.
+    pub fn lines_mut(&mut self) -> LinesMut<'_> {
+        self.markdown.lines_mut()
+    }
+
     // Return the markdown as a string.  Note that the line terminator will always be a line feed.
     #[must_use]
     pub fn as_string(&self) -> &str {
         self.markdown.as_string()
     }
+
+    /// Returns the markdown as a string with the given line terminator.
+    #[must_use]
+    pub fn to_string(&self, line_terminator: LineTerminator) -> String {
+        self.markdown.to_string(line_terminator)
+    }
 }
 
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum ReadmeError {
     #[error("failed to read README file \"{0}\"")]
     ErrorReadingReadmeFromFile(PathBuf),
@@ -228,11 +786,26 @@ impl From<MarkdownError> for ReadmeError {
 }
 
 #[derive(Eq, PartialEq, Debug, Copy, Clone)]
+#[non_exhaustive]
 pub enum LineTerminator {
     Lf,
     CrLf,
+    /// Used by old Mac OS files.
+    Cr,
 }
 
+impl LineTerminator {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LineTerminator::Lf => "\n",
+            LineTerminator::CrLf => "\r\n",
+            LineTerminator::Cr => "\r",
+        }
+    }
+}
+
+#[derive(Eq, PartialEq, Clone)]
 pub struct Readme {
     pub markdown: Markdown,
 }
@@ -256,6 +829,20 @@ impl Readme {
         self.markdown.lines()
     }
 
+    #[must_use]
+    pub fn into_lines(self) -> Vec<String> {
+        self.markdown.into_lines()
+    }
+
+    /// Returns the lines for editing.  The changes are applied when the returned value is dropped.
+    pub fn lines_mut(&mut self) -> LinesMut<'_> {
+        self.markdown.lines_mut()
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(file = %file.as_ref().display()))
+    )]
     pub fn write_to_file(
         &self,
         file: impl AsRef<Path>,
@@ -277,17 +864,84 @@ impl Readme {
     pub fn as_string(&self) -> &str {
         self.markdown.as_string()
     }
+
+    /// Returns the markdown as a string with the given line terminator.
+    #[must_use]
+    pub fn to_string(&self, line_terminator: LineTerminator) -> String {
+        self.markdown.to_string(line_terminator)
+    }
 }
 
 pub fn infer_line_terminator(file_path: impl AsRef<Path>) -> std::io::Result<LineTerminator> {
     let content: String = std::fs::read_to_string(file_path.as_ref())?;
 
+    Ok(content_line_terminator(&content))
+}
+
+/// The most common line terminator of the content.
+fn content_line_terminator(content: &str) -> LineTerminator {
     let crlf_lines: usize = content.matches("\r\n").count();
     let lf_lines: usize = content.matches('\n').count() - crlf_lines;
+    let cr_lines: usize = content.matches('\r').count() - crlf_lines;
 
-    if crlf_lines > lf_lines {
-        Ok(LineTerminator::CrLf)
+    if cr_lines > lf_lines && cr_lines > crlf_lines {
+        LineTerminator::Cr
+    } else if crlf_lines > lf_lines {
+        LineTerminator::CrLf
     } else {
-        Ok(LineTerminator::Lf)
+        LineTerminator::Lf
+    }
+}
+
+/// Whether the file ends with a line terminator.  Empty files are considered to end with one.
+pub fn infer_final_newline(file_path: impl AsRef<Path>) -> std::io::Result<bool> {
+    let content: String = std::fs::read_to_string(file_path.as_ref())?;
+
+    Ok(content.is_empty() || content.ends_with(['\n', '\r']))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_doc_concat() {
+        let docs = [
+            Doc::from_str("# Foo\n\nFoo.\n\n"),
+            Doc::from_str("\n"),
+            Doc::from_str("# Bar\n\nBar.\n"),
+        ];
+
+        assert_eq!(Doc::concat(&docs, "\n\n").as_string(), "# Foo\n\nFoo.\n\n# Bar\n\nBar.\n");
+        assert_eq!(
+            Doc::concat(&docs, "\n\n---\n\n").as_string(),
+            "# Foo\n\nFoo.\n\n---\n\n# Bar\n\nBar.\n"
+        );
+        assert!(Doc::concat(&[], "\n\n").is_empty());
+
+        let doc = Doc::from_str("Foo.\n").prepend("# Title\n\n").append("\n---\n");
+
+        assert_eq!(doc.as_string(), "# Title\n\nFoo.\n\n---\n");
+    }
+
+    #[test]
+    fn test_dependency_requirement_version() {
+        let dependency = |requirement: &str| Dependency {
+            name: "foo".to_owned(),
+            package_name: "foo".to_owned(),
+            version: "1.4.2".to_owned(),
+            requirement: requirement.to_owned(),
+            entryfile: PathBuf::from("/deps/foo/src/lib.rs"),
+            published: true,
+            documentation_url: None,
+            repository: None,
+        };
+
+        assert_eq!(dependency("^1.0").requirement_version(), "1.0");
+        assert_eq!(dependency("^0.3.1-alpha.1").requirement_version(), "0.3.1-alpha.1");
+        assert_eq!(dependency("=1.4.0").requirement_version(), "1.4.0");
+        assert_eq!(dependency(">=1, <3").requirement_version(), "latest");
+        assert_eq!(dependency("*").requirement_version(), "latest");
     }
 }
@@ -0,0 +1,163 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+//! Puts the logo of the crate, the `html_logo_url` of its `#![doc(…)]` attributes, at the top of
+//! the README, so that it has the same branding in GitHub as the documentation in docs.rs.
+
+use crate::inject_doc::generated_regions;
+use crate::{InjectDocError, Readme};
+use std::ops::Range;
+
+const MARKER_LOGO_START: &str = "<!-- cargo-rdme logo start -->";
+const MARKER_LOGO_END: &str = "<!-- cargo-rdme logo end -->";
+
+/// The html of the logo, centered.
+#[must_use]
+pub fn logo_html(logo_url: &str, package_name: &str) -> String {
+    let logo_url = logo_url.replace('"', "&quot;");
+
+    format!("<p align=\"center\">\n  <img src=\"{logo_url}\" alt=\"{package_name}\">\n</p>\n")
+}
+
+enum LogoMarker {
+    Start,
+    End,
+}
+
+/// The logo markers of the README, with the range of their lines.  Markers in code blocks or in
+/// the regions generated from the crate’s documentation are ignored.
+fn logo_markers(readme: &Readme) -> Vec<(Range<usize>, LogoMarker, usize)> {
+    use pulldown_cmark::{Event, Options, Parser};
+
+    let source = readme.as_string();
+    let doc_regions = generated_regions(readme);
+
+    Parser::new_ext(source, Options::all())
+        .into_offset_iter()
+        .filter_map(|(event, range)| match event {
+            Event::Html(html) if !doc_regions.iter().any(|r| r.contains(&range.start)) => {
+                let line_start = source[..range.start].rfind('\n').map_or(0, |i| i + 1);
+
+                if !source[line_start..range.start].trim().is_empty() {
+                    return None;
+                }
+
+                let marker = match html.trim() {
+                    MARKER_LOGO_START => LogoMarker::Start,
+                    MARKER_LOGO_END => LogoMarker::End,
+                    _ => return None,
+                };
+                let line_end =
+                    source[range.start..].find('\n').map_or(source.len(), |i| range.start + i + 1);
+                let line_number = source[..line_start].matches('\n').count() + 1;
+
+                Some((line_start..line_end, marker, line_number))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Puts the logo between the logo markers of the README.  If the README has no logo markers, they
+/// are added, with the logo, at the top of the README.
+pub fn inject_logo_in_readme(readme: &Readme, logo: &str) -> Result<Readme, InjectDocError> {
+    let source = readme.as_string();
+    let region = format!("{MARKER_LOGO_START}\n\n{logo}\n{MARKER_LOGO_END}\n");
+    let mut start: Option<(usize, usize)> = None;
+    let mut replaced: Option<Range<usize>> = None;
+
+    for (range, marker, line_number) in logo_markers(readme) {
+        match (marker, start) {
+            (LogoMarker::Start, None) if replaced.is_none() => {
+                start = Some((range.start, line_number));
+            }
+            (LogoMarker::End, Some((region_start, _))) => {
+                replaced = Some(region_start..range.end);
+                start = None;
+            }
+            (LogoMarker::End, None) => {
+                return Err(InjectDocError::UnexpectedMarkerCargoRdmeEnd { line_number });
+            }
+            (LogoMarker::Start, _) => {
+                return Err(InjectDocError::UnmatchedMarkerCargoRdmeStart { line_number });
+            }
+        }
+    }
+
+    if let Some((_, line_number)) = start {
+        return Err(InjectDocError::UnmatchedMarkerCargoRdmeStart { line_number });
+    }
+
+    let new_readme = match replaced {
+        Some(range) => format!("{}{region}{}", &source[..range.start], &source[range.end..]),
+        None => format!("{region}\n{source}"),
+    };
+
+    Ok(Readme::from_str(new_readme))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_inject_logo_in_readme() {
+        let readme = indoc! { r#"
+            # My crate
+
+            ```markdown
+            <!-- cargo-rdme logo start -->
+            ```
+            "#
+        };
+        let expected = indoc! { r#"
+            <!-- cargo-rdme logo start -->
+
+            <p align="center">
+              <img src="https://example.com/logo.png" alt="my-crate">
+            </p>
+
+            <!-- cargo-rdme logo end -->
+
+            # My crate
+
+            ```markdown
+            <!-- cargo-rdme logo start -->
+            ```
+            "#
+        };
+
+        let logo = logo_html("https://example.com/logo.png", "my-crate");
+        let new_readme = inject_logo_in_readme(&Readme::from_str(readme), &logo).unwrap();
+
+        assert_eq!(new_readme.as_string(), expected);
+
+        // Updating the logo replaces the region.
+        let logo = logo_html("https://example.com/new-logo.png", "my-crate");
+        let new_readme = inject_logo_in_readme(&new_readme, &logo).unwrap();
+
+        assert_eq!(new_readme.as_string(), expected.replace("/logo.png", "/new-logo.png"));
+    }
+
+    #[test]
+    fn test_inject_logo_in_readme_unmatched_markers() {
+        let logo = logo_html("https://example.com/logo.png", "my-crate");
+        let readme = Readme::from_str("Foo\n\n<!-- cargo-rdme logo start -->\n");
+
+        assert_eq!(
+            inject_logo_in_readme(&readme, &logo).err(),
+            Some(InjectDocError::UnmatchedMarkerCargoRdmeStart { line_number: 3 })
+        );
+
+        let readme = Readme::from_str("<!-- cargo-rdme logo end -->\n");
+
+        assert_eq!(
+            inject_logo_in_readme(&readme, &logo).err(),
+            Some(InjectDocError::UnexpectedMarkerCargoRdmeEnd { line_number: 1 })
+        );
+    }
+}
@@ -10,6 +10,9 @@
 //! # Installation
 //!
 //! You can install cargo rdme with cargo by running `cargo install cargo-rdme`.
+//! Running `cargo rdme selftest` then checks that the installation works as released: it runs
+//! cargo rdme on a few bundled example projects and compares the generated READMEs with the
+//! expected ones.
 //!
 //! # Usage
 //!
@@ -36,9 +39,68 @@
 //! <!-- cargo-rdme end -->
 //! ```
 //!
+//! The `<!-- cargo-rdme -->` marker is a shorthand that is expanded into the start and end markers
+//! on the first run, so you can also add an empty pair of start and end markers to your README
+//! directly.
+//!
+//! Regions of the README maintained by other tools, between comments such as
+//! `<!-- START doctoc -->` and `<!-- END doctoc -->`, are left alone: cargo rdme ignores its markers
+//! inside them, with a warning.
+//!
+//! If you would rather not have markers in your README, set `readme-section = "Documentation"` in
+//! the configuration file (or use `--readme-section Documentation`): cargo rdme then replaces the
+//! content of the README section with that heading, up to the next heading of the same level, with
+//! the crate’s documentation.
+//!
+//! You can also add a `<!-- cargo-rdme usage -->` marker where your README explains how to add the
+//! crate as a dependency.  cargo rdme replaces it with a toml snippet with the current version of
+//! the crate, e.g. `mycrate = "1"`, so that the instructions never lag behind releases.  Set
+//! `usage-version = "exact"` in the configuration file (or use `--usage-version exact`) for the
+//! exact version, e.g. `mycrate = "1.2.3"`.  With `usage-style = "cargo-add"` the snippet is a
+//! `cargo add mycrate` command instead, and with `"both"` it has the command and the toml snippet.
+//! The features to enable can be listed in `usage-features`.
+//!
+//! If your crate has a logo, declared with `#![doc(html_logo_url = "…")]` for docs.rs, set
+//! `logo = true` (or use `--logo`) to also show it, centered, at the top of the README.  cargo rdme
+//! puts it between `<!-- cargo-rdme logo start -->` and `<!-- cargo-rdme logo end -->` markers,
+//! which you can move elsewhere, and keeps it up to date with the logo of the documentation.
+//!
 //! Whenever change your crate’s documentation you just need to run `cargo rdme` to update your
 //! README file.
 //!
+//! If you are not in the crate’s directory you can point cargo rdme to it with
+//! `cargo rdme --manifest-path path/to/Cargo.toml`.  The `cargo-rdme` binary can also be run
+//! directly, without going through cargo.
+//!
+//! To update other READMEs, e.g. `docs/PACKAGING.md`, without changing the configuration, use
+//! `cargo rdme --readme-path docs/PACKAGING.md`.  `--readme-path` can be given more than once, and
+//! overrides the README of the configuration file and of `Cargo.toml`.
+//!
+//! When run from a build script or an xtask, where cargo sets `CARGO_MANIFEST_DIR`, cargo rdme uses
+//! the project of that directory, unless `--manifest-path` is given.  As with other cargo commands,
+//! you can pass `--locked`, `--frozen`, or `--offline`, which cargo rdme passes on to cargo so that
+//! it does not update `Cargo.lock` or access the network.
+//!
+//! With `cargo rdme --fix` cargo rdme also works as a light README formatter, fixing these issues
+//! in the whole README, not only in the crate’s documentation:
+//!
+//! * A duplicate title, i.e. the documentation starts with the same title as the README.
+//! * Lines indented with tabs.
+//! * Trailing whitespace (hard line breaks are kept).
+//! * Code block languages that not every renderer highlights, e.g. `rs` instead of `rust`.
+//! * Dependency snippets in toml code blocks with an outdated version of the crate.
+//!
+//! cargo rdme keeps the READMEs it overwrites in `target/cargo-rdme/history/`.  If a run did
+//! something you did not want, `cargo rdme undo` restores the READMEs written by the last run to
+//! what they were before.  Run it again to undo the run before that.
+//!
+//! With `cargo rdme --verify-examples` (or `verify-examples = true` in the configuration file)
+//! cargo rdme checks that the rust examples of the documentation compile, and does not update the
+//! README if one does not.  Like rustdoc, it checks the examples with their hidden lines, and skips
+//! the ones tagged with `ignore` or `compile_fail`.  They are compiled with `cargo check`, in the
+//! edition of your crate, as examples of a package in `target/cargo-rdme/verify-examples/` that
+//! depends on your crate, so `no_run` and `should_panic` examples only need to compile.
+//!
 //! # Automatic transformations
 //!
 //! The documentation of your crate doesn’t always map directly to a good README.  For example,
@@ -52,6 +114,8 @@
 //! Rust code block are transformed in two ways by cargo rdme:
 //!
 //! 1. Rust code blocks with lines starting with `#` will be omitted, just like in `rustdoc`.
+//!    If the hidden lines of an example are worth showing in the README, tag its code block with
+//!    `rdme-keep-hidden`, e.g. ` ```rust,rdme-keep-hidden`, and they are shown without the `#`.
 //! 2. Rust code blocks get annotated with the `rust` markdown tag so it gets proper syntax
 //!    highlighting.  We also remove tags that only concern `rustdoc` such as `should_panic`.
 //!
@@ -108,10 +172,17 @@
 //! This links would not make sense in your README file, so cargo rdme automatically generate
 //! links to [docs.rs](https://docs.rs) for these intralinks.
 //!
-//! Currently we only support links of the form `[⋯](crate::⋯)`, so be sure to use that format.
-//! Links to the standard library are also supported, and they must be of the form
-//! `[⋯](::<crate>::⋯)`, where `<crate>` is a crate that is part of the standard library, such as
-//! `std`, `core`, or `alloc`.  Reference-style links are also supported.
+//! The link forms understood by rustdoc are supported, such as `[⋯](crate::⋯)`, `[⋯](Foo)`,
+//! `[⋯][Foo]`, ``[`Foo`]``, ``[`Foo::new()`]``, and `[⋯](struct@Foo)`.  Paths are resolved
+//! relative to the crate root, and links to methods (including trait methods), associated items,
+//! struct fields, and enum variants generate the appropriate anchor.  Links to the standard
+//! library are also supported, either through the prelude (e.g. ``[`Vec::push`]``) or with an
+//! explicit path such as `[⋯](::std::fs::copy)` or `[⋯](std::fs::copy)`.  Primitive types and
+//! keywords, such as ``[`u32`]``, `[⋯](slice)`, or ``[`keyword@match`]``, link to their page in
+//! the standard library documentation.  A path can also use a
+//! [doc alias](https://doc.rust-lang.org/rustdoc/advanced-features.html#add-aliases-for-an-item-in-documentation-search)
+//! of an item, e.g. ``[`Coord`]`` for a `#[doc(alias = "Coord")] struct Point`, unless an item has
+//! that name.
 //!
 //! Take a look at the example below:
 //!
@@ -143,6 +214,21 @@
 //! </tr>
 //! </table>
 //!
+//! If your crate’s documentation is not hosted in docs.rs, and `package.documentation` in
+//! `Cargo.toml` points to it (e.g. `https://example.com/docs/prime/`), the links to the crate’s items
+//! will use that url instead.  If the crate declares where its documentation is published with
+//! `#![doc(html_root_url = "...")]`, that url takes precedence, as it does for rustdoc.  You can
+//! also set the documentation url explicitly in the configuration file (see `docs-url-template`
+//! below).
+//!
+//! Links to items of dependencies, such as `[serde_json::Value]`, point to the documentation of the
+//! version of the dependency locked in `Cargo.lock` (or the version cargo would lock, if there is no
+//! `Cargo.lock`), rather than to the latest version.  Path and git dependencies are not in docs.rs,
+//! so links to their items use the documentation url of their manifest (`package.documentation`)
+//! instead, or become code spans if there is none.  With `--deterministic` the links use the
+//! version requirement of the manifest instead (e.g. `1.0` for `serde_json = "1.0"`), which does
+//! not depend on `Cargo.lock`.
+//!
 //! Note that there is some limitations in intralink support.  This is a complex feature: cargo rdme
 //! needs to do some work to be able to create the link to docs.rs.  This is because the link
 //! includes the kind of item the intralink points to, in the case of `is_prime` we need to discover
@@ -158,6 +244,85 @@
 //! the `--heading-base-level` command line flag, or in the configuration file (see example
 //! below).
 //!
+//! You can also ask cargo rdme to add a heading at the top of the inserted documentation with the
+//! `--heading` command line flag (e.g. `--heading Documentation`).  The level of that heading is
+//! adjusted to the surrounding README section, and the crate’s documentation is nested under it.
+//!
+//! ## Blank lines
+//!
+//! Doc comments often start or end with empty lines, which would add extra spacing between the
+//! markers in the README.  cargo rdme removes the blank lines at the start and at the end of the
+//! documentation, and collapses consecutive blank lines into one, except in code blocks.  Use
+//! `--blank-lines keep`, or `blank-lines = "keep"` in the configuration file, to keep them.
+//!
+//! ## Setext headings
+//!
+//! Setext headings (a line underlined with `===` or `---`) of the crate’s documentation are
+//! converted to `#` headings when their level is adjusted to the README, since setext headings
+//! only have two levels.  Use `--setext-headings atx`, or `setext-headings = "atx"` in the
+//! configuration file, to convert every setext heading, so that the README has a single heading
+//! style.
+//!
+//! ## Nested lists
+//!
+//! Rustdoc accepts nested lists indented to the content of their parent item, i.e. two spaces
+//! after `-` and three after `1.`, but crates.io sometimes needs four.  Use
+//! `--list-indentation normalize`, or `list-indentation = "normalize"` in the configuration file,
+//! to indent nested lists with four spaces per level.
+//!
+//! ## Hard line breaks
+//!
+//! A line of the crate’s documentation ending in two spaces, or in a backslash, is a hard line
+//! break.  Trailing spaces are invisible and easily lost, e.g. by editors that trim them, so use
+//! `--hard-breaks html`, or `hard-breaks = "html"` in the configuration file, to replace hard
+//! breaks with `<br>` in the README.  With `collapse` the lines are joined in the same paragraph
+//! instead.
+//!
+//! ## Link style
+//!
+//! Some projects enforce a single style of links, e.g. with markdownlint.  Use
+//! `--link-style inline`, or `link-style = "inline"` in the configuration file, to write all the
+//! links of the documentation with their destination, and `reference` to write them as reference
+//! links, with their definitions at the end of the documentation.  This also applies to the links
+//! generated for intralinks.
+//!
+//! # Generated documentation
+//!
+//! If your build script generates the crate’s documentation you can set `build-script = true` in
+//! the configuration file (or use `--build-script`).  cargo rdme will then run `cargo check`, which
+//! runs the build script, and read the documentation from its output directory (`OUT_DIR`) if
+//! your crate includes it with `#![doc = include_str!(concat!(env!("OUT_DIR"), "/docs.md"))]`.
+//! You can also point to the generated file explicitly with `doc-source = "$OUT_DIR/docs.md"`.
+//!
+//! If the crate’s entrypoint only has its documentation behind `#[cfg]` facades, you can also
+//! choose the file to extract the documentation from in the entrypoint itself, with a comment
+//! instead of the configuration file:
+//!
+//! ```rust
+//! // cargo-rdme: doc-source = "docs/README-source.md"
+//! #![cfg_attr(feature = "nightly", doc = include_str!("../docs/nightly.md"))]
+//! ```
+//!
+//! The path is relative to the package directory, and the `doc-source` option, if set, takes
+//! precedence over it.
+//!
+//! # Features
+//!
+//! Documentation that depends on the crate’s features, such as
+//! `#![cfg_attr(feature = "std", doc = "…")]`, is included in the README if its predicate holds
+//! with the default features of the crate.  The crate’s documentation can also have parts that are
+//! only included in the README for some features:
+//!
+//! ```markdown
+//! <!-- rdme:if all(feature = "std", not(feature = "nightly")) -->
+//! This crate uses the standard library.
+//! <!-- rdme:endif -->
+//! ```
+//!
+//! Use `--features`, `--all-features`, and `--no-default-features`, with the same meaning as in
+//! cargo, to choose which features are enabled.  Besides `feature = "…"`, only `doc` holds: the
+//! crate is not built for any target.
+//!
 //! # Configuration file
 //!
 //! If the default behavior of `cargo rdme` is not appropriate for your project you can crate a
@@ -169,9 +334,14 @@
 //! # in the project’s `Cargo.toml`.
 //! readme-path = "MY-README.md"
 //!
-//! # What line terminator to use when generating the README file.  This can be "lf" or "crlf".
+//! # What line terminator to use when generating the README file.  This can be "lf", "crlf", or
+//! # "cr".  By default the README keeps the line terminator it has.
 //! line-terminator = "lf"
 //!
+//! # Whether the README file ends with a line terminator.  This can be "always" or "never".  By
+//! # default the README keeps ending with one, or not, as it does now.
+//! final-newline = "always"
+//!
 //! # If you are using a workspace to hold multiple projects, use this to select the project from
 //! # which to extract the documentation from.  It can be useful to also set `readme-path` to create
 //! # the README file in the root of the project.
@@ -182,13 +352,139 @@
 //! # belonging to the README section where the insertion happens.
 //! heading-base-level = 0
 //!
+//! # Heading to add at the top of the crate’s documentation inserted in the README.
+//! heading = "Documentation"
+//!
+//! # Heading of the README section whose content is replaced with the crate’s documentation,
+//! # instead of the content between the cargo rdme markers.
+//! readme-section = "Documentation"
+//!
+//! # Add the documentation (`//!`) of an integration test, e.g. `tests/cookbook.rs`, as a
+//! # “Cookbook” section at the end of the crate’s documentation.  This is useful for guides made
+//! # of examples, which are tested along with the crate.
+//! cookbook = "cookbook"
+//!
+//! # Add a hash of the generated documentation to the end marker.  If someone edits the generated
+//! # documentation by hand, cargo rdme will refuse to overwrite it unless `--force` is used.
+//! content-hash = true
+//!
+//! # Experimental: instead of refusing to overwrite hand edits of the generated documentation,
+//! # merge them with the new documentation.  Conflicting edits are reported.  This requires
+//! # `content-hash`, since the base of the merge is the previously generated README.
+//! merge-hand-edits = true
+//!
+//! # Number the headings of the crate’s documentation inserted in the README (`1.`, `1.1.`, …).
+//! # Links to those headings, e.g. in a table of contents, are updated accordingly.
+//! heading-numbering = true
+//!
+//! # Align the columns of the tables of the crate’s documentation inserted in the README, so that
+//! # they are readable in the raw markdown and produce stable diffs.
+//! format-tables = true
+//!
+//! # Move the reference link definitions (`[foo]: https://…`) of the crate’s documentation inserted
+//! # in the README to its end, sorted by label.  Definitions with the same target are merged.
+//! sort-reference-links = true
+//!
+//! # Turn every warning (e.g. unresolved intralinks) into an error, and fail if a package of the
+//! # workspace is skipped.  This is useful for a zero-tolerance CI.
+//! strict = true
+//!
+//! # Run `cargo check` before extracting the documentation, so that it can be read from the output
+//! # of the build script (`OUT_DIR`).  See “Generated documentation” above.
+//! build-script = true
+//!
+//! # Convert emoji shortcodes such as `:crab:` to unicode emoji, since crates.io does not render
+//! # shortcodes.  This can be "unicode", or "shortcodes" to convert unicode emoji to shortcodes.
+//! # Every shortcode of GitHub (i.e. of gemoji) is supported.
+//! emoji = "unicode"
+//!
+//! # What to do with collapsible `<details>` blocks, which crates.io does not support.  This can
+//! # be "keep", "unwrap" (the `<summary>` becomes a heading of the content), or "drop".
+//! details = "unwrap"
+//!
+//! # What to do with mermaid diagrams, which crates.io does not render.  This can be "keep",
+//! # "image" (an image rendered by mermaid.ink), or "note" (a note linking to the crate’s
+//! # documentation).
+//! mermaid = "image"
+//!
+//! # What to do with math (`$…$`, `$$…$$`, and ```` ```math ```` blocks), which crates.io does not
+//! # render.  This can be "keep", "code" (code with the LaTeX source), or "image" (images rendered
+//! # by latex.codecogs.com).
+//! math = "code"
+//!
+//! # Blank lines at the start and at the end of the crate’s documentation are removed, and
+//! # consecutive blank lines are collapsed into one.  Set this to "keep" to keep them as they are.
+//! blank-lines = "keep"
+//!
+//! # Setext headings (underlined with `===` or `---`) are kept as they are.  Set this to "atx" to
+//! # convert them to `#` headings.
+//! setext-headings = "atx"
+//!
+//! # Indentation of nested lists, which is kept by default.  Set this to "normalize" to indent
+//! # them with four spaces per level, which crates.io needs in some cases.
+//! list-indentation = "normalize"
+//!
+//! # What to do with hard line breaks (lines ending in two spaces or a backslash).  This can be
+//! # "keep" (the default), "html" to replace them with `<br>`, or "collapse" to join the lines.
+//! hard-breaks = "html"
+//!
+//! # Style of the links of the documentation.  This can be "keep" (the default), "inline" (e.g.
+//! # `[foo](https://example.com)`), or "reference" (e.g. `[foo][foo]` with a definition of `foo`).
+//! link-style = "reference"
+//!
+//! # Version requirement of the dependency snippet generated in the `<!-- cargo-rdme usage -->`
+//! # marker.  This can be "major" (the default, e.g. `mycrate = "1"`), or "exact" (e.g.
+//! # `mycrate = "1.2.3"`).
+//! usage-version = "exact"
+//!
+//! # Style of that snippet.  This can be "toml" (the default), "cargo-add" (a `cargo add mycrate`
+//! # command), or "both".
+//! usage-style = "both"
+//!
+//! # Features of the crate to enable in that snippet.
+//! usage-features = ["serde"]
+//!
+//! # Put the logo of the crate, from `#![doc(html_logo_url = "…")]`, at the top of the README.
+//! logo = true
+//!
+//! # Check that the rust examples of the documentation compile.  See `--verify-examples` above.
+//! verify-examples = true
+//!
+//! # Do nothing, instead of failing, if the crate has no documentation.
+//! allow-missing-docs = true
+//!
+//! # Features of the crate enabled when evaluating `cfg_attr` doc attributes and `rdme:if`
+//! # directives.  See “Features” above.
+//! features = ["std"]
+//! all-features = false
+//! no-default-features = false
+//!
+//! # Warn about headings of the README, including the ones written by hand, whose capitalization
+//! # differs from the others.  This can be "sentence" (“Getting started”), "title" (“Getting
+//! # Started”), or "consistent" (the case of most headings).  In check mode these warnings fail.
+//! heading-case = "consistent"
+//!
+//! # With `--workspace`, warn if the `package.description` of the package shares less than this
+//! # percentage of words with the first sentence of the crate’s documentation.
+//! description-similarity = 50
+//!
+//! # Extract the crate’s documentation from this file instead of the crate’s entrypoint.  This is
+//! # useful if the entrypoint has no `//!` documentation, e.g. when it is mostly behind `#[cfg]`.
+//! # This can also be a markdown file, e.g. if your documentation is in `docs/crate.md` and you
+//! # include it in the crate with `#![doc = include_str!("../docs/crate.md")]`.
+//! doc-source = "src/docs.rs"
+//!
 //! # The default entrypoint will be `src/lib.rs`.  You can change that in the `entrypoint` table.
 //! [entrypoint]
-//! # The entrypoint type can be "lib" or "bin".
+//! # The entrypoint type can be "lib", "bin", or "example".
 //! type = "bin"
 //! # When you set type to "bin" the entrypoint default to `src/main.rs`.  If you have binary targets
 //! # specified in your cargo manifest you can select them by name with `bin-name`.
 //! bin-name = "my-bin-name"
+//! # When you set type to "example" the documentation is extracted from the example named
+//! # `example-name`, e.g. `examples/demo.rs`.  This is useful if the best “getting started” text
+//! # of your crate lives in an example.
+//! # example-name = "demo"
 //!
 //! [intralinks]
 //! # Defines the base url to use in intralinks urls.  The default value is `https://docs.rs`.
@@ -197,16 +493,188 @@
 //! docs-rs-version = "1.0.0"
 //! # If this is set the intralinks will be stripping in the README file.
 //! strip-links = false
+//! # Defines the url template to use in intralinks urls, instead of docs.rs.  The placeholders
+//! # `{crate}`, `{version}`, and `{path}` (the page path generated by rustdoc) are expanded.
+//! docs-url-template = "https://docs.internal/{crate}/{version}/{path}"
+//! # The type `Self` refers to in links such as `[Self::new]`.  If this is not set these links
+//! # become code spans, since there is no `Self` in the crate’s documentation.
+//! self-type = "MyType"
+//! # Link targets that are left as they are, without warnings, e.g. pseudo-links handled by a
+//! # proc-macro.  A `*` matches any sequence of characters.
+//! ignore = ["__private::*", "MyMacroHelper"]
+//!
+//! # Defines url templates for specific crates, which take precedence over `docs-url-template`.
+//! [intralinks.docs-url-templates]
+//! std = "https://std.internal/{path}"
+//!
+//! # Additional READMEs, e.g. translations of the README, updated along with the README.  Each
+//! # one has its own `readme-path` and, optionally, a `doc-source` with the documentation to
+//! # inject, otherwise it gets the crate’s documentation.  With `shared-code-blocks` the code
+//! # blocks of `doc-source` are replaced, in order, by the ones of the crate’s documentation, so
+//! # that the examples are only maintained in one place.
+//! [[translations]]
+//! readme-path = "README.zh-CN.md"
+//! doc-source = "docs/lib.zh-CN.md"
+//! shared-code-blocks = true
+//!
+//! # With `code-blocks-only` the README gets the crate’s documentation once, to be translated in
+//! # the README itself.  From then on only its code blocks are updated, and cargo rdme warns about
+//! # the sections of the documentation whose text changed since the last update.
+//! [[translations]]
+//! readme-path = "README.pt.md"
+//! code-blocks-only = true
 //! ```
 //!
 //! These setting can be overridden with command line flags.  Run `cargo rdme --help` for more
 //! information.
 //!
+//! The same settings can be set in the `[package.metadata.rdme]` table of your `Cargo.toml`.
+//!
+//! Your personal defaults, e.g. the line terminator you prefer, can be set in
+//! `~/.config/cargo-rdme/config.toml` (or in `cargo-rdme/config.toml` under `$XDG_CONFIG_HOME`), so
+//! that you do not need to repeat them in every project.  It accepts the same settings, and the
+//! configuration of the project takes precedence over it.
+//!
+//! Every setting can also be set with an environment variable, named after the setting with a
+//! `CARGO_RDME_` prefix, e.g. `CARGO_RDME_LINE_TERMINATOR=crlf` for `line-terminator = "crlf"`, which
+//! is handy in CI matrices.  Values are toml values, e.g. `CARGO_RDME_FEATURES='["std"]'`, but
+//! strings do not need quotes.  From the highest to the lowest precedence, settings come from:
+//!
+//! 1. the command line;
+//! 2. the `CARGO_RDME_*` environment variables;
+//! 3. `.cargo-rdme.toml`;
+//! 4. `[package.metadata.rdme]`;
+//! 5. `[workspace.metadata.rdme]` (see “Workspace configuration” below);
+//! 6. the user configuration.
+//!
+//! A JSON schema of the configuration is available in
+//! [`config-schema.json`](https://github.com/orium/cargo-rdme/blob/main/config-schema.json), and
+//! can also be printed with `cargo rdme config-schema`.  Editors can use it to validate and
+//! complete your configuration.
+//!
+//! ## Workspaces
+//!
+//! To update the README of every package of a workspace run `cargo rdme --workspace`.  Packages
+//! without a README or without crate documentation are skipped, and a summary with the status of
+//! each package is printed at the end.
+//!
+//! cargo rdme will also warn you if some packages use cargo rdme markers while others include the
+//! README in the crate documentation (e.g. with `#![doc = include_str!("../README.md")]`), since
+//! mixing both approaches in the same workspace tends to be confusing.
+//!
+//! To keep the listings of the registry consistent with the documentation, set
+//! `description-similarity` (e.g. `--description-similarity 50`) and cargo rdme will warn about
+//! packages whose `package.description` is less similar than that, in percentage of shared words,
+//! to the first sentence of their crate documentation.
+//!
+//! If processing a big workspace is slow, `--timings` reports how long each step (reading the
+//! manifest, extracting and transforming the documentation, and updating the README) took for
+//! every package.
+//!
+//! Packages are processed in parallel, by as many threads as there are logical CPUs, which
+//! `--jobs` (or `-j`) changes, e.g. `--jobs 1` processes them one at a time.  The output is the
+//! same regardless: what is printed for each package is always in the order of the packages.
+//! For workspaces with many packages, `--progress` shows how many packages were processed so far,
+//! and how many remain, when the output goes to a terminal.
+//!
+//! To get an overview of the documentation of the workspace run `cargo rdme stats`.  It reports,
+//! for every package, the length of the crate documentation and of the README, whether the README
+//! has cargo rdme markers, and how many intralinks there are and how many of them are unresolved.
+//! With `--json` the report is printed as JSON, e.g. to feed a documentation health dashboard.
+//!
+//! To process packages that are not part of the same workspace, e.g. repositories checked out side
+//! by side in a CI job, pass their directories with `--package-dir` (which can be used multiple
+//! times), or list them in a file with `--package-dir-list`, one directory per line.  Each package
+//! is processed independently, with the configuration file of its own directory, and a summary is
+//! printed at the end, just like with `--workspace`:
+//!
+//! ```bash
+//! cargo rdme --check --package-dir foo --package-dir bar
+//! ```
+//!
+//! ### Workspace configuration
+//!
+//! In a workspace you can also configure cargo rdme for all its packages in the workspace’s
+//! `Cargo.toml`, under `[workspace.metadata.rdme]`.  This table accepts the same settings as the
+//! configuration file, and packages can override them, or opt out entirely:
+//!
+//! ```toml
+//! [workspace.metadata.rdme]
+//! heading = "Documentation"
+//!
+//! [workspace.metadata.rdme.packages.my-cli]
+//! entrypoint = { type = "bin" }
+//! # Relative paths are relative to the package directory.
+//! readme-path = "../README.md"
+//!
+//! [workspace.metadata.rdme.packages.my-internal-crate]
+//! disabled = true
+//! ```
+//!
+//! The settings of `.cargo-rdme.toml` take precedence over the ones in `[package.metadata.rdme]`,
+//! which take precedence over the ones in the workspace, which take precedence over your user
+//! configuration.
+//!
+//! # Releases
+//!
+//! When you release a new version of your crate you can run `cargo rdme freeze` to pin the README
+//! to that version: links to the `latest` documentation of your crate in docs.rs will point to the
+//! version in your `Cargo.toml`, and so will the dependency snippets in toml code blocks (e.g.
+//! `mycrate = "1.2"`).  This way the README published with the release points to immutable
+//! documentation.
+//!
+//! Before publishing, `cargo rdme preview --target crates-io` shows the README as crates.io will:
+//! it renders the README that cargo rdme would generate like crates.io does, sanitizing the html
+//! and pointing relative links to your repository, to an html file in `target/cargo-rdme/preview/`,
+//! and opens it in the browser.  The README itself is not changed.  Use `--target github` to see it
+//! as GitHub shows it instead.  While you work on the documentation, `cargo rdme preview --serve`
+//! serves the preview at `http://127.0.0.1:8000/`, and the page reloads itself whenever a file of
+//! the package changes.
+//!
+//! # mdBook
+//!
+//! If your crate has a book made with [mdBook](https://rust-lang.github.io/mdBook/), the
+//! `mdbook-rdme` preprocessor injects the crate’s documentation in the chapters of the book that
+//! have cargo rdme markers, just like in the README.  Install it with
+//! `cargo install cargo-rdme --features mdbook` and add it to your `book.toml`:
+//!
+//! ```toml
+//! [preprocessor.rdme]
+//! # The manifest of the crate, relative to the book.  By default it is found from the book
+//! # directory.
+//! manifest-path = "../Cargo.toml"
+//! ```
+//!
+//! The preprocessor also supports `workspace-project` and `heading-base-level`, with the same
+//! meaning as in the configuration file of cargo rdme.  The documentation goes through the same
+//! transformations as in the README, with the configuration of the crate, e.g. its
+//! `.cargo-rdme.toml`.  The preprocessor runs `cargo rdme mdbook`, which is installed with it.
+//!
 //! # Integration with CI
 //!
 //! To verify that your README is up to date with your crate’s documentation you can run
 //! `cargo rdme --check`.  The exit code will be `0` if the README is up to date, `3` if it’s
-//! not, or `4` if there were warnings.
+//! not, or `4` if there were warnings.  In check mode cargo rdme does not write to the package
+//! directory, so it works in read-only checkouts (e.g. in the Nix store), where updating the
+//! README fails right away with an error.  There, `cargo rdme --stdout` prints the updated README
+//! instead of writing it.  The examples are not verified in check mode, since `verify-examples`
+//! builds them in the target directory, and the only write is by the `cargo check` of
+//! `build-script`, to the target directory, so with that option set `CARGO_TARGET_DIR` to a
+//! writable directory.
+//!
+//! For reproducible builds use `--deterministic`, so that the README does not depend on the machine
+//! cargo rdme runs on: the user configuration and the `CARGO_RDME_*` environment variables are
+//! ignored, and links to dependencies do not depend on `Cargo.lock` (see above).  cargo rdme does
+//! not otherwise use timestamps or the locale to generate the README.
+//!
+//! For zero tolerance you can add `--strict`, which turns every warning into an error (exit code
+//! `1`): the README is not updated if a warning is emitted while processing the documentation,
+//! and in a workspace a package without a README or crate documentation is a failure instead of
+//! being skipped.
+//!
+//! Errors and warnings have a stable code, e.g. `warning[RDME0003]: …`.  Run
+//! `cargo rdme explain RDME0003` for a detailed explanation of a code, with examples and possible
+//! fixes, or `cargo rdme explain` to list every code.
 //!
 //! If you use GitHub Actions you can add this step to verify if the README is up to date:
 //!
@@ -216,21 +684,89 @@
 //!     cargo install cargo-rdme
 //!     cargo rdme --check
 //! ```
+//!
+//! So that reviewers can see how the READMEs drifted without reading the logs of the CI, use
+//! `--summary <FILE>` to write a markdown summary with the status of every package and, collapsed,
+//! the diff of each README that changed (or would change, with `--check`).  This is meant to be
+//! posted as a comment of the pull request.  With `--summary -` the summary is printed instead,
+//! e.g. to append it to the `$GITHUB_STEP_SUMMARY` of GitHub Actions.
+//!
+//! For auditing, e.g. in release pipelines, `--report <FILE>` writes a JSON report of the run: the
+//! status of every package, the files written, and the warnings and errors, with their code and the
+//! file they are about.  Its format does not depend on the terminal, unlike the output of cargo
+//! rdme, and `--report -` prints it instead.
+//!
+//! To check the README in a hook of the [pre-commit](https://pre-commit.com) framework add this to
+//! your `.pre-commit-config.yaml` (`cargo rdme hook-config pre-commit` prints the definition of
+//! the hook):
+//!
+//! ```yaml
+//! repos:
+//!   - repo: https://github.com/orium/cargo-rdme
+//!     rev: v1.4.8 # The version of cargo rdme to use.
+//!     hooks:
+//!       - id: cargo-rdme
+//! ```
+//!
+//! For GitLab CI, `cargo rdme hook-config gitlab-ci` prints a job to add to your `.gitlab-ci.yml`.
+//! These integrations run `cargo rdme --check`, whose arguments and exit codes are stable.
 
-use crate::options::{EntrypointOpt, LineTerminatorOpt};
-use cargo_rdme::transform::IntralinkError;
+use crate::console::Progress;
+use crate::diagnostic::{Diagnostic, Snippet};
+use crate::explain::DiagnosticCode;
+use crate::history::{content_hash, History};
+use crate::options::{
+    BlankLinesOpt, CmdOptions, ConfigFileOptions, ConfigFileOptionsError, DetailsOpt, EmojiOpt,
+    EntrypointOpt, FinalNewlineOpt, HardBreaksOpt, HeadingCaseOpt, LineTerminatorOpt, LinkStyleOpt,
+    ListIndentationOpt, MathOpt, MermaidOpt, PreviewTargetOpt, SetextHeadingsOpt, Translation,
+    UsageStyleOpt, UsageVersionOpt,
+};
+use crate::parallel::for_each_parallel;
+use crate::report::{PackageReport, PackageStatus, Report};
+use crate::stats::PackageStats;
+use crate::summary::{summary_markdown, PackageSummary, ReadmeChange};
+use crate::timings::Timings;
+use crate::workspace_lint::{
+    description_mismatch_warning, doc_first_sentence, doc_strategy, mixed_doc_strategies_warning,
+    DocStrategy,
+};
+use cargo_rdme::transform::{CfgDirectiveError, IntralinkError, IntralinksConfig};
 use cargo_rdme::{
-    extract_doc_from_source_file, infer_line_terminator, inject_doc_in_readme, LineTerminator,
-    Project,
+    doc_examples, freeze_readme, heading_case_mismatches, inject_logo_in_readme,
+    inject_usage_in_readme, logo_html, merge3, preview_page, region_content_hashes,
+    source_doc_meta, usage_snippet, CfgContext, Doc, FeatureSelection, HeadingCase, InjectDocError,
+    PreviewTarget, ProjectError, Readme, RegionContentHash, UsageConfig, UsageStyle, UsageVersion,
+    VerifyExamplesError,
+};
+use cargo_rdme::{
+    extract_doc_from_source_file, extract_doc_from_source_file_with_cfg, fix_doc, fix_readme,
+    foreign_region_overlaps, infer_final_newline, infer_line_terminator, inject_doc_in_readme,
+    inject_doc_in_readme_section, readme_has_markers, source_doc_source_pragma,
+    source_html_root_url, source_out_dir_doc_include, sync_translation, DocSourceMap, FixContext,
+    LineTerminator, NewReadme, Project,
 };
-use cargo_rdme::{Doc, ProjectError, Readme};
 use std::cell::Cell;
+use std::fmt::{Display, Formatter};
 use std::path::{Path, PathBuf};
 use thiserror::Error;
 
 #[macro_use]
 mod console;
+mod diagnostic;
+mod explain;
+mod history;
+mod hook_config;
+#[cfg(feature = "mdbook")]
+mod mdbook;
 mod options;
+mod parallel;
+mod report;
+mod selftest;
+mod serve;
+mod stats;
+mod summary;
+mod timings;
+mod workspace_lint;
 
 enum ExitCode {
     Ok = 0,
@@ -251,15 +787,30 @@ impl From<RunError> for ExitCode {
             | RunError::ReadmeError(_)
             | RunError::NoEntrySourceFile
             | RunError::NoReadmeFile
-            | RunError::NoRustdoc
-            | RunError::InjectDocError(_)
+            | RunError::NoRustdoc(_)
+            | RunError::InjectDocError(..)
+            | RunError::ConfigError(_)
+            | RunError::ReadmeHandEdited
+            | RunError::NoMergeBase
+            | RunError::HandEditsMergeConflict(..)
             | RunError::TransformIntraLinkError(_)
+            | RunError::CfgDirectiveError(_)
+            | RunError::StrictWarnings
+            | RunError::NoBuildScriptOutDir
+            | RunError::NoCookbookTest(_)
+            | RunError::PackageDirListError(..)
+            | RunError::NothingToUndo
+            | RunError::UndoReadmeChanged(_)
+            | RunError::VerifyExamplesError(_)
+            | RunError::ExamplesDoNotCompile(_)
+            | RunError::ReadmeReadOnly(..)
             | RunError::IOError(_) => ExitCode::Error,
             RunError::ReadmeNotUpdatedUncommittedChanges => {
                 ExitCode::ReadmeNotUpdatedUncommittedChanges
             }
-            RunError::CheckReadmeMismatch => ExitCode::CheckMismatch,
+            RunError::CheckReadmeMismatch(_) => ExitCode::CheckMismatch,
             RunError::CheckHasWarnings => ExitCode::CheckHasWarnings,
+            RunError::PackagesFailed { first_error, .. } => (*first_error).into(),
         }
     }
 }
@@ -276,22 +827,90 @@ enum RunError {
     NoEntrySourceFile,
     #[error("crate's README file not found")]
     NoReadmeFile,
-    #[error("crate-level rustdoc not found")]
-    NoRustdoc,
+    #[error("crate-level rustdoc not found in \"{}\"", .0.display())]
+    NoRustdoc(PathBuf),
+    #[error("integration test `{0}` of the `cookbook` option not found")]
+    NoCookbookTest(String),
+    #[error("`doc-source` refers to `$OUT_DIR`, which requires the `build-script` option and a package with a build script")]
+    NoBuildScriptOutDir,
     #[error("failed to inject the documentation in the README: {0}")]
-    InjectDocError(cargo_rdme::InjectDocError),
+    InjectDocError(cargo_rdme::InjectDocError, PathBuf),
     #[error("IO error: {0}")]
     IOError(std::io::Error),
+    #[error("failed to read the list of package directories \"{}\": {}", .0.display(), .1)]
+    PackageDirListError(PathBuf, std::io::Error),
     #[error(
         "not updating README: it has uncommitted changes (use `--force` to bypass this check)"
     )]
     ReadmeNotUpdatedUncommittedChanges,
+    #[error("not updating README: \"{}\" is not writable: {}", .0.display(), .1)]
+    ReadmeReadOnly(PathBuf, std::io::Error),
+    #[error("not updating README: the generated documentation was edited by hand (use `--force` to overwrite it)")]
+    ReadmeHandEdited,
+    #[error("unable to merge hand edits: the previously generated README is not available")]
+    NoMergeBase,
+    #[error("unable to merge hand edits: they conflict with the changes in the documentation (use `--force` to overwrite them)")]
+    HandEditsMergeConflict(cargo_rdme::MergeConflicts, PathBuf),
+    #[error("{0}")]
+    ConfigError(ConfigFileOptionsError),
     #[error("failed to transform intralinks: {0}")]
     TransformIntraLinkError(IntralinkError),
+    #[error("failed to evaluate `rdme:if` directives: {0}")]
+    CfgDirectiveError(CfgDirectiveError),
     #[error("README is not up to date")]
-    CheckReadmeMismatch,
+    CheckReadmeMismatch(Option<Box<ReadmeMismatch>>),
     #[error("README is up to date, but warnings were emitted")]
     CheckHasWarnings,
+    #[error("warnings were emitted, which are errors in strict mode")]
+    StrictWarnings,
+    #[error("nothing to undo: cargo rdme did not write any README since the target directory was created")]
+    NothingToUndo,
+    #[error("not undoing: README \"{}\" changed since cargo rdme wrote it (use `--force` to restore it anyway)", .0.display())]
+    UndoReadmeChanged(PathBuf),
+    #[error("failed to verify the examples of the documentation: {0}")]
+    VerifyExamplesError(VerifyExamplesError),
+    #[error("{0} example(s) of the documentation do not compile")]
+    ExamplesDoNotCompile(usize),
+    #[error("failed to process {} package(s): {}", .failed.len(), .failed.join(", "))]
+    PackagesFailed { failed: Vec<String>, first_error: Box<RunError> },
+}
+
+impl RunError {
+    fn code(&self) -> Option<DiagnosticCode> {
+        match self {
+            RunError::NoReadmeFile => Some(DiagnosticCode::NoReadmeFile),
+            RunError::NoRustdoc(_) => Some(DiagnosticCode::NoRustdoc),
+            RunError::NoBuildScriptOutDir => Some(DiagnosticCode::NoBuildScriptOutDir),
+            RunError::InjectDocError(InjectDocError::ChangedOutsideMarkers { .. }, _) => None,
+            RunError::InjectDocError(..) => Some(DiagnosticCode::InvalidMarkers),
+            RunError::ReadmeNotUpdatedUncommittedChanges => {
+                Some(DiagnosticCode::ReadmeUncommittedChanges)
+            }
+            RunError::ReadmeHandEdited => Some(DiagnosticCode::ReadmeHandEdited),
+            RunError::NoMergeBase | RunError::HandEditsMergeConflict(..) => {
+                Some(DiagnosticCode::HandEditsMergeConflict)
+            }
+            RunError::ConfigError(_) => Some(DiagnosticCode::InvalidConfig),
+            RunError::CheckReadmeMismatch(_) => Some(DiagnosticCode::ReadmeMismatch),
+            RunError::CheckHasWarnings => Some(DiagnosticCode::CheckHasWarnings),
+            RunError::StrictWarnings => Some(DiagnosticCode::StrictWarnings),
+            RunError::ProjectError(_)
+            | RunError::ExtractDocError(_)
+            | RunError::ReadmeError(_)
+            | RunError::NoEntrySourceFile
+            | RunError::NoCookbookTest(_)
+            | RunError::IOError(_)
+            | RunError::PackageDirListError(..)
+            | RunError::NothingToUndo
+            | RunError::UndoReadmeChanged(_)
+            | RunError::VerifyExamplesError(_)
+            | RunError::ExamplesDoNotCompile(_)
+            | RunError::ReadmeReadOnly(..)
+            | RunError::TransformIntraLinkError(_)
+            | RunError::CfgDirectiveError(_)
+            | RunError::PackagesFailed { .. } => None,
+        }
+    }
 }
 
 impl From<ProjectError> for RunError {
@@ -312,9 +931,9 @@ impl From<cargo_rdme::ReadmeError> for RunError {
     }
 }
 
-impl From<cargo_rdme::InjectDocError> for RunError {
-    fn from(e: cargo_rdme::InjectDocError) -> RunError {
-        RunError::InjectDocError(e)
+impl From<ConfigFileOptionsError> for RunError {
+    fn from(e: ConfigFileOptionsError) -> RunError {
+        RunError::ConfigError(e)
     }
 }
 
@@ -330,28 +949,98 @@ impl From<IntralinkError> for RunError {
     }
 }
 
+impl From<CfgDirectiveError> for RunError {
+    fn from(e: CfgDirectiveError) -> RunError {
+        RunError::CfgDirectiveError(e)
+    }
+}
+
+impl From<VerifyExamplesError> for RunError {
+    fn from(e: VerifyExamplesError) -> RunError {
+        RunError::VerifyExamplesError(e)
+    }
+}
+
 impl From<std::convert::Infallible> for RunError {
     fn from(_: std::convert::Infallible) -> RunError {
         unreachable!()
     }
 }
 
+/// How the README file is written.
+#[derive(Clone, Copy, Debug)]
+struct ReadmeFormat {
+    line_terminator: LineTerminator,
+    /// Whether the README ends with a line terminator.
+    final_newline: bool,
+}
+
+impl ReadmeFormat {
+    fn new(options: &options::Options, readme_path: &Path) -> std::io::Result<ReadmeFormat> {
+        Ok(ReadmeFormat {
+            line_terminator: line_terminator(options.line_terminator, readme_path)?,
+            final_newline: final_newline(options.final_newline, readme_path)?,
+        })
+    }
+
+    fn render(self, readme: &Readme) -> String {
+        let readme_str = readme.to_string(self.line_terminator);
+        let terminator = self.line_terminator.as_str();
+
+        match self.final_newline {
+            true => readme_str,
+            false => readme_str.strip_suffix(terminator).unwrap_or(&readme_str).to_owned(),
+        }
+    }
+}
+
 /// Check if the README is up to date.
 ///
-/// This will check if the README has the given line terminator as well.
+/// This will check if the README has the given format (e.g. line terminator) as well.
 fn is_readme_up_to_date(
     readme_path: impl AsRef<Path>,
     new_readme: &Readme,
-    line_terminator: LineTerminator,
+    format: ReadmeFormat,
 ) -> Result<bool, RunError> {
     let current_readme_raw: String = std::fs::read_to_string(readme_path)?;
-    let new_readme_raw: Vec<u8> = {
-        let mut bytes: Vec<u8> = Vec::with_capacity(32 * 1024);
-        new_readme.write(&mut bytes, line_terminator)?;
-        bytes
-    };
 
-    Ok(current_readme_raw.as_bytes() == new_readme_raw.as_slice())
+    Ok(current_readme_raw == format.render(new_readme))
+}
+
+/// Where the README differs from the generated README.
+#[derive(Debug)]
+struct ReadmeMismatch {
+    readme_path: PathBuf,
+    /// First line of the README that differs.
+    line_number: usize,
+    /// The file and line of the documentation that line is generated from.
+    source: Option<(PathBuf, usize)>,
+}
+
+fn readme_mismatch(
+    readme_path: &Path,
+    new_readme: &Readme,
+    format: ReadmeFormat,
+    source_map: Option<&DocSourceMap>,
+) -> Option<Box<ReadmeMismatch>> {
+    let current_readme = std::fs::read_to_string(readme_path).ok()?;
+    let new_readme_str = format.render(new_readme);
+    let line_index = current_readme
+        .split_inclusive('\n')
+        .zip(new_readme_str.split_inclusive('\n'))
+        .position(|(current, new)| current != new)
+        .unwrap_or_else(|| current_readme.lines().count().min(new_readme_str.lines().count()));
+    let source = source_map.and_then(|source_map| {
+        let source_line = source_map.readme_source_lines(new_readme).get(line_index).copied()??;
+
+        Some((source_map.file().to_path_buf(), source_line))
+    });
+
+    Some(Box::new(ReadmeMismatch {
+        readme_path: readme_path.to_path_buf(),
+        line_number: line_index + 1,
+        source,
+    }))
 }
 
 fn entrypoint<'a>(project: &'a Project, entrypoint_opt: &EntrypointOpt) -> Option<&'a Path> {
@@ -362,9 +1051,28 @@ fn entrypoint<'a>(project: &'a Project, entrypoint_opt: &EntrypointOpt) -> Optio
         EntrypointOpt::Lib => project.get_lib_entryfile_path(),
         EntrypointOpt::BinDefault => project.get_bin_default_entryfile_path(),
         EntrypointOpt::BinName(name) => project.get_bin_entryfile_path(name),
+        EntrypointOpt::Example(name) => project.get_example_entryfile_path(name),
     }
 }
 
+/// Adds the documentation of the integration test `test_name` as a “Cookbook” section.
+fn add_cookbook(project: &Project, doc: &Doc, test_name: &str) -> Result<Doc, RunError> {
+    let test_file = project
+        .get_test_entryfile_path(test_name)
+        .ok_or_else(|| RunError::NoCookbookTest(test_name.to_owned()))?;
+    let cookbook = extract_doc_from_source_file(test_file)?
+        .ok_or_else(|| RunError::NoRustdoc(test_file.to_path_buf()))?;
+
+    Ok(doc.with_section("Cookbook", &cookbook))
+}
+
+fn is_markdown_file(path: impl AsRef<Path>) -> bool {
+    path.as_ref()
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("md") || ext.eq_ignore_ascii_case("markdown"))
+}
+
 fn line_terminator(
     line_terminator_opt: LineTerminatorOpt,
     readme_path: impl AsRef<Path>,
@@ -373,6 +1081,18 @@ fn line_terminator(
         LineTerminatorOpt::Auto => infer_line_terminator(readme_path),
         LineTerminatorOpt::Lf => Ok(LineTerminator::Lf),
         LineTerminatorOpt::CrLf => Ok(LineTerminator::CrLf),
+        LineTerminatorOpt::Cr => Ok(LineTerminator::Cr),
+    }
+}
+
+fn final_newline(
+    final_newline_opt: FinalNewlineOpt,
+    readme_path: impl AsRef<Path>,
+) -> std::io::Result<bool> {
+    match final_newline_opt {
+        FinalNewlineOpt::Auto => infer_final_newline(readme_path),
+        FinalNewlineOpt::Always => Ok(true),
+        FinalNewlineOpt::Never => Ok(false),
     }
 }
 
@@ -380,6 +1100,42 @@ struct Warnings {
     had_warnings: bool,
 }
 
+fn intralinks_config(
+    project: &Project,
+    entrypoint: &Path,
+    options: &options::Options,
+) -> IntralinksConfig {
+    let mut intralinks_config = options.intralinks.clone().unwrap_or_default();
+
+    intralinks_config.docs_rs.dependencies = project.get_dependencies().to_vec();
+
+    // The version a dependency resolves to depends on `Cargo.lock`, or on the versions published
+    // if there is none, but its requirement is in the manifest.
+    if options.deterministic {
+        for dependency in &mut intralinks_config.docs_rs.dependencies {
+            dependency.version = dependency.requirement_version();
+        }
+    }
+
+    // If the entrypoint cannot be read or parsed the intralinks transform reports it.
+    let html_root_url: Option<String> = std::fs::read_to_string(entrypoint)
+        .ok()
+        .and_then(|source| source_html_root_url(&source).ok().flatten());
+
+    if let Some(html_root_url) = html_root_url {
+        intralinks_config.docs_rs.set_html_root_url(&html_root_url, project.get_package_name());
+    }
+
+    if let Some(documentation_url) = project.get_documentation_url() {
+        intralinks_config
+            .docs_rs
+            .set_package_documentation_url(documentation_url, project.get_package_name());
+    }
+
+    intralinks_config
+}
+
+#[allow(clippy::too_many_lines)]
 fn transform_doc(
     doc: &Doc,
     project: &Project,
@@ -387,30 +1143,192 @@ fn transform_doc(
     options: &options::Options,
 ) -> Result<(Doc, Warnings), RunError> {
     use cargo_rdme::transform::{
-        DocTransform, DocTransformIntralinks, DocTransformRustMarkdownTag,
-        DocTransformRustRemoveComments,
+        DetailsAction, DocTransform, DocTransformBlankLines, DocTransformCfgDirectives,
+        DocTransformDetails, DocTransformEmoji, DocTransformFormatTables, DocTransformHardBreaks,
+        DocTransformHeadingNumbering, DocTransformIntralinks, DocTransformLinkStyle,
+        DocTransformListIndentation, DocTransformMath, DocTransformMermaid,
+        DocTransformReferenceLinks, DocTransformRustMarkdownTag, DocTransformRustRemoveComments,
+        DocTransformSetextHeadings, EmojiConversion, HardBreakAction, LinkStyle, MathAction,
+        MermaidAction,
     };
 
+    let transform = DocTransformCfgDirectives::new(cfg_context(project, options));
+    let doc = transform.transform(doc)?;
+
     let transform = DocTransformRustRemoveComments::new();
     // TODO Use `into_ok()` once it is stable (https://github.com/rust-lang/rust/issues/61695).
-    let doc = transform.transform(doc)?;
+    let doc = transform.transform(&doc)?;
 
     let transform = DocTransformRustMarkdownTag::new();
     // TODO Use `into_ok()` once it is stable (https://github.com/rust-lang/rust/issues/61695).
     let doc = transform.transform(&doc)?;
 
+    let doc = match options.blank_lines {
+        None | Some(BlankLinesOpt::Trim) => {
+            let transform = DocTransformBlankLines::new();
+            // TODO Use `into_ok()` once it is stable (https://github.com/rust-lang/rust/issues/61695).
+            transform.transform(&doc)?
+        }
+        Some(BlankLinesOpt::Keep) => doc,
+    };
+
+    let doc = match options.setext_headings {
+        Some(SetextHeadingsOpt::Atx) => {
+            let transform = DocTransformSetextHeadings::new();
+            // TODO Use `into_ok()` once it is stable (https://github.com/rust-lang/rust/issues/61695).
+            transform.transform(&doc)?
+        }
+        None | Some(SetextHeadingsOpt::Keep) => doc,
+    };
+
+    let doc = match options.list_indentation {
+        Some(ListIndentationOpt::Normalize) => {
+            let transform = DocTransformListIndentation::new();
+            // TODO Use `into_ok()` once it is stable (https://github.com/rust-lang/rust/issues/61695).
+            transform.transform(&doc)?
+        }
+        None | Some(ListIndentationOpt::Keep) => doc,
+    };
+
+    let hard_break_action = match options.hard_breaks {
+        None | Some(HardBreaksOpt::Keep) => None,
+        Some(HardBreaksOpt::Html) => Some(HardBreakAction::Html),
+        Some(HardBreaksOpt::Collapse) => Some(HardBreakAction::Collapse),
+    };
+    let doc = match hard_break_action {
+        Some(action) => {
+            let transform = DocTransformHardBreaks::new(action);
+            // TODO Use `into_ok()` once it is stable (https://github.com/rust-lang/rust/issues/61695).
+            transform.transform(&doc)?
+        }
+        None => doc,
+    };
+
+    let doc = match options.heading_numbering {
+        true => {
+            let transform = DocTransformHeadingNumbering::new();
+            // TODO Use `into_ok()` once it is stable (https://github.com/rust-lang/rust/issues/61695).
+            transform.transform(&doc)?
+        }
+        false => doc,
+    };
+
+    let doc = match options.emoji {
+        Some(emoji) => {
+            let conversion = match emoji {
+                EmojiOpt::Unicode => EmojiConversion::ShortcodeToUnicode,
+                EmojiOpt::Shortcodes => EmojiConversion::UnicodeToShortcode,
+            };
+            let transform = DocTransformEmoji::new(conversion);
+            // TODO Use `into_ok()` once it is stable (https://github.com/rust-lang/rust/issues/61695).
+            transform.transform(&doc)?
+        }
+        None => doc,
+    };
+
+    let details_action = match options.details {
+        None | Some(DetailsOpt::Keep) => None,
+        Some(DetailsOpt::Unwrap) => Some(DetailsAction::Unwrap),
+        Some(DetailsOpt::Drop) => Some(DetailsAction::Drop),
+    };
+    let doc = match details_action {
+        Some(action) => {
+            let transform = DocTransformDetails::new(action);
+            // TODO Use `into_ok()` once it is stable (https://github.com/rust-lang/rust/issues/61695).
+            transform.transform(&doc)?
+        }
+        None => doc,
+    };
+
+    let mermaid_action = match options.mermaid {
+        None | Some(MermaidOpt::Keep) => None,
+        Some(MermaidOpt::Image) => Some(MermaidAction::Image),
+        Some(MermaidOpt::Note) => {
+            let documentation_url = match project.get_documentation_url() {
+                Some(url) => url.to_owned(),
+                None => format!("https://docs.rs/{}", project.get_package_name()),
+            };
+
+            Some(MermaidAction::Note { documentation_url })
+        }
+    };
+    let doc = match mermaid_action {
+        Some(action) => {
+            let transform = DocTransformMermaid::new(action);
+            // TODO Use `into_ok()` once it is stable (https://github.com/rust-lang/rust/issues/61695).
+            transform.transform(&doc)?
+        }
+        None => doc,
+    };
+
+    let math_action = match options.math {
+        None | Some(MathOpt::Keep) => None,
+        Some(MathOpt::Code) => Some(MathAction::Code),
+        Some(MathOpt::Image) => Some(MathAction::Image),
+    };
     let had_warnings = Cell::new(false);
+
+    let doc = match math_action {
+        Some(action) => {
+            // The conversion was asked for by the user, so these warnings only fail in strict mode.
+            let transform = DocTransformMath::new(action, |msg| {
+                print_warning!(code: DiagnosticCode::MathConverted, "{}", msg);
+                had_warnings.set(had_warnings.get() || options.strict);
+            });
+            // TODO Use `into_ok()` once it is stable (https://github.com/rust-lang/rust/issues/61695).
+            transform.transform(&doc)?
+        }
+        None => doc,
+    };
+
+    let doc = match options.format_tables {
+        true => {
+            let transform = DocTransformFormatTables::new();
+            // TODO Use `into_ok()` once it is stable (https://github.com/rust-lang/rust/issues/61695).
+            transform.transform(&doc)?
+        }
+        false => doc,
+    };
+
+    let intralinks_config = intralinks_config(project, entrypoint.as_ref(), options);
     let transform = DocTransformIntralinks::new(
         project.get_package_name(),
         entrypoint,
         |msg| {
-            print_warning!("{}", msg);
+            print_warning!(code: DiagnosticCode::UnresolvedIntralink, "{}", msg);
             had_warnings.set(true);
         },
-        options.intralinks.clone(),
+        Some(intralinks_config),
     );
 
-    Ok((transform.transform(&doc)?, Warnings { had_warnings: had_warnings.into_inner() }))
+    let doc = transform.transform(&doc)?;
+
+    // This comes after the intralinks, so that their links are converted too.
+    let link_style = match options.link_style {
+        None | Some(LinkStyleOpt::Keep) => None,
+        Some(LinkStyleOpt::Inline) => Some(LinkStyle::Inline),
+        Some(LinkStyleOpt::Reference) => Some(LinkStyle::Reference),
+    };
+    let doc = match link_style {
+        Some(style) => {
+            let transform = DocTransformLinkStyle::new(style);
+            // TODO Use `into_ok()` once it is stable (https://github.com/rust-lang/rust/issues/61695).
+            transform.transform(&doc)?
+        }
+        None => doc,
+    };
+
+    // This comes after the intralinks, so that the definitions of their links are sorted too.
+    let doc = match options.sort_reference_links {
+        true => {
+            let transform = DocTransformReferenceLinks::new();
+            // TODO Use `into_ok()` once it is stable (https://github.com/rust-lang/rust/issues/61695).
+            transform.transform(&doc)?
+        }
+        false => doc,
+    };
+
+    Ok((doc, Warnings { had_warnings: had_warnings.into_inner() }))
 }
 
 /// Check if the `path` has local changes that were not yet commited.
@@ -429,94 +1347,1725 @@ fn git_is_current(path: impl AsRef<Path>) -> Option<bool> {
     Some(status == Status::CURRENT)
 }
 
+/// Fails if the README cannot be written, e.g. in a read-only checkout, before anything is written
+/// for it.  Opening the file for appending does not change it.
+fn check_readme_writable(readme_path: &Path) -> Result<(), RunError> {
+    match std::fs::OpenOptions::new().append(true).open(readme_path) {
+        Ok(_) => Ok(()),
+        Err(e) => Err(RunError::ReadmeReadOnly(readme_path.to_path_buf(), e)),
+    }
+}
+
 fn update_readme(
     new_readme: &Readme,
     readme_path: impl AsRef<Path>,
-    line_terminator: LineTerminator,
+    format: ReadmeFormat,
     ignore_uncommitted_changes: bool,
+    history: &History,
 ) -> Result<(), RunError> {
-    match ignore_uncommitted_changes || git_is_current(&readme_path).unwrap_or(true) {
-        true => Ok(new_readme.write_to_file(&readme_path, line_terminator)?),
-        false => Err(RunError::ReadmeNotUpdatedUncommittedChanges),
+    if !ignore_uncommitted_changes && !git_is_current(&readme_path).unwrap_or(true) {
+        return Err(RunError::ReadmeNotUpdatedUncommittedChanges);
     }
-}
 
-fn run(options: options::Options) -> Result<(), RunError> {
-    let project: Project = match options.workspace_project {
-        None => Project::from_current_dir()?,
-        Some(ref project) => Project::from_current_dir_workspace_project(project)?,
-    };
-    let entryfile: &Path =
-        entrypoint(&project, &options.entrypoint).ok_or(RunError::NoEntrySourceFile)?;
-    let doc: Doc = match extract_doc_from_source_file(entryfile)? {
-        None => return Err(RunError::NoRustdoc),
-        Some(doc) => doc,
-    };
+    let old_readme = std::fs::read(&readme_path)?;
+    let new_readme = format.render(new_readme);
 
-    let (doc, warnings) = transform_doc(&doc, &project, entryfile, &options)?;
+    std::fs::write(&readme_path, &new_readme)?;
 
-    let readme_path: PathBuf = match options.readme_path {
-        None => project.get_readme_path().ok_or(RunError::NoReadmeFile)?,
-        Some(path) => {
-            if !path.is_file() {
-                return Err(RunError::NoReadmeFile);
-            }
-            path
-        }
-    };
-    let original_readme: Readme = Readme::from_file(&readme_path)?;
-    let new_readme = inject_doc_in_readme(&original_readme, &doc, options.heading_base_level)?;
+    if let Err(e) = history.record(readme_path.as_ref(), &old_readme, new_readme.as_bytes()) {
+        print_warning!("unable to record the README in the history of `cargo rdme undo`: {}", e);
+    }
 
-    if !new_readme.had_marker {
-        let msg = indoc::formatdoc! { "
-            No marker found in the README file ({readme_filepath}).
+    Ok(())
+}
 
-            cargo-rdme expects a marker in the README where the crate’s documentation will
-            be inserted.  This is the marker you should add to your README:
+/// Restores the READMEs written by the last run to what they were before.  The history is kept in
+/// the target directory, which the packages of a workspace share, so this undoes `--workspace`
+/// runs as well.
+fn run_undo(cmd_options: &CmdOptions) -> Result<(), RunError> {
+    let projects =
+        Project::workspace_members(cmd_options.manifest_path(), cmd_options.cargo_flags())?;
+    let project = projects.first().ok_or(RunError::NothingToUndo)?;
+    let history = History::new(project.get_target_directory());
+    let entries = history.last_run()?;
 
-            {marker}",
-            readme_filepath = readme_path.display(),
-            marker = cargo_rdme::MARKER_RDME,
-        };
-        print_info!("{}", msg);
+    if entries.is_empty() {
+        return Err(RunError::NothingToUndo);
     }
 
-    let line_terminator = line_terminator(options.line_terminator, &readme_path)?;
-
-    match options.check {
-        false => update_readme(&new_readme.readme, readme_path, line_terminator, options.force),
-        true => {
-            if !is_readme_up_to_date(&readme_path, &new_readme.readme, line_terminator)? {
-                return Err(RunError::CheckReadmeMismatch);
-            }
-
-            if warnings.had_warnings && !options.no_fail_on_warnings {
-                return Err(RunError::CheckHasWarnings);
-            }
+    // Nothing is restored if a README changed since, so that the run is not partially undone.
+    for entry in &entries {
+        let current = std::fs::read(&entry.readme_path).unwrap_or_default();
 
-            Ok(())
+        if !cmd_options.force() && content_hash(&current) != entry.new_hash {
+            return Err(RunError::UndoReadmeChanged(entry.readme_path.clone()));
         }
     }
-}
 
-fn main() {
-    let cmd_options = options::cmd_options();
+    for entry in entries.iter().rev() {
+        std::fs::write(&entry.readme_path, history.content(entry.old_hash)?)?;
+        print_info!("restored {}", entry.readme_path.display());
+    }
+
+    Ok(history.forget_last_run()?)
+}
+
+/// Where we keep a copy of the last generated README, to use as the base of the merge when the
+/// generated documentation is edited by hand.
+fn merge_base_path(project: &Project, readme_path: &Path) -> PathBuf {
+    let readme_filename = readme_path.file_name().unwrap_or(readme_path.as_os_str());
+
+    project
+        .get_target_directory()
+        .join("cargo-rdme")
+        .join(project.get_package_name())
+        .join(readme_filename)
+}
+
+fn save_merge_base(merge_base_path: &Path, generated_readme: &Readme) {
+    let result = merge_base_path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|()| std::fs::write(merge_base_path, generated_readme.as_string()));
+
+    if let Err(e) = result {
+        print_warning!("unable to save a copy of the generated README: {}", e);
+    }
+}
+
+/// Merges the hand edits of the README with the newly generated README.  The base of the merge is
+/// the previously generated README, which must be the one the hashes of the markers refer to.
+fn merge_hand_edits(
+    merge_base_path: &Path,
+    readme_path: &Path,
+    readme: &Readme,
+    generated_readme: &Readme,
+) -> Result<Readme, RunError> {
+    let marker_hashes = |readme: &Readme| -> Vec<u64> {
+        region_content_hashes(readme).iter().map(|h| h.marker_hash).collect()
+    };
+
+    let base = Readme::from_file(merge_base_path)
+        .ok()
+        .filter(|base| region_content_hashes(base).iter().all(RegionContentHash::is_pristine))
+        .filter(|base| marker_hashes(base) == marker_hashes(readme))
+        .ok_or(RunError::NoMergeBase)?;
+
+    let merged = merge3(base.as_string(), readme.as_string(), generated_readme.as_string())
+        .map_err(|conflicts| RunError::HandEditsMergeConflict(conflicts, readme_path.to_owned()))?;
+
+    Ok(Readme::from_str(merged))
+}
+
+/// What happened to the README of a package.
+enum PackageOutcome {
+    Updated,
+    UpToDate,
+    Skipped(String),
+}
+
+impl Display for PackageOutcome {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PackageOutcome::Updated => f.write_str("updated"),
+            PackageOutcome::UpToDate => f.write_str("up-to-date"),
+            PackageOutcome::Skipped(reason) => write!(f, "skipped ({reason})"),
+        }
+    }
+}
+
+/// Runs cargo rdme, adding the report of every package processed to `reports`.
+fn run(
+    cmd_options: CmdOptions,
+    config_file_options: Option<ConfigFileOptions>,
+    reports: &mut Vec<PackageReport>,
+) -> Result<(), RunError> {
+    if cmd_options.stats() {
+        return run_stats(&cmd_options, config_file_options.as_ref());
+    }
+
+    if cmd_options.undo() {
+        return run_undo(&cmd_options);
+    }
+
+    if let Some(address) = cmd_options.preview_serve() {
+        return run_preview_server(&cmd_options, config_file_options.as_ref(), address);
+    }
+
+    let strict = cmd_options.strict()
+        || config_file_options.as_ref().and_then(ConfigFileOptions::strict).unwrap_or(false);
+
+    if cmd_options.workspace() {
+        run_workspace(&cmd_options, config_file_options.as_ref(), strict, reports)?;
+    } else if !cmd_options.package_dirs().is_empty() || cmd_options.package_dir_list().is_some() {
+        run_package_dirs(&cmd_options, strict, reports)?;
+    } else {
+        let workspace_project = cmd_options.workspace_project().or_else(|| {
+            config_file_options.as_ref().and_then(ConfigFileOptions::workspace_project)
+        });
+        let report_timings = cmd_options.timings();
+        let summary_path = cmd_options.summary_path().map(Path::to_path_buf);
+        let mut timings = Timings::default();
+        let mut changes = Vec::new();
+        let project: Project = timings.time("manifest", || {
+            Project::load(cmd_options.manifest_path(), workspace_project, cmd_options.cargo_flags())
+        })?;
+        let result =
+            run_package(&project, cmd_options, config_file_options, &mut timings, &mut changes);
+
+        if report_timings {
+            print_info!("timings of `{}`: {}", project.get_package_name(), timings);
+        }
+
+        let status = result.as_ref().map_err(|e| run_error_diagnostic(e).message().to_owned());
+
+        reports.push(package_report(project.get_package_name().to_owned(), status, &changes));
+
+        if let Some(summary_path) = summary_path {
+            let status = match &result {
+                Ok(outcome) => outcome.to_string(),
+                Err(e) => format!("failed: {e}"),
+            };
+            let package =
+                PackageSummary { name: project.get_package_name().to_owned(), status, changes };
+
+            write_summary(&summary_path, &[package])?;
+        }
+
+        match result? {
+            PackageOutcome::Skipped(reason) => {
+                print_info!("skipping package `{}`: {}", project.get_package_name(), reason);
+            }
+            PackageOutcome::Updated | PackageOutcome::UpToDate => (),
+        }
+    }
+
+    // Warnings emitted outside of the processing of the documentation, such as the ones about the
+    // workspace, are also errors in strict mode.
+    match strict && console::warning_count() > 0 {
+        true => Err(RunError::StrictWarnings),
+        false => Ok(()),
+    }
+}
+
+/// Processes every package of the workspace.  Packages without a README or crate documentation
+/// are skipped, unless in strict mode, and failures do not stop the remaining packages from being
+/// processed.
+fn run_workspace(
+    cmd_options: &CmdOptions,
+    config_file_options: Option<&ConfigFileOptions>,
+    strict: bool,
+    reports: &mut Vec<PackageReport>,
+) -> Result<(), RunError> {
+    let mut manifest_timings = Timings::default();
+    let projects = manifest_timings.time("manifest", || {
+        Project::workspace_members(cmd_options.manifest_path(), cmd_options.cargo_flags())
+    })?;
+
+    if cmd_options.timings() {
+        print_info!("timings of the workspace: {}", manifest_timings);
+    }
+
+    let mut summary = BatchSummary::new("package", strict);
+
+    for_each_parallel(
+        &projects,
+        cmd_options.jobs(),
+        &Progress::new("packages", projects.len(), cmd_options.progress()),
+        |project| {
+            let mut timings = Timings::default();
+            let mut changes = Vec::new();
+            let result = run_package(
+                project,
+                cmd_options.clone(),
+                config_file_options.cloned(),
+                &mut timings,
+                &mut changes,
+            );
+
+            if cmd_options.timings() {
+                print_info!("timings of `{}`: {}", project.get_package_name(), timings);
+            }
+
+            (result, changes)
+        },
+        |project, (result, changes)| {
+            summary.add(project.get_package_name().to_owned(), result, changes);
+        },
+    );
+
+    summary.print();
+
+    if let Some(summary_path) = cmd_options.summary_path() {
+        write_summary(summary_path, &summary.packages)?;
+    }
+
+    reports.append(&mut summary.reports);
+
+    let doc_strategies: Vec<(&str, DocStrategy)> = projects
+        .iter()
+        .filter_map(|project| Some((project.get_package_name(), doc_strategy(project)?)))
+        .collect();
+
+    if let Some(warning) = mixed_doc_strategies_warning(&doc_strategies) {
+        print_warning!("{}", warning);
+    }
+
+    for project in &projects {
+        // Packages whose options are invalid already failed above.
+        let Ok(options) =
+            package_options(project, cmd_options.clone(), config_file_options.cloned())
+        else {
+            continue;
+        };
+
+        if let Some(warning) = package_description_warning(project, &options) {
+            print_warning!("{}", warning);
+        }
+    }
+
+    summary.into_result()
+}
+
+/// Checks the description of the package against its crate documentation, if the
+/// `description-similarity` option is set.
+fn package_description_warning(project: &Project, options: &options::Options) -> Option<String> {
+    let min_similarity = options.description_similarity?;
+    let description = project.get_description()?;
+    let entryfile = entrypoint(project, &options.entrypoint)?;
+    let first_sentence = doc_first_sentence(&package_doc(project, options, entryfile)?)?;
+
+    description_mismatch_warning(
+        project.get_package_name(),
+        description,
+        &first_sentence,
+        min_similarity,
+    )
+}
+
+/// The status of each of the packages processed in a batch, i.e. with `--workspace` or
+/// `--package-dir`.
+struct BatchSummary {
+    /// What identifies each package in the summary, e.g. `package` or `directory`.
+    label: &'static str,
+    strict: bool,
+    statuses: Vec<(String, String)>,
+    /// The summary of each package for `--summary`, which also has the reason of failures.
+    packages: Vec<PackageSummary>,
+    /// The report of each package for `--report`.
+    reports: Vec<PackageReport>,
+    /// The packages that failed, with the reason.
+    failures: Vec<(String, String)>,
+    first_error: Option<RunError>,
+}
+
+impl BatchSummary {
+    fn new(label: &'static str, strict: bool) -> BatchSummary {
+        BatchSummary {
+            label,
+            strict,
+            statuses: Vec::new(),
+            packages: Vec::new(),
+            reports: Vec::new(),
+            failures: Vec::new(),
+            first_error: None,
+        }
+    }
+
+    /// Records the result of processing a package.  Packages without a README or crate
+    /// documentation are skipped, unless in strict mode.
+    fn add(
+        &mut self,
+        name: String,
+        result: Result<PackageOutcome, RunError>,
+        changes: Vec<ReadmeChange>,
+    ) {
+        let (status, summary_status) = match result {
+            Ok(outcome) => {
+                self.reports.push(package_report(name.clone(), Ok(&outcome), &changes));
+
+                (outcome.to_string(), outcome.to_string())
+            }
+            Err(
+                e @ (RunError::NoEntrySourceFile | RunError::NoReadmeFile | RunError::NoRustdoc(_)),
+            ) if !self.strict => {
+                let outcome = PackageOutcome::Skipped(e.to_string());
+                let status = outcome.to_string();
+
+                self.reports.push(package_report(name.clone(), Ok(&outcome), &changes));
+
+                (status.clone(), status)
+            }
+            Err(e) => {
+                let diagnostic = run_error_diagnostic(&e);
+
+                console::emit(
+                    console::Level::Error,
+                    diagnostic.code(),
+                    diagnostic.path(),
+                    format!("{} `{}`: {}", self.label, name, diagnostic.message()),
+                );
+                eprint!("{}", diagnostic.render_details());
+
+                let summary_status = format!("failed: {}", diagnostic.message());
+                let message = diagnostic.message().to_owned();
+
+                self.reports.push(package_report(name.clone(), Err(message), &changes));
+                self.failures.push((name.clone(), diagnostic.message().to_owned()));
+                self.first_error.get_or_insert(e);
+
+                ("failed".to_owned(), summary_status)
+            }
+        };
+
+        self.packages.push(PackageSummary { name: name.clone(), status: summary_status, changes });
+        self.statuses.push((name, status));
+    }
+
+    fn print(&self) {
+        let name_width = self
+            .statuses
+            .iter()
+            .map(|(name, _)| name.len())
+            .chain([self.label.len()])
+            .max()
+            .unwrap_or(0);
+
+        print_info!("{:name_width$}  {}", self.label, "status");
+
+        for (name, status) in &self.statuses {
+            print_info!("{:name_width$}  {}", name, status);
+        }
+
+        // The errors were printed as they happened, possibly far above, so they are all repeated
+        // at the end.
+        for (name, message) in &self.failures {
+            print_error!("{} `{}` failed: {}", self.label, name, message);
+        }
+    }
+
+    fn into_result(self) -> Result<(), RunError> {
+        match self.first_error {
+            None => Ok(()),
+            Some(first_error) => Err(RunError::PackagesFailed {
+                failed: self.failures.into_iter().map(|(name, _)| name).collect(),
+                first_error: Box::new(first_error),
+            }),
+        }
+    }
+}
+
+/// The report of a package for `--report`, from its outcome or the message of its failure.  The
+/// READMEs that changed were only written if the package was updated.
+fn package_report(
+    name: String,
+    status: Result<&PackageOutcome, String>,
+    changes: &[ReadmeChange],
+) -> PackageReport {
+    let status = match status {
+        Ok(PackageOutcome::Updated) => PackageStatus::Updated,
+        Ok(PackageOutcome::UpToDate) => PackageStatus::UpToDate,
+        Ok(PackageOutcome::Skipped(reason)) => PackageStatus::Skipped(reason.clone()),
+        Err(message) => PackageStatus::Failed(message),
+    };
+    let files_written = match status {
+        PackageStatus::Updated => changes.iter().map(|change| change.readme_path.clone()).collect(),
+        _ => Vec::new(),
+    };
+
+    PackageReport { name, status, files_written }
+}
+
+/// Writes the JSON report of `--report`, or prints it if the path is `-`.  The diagnostics are the
+/// warnings and errors printed so far.
+fn write_report(
+    report_path: &Path,
+    exit_code: i32,
+    packages: Vec<PackageReport>,
+) -> std::io::Result<()> {
+    let report =
+        Report::new(exit_code, packages, console::diagnostics(), &std::env::current_dir()?)
+            .to_json();
+
+    match report_path == Path::new("-") {
+        true => print!("{report}"),
+        false => std::fs::write(report_path, report)?,
+    }
+
+    Ok(())
+}
+
+/// Writes the markdown summary of `--summary`, or prints it if the path is `-`.
+fn write_summary(summary_path: &Path, packages: &[PackageSummary]) -> Result<(), RunError> {
+    let summary = summary_markdown(packages, &std::env::current_dir()?);
+
+    match summary_path == Path::new("-") {
+        true => print!("{summary}"),
+        false => std::fs::write(summary_path, summary)?,
+    }
+
+    Ok(())
+}
 
-    let exit_code: ExitCode = match std::env::current_dir() {
-        Ok(current_dir) => match options::config_file_options(current_dir) {
-            Ok(config_file_options) => {
-                let options = options::merge_options(cmd_options, config_file_options);
-
-                match run(options) {
-                    Ok(()) => ExitCode::Ok,
-                    Err(e) => {
-                        print_error!("{}", &e);
-                        e.into()
-                    }
+/// Parses a list of package directories: one directory per line, ignoring blank lines and lines
+/// starting with `#`.  Relative directories are relative to `base_dir`, the directory of the list.
+fn parse_package_dir_list(list: &str, base_dir: &Path) -> Vec<PathBuf> {
+    list.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| base_dir.join(line))
+        .collect()
+}
+
+fn package_dirs(cmd_options: &CmdOptions) -> Result<Vec<PathBuf>, RunError> {
+    let mut dirs = cmd_options.package_dirs().to_vec();
+
+    if let Some(list_path) = cmd_options.package_dir_list() {
+        let list = std::fs::read_to_string(list_path)
+            .map_err(|e| RunError::PackageDirListError(list_path.to_owned(), e))?;
+        let base_dir = list_path.parent().unwrap_or(Path::new(""));
+
+        dirs.extend(parse_package_dir_list(&list, base_dir));
+    }
+
+    Ok(dirs)
+}
+
+/// Processes the package of each directory given with `--package-dir` or `--package-dir-list`,
+/// independently of each other: every package uses the configuration file of its own directory.
+/// Like with `--workspace`, failures do not stop the remaining packages from being processed.
+fn run_package_dirs(
+    cmd_options: &CmdOptions,
+    strict: bool,
+    reports: &mut Vec<PackageReport>,
+) -> Result<(), RunError> {
+    let current_dir = std::env::current_dir()?;
+    let mut summary = BatchSummary::new("directory", strict);
+
+    let dirs = package_dirs(cmd_options)?;
+    let ignore_env = cmd_options.deterministic();
+
+    for_each_parallel(
+        &dirs,
+        cmd_options.jobs(),
+        &Progress::new("directories", dirs.len(), cmd_options.progress()),
+        |dir| {
+            let mut timings = Timings::default();
+            let mut changes = Vec::new();
+            let result = options::config_file_options(current_dir.join(dir), ignore_env)
+                .map_err(RunError::from)
+                .and_then(|config_file_options| {
+                    let project = timings.time("manifest", || {
+                        let manifest_path = dir.join("Cargo.toml");
+
+                        Project::load(Some(&manifest_path), None, cmd_options.cargo_flags())
+                    })?;
+
+                    run_package(
+                        &project,
+                        cmd_options.clone(),
+                        config_file_options,
+                        &mut timings,
+                        &mut changes,
+                    )
+                });
+
+            if cmd_options.timings() {
+                print_info!("timings of `{}`: {}", dir.display(), timings);
+            }
+
+            (result, changes)
+        },
+        |dir, (result, changes)| summary.add(dir.display().to_string(), result, changes),
+    );
+
+    summary.print();
+
+    if let Some(summary_path) = cmd_options.summary_path() {
+        write_summary(summary_path, &summary.packages)?;
+    }
+
+    reports.append(&mut summary.reports);
+
+    summary.into_result()
+}
+
+/// Gathers the statistics of a package.  This never fails: whatever cannot be determined is left
+/// out of the statistics.
+fn package_stats(project: &Project, options: &options::Options) -> PackageStats {
+    use cargo_rdme::transform::{intralink_count, DocTransform, DocTransformIntralinks};
+
+    let readme: Option<Readme> =
+        readme_path(project, options).ok().and_then(|path| Readme::from_file(path).ok());
+    let entryfile: Option<&Path> = entrypoint(project, &options.entrypoint);
+    let doc: Option<Doc> = entryfile.and_then(|entryfile| package_doc(project, options, entryfile));
+    let unresolved_intralinks = entryfile.zip(doc.as_ref()).and_then(|(entryfile, doc)| {
+        let unresolved = Cell::new(0);
+        let transform = DocTransformIntralinks::new(
+            project.get_package_name(),
+            entryfile,
+            |_| unresolved.set(unresolved.get() + 1),
+            Some(intralinks_config(project, entryfile, options)),
+        );
+
+        transform.transform(doc).ok().map(|_| unresolved.get())
+    });
+
+    PackageStats {
+        package_name: project.get_package_name().to_owned(),
+        doc_lines: doc.as_ref().map(|doc| doc.lines().count()),
+        readme_lines: readme.as_ref().map(|readme| readme.lines().count()),
+        has_markers: readme.as_ref().is_some_and(readme_has_markers),
+        intralinks: doc.as_ref().map_or(0, intralink_count),
+        unresolved_intralinks,
+    }
+}
+
+/// The crate documentation of a package, for lints and statistics.  We do not run the build
+/// script, so documentation generated by it is not found.
+fn package_doc(project: &Project, options: &options::Options, entryfile: &Path) -> Option<Doc> {
+    let doc_source = doc_source(project, options, entryfile, None).ok()?;
+
+    read_doc(&doc_source, &cfg_context(project, options)).ok()
+}
+
+/// Prints the statistics of the documentation of every package of the workspace.
+fn run_stats(
+    cmd_options: &CmdOptions,
+    config_file_options: Option<&ConfigFileOptions>,
+) -> Result<(), RunError> {
+    let projects =
+        Project::workspace_members(cmd_options.manifest_path(), cmd_options.cargo_flags())?;
+    let stats = projects
+        .iter()
+        .map(|project| {
+            let options =
+                package_options(project, cmd_options.clone(), config_file_options.cloned())?;
+
+            Ok(package_stats(project, &options))
+        })
+        .collect::<Result<Vec<PackageStats>, RunError>>()?;
+
+    match cmd_options.stats_json() {
+        true => print!("{}", stats::stats_json(&stats)),
+        false => print!("{}", stats::stats_table(&stats)),
+    }
+
+    Ok(())
+}
+
+fn package_options(
+    project: &Project,
+    cmd_options: CmdOptions,
+    config_file_options: Option<ConfigFileOptions>,
+) -> Result<options::Options, RunError> {
+    // The options of `.cargo-rdme.toml` take precedence over the ones in the package manifest,
+    // which take precedence over the ones in the workspace manifest, which take precedence over
+    // the ones of the user configuration.  The user configuration is not in the project, so
+    // `--deterministic` ignores it.
+    let user_config_options = match cmd_options.deterministic() {
+        true => None,
+        false => options::user_config_options()?,
+    };
+    let config_file_options = [
+        config_file_options,
+        options::package_config_options(project)?,
+        options::workspace_config_options(project)?,
+        user_config_options,
+    ]
+    .into_iter()
+    .flatten()
+    .reduce(ConfigFileOptions::or);
+
+    Ok(options::merge_options(cmd_options, config_file_options))
+}
+
+fn run_package(
+    project: &Project,
+    cmd_options: CmdOptions,
+    config_file_options: Option<ConfigFileOptions>,
+    timings: &mut Timings,
+    changes: &mut Vec<ReadmeChange>,
+) -> Result<PackageOutcome, RunError> {
+    let options =
+        timings.time("config", || package_options(project, cmd_options, config_file_options))?;
+
+    if options.disabled {
+        return Ok(PackageOutcome::Skipped("disabled in the configuration".to_owned()));
+    }
+
+    let outcome = match options.freeze {
+        true => timings.time("freeze", || freeze(project, &options)),
+        false => run_project(project, &options, timings, changes),
+    };
+
+    match outcome {
+        Err(RunError::NoRustdoc(doc_source)) if options.allow_missing_docs => {
+            Ok(PackageOutcome::Skipped(format!(
+                "no crate documentation in \"{}\"",
+                doc_source.display()
+            )))
+        }
+        outcome => outcome,
+    }
+}
+
+/// Decides what to do when the generated documentation in the README was edited by hand: refuse
+/// to update the README, overwrite the edits, or merge them with the new documentation.
+fn resolve_hand_edits(
+    original_readme: &Readme,
+    generated_readme: &Readme,
+    hand_edited_regions: &[usize],
+    readme_path: &Path,
+    merge_base_path: &Path,
+    options: &options::Options,
+) -> Result<Readme, RunError> {
+    let warn_hand_edits = || {
+        for line_number in hand_edited_regions {
+            print_warning!(
+                code: DiagnosticCode::ReadmeHandEdited,
+                path: readme_path,
+                "the documentation in the README ({}) at line {} was edited by hand since it was generated, and updating the README discards those edits",
+                readme_path.display(),
+                line_number,
+            );
+        }
+    };
+
+    let readme = match hand_edited_regions.is_empty() {
+        true => generated_readme.clone(),
+        false if options.check => {
+            warn_hand_edits();
+            generated_readme.clone()
+        }
+        false if options.merge_hand_edits => {
+            match merge_hand_edits(merge_base_path, readme_path, original_readme, generated_readme)
+            {
+                Ok(merged) => {
+                    print_info!(
+                        "merged the hand edits of the README ({}) with the new documentation",
+                        readme_path.display()
+                    );
+                    merged
                 }
+                Err(e) if options.force => {
+                    warn_hand_edits();
+                    print_warning!("{}", e);
+                    generated_readme.clone()
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        false => {
+            warn_hand_edits();
+
+            if !options.force {
+                return Err(RunError::ReadmeHandEdited);
             }
+
+            generated_readme.clone()
+        }
+    };
+
+    Ok(readme)
+}
+
+fn readme_path(project: &Project, options: &options::Options) -> Result<PathBuf, RunError> {
+    match &options.readme_path {
+        None => project.get_readme_path().ok_or(RunError::NoReadmeFile),
+        Some(path) if path.is_file() => Ok(path.clone()),
+        Some(_) => Err(RunError::NoReadmeFile),
+    }
+}
+
+/// Pins the README, its translations, and the other READMEs given on the command line, to the
+/// current version of the crate.
+fn freeze(project: &Project, options: &options::Options) -> Result<PackageOutcome, RunError> {
+    let mut outcome = freeze_readme_file(project, options)?;
+
+    for translation in &options.translations {
+        if let PackageOutcome::Updated =
+            freeze_readme_file(project, &translation_options(options, translation))?
+        {
+            outcome = PackageOutcome::Updated;
+        }
+    }
+
+    for readme_path in &options.extra_readme_paths {
+        if let PackageOutcome::Updated =
+            freeze_readme_file(project, &extra_readme_options(options, readme_path))?
+        {
+            outcome = PackageOutcome::Updated;
+        }
+    }
+
+    Ok(outcome)
+}
+
+fn freeze_readme_file(
+    project: &Project,
+    options: &options::Options,
+) -> Result<PackageOutcome, RunError> {
+    let readme_path: PathBuf = readme_path(project, options)?;
+    let readme: Readme = Readme::from_file(&readme_path)?;
+    let frozen_readme = freeze_readme(&readme, project.get_package_name(), project.get_version());
+
+    let format = ReadmeFormat::new(options, &readme_path)?;
+
+    match is_readme_up_to_date(&readme_path, &frozen_readme, format)? {
+        true => Ok(PackageOutcome::UpToDate),
+        false => {
+            let history = History::new(project.get_target_directory());
+
+            check_readme_writable(&readme_path)?;
+            update_readme(&frozen_readme, readme_path, format, options.force, &history)?;
+
+            Ok(PackageOutcome::Updated)
+        }
+    }
+}
+
+/// The file to extract the documentation from.  If the build script ran, the documentation can be
+/// in its output: `doc-source` can start with `$OUT_DIR`, and the entrypoint can include its
+/// documentation from `OUT_DIR`.
+fn doc_source(
+    project: &Project,
+    options: &options::Options,
+    entryfile: &Path,
+    out_dir: Option<&Path>,
+) -> Result<PathBuf, RunError> {
+    // The `doc-source` option takes precedence over the pragma of the entrypoint.  If the
+    // entrypoint cannot be read, reading the documentation from it reports the error.
+    let pragma_doc_source = || -> Option<PathBuf> {
+        let source: String = std::fs::read_to_string(entryfile).ok()?;
+
+        source_doc_source_pragma(&source).map(|file| project.get_directory().join(file))
+    };
+    let Some(doc_source) = options.doc_source.clone().or_else(pragma_doc_source) else {
+        let Some(out_dir) = out_dir else {
+            return Ok(entryfile.to_path_buf());
+        };
+        let source: String = std::fs::read_to_string(entryfile).map_err(|_| {
+            cargo_rdme::ExtractDocError::ErrorReadingSourceFile(entryfile.to_path_buf())
+        })?;
+
+        return Ok(match source_out_dir_doc_include(&source)? {
+            Some(file) => out_dir.join(file),
+            None => entryfile.to_path_buf(),
+        });
+    };
+
+    // The path of `doc-source` is relative to the package directory.
+    match (doc_source.strip_prefix(project.get_directory().join("$OUT_DIR")), out_dir) {
+        (Ok(file), Some(out_dir)) => Ok(out_dir.join(file)),
+        (Ok(_), None) => Err(RunError::NoBuildScriptOutDir),
+        (Err(_), _) => Ok(doc_source),
+    }
+}
+
+/// The configuration `cfg_attr` doc attributes and `rdme:if` directives are evaluated against,
+/// with the features selected by the options.
+fn cfg_context(project: &Project, options: &options::Options) -> CfgContext {
+    let selection = FeatureSelection::default()
+        .with_features(options.features.clone().unwrap_or_default())
+        .with_all_features(options.all_features)
+        .with_no_default_features(options.no_default_features);
+
+    CfgContext::new(selection.enabled_features(project.get_features()))
+}
+
+fn read_doc(doc_source: &Path, cfg: &CfgContext) -> Result<Doc, RunError> {
+    let doc: Option<Doc> = match is_markdown_file(doc_source) {
+        true => Some(Doc::from_markdown_file(doc_source)?).filter(|doc| !doc.is_empty()),
+        false => extract_doc_from_source_file_with_cfg(doc_source, Some(cfg))?,
+    };
+
+    doc.ok_or_else(|| RunError::NoRustdoc(doc_source.to_path_buf()))
+}
+
+/// The options to update a translation of the README, which are the options of the package with
+/// the README path of the translation.
+fn translation_options(options: &options::Options, translation: &Translation) -> options::Options {
+    options::Options {
+        readme_path: Some(translation.readme_path.clone()),
+        translations: Vec::new(),
+        code_blocks_only: translation.code_blocks_only,
+        // The text of the README is meant to be edited by hand when only the code blocks are
+        // updated.
+        content_hash: options.content_hash && !translation.code_blocks_only,
+        merge_hand_edits: options.merge_hand_edits && !translation.code_blocks_only,
+        ..options.clone()
+    }
+}
+
+/// The options to update a README given on the command line after the first one.
+fn extra_readme_options(options: &options::Options, readme_path: &Path) -> options::Options {
+    options::Options {
+        readme_path: Some(readme_path.to_path_buf()),
+        extra_readme_paths: Vec::new(),
+        translations: Vec::new(),
+        ..options.clone()
+    }
+}
+
+/// Fails if the README, its translations, or the other READMEs given on the command line cannot be
+/// written.
+fn check_readmes_writable(project: &Project, options: &options::Options) -> Result<(), RunError> {
+    check_readme_writable(&readme_path(project, options)?)?;
+
+    for translation in &options.translations {
+        check_readme_writable(&readme_path(project, &translation_options(options, translation))?)?;
+    }
+
+    for readme_path in &options.extra_readme_paths {
+        check_readme_writable(readme_path)?;
+    }
+
+    Ok(())
+}
+
+/// Updates the README, its translations, and the other READMEs given on the command line.  The
+/// README is updated if any of them is.
+fn run_project(
+    project: &Project,
+    options: &options::Options,
+    timings: &mut Timings,
+    changes: &mut Vec<ReadmeChange>,
+) -> Result<PackageOutcome, RunError> {
+    // This is checked before the documentation is extracted and transformed, which can take a
+    // while, e.g. with the build script.
+    if !options.check && !options.stdout && options.preview.is_none() {
+        check_readmes_writable(project, options)?;
+    }
+
+    let entryfile: &Path =
+        entrypoint(project, &options.entrypoint).ok_or(RunError::NoEntrySourceFile)?;
+    let out_dir: Option<PathBuf> = match options.build_script {
+        true => timings.time("build script", || project.build_script_out_dir())?,
+        false => None,
+    };
+    let doc_source: &Path = &doc_source(project, options, entryfile, out_dir.as_deref())?;
+    let cfg = cfg_context(project, options);
+    let doc: Doc = timings.time("doc", || read_doc(doc_source, &cfg))?;
+    let mut outcome = run_readme(project, options, entryfile, doc_source, &doc, timings, changes)?;
+
+    for translation in &options.translations {
+        let translation_options = translation_options(options, translation);
+        let translation_outcome = match &translation.doc_source {
+            None => run_readme(
+                project,
+                &translation_options,
+                entryfile,
+                doc_source,
+                &doc,
+                timings,
+                changes,
+            )?,
+            Some(translation_doc_source) => {
+                let translation_doc = timings.time("doc", || {
+                    let translation_doc = read_doc(translation_doc_source, &cfg)?;
+
+                    Ok::<_, RunError>(match translation.shared_code_blocks {
+                        true => share_code_blocks(&translation_doc, &doc, translation_doc_source)?,
+                        false => translation_doc,
+                    })
+                })?;
+
+                run_readme(
+                    project,
+                    &translation_options,
+                    entryfile,
+                    translation_doc_source,
+                    &translation_doc,
+                    timings,
+                    changes,
+                )?
+            }
+        };
+
+        if let PackageOutcome::Updated = translation_outcome {
+            outcome = PackageOutcome::Updated;
+        }
+    }
+
+    for readme_path in &options.extra_readme_paths {
+        let readme_options = extra_readme_options(options, readme_path);
+
+        if let PackageOutcome::Updated =
+            run_readme(project, &readme_options, entryfile, doc_source, &doc, timings, changes)?
+        {
+            outcome = PackageOutcome::Updated;
+        }
+    }
+
+    Ok(outcome)
+}
+
+/// The documentation of the package with the transformations of its options applied, as it is
+/// injected in the README.
+#[cfg(feature = "mdbook")]
+fn transformed_package_doc(project: &Project, options: &options::Options) -> Result<Doc, RunError> {
+    let entryfile: &Path =
+        entrypoint(project, &options.entrypoint).ok_or(RunError::NoEntrySourceFile)?;
+    let out_dir: Option<PathBuf> = match options.build_script {
+        true => project.build_script_out_dir()?,
+        false => None,
+    };
+    let doc_source: &Path = &doc_source(project, options, entryfile, out_dir.as_deref())?;
+    let doc: Doc = read_doc(doc_source, &cfg_context(project, options))?;
+    let doc: Doc = match &options.cookbook {
+        Some(test_name) => add_cookbook(project, &doc, test_name)?,
+        None => doc,
+    };
+    let (doc, warnings) = transform_doc(&doc, project, entryfile, options)?;
+
+    if options.strict && warnings.had_warnings {
+        return Err(RunError::StrictWarnings);
+    }
+
+    Ok(doc)
+}
+
+/// Replaces the code blocks of the documentation of a translation with the ones of the crate's
+/// documentation.
+fn share_code_blocks(
+    translation_doc: &Doc,
+    doc: &Doc,
+    translation_doc_source: &Path,
+) -> Result<Doc, RunError> {
+    use cargo_rdme::transform::{DocTransform, DocTransformSharedCodeBlocks};
+
+    let transform = DocTransformSharedCodeBlocks::new(doc, |msg| {
+        print_warning!(
+            path: translation_doc_source,
+            "{} ({})",
+            msg,
+            translation_doc_source.display()
+        );
+    });
+
+    // TODO Use `into_ok()` once it is stable (https://github.com/rust-lang/rust/issues/61695).
+    Ok(transform.transform(translation_doc)?)
+}
+
+/// Injects the documentation read from `doc_source` in the README given by `options`.
+fn run_readme(
+    project: &Project,
+    options: &options::Options,
+    entryfile: &Path,
+    doc_source: &Path,
+    doc: &Doc,
+    timings: &mut Timings,
+    changes: &mut Vec<ReadmeChange>,
+) -> Result<PackageOutcome, RunError> {
+    // This is only used to report where problems of the README come from, so we do not fail if
+    // it is not available.
+    let source_map: Option<DocSourceMap> = match is_markdown_file(doc_source) {
+        true => DocSourceMap::from_markdown_file(doc_source).ok(),
+        false => DocSourceMap::from_source_file(doc_source).ok().flatten(),
+    };
+    let doc: Doc = match &options.cookbook {
+        Some(test_name) => timings.time("doc", || add_cookbook(project, doc, test_name))?,
+        None => doc.clone(),
+    };
+
+    // The examples are checked as rustdoc sees them, before the transformations tag them and
+    // remove their hidden lines.  In check mode nothing is written, and verifying the examples
+    // writes a package with them in the target directory.
+    match (options.verify_examples, options.check) {
+        (true, false) => verify_doc_examples(project, &doc, source_map.as_ref())?,
+        (true, true) => print_info!(
+            "not verifying the examples of `{}` in check mode",
+            project.get_package_name()
+        ),
+        (false, _) => (),
+    }
+
+    let (doc, warnings) =
+        timings.time("transforms", || transform_doc(&doc, project, entryfile, options))?;
+
+    // In strict mode we do not touch the README if there were warnings.
+    if options.strict && warnings.had_warnings {
+        return Err(RunError::StrictWarnings);
+    }
+
+    let logo = crate_logo(project, entryfile, options);
+
+    timings.time("readme", || {
+        update_package_readme(
+            project,
+            options,
+            &doc,
+            &warnings,
+            source_map.as_ref(),
+            logo.as_deref(),
+            changes,
+        )
+    })
+}
+
+/// The logo to put at the top of the README if the `logo` option is set: the `html_logo_url` of
+/// the `#![doc(…)]` attributes of the crate.
+fn crate_logo(project: &Project, entryfile: &Path, options: &options::Options) -> Option<String> {
+    if !options.logo {
+        return None;
+    }
+
+    // If the entrypoint cannot be read or parsed extracting the documentation already failed.
+    let logo_url = std::fs::read_to_string(entryfile)
+        .ok()
+        .and_then(|source| source_doc_meta(&source).ok())
+        .and_then(|doc_meta| doc_meta.html_logo_url);
+
+    if logo_url.is_none() {
+        print_warning!(
+            path: entryfile,
+            "the `logo` option is set, but `{}` has no `#![doc(html_logo_url = \"…\")]` attribute",
+            entryfile.display()
+        );
+    }
+
+    logo_url.map(|logo_url| logo_html(&logo_url, project.get_package_name()))
+}
+
+/// Applies the fixes of `--fix` to the README, outside of the generated documentation, and to the
+/// documentation that will be injected in it.
+fn apply_fixes(
+    project: &Project,
+    readme_path: &Path,
+    readme: &Readme,
+    doc: &Doc,
+    source_map: Option<&DocSourceMap>,
+) -> (Readme, Doc) {
+    let context = FixContext::new(project.get_package_name(), project.get_version());
+    let source_lines = source_map.map(|source_map| source_map.source_lines(doc.lines()));
+    let (doc, doc_fixes) = fix_doc(doc, readme, context);
+    let (readme, readme_fixes) = fix_readme(readme, context);
+
+    for fix in doc_fixes {
+        let source_line = source_lines
+            .as_ref()
+            .and_then(|source_lines| source_lines.get(fix.line_number - 1).copied().flatten());
+
+        match source_map.zip(source_line) {
+            Some((source_map, source_line)) => print_info!(
+                "fixed {} in line {} of the documentation ({}:{})",
+                fix.lint,
+                fix.line_number,
+                source_map.file().display(),
+                source_line
+            ),
+            None => {
+                print_info!("fixed {} in line {} of the documentation", fix.lint, fix.line_number);
+            }
+        }
+    }
+
+    for fix in readme_fixes {
+        print_info!(
+            "fixed {} in line {} of the README ({})",
+            fix.lint,
+            fix.line_number,
+            readme_path.display()
+        );
+    }
+
+    (readme, doc)
+}
+
+/// Updates the code blocks of a translation of the README, whose text is translated by hand, and
+/// warns about the sections of the documentation that changed since the last update, whose
+/// translation may need to be revised.  If the README has no documentation yet, it is injected, to
+/// be translated.
+fn sync_translation_readme(
+    readme: &Readme,
+    doc: &Doc,
+    readme_path: &Path,
+    options: &options::Options,
+) -> Result<NewReadme, RunError> {
+    let emit_warning =
+        |msg: &str| print_warning!(path: readme_path, "{} ({})", msg, readme_path.display());
+    let synced = match sync_translation(readme, doc, emit_warning) {
+        Some(synced) => synced,
+        None => {
+            let new_readme = inject_doc_in_readme(
+                readme,
+                doc,
+                &options.inject_options().with_content_hash(false),
+            )
+            .map_err(|e| RunError::InjectDocError(e, readme_path.to_path_buf()))?;
+
+            match sync_translation(&new_readme.readme, doc, emit_warning) {
+                Some(synced) => synced,
+                None => return Ok(new_readme),
+            }
+        }
+    };
+
+    for heading in &synced.changed_sections {
+        match heading.is_empty() {
+            true => print_warning!(
+                path: readme_path,
+                "the introduction of the documentation changed since the last update of {}",
+                readme_path.display()
+            ),
+            false => print_warning!(
+                path: readme_path,
+                "section “{}” of the documentation changed since the last update of {}",
+                heading,
+                readme_path.display()
+            ),
+        }
+    }
+
+    Ok(NewReadme::from(synced))
+}
+
+/// Explains where the documentation goes, when the README has no place for it.
+fn print_missing_marker_info(readme_path: &Path, options: &options::Options) {
+    match &options.readme_section {
+        Some(section) => print_info!(
+            "No section “{}” found in the README file ({}), where the crate’s documentation would \
+             be inserted.",
+            section,
+            readme_path.display()
+        ),
+        None => {
+            let msg = indoc::formatdoc! { "
+                No marker found in the README file ({readme_filepath}).
+
+                cargo-rdme expects a marker in the README where the crate’s documentation will
+                be inserted.  This is the marker you should add to your README:
+
+                {marker}",
+                readme_filepath = readme_path.display(),
+                marker = cargo_rdme::MARKER_RDME,
+            };
+            print_info!("{}", msg);
+        }
+    }
+}
+
+/// Warns about the cargo rdme markers inside the regions of other tools, e.g. a table of contents
+/// generator, since they are ignored.
+fn warn_foreign_region_overlaps(readme: &Readme, readme_path: &Path) {
+    for overlap in foreign_region_overlaps(readme) {
+        print_warning!(
+            path: readme_path,
+            "ignoring the cargo rdme marker in line {} of the README ({}), since it is inside the \
+             region of {} that starts in line {}",
+            overlap.line_number,
+            readme_path.display(),
+            overlap.region.tool,
+            overlap.region.line_number
+        );
+    }
+}
+
+/// Injects the documentation in the README of the package and updates it, or checks that it is up
+/// to date.
+fn update_package_readme(
+    project: &Project,
+    options: &options::Options,
+    doc: &Doc,
+    warnings: &Warnings,
+    source_map: Option<&DocSourceMap>,
+    logo: Option<&str>,
+    changes: &mut Vec<ReadmeChange>,
+) -> Result<PackageOutcome, RunError> {
+    let readme_path: PathBuf = readme_path(project, options)?;
+    let original_readme: Readme = Readme::from_file(&readme_path)?;
+
+    warn_foreign_region_overlaps(&original_readme, &readme_path);
+
+    let fixed =
+        options.fix.then(|| apply_fixes(project, &readme_path, &original_readme, doc, source_map));
+    let (readme_to_update, doc) = match &fixed {
+        Some((readme, doc)) => (readme, doc),
+        None => (&original_readme, doc),
+    };
+    let usage = UsageConfig::default()
+        .with_version(match options.usage_version {
+            None | Some(UsageVersionOpt::Major) => UsageVersion::Major,
+            Some(UsageVersionOpt::Exact) => UsageVersion::Exact,
+        })
+        .with_style(match options.usage_style {
+            None | Some(UsageStyleOpt::Toml) => UsageStyle::Toml,
+            Some(UsageStyleOpt::CargoAdd) => UsageStyle::CargoAdd,
+            Some(UsageStyleOpt::Both) => UsageStyle::Both,
+        })
+        .with_features(options.usage_features.clone().unwrap_or_default());
+    let snippet = usage_snippet(project.get_package_name(), project.get_version(), &usage);
+    let readme_to_update = &inject_usage_in_readme(readme_to_update, &snippet)
+        .map_err(|e| RunError::InjectDocError(e, readme_path.clone()))?;
+    let readme_to_update = &match logo {
+        Some(logo) => inject_logo_in_readme(readme_to_update, logo)
+            .map_err(|e| RunError::InjectDocError(e, readme_path.clone()))?,
+        None => readme_to_update.clone(),
+    };
+    let new_readme = match (options.code_blocks_only, &options.readme_section) {
+        (true, _) => sync_translation_readme(readme_to_update, doc, &readme_path, options)?,
+        (false, Some(section)) => inject_doc_in_readme_section(
+            readme_to_update,
+            doc,
+            section,
+            options.heading_base_level,
+            options.heading.as_deref(),
+        ),
+        (false, None) => inject_doc_in_readme(readme_to_update, doc, &options.inject_options())
+            .map_err(|e| RunError::InjectDocError(e, readme_path.clone()))?,
+    };
+
+    if !new_readme.had_marker {
+        print_missing_marker_info(&readme_path, options);
+    }
+
+    let merge_base_path = merge_base_path(project, &readme_path);
+    let readme: Readme = resolve_hand_edits(
+        &original_readme,
+        &new_readme.readme,
+        &new_readme.hand_edited_regions,
+        &readme_path,
+        &merge_base_path,
+        options,
+    )?;
+
+    if let Some(target) = options.preview {
+        preview_readme(project, &readme, &readme_path, target, options.preview_no_open)?;
+
+        return Ok(PackageOutcome::UpToDate);
+    }
+
+    let format = ReadmeFormat::new(options, &readme_path)?;
+
+    let up_to_date = is_readme_up_to_date(&readme_path, &readme, format)?;
+    let heading_case_warnings = lint_heading_case(&readme, &readme_path, options);
+
+    if !up_to_date {
+        changes.push(ReadmeChange {
+            readme_path: readme_path.clone(),
+            old: std::fs::read_to_string(&readme_path)?,
+            new: format.render(&readme),
+        });
+    }
+
+    if options.stdout {
+        print!("{}", format.render(&readme));
+
+        return Ok(PackageOutcome::UpToDate);
+    }
+
+    match options.check {
+        false => {
+            if !up_to_date {
+                let history = History::new(project.get_target_directory());
+
+                update_readme(&readme, &readme_path, format, options.force, &history)?;
+            }
+
+            // Only saved once the README has the generated content, since it is the base of the
+            // merge of the next hand edits.
+            if options.content_hash {
+                save_merge_base(&merge_base_path, &new_readme.readme);
+            }
+
+            match up_to_date {
+                true => Ok(PackageOutcome::UpToDate),
+                false => Ok(PackageOutcome::Updated),
+            }
+        }
+        true => {
+            if !up_to_date {
+                let mismatch = readme_mismatch(&readme_path, &readme, format, source_map);
+
+                return Err(RunError::CheckReadmeMismatch(mismatch));
+            }
+
+            if (warnings.had_warnings || heading_case_warnings) && !options.no_fail_on_warnings {
+                return Err(RunError::CheckHasWarnings);
+            }
+
+            Ok(PackageOutcome::UpToDate)
+        }
+    }
+}
+
+/// Renders the final README as `target` would to an html file, and opens it in the browser.
+fn preview_readme(
+    project: &Project,
+    readme: &Readme,
+    readme_path: &Path,
+    target: PreviewTargetOpt,
+    no_open: bool,
+) -> Result<(), RunError> {
+    let target = match target {
+        PreviewTargetOpt::CratesIo => PreviewTarget::CratesIo,
+        PreviewTargetOpt::GitHub => PreviewTarget::GitHub,
+    };
+    let html = preview_page(readme, target, project.get_package_name(), project.get_repository());
+    let preview_path = preview_path(project, readme_path);
+
+    if let Some(preview_dir) = preview_path.parent() {
+        std::fs::create_dir_all(preview_dir)?;
+    }
+
+    std::fs::write(&preview_path, html)?;
+
+    print_info!("preview of {} written to {}", readme_path.display(), preview_path.display());
+
+    if !no_open {
+        if let Err(e) = open_in_browser(&preview_path) {
+            print_warning!("unable to open {} in the browser: {}", preview_path.display(), e);
+        }
+    }
+
+    Ok(())
+}
+
+/// Where the preview of the README is written.
+fn preview_path(project: &Project, readme_path: &Path) -> PathBuf {
+    let readme_filename = readme_path.file_name().unwrap_or(readme_path.as_os_str());
+
+    project
+        .get_target_directory()
+        .join("cargo-rdme")
+        .join("preview")
+        .join(project.get_package_name())
+        .join(readme_filename)
+        .with_extension("html")
+}
+
+/// Serves the preview of the README of the package until interrupted, rendering it again on
+/// every page load.
+fn run_preview_server(
+    cmd_options: &CmdOptions,
+    config_file_options: Option<&ConfigFileOptions>,
+    address: &str,
+) -> Result<(), RunError> {
+    let workspace_project = cmd_options
+        .workspace_project()
+        .or_else(|| config_file_options.and_then(ConfigFileOptions::workspace_project));
+    let project =
+        Project::load(cmd_options.manifest_path(), workspace_project, cmd_options.cargo_flags())?;
+    let listener = std::net::TcpListener::bind(address)?;
+    let url = format!("http://{}/", listener.local_addr()?);
+    let render = || -> Result<String, RunError> {
+        let options = package_options(&project, cmd_options.clone(), config_file_options.cloned())?;
+        let readme_path = readme_path(&project, &options)?;
+
+        run_package(
+            &project,
+            cmd_options.clone(),
+            config_file_options.cloned(),
+            &mut Timings::default(),
+            &mut Vec::new(),
+        )?;
+
+        Ok(std::fs::read_to_string(preview_path(&project, &readme_path))?)
+    };
+
+    print_info!("serving the preview of the README at {} (press Ctrl-C to stop)", url);
+
+    if !cmd_options.preview_no_open() {
+        if let Err(e) = open_in_browser(&url) {
+            print_warning!("unable to open {} in the browser: {}", url, e);
+        }
+    }
+
+    serve::serve(
+        &listener,
+        || render().map_err(|e| e.to_string()),
+        || serve::directory_fingerprint(project.get_directory(), project.get_target_directory()),
+    );
+
+    Ok(())
+}
+
+/// Opens the file or url with the default application of the system, without waiting for it.
+fn open_in_browser(path: impl AsRef<std::ffi::OsStr>) -> std::io::Result<()> {
+    use std::process::{Command, Stdio};
+
+    let mut command = match std::env::consts::OS {
+        "macos" => Command::new("open"),
+        "windows" => {
+            let mut command = Command::new("cmd");
+            command.args(["/C", "start", ""]);
+            command
+        }
+        _ => Command::new("xdg-open"),
+    };
+
+    command.arg(path).stdout(Stdio::null()).stderr(Stdio::null()).spawn().map(drop)
+}
+
+/// Checks that the rust examples of the documentation compile, reporting the ones that do not.
+fn verify_doc_examples(
+    project: &Project,
+    doc: &Doc,
+    source_map: Option<&DocSourceMap>,
+) -> Result<(), RunError> {
+    let failures = project.verify_examples(&doc_examples(doc))?;
+    let source_lines = source_map.map(|source_map| source_map.source_lines(doc.lines()));
+
+    for failure in &failures {
+        let source_line = source_lines
+            .as_ref()
+            .and_then(|source_lines| source_lines.get(failure.line_number - 1).copied().flatten());
+        let errors = failure.errors.join("\n");
+
+        match source_map.zip(source_line) {
+            Some((source_map, source_line)) => print_error!(
+                path: source_map.file(),
+                "example at {}:{} does not compile:\n{}",
+                source_map.file().display(),
+                source_line,
+                errors.trim_end(),
+            ),
+            None => print_error!(
+                "example in line {} of the documentation does not compile:\n{}",
+                failure.line_number,
+                errors.trim_end(),
+            ),
+        }
+    }
+
+    match failures.is_empty() {
+        true => Ok(()),
+        false => Err(RunError::ExamplesDoNotCompile(failures.len())),
+    }
+}
+
+/// Warns about the headings of the final README that are not in the case asked for by the
+/// `heading-case` option.  Returns whether there were any warnings.
+fn lint_heading_case(readme: &Readme, readme_path: &Path, options: &options::Options) -> bool {
+    let expected = match options.heading_case {
+        None => return false,
+        Some(HeadingCaseOpt::Sentence) => Some(HeadingCase::Sentence),
+        Some(HeadingCaseOpt::Title) => Some(HeadingCase::Title),
+        Some(HeadingCaseOpt::Consistent) => None,
+    };
+    let mismatches = heading_case_mismatches(readme, expected);
+
+    for mismatch in &mismatches {
+        print_warning!(
+            code: DiagnosticCode::HeadingCase,
+            path: readme_path,
+            "heading “{}” is not in {} ({}:{})",
+            mismatch.heading,
+            mismatch.expected,
+            readme_path.display(),
+            mismatch.line_number,
+        );
+    }
+
+    !mismatches.is_empty()
+}
+
+fn run_error_diagnostic(error: &RunError) -> Diagnostic {
+    let diagnostic = match error {
+        RunError::InjectDocError(e, readme_path) => {
+            let (line_number, label, help) = match e {
+                InjectDocError::UnexpectedMarkerCargoRdmeEnd { line_number } => (
+                    *line_number,
+                    "end marker without a matching start marker",
+                    "remove it or add a `<!-- cargo-rdme start -->` marker before it",
+                ),
+                InjectDocError::UnmatchedMarkerCargoRdmeStart { line_number } => (
+                    *line_number,
+                    "this start marker is never closed",
+                    "add a `<!-- cargo-rdme end -->` marker after it",
+                ),
+                InjectDocError::ChangedOutsideMarkers { line_number } => (
+                    *line_number,
+                    "this line would change",
+                    "this is a bug in cargo rdme, please report it at https://github.com/orium/cargo-rdme/issues",
+                ),
+                _ => return Diagnostic::new(error.to_string()).with_code(error.code()),
+            };
+            let snippet = std::fs::read_to_string(readme_path)
+                .ok()
+                .and_then(|source| Snippet::from_line(readme_path, source, line_number, label));
+
+            Diagnostic::new(error.to_string()).with_snippet(snippet).with_help(help)
+        }
+        RunError::HandEditsMergeConflict(conflicts, readme_path) => {
+            let source = std::fs::read_to_string(readme_path).ok();
+            let snippet =
+                source.zip(conflicts.line_numbers.first()).and_then(|(source, &line_number)| {
+                    Snippet::from_line(
+                        readme_path,
+                        source,
+                        line_number,
+                        "this hand edit conflicts with the new documentation",
+                    )
+                });
+
+            Diagnostic::new(error.to_string()).with_snippet(snippet)
+        }
+        RunError::CheckReadmeMismatch(Some(mismatch)) => {
+            let snippet = std::fs::read_to_string(&mismatch.readme_path).ok().and_then(|source| {
+                Snippet::from_line(
+                    &mismatch.readme_path,
+                    source,
+                    mismatch.line_number,
+                    "this line differs from the generated README",
+                )
+            });
+            let help = match &mismatch.source {
+                Some((file, line_number)) => format!(
+                    "this line is generated from {}:{line_number}: edit the documentation there, if needed, and run `cargo rdme`",
+                    file.display()
+                ),
+                None => "run `cargo rdme` to update the README".to_owned(),
+            };
+
+            Diagnostic::new(error.to_string()).with_snippet(snippet).with_help(help)
+        }
+        RunError::NoRustdoc(_) => Diagnostic::new(error.to_string()).with_help(indoc::indoc! { "
+            cargo-rdme extracts the crate’s documentation from the `//!` comments (or
+            `#![doc = \"…\"]` attributes) at the top of the crate’s entrypoint.  To document
+            the crate add them to that file:
+
+            //! My crate does things.
+
+            If your documentation lives in another file (common in proc-macro crates or when
+            the crate root is mostly behind `#[cfg]`) you can point cargo-rdme to it with
+            `--doc-source <path>`, or in `.cargo-rdme.toml`:
+
+            doc-source = \"<path>\"
+
+            You can also add this comment to the entrypoint:
+
+            // cargo-rdme: doc-source = \"<path>\"
+
+            To use another entrypoint, e.g. the binary of the package, use `--entrypoint bin`.
+            If the package is not meant to have documentation, `--allow-missing-docs` makes
+            cargo-rdme do nothing instead of failing."
+        }),
+        RunError::ReadmeReadOnly(..) => Diagnostic::new(error.to_string()).with_help(
+            "to check that the README is up to date without writing anything, run `cargo rdme --check`, or to print the updated README, run `cargo rdme --stdout`",
+        ),
+        _ => Diagnostic::new(error.to_string()),
+    };
+
+    diagnostic.with_code(error.code())
+}
+
+fn config_file_error_diagnostic(
+    error: &ConfigFileOptionsError,
+    current_dir: impl AsRef<Path>,
+) -> Diagnostic {
+    let config_file = cargo_rdme::find_first_file_in_ancestors(current_dir, ".cargo-rdme.toml")
+        .and_then(|path| std::fs::read_to_string(&path).ok().map(|source| (path, source)));
+    let message = match error {
+        // The `Display` of toml errors has its own snippet of the file.
+        ConfigFileOptionsError::ErrorParsingToml(e) => {
+            format!("unable to read config file: failed to parse toml: {}", e.message())
+        }
+        _ => format!("unable to read config file: {error}"),
+    };
+
+    let snippet = config_file.and_then(|(path, source)| match error {
+        ConfigFileOptionsError::ErrorReadingConfigFile(_)
+        | ConfigFileOptionsError::InvalidWorkspaceConfig(_)
+        | ConfigFileOptionsError::InvalidPackageConfig(_)
+        | ConfigFileOptionsError::InvalidUserConfig(..)
+        | ConfigFileOptionsError::InvalidEnvConfig(_) => None,
+        ConfigFileOptionsError::ErrorParsingToml(e) => {
+            e.span().map(|span| Snippet::new(path, source, span, e.message()))
+        }
+        ConfigFileOptionsError::InvalidField(field) => {
+            let key = field.rsplit('.').next().unwrap_or(field);
+
+            Snippet::from_toml_key(path, source, key, "invalid value")
+        }
+        ConfigFileOptionsError::InvalidEntrypointTable => {
+            Snippet::from_toml_key(path, source, "[entrypoint]", "invalid entrypoint table")
+        }
+    });
+
+    Diagnostic::new(message).with_snippet(snippet).with_code(Some(DiagnosticCode::InvalidConfig))
+}
+
+fn print_diagnostic(diagnostic: &Diagnostic) {
+    console::emit(
+        console::Level::Error,
+        diagnostic.code(),
+        diagnostic.path(),
+        diagnostic.message(),
+    );
+    eprint!("{}", diagnostic.render_details());
+}
+
+/// The directory where the configuration file is searched from: the directory of the project,
+/// which is not the current directory if `--manifest-path` is used.
+fn config_dir(cmd_options: &CmdOptions) -> std::io::Result<PathBuf> {
+    std::env::current_dir().map(|current_dir| {
+        match cmd_options.manifest_path().and_then(Path::parent) {
+            Some(manifest_dir) => current_dir.join(manifest_dir),
+            None => current_dir,
+        }
+    })
+}
+
+fn main() {
+    let cmd_options = options::cmd_options();
+
+    if cmd_options.config_schema() {
+        print!("{}", options::CONFIG_SCHEMA);
+        return;
+    }
+
+    if cmd_options.explain() {
+        match cmd_options.explain_code().map(str::parse::<DiagnosticCode>) {
+            None => print!("{}", explain::list_codes()),
+            Some(Ok(code)) => print!("{}", explain::explain(code)),
+            Some(Err(_)) => {
+                print_error!(
+                    "unknown code `{}` (run `cargo rdme explain` to list every code)",
+                    cmd_options.explain_code().unwrap_or_default()
+                );
+                std::process::exit(ExitCode::Error as i32);
+            }
+        }
+
+        return;
+    }
+
+    if let Some(hook_config) = cmd_options.hook_config() {
+        print!("{}", hook_config::hook_config(hook_config));
+        return;
+    }
+
+    if cmd_options.selftest() {
+        let exit_code = match selftest::selftest() {
+            true => ExitCode::Ok,
+            false => ExitCode::Error,
+        };
+
+        std::process::exit(exit_code as i32);
+    }
+
+    #[cfg(feature = "mdbook")]
+    if cmd_options.mdbook() {
+        std::process::exit(mdbook::run_mdbook(&cmd_options) as i32);
+    }
+
+    let config_dir = config_dir(&cmd_options);
+    let ignore_env = cmd_options.deterministic();
+    let report_path = cmd_options.report_path().map(Path::to_path_buf);
+    let mut reports = Vec::new();
+    let exit_code: ExitCode = match config_dir {
+        Ok(config_dir) => match options::config_file_options(&config_dir, ignore_env) {
+            Ok(config_file_options) => match run(cmd_options, config_file_options, &mut reports) {
+                Ok(()) => ExitCode::Ok,
+                Err(e) => {
+                    print_diagnostic(&run_error_diagnostic(&e));
+                    e.into()
+                }
+            },
             Err(e) => {
-                print_error!("unable to read config file: {}", e);
+                print_diagnostic(&config_file_error_diagnostic(&e, &config_dir));
                 ExitCode::Error
             }
         },
@@ -526,5 +3075,14 @@ fn main() {
         }
     };
 
-    std::process::exit(exit_code as i32);
+    let exit_code = exit_code as i32;
+
+    if let Some(report_path) = report_path {
+        if let Err(e) = write_report(&report_path, exit_code, reports) {
+            print_error!("unable to write the report \"{}\": {}", report_path.display(), e);
+            std::process::exit(ExitCode::Error as i32);
+        }
+    }
+
+    std::process::exit(exit_code);
 }
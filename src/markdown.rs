@@ -7,10 +7,12 @@ use crate::LineTerminator;
 use itertools::Itertools;
 use std::fmt::Formatter;
 use std::fs::File;
+use std::ops::{Deref, DerefMut};
 use std::path::{Path, PathBuf};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum MarkdownError {
     #[error("failed to read markdown file \"{0}\"")]
     ErrorReadingMarkdownFromFile(PathBuf),
@@ -27,6 +29,10 @@ pub struct Markdown {
 }
 
 impl Markdown {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(file = %file_path.as_ref().display()))
+    )]
     pub fn from_file(file_path: impl AsRef<Path>) -> Result<Markdown, MarkdownError> {
         let content: String = std::fs::read_to_string(file_path.as_ref()).map_err(|_| {
             MarkdownError::ErrorReadingMarkdownFromFile(file_path.as_ref().to_path_buf())
@@ -36,7 +42,7 @@ impl Markdown {
     }
 
     pub fn from_str(str: impl Into<String>) -> Markdown {
-        let mut content = str.into().replace("\r\n", "\n");
+        let mut content = str.into().replace("\r\n", "\n").replace('\r', "\n");
 
         // Lines must always end in newlines.
         if !content.ends_with('\n') {
@@ -56,6 +62,18 @@ impl Markdown {
         self.content.lines()
     }
 
+    pub fn into_lines(self) -> Vec<String> {
+        self.content.lines().map(ToOwned::to_owned).collect()
+    }
+
+    /// Returns the lines of the markdown for editing.  The markdown is updated when the returned
+    /// value is dropped.
+    pub fn lines_mut(&mut self) -> LinesMut<'_> {
+        let lines = self.lines().map(ToOwned::to_owned).collect();
+
+        LinesMut { markdown: self, lines }
+    }
+
     pub fn write_to_file(
         &self,
         file: impl AsRef<Path>,
@@ -73,10 +91,7 @@ impl Markdown {
     ) -> Result<(), MarkdownError> {
         let mut write_line = |line: &str| -> std::io::Result<()> {
             writer.write_all(line.as_bytes())?;
-            match line_terminator {
-                LineTerminator::Lf => writer.write_all("\n".as_bytes()),
-                LineTerminator::CrLf => writer.write_all("\r\n".as_bytes()),
-            }
+            writer.write_all(line_terminator.as_str().as_bytes())
         };
 
         for line in self.lines() {
@@ -90,6 +105,63 @@ impl Markdown {
     pub fn as_string(&self) -> &str {
         &self.content
     }
+
+    /// Returns the markdown as a string with the given line terminator.
+    pub fn to_string(&self, line_terminator: LineTerminator) -> String {
+        match line_terminator {
+            LineTerminator::Lf => self.content.clone(),
+            LineTerminator::CrLf => self.content.replace('\n', "\r\n"),
+            LineTerminator::Cr => self.content.replace('\n', "\r"),
+        }
+    }
+}
+
+/// Mutable view over the lines of a markdown document.  See [`Doc::lines_mut()`] and
+/// [`Readme::lines_mut()`].
+///
+/// [`Doc::lines_mut()`]: crate::Doc::lines_mut
+/// [`Readme::lines_mut()`]: crate::Readme::lines_mut
+pub struct LinesMut<'a> {
+    markdown: &'a mut Markdown,
+    lines: Vec<String>,
+}
+
+impl Deref for LinesMut<'_> {
+    type Target = Vec<String>;
+
+    fn deref(&self) -> &Vec<String> {
+        &self.lines
+    }
+}
+
+impl DerefMut for LinesMut<'_> {
+    fn deref_mut(&mut self) -> &mut Vec<String> {
+        &mut self.lines
+    }
+}
+
+impl Drop for LinesMut<'_> {
+    fn drop(&mut self) {
+        if self.lines.iter().map(String::as_str).eq(self.markdown.lines()) {
+            return;
+        }
+
+        // Rewrite the content in its own buffer, line by line, rather than joining the lines and
+        // parsing them back.
+        let content = &mut self.markdown.content;
+        let len = self.lines.iter().map(|line| line.len() + 1).sum();
+
+        content.clear();
+        content.reserve(len);
+
+        for line in &self.lines {
+            match line.contains('\r') {
+                true => content.push_str(&line.replace("\r\n", "\n").replace('\r', "\n")),
+                false => content.push_str(line),
+            }
+            content.push('\n');
+        }
+    }
 }
 
 impl std::fmt::Debug for Markdown {
@@ -104,3 +176,65 @@ impl std::fmt::Debug for Markdown {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_markdown_lines_mut() {
+        let mut markdown = Markdown::from_str("# Foo\n\nBar\n");
+
+        {
+            let mut lines = markdown.lines_mut();
+
+            lines[0].push_str(" Bar");
+            lines.push("Baz".to_owned());
+        }
+
+        assert_eq!(markdown.as_string(), "# Foo Bar\n\nBar\nBaz\n");
+        assert_eq!(markdown.into_lines(), vec!["# Foo Bar", "", "Bar", "Baz"]);
+    }
+
+    #[test]
+    fn test_markdown_lines_mut_line_terminators() {
+        let mut markdown = Markdown::from_str("# Foo\n");
+
+        {
+            let mut lines = markdown.lines_mut();
+
+            lines.push("Bar\r\nBaz\rQux".to_owned());
+            lines.push(String::new());
+        }
+
+        assert_eq!(markdown.as_string(), "# Foo\nBar\nBaz\nQux\n\n");
+        assert_eq!(markdown, Markdown::from_str("# Foo\nBar\nBaz\nQux\n\n"));
+
+        markdown.lines_mut().clear();
+
+        assert_eq!(markdown.as_string(), "");
+    }
+
+    #[test]
+    fn test_markdown_to_string() {
+        let markdown = Markdown::from_str("# Foo\r\n\nBar");
+
+        assert_eq!(markdown.to_string(LineTerminator::Lf), "# Foo\n\nBar\n");
+        assert_eq!(markdown.to_string(LineTerminator::CrLf), "# Foo\r\n\r\nBar\r\n");
+
+        let mut bytes: Vec<u8> = Vec::new();
+        markdown.write(&mut bytes, LineTerminator::CrLf).unwrap();
+
+        assert_eq!(String::from_utf8(bytes).unwrap(), markdown.to_string(LineTerminator::CrLf));
+    }
+
+    #[test]
+    fn test_markdown_cr_line_terminator() {
+        let markdown = Markdown::from_str("# Foo\r\rBar\r");
+
+        assert_eq!(markdown.as_string(), "# Foo\n\nBar\n");
+        assert_eq!(markdown.to_string(LineTerminator::Cr), "# Foo\r\rBar\r");
+        assert_eq!(Markdown::from_str(markdown.to_string(LineTerminator::Cr)), markdown);
+    }
+}
@@ -0,0 +1,222 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+//! `cargo rdme mdbook`: the mdBook preprocessor run by `mdbook-rdme`.  It injects the crate’s
+//! documentation in the chapters of the book with cargo rdme markers, with the same configuration
+//! and transformations as the README.
+
+use crate::options::{self, CmdOptions, ConfigFileOptions};
+use crate::{config_dir, package_options, transformed_package_doc, ExitCode, RunError};
+use cargo_rdme::{inject_doc_in_readme, readme_has_markers, Doc, InjectDocError, Project, Readme};
+use serde_json::Value;
+use std::io::Read;
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+enum MdbookError {
+    #[error("failed to read the book from mdBook: {0}")]
+    ReadInput(std::io::Error),
+    #[error("failed to parse the book from mdBook: {0}")]
+    ParseInput(serde_json::Error),
+    #[error("unexpected book from mdBook: {0}")]
+    UnexpectedInput(&'static str),
+    #[error("failed to get the crate documentation: {0}")]
+    RunError(RunError),
+    #[error("failed to inject the documentation in chapter \"{1}\": {0}")]
+    InjectDocError(InjectDocError, String),
+}
+
+impl From<RunError> for MdbookError {
+    fn from(e: RunError) -> MdbookError {
+        MdbookError::RunError(e)
+    }
+}
+
+/// The options of the preprocessor, from the `[preprocessor.rdme]` table of `book.toml`.
+#[derive(PartialEq, Eq, Debug)]
+struct BookOptions {
+    manifest_path: Option<PathBuf>,
+    workspace_project: Option<String>,
+    heading_base_level: Option<u8>,
+}
+
+impl BookOptions {
+    fn from_context(context: &Value) -> BookOptions {
+        let root: PathBuf = context["root"].as_str().map_or_else(PathBuf::new, PathBuf::from);
+        let config = &context["config"]["preprocessor"]["rdme"];
+
+        BookOptions {
+            // Relative to the book.
+            manifest_path: config["manifest-path"].as_str().map(|path| root.join(path)),
+            workspace_project: config["workspace-project"].as_str().map(ToOwned::to_owned),
+            heading_base_level: config["heading-base-level"]
+                .as_u64()
+                .and_then(|level| u8::try_from(level).ok()),
+        }
+    }
+}
+
+/// The crate’s documentation, as cargo rdme injects it in the README.  The options of the book take
+/// the place of the command line options, and the configuration files apply as usual.
+fn crate_doc(cmd_options: &CmdOptions) -> Result<(Doc, options::Options), RunError> {
+    let config_file_options: Option<ConfigFileOptions> =
+        options::config_file_options(config_dir(cmd_options)?, cmd_options.deterministic())?;
+    let workspace_project = cmd_options
+        .workspace_project()
+        .or_else(|| config_file_options.as_ref().and_then(ConfigFileOptions::workspace_project));
+    let project =
+        Project::load(cmd_options.manifest_path(), workspace_project, cmd_options.cargo_flags())?;
+    let options = package_options(&project, cmd_options.clone(), config_file_options)?;
+    let doc = transformed_package_doc(&project, &options)?;
+
+    Ok((doc, options))
+}
+
+/// Calls `f` with every chapter of the book items, including nested chapters.
+fn for_each_chapter(
+    items: &mut [Value],
+    f: &mut impl FnMut(&mut Value) -> Result<(), MdbookError>,
+) -> Result<(), MdbookError> {
+    for item in items {
+        // Other items are separators and part titles.
+        if let Some(chapter) = item.get_mut("Chapter") {
+            f(chapter)?;
+
+            if let Some(Value::Array(sub_items)) = chapter.get_mut("sub_items") {
+                for_each_chapter(sub_items, f)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Calls `inject` with the content of every chapter with cargo rdme markers, and replaces it with
+/// the returned content.
+fn preprocess(
+    input: Value,
+    mut inject: impl FnMut(&BookOptions, &str, &Readme) -> Result<Readme, MdbookError>,
+) -> Result<Value, MdbookError> {
+    let Value::Array(input) = input else {
+        return Err(MdbookError::UnexpectedInput(
+            "expected an array with the context and the book",
+        ));
+    };
+    let [context, mut book] = <[Value; 2]>::try_from(input).map_err(|_| {
+        MdbookError::UnexpectedInput("expected an array with the context and the book")
+    })?;
+    let book_options = BookOptions::from_context(&context);
+
+    let Some(Value::Array(sections)) = book.get_mut("sections") else {
+        return Err(MdbookError::UnexpectedInput("the book has no sections"));
+    };
+
+    for_each_chapter(sections, &mut |chapter| {
+        let name = chapter["name"].as_str().unwrap_or_default().to_owned();
+        let Some(Value::String(content)) = chapter.get_mut("content") else {
+            return Ok(());
+        };
+        let readme = Readme::from_str(content.as_str());
+
+        if readme_has_markers(&readme) {
+            inject(&book_options, &name, &readme)?.as_string().clone_into(content);
+        }
+
+        Ok(())
+    })?;
+
+    Ok(book)
+}
+
+fn run(cmd_options: &CmdOptions) -> Result<(), MdbookError> {
+    let mut input = String::new();
+
+    std::io::stdin().read_to_string(&mut input).map_err(MdbookError::ReadInput)?;
+
+    let input: Value = serde_json::from_str(&input).map_err(MdbookError::ParseInput)?;
+    // The documentation is only extracted if some chapter has markers.
+    let mut doc: Option<(Doc, options::Options)> = None;
+    let book = preprocess(input, |book_options, chapter_name, readme| {
+        let (doc, options) = match &doc {
+            Some(doc) => doc,
+            None => doc.insert(crate_doc(&cmd_options.clone().with_book_options(
+                book_options.manifest_path.clone(),
+                book_options.workspace_project.clone(),
+                book_options.heading_base_level,
+            ))?),
+        };
+        let new_readme =
+            inject_doc_in_readme(readme, doc, &options.inject_options().with_content_hash(false))
+                .map_err(|e| MdbookError::InjectDocError(e, chapter_name.to_owned()))?;
+
+        Ok(new_readme.readme)
+    })?;
+
+    println!("{book}");
+
+    Ok(())
+}
+
+pub fn run_mdbook(cmd_options: &CmdOptions) -> ExitCode {
+    // mdBook asks if a renderer is supported.  The chapters stay markdown, so they all are.
+    if cmd_options.mdbook_supports().is_some() {
+        return ExitCode::Ok;
+    }
+
+    match run(cmd_options) {
+        Ok(()) => ExitCode::Ok,
+        Err(e) => {
+            print_error!("{}", e);
+            ExitCode::Error
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cargo_rdme::InjectOptions;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_preprocess() {
+        let input = r##"[{"root":"/book","config":{}},{"sections":[{"Chapter":{"name":"Intro","content":"# Intro\n","sub_items":[{"Chapter":{"name":"Sub","content":"<!-- cargo-rdme -->\n","sub_items":[]}}]}},"Separator",{"PartTitle":"Part"}],"__non_exhaustive":null}]"##;
+        let expected = r##"{"__non_exhaustive":null,"sections":[{"Chapter":{"content":"# Intro\n","name":"Intro","sub_items":[{"Chapter":{"content":"<!-- cargo-rdme start -->\n\nThe documentation.\n\n<!-- cargo-rdme end -->\n","name":"Sub","sub_items":[]}}]}},"Separator",{"PartTitle":"Part"}]}"##;
+        let mut chapters = Vec::new();
+
+        let book = preprocess(serde_json::from_str(input).unwrap(), |_, name, readme| {
+            chapters.push(name.to_owned());
+
+            let doc = Doc::from_str("The documentation.");
+
+            Ok(inject_doc_in_readme(readme, &doc, &InjectOptions::default()).unwrap().readme)
+        })
+        .unwrap();
+
+        assert_eq!(book.to_string(), expected);
+        assert_eq!(chapters, vec!["Sub"]);
+    }
+
+    #[test]
+    fn test_book_options_from_context() {
+        let context = r#"{"root":"/book","config":{"preprocessor":{"rdme":{"manifest-path":"../Cargo.toml","heading-base-level":2}}}}"#;
+
+        assert_eq!(
+            BookOptions::from_context(&serde_json::from_str(context).unwrap()),
+            BookOptions {
+                manifest_path: Some(PathBuf::from("/book/../Cargo.toml")),
+                workspace_project: None,
+                heading_base_level: Some(2),
+            }
+        );
+        assert_eq!(
+            BookOptions::from_context(
+                &serde_json::from_str(r#"{"root":"/book","config":{}}"#).unwrap()
+            ),
+            BookOptions { manifest_path: None, workspace_project: None, heading_base_level: None }
+        );
+    }
+}
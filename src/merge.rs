@@ -0,0 +1,280 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+//! Line based three-way merge, and diffs.
+
+use std::fmt::Write;
+
+#[derive(PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub struct MergeConflicts {
+    /// Lines (starting at 1) of `ours` where the conflicting changes are.
+    pub line_numbers: Vec<usize>,
+}
+
+/// Pairs of indexes of equal lines of `a` and `b` that form a longest common subsequence.
+fn longest_common_subsequence(a: &[&str], b: &[&str]) -> Vec<(usize, usize)> {
+    // `lengths[i][j]` is the length of the LCS of `a[i..]` and `b[j..]`.
+    let mut lengths = vec![vec![0_u32; b.len() + 1]; a.len() + 1];
+
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lengths[i][j] = match a[i] == b[j] {
+                true => lengths[i + 1][j + 1] + 1,
+                false => lengths[i + 1][j].max(lengths[i][j + 1]),
+            };
+        }
+    }
+
+    let mut pairs = Vec::with_capacity(lengths[0][0] as usize);
+    let (mut i, mut j) = (0, 0);
+
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            pairs.push((i, j));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+
+    pairs
+}
+
+/// Merges the changes from `base` to `ours` and from `base` to `theirs`.  Changes that touch the
+/// same region of `base` in different ways are reported as conflicts.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+pub fn merge3(base: &str, ours: &str, theirs: &str) -> Result<String, MergeConflicts> {
+    let base: Vec<&str> = base.lines().collect();
+    let ours: Vec<&str> = ours.lines().collect();
+    let theirs: Vec<&str> = theirs.lines().collect();
+
+    let mut base_to_ours: Vec<Option<usize>> = vec![None; base.len()];
+    let mut base_to_theirs: Vec<Option<usize>> = vec![None; base.len()];
+
+    for (b, o) in longest_common_subsequence(&base, &ours) {
+        base_to_ours[b] = Some(o);
+    }
+
+    for (b, t) in longest_common_subsequence(&base, &theirs) {
+        base_to_theirs[b] = Some(t);
+    }
+
+    // Lines of base that are kept by both sides.  They split the files in chunks that can be
+    // merged independently.
+    let anchors = (0..base.len())
+        .filter_map(|b| Some((b, base_to_ours[b]?, base_to_theirs[b]?)))
+        .chain(std::iter::once((base.len(), ours.len(), theirs.len())));
+
+    let mut merged: Vec<&str> = Vec::with_capacity(ours.len().max(theirs.len()));
+    let mut conflicts: Vec<usize> = Vec::new();
+    let (mut b_start, mut o_start, mut t_start) = (0, 0, 0);
+
+    for (b_end, o_end, t_end) in anchors {
+        let base_chunk = &base[b_start..b_end];
+        let ours_chunk = &ours[o_start..o_end];
+        let theirs_chunk = &theirs[t_start..t_end];
+
+        if ours_chunk == base_chunk || ours_chunk == theirs_chunk {
+            merged.extend_from_slice(theirs_chunk);
+        } else if theirs_chunk == base_chunk {
+            merged.extend_from_slice(ours_chunk);
+        } else {
+            conflicts.push(o_start + 1);
+        }
+
+        if b_end < base.len() {
+            merged.push(base[b_end]);
+        }
+
+        (b_start, o_start, t_start) = (b_end + 1, o_end + 1, t_end + 1);
+    }
+
+    match conflicts.is_empty() {
+        true => {
+            let mut merged = merged.join("\n");
+            merged.push('\n');
+            Ok(merged)
+        }
+        false => Err(MergeConflicts { line_numbers: conflicts }),
+    }
+}
+
+/// Lines of context around the changes of a diff.
+const DIFF_CONTEXT: usize = 3;
+
+/// Line of a diff: `' '` if the line is in both texts, `'-'` if it was removed, and `'+'` if it was
+/// added.
+type DiffLine<'a> = (char, &'a str);
+
+/// Renders the changes from `old` to `new` as a unified diff, without the file headers.  This is
+/// empty if the texts have the same lines.
+#[must_use]
+pub fn unified_diff(old: &str, new: &str) -> String {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let mut lines: Vec<DiffLine<'_>> = Vec::with_capacity(old.len().max(new.len()));
+    let (mut o, mut n) = (0, 0);
+
+    for (o_end, n_end) in longest_common_subsequence(&old, &new)
+        .into_iter()
+        .chain(std::iter::once((old.len(), new.len())))
+    {
+        lines.extend(old[o..o_end].iter().map(|line| ('-', *line)));
+        lines.extend(new[n..n_end].iter().map(|line| ('+', *line)));
+
+        if o_end < old.len() {
+            lines.push((' ', old[o_end]));
+        }
+
+        (o, n) = (o_end + 1, n_end + 1);
+    }
+
+    let changed: Vec<usize> = (0..lines.len()).filter(|&i| lines[i].0 != ' ').collect();
+    let mut diff = String::new();
+    let mut i = 0;
+
+    // Each hunk goes from the context before a change to the context after the last change that
+    // is close enough to be part of the same hunk.
+    while i < changed.len() {
+        let start = changed[i].saturating_sub(DIFF_CONTEXT);
+        let mut last = changed[i];
+
+        while i + 1 < changed.len() && changed[i + 1] - last <= 2 * DIFF_CONTEXT + 1 {
+            i += 1;
+            last = changed[i];
+        }
+
+        let end = (last + DIFF_CONTEXT + 1).min(lines.len());
+        let count = |before: &[DiffLine<'_>], kind: char| {
+            before.iter().filter(|(k, _)| *k == ' ' || *k == kind).count()
+        };
+        let old_start = count(&lines[..start], '-');
+        let new_start = count(&lines[..start], '+');
+        let old_count = count(&lines[start..end], '-');
+        let new_count = count(&lines[start..end], '+');
+
+        let _ = writeln!(
+            diff,
+            "@@ -{},{} +{},{} @@",
+            old_start + usize::from(old_count > 0),
+            old_count,
+            new_start + usize::from(new_count > 0),
+            new_count
+        );
+
+        for (kind, line) in &lines[start..end] {
+            diff.push(*kind);
+            diff.push_str(line);
+            diff.push('\n');
+        }
+
+        i += 1;
+    }
+
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_merge3_keeps_both_changes() {
+        let base = indoc! { "
+            # Crate
+
+            This crate is grate.
+
+            ## Usage
+
+            Call `foo()`.
+            "
+        };
+        let ours = indoc! { "
+            # Crate
+
+            This crate is great.
+
+            ## Usage
+
+            Call `foo()`.
+            "
+        };
+        let theirs = indoc! { "
+            # Crate
+
+            This crate is grate.
+
+            ## Usage
+
+            Call `foo()` or `bar()`.
+            "
+        };
+
+        let expected = indoc! { "
+            # Crate
+
+            This crate is great.
+
+            ## Usage
+
+            Call `foo()` or `bar()`.
+            "
+        };
+
+        assert_eq!(merge3(base, ours, theirs).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_merge3_same_change() {
+        let base = "a\nb\nc\n";
+        let ours = "a\nB\nc\n";
+
+        assert_eq!(merge3(base, ours, ours).unwrap(), ours);
+        assert_eq!(merge3(base, base, ours).unwrap(), ours);
+        assert_eq!(merge3(base, ours, base).unwrap(), ours);
+    }
+
+    #[test]
+    fn test_merge3_conflict() {
+        let base = "a\nb\nc\nd\n";
+        let ours = "a\nb\nC\nd\n";
+        let theirs = "a\nb\nX\nd\n";
+
+        assert_eq!(merge3(base, ours, theirs), Err(MergeConflicts { line_numbers: vec![3] }));
+    }
+
+    #[test]
+    fn test_unified_diff() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\n";
+        let new = "a\nB\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\nm\n";
+
+        let expected = indoc! { "
+            @@ -1,5 +1,5 @@
+             a
+            -b
+            +B
+             c
+             d
+             e
+            @@ -10,3 +10,4 @@
+             j
+             k
+             l
+            +m
+            "
+        };
+
+        assert_eq!(unified_diff(old, new), expected);
+        assert_eq!(unified_diff("a\n", "a\n"), "");
+        assert_eq!(unified_diff("", "a\n"), "@@ -0,0 +1,1 @@\n+a\n");
+    }
+}
@@ -3,16 +3,22 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/.
  */
 
-use cargo_rdme::find_first_file_in_ancestors;
-use cargo_rdme::transform::{IntralinksConfig, IntralinksDocsRsConfig};
+use cargo_rdme::transform::IntralinksConfig;
+use cargo_rdme::{find_first_file_in_ancestors, CargoFlags, InjectOptions, Project};
 use clap::{value_parser, ArgAction};
+use std::collections::BTreeMap;
 use std::error::Error;
 use std::ffi::OsString;
 use std::fmt::{Display, Formatter};
+use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use thiserror::Error;
 
+/// JSON schema of the configuration, which is the same in `.cargo-rdme.toml`,
+/// `[package.metadata.rdme]`, and `[workspace.metadata.rdme]`.
+pub const CONFIG_SCHEMA: &str = include_str!("../config-schema.json");
+
 const PROJECT_NAME: &str = env!("CARGO_PKG_NAME");
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
@@ -35,6 +41,7 @@ pub enum LineTerminatorOpt {
     Auto,
     Lf,
     CrLf,
+    Cr,
 }
 
 impl FromStr for LineTerminatorOpt {
@@ -45,6 +52,299 @@ impl FromStr for LineTerminatorOpt {
             "auto" => Ok(LineTerminatorOpt::Auto),
             "lf" => Ok(LineTerminatorOpt::Lf),
             "crlf" => Ok(LineTerminatorOpt::CrLf),
+            "cr" => Ok(LineTerminatorOpt::Cr),
+            v => Err(InvalidOptValue { value: v.to_owned() }),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Default, Clone, Copy)]
+pub enum FinalNewlineOpt {
+    /// Keeps the README ending with a line terminator, or not, as it is now.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl FromStr for FinalNewlineOpt {
+    type Err = InvalidOptValue;
+
+    fn from_str(s: &str) -> Result<FinalNewlineOpt, InvalidOptValue> {
+        match s {
+            "auto" => Ok(FinalNewlineOpt::Auto),
+            "always" => Ok(FinalNewlineOpt::Always),
+            "never" => Ok(FinalNewlineOpt::Never),
+            v => Err(InvalidOptValue { value: v.to_owned() }),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum EmojiOpt {
+    Unicode,
+    Shortcodes,
+}
+
+impl FromStr for EmojiOpt {
+    type Err = InvalidOptValue;
+
+    fn from_str(s: &str) -> Result<EmojiOpt, InvalidOptValue> {
+        match s {
+            "unicode" => Ok(EmojiOpt::Unicode),
+            "shortcodes" => Ok(EmojiOpt::Shortcodes),
+            v => Err(InvalidOptValue { value: v.to_owned() }),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum DetailsOpt {
+    Keep,
+    Unwrap,
+    Drop,
+}
+
+impl FromStr for DetailsOpt {
+    type Err = InvalidOptValue;
+
+    fn from_str(s: &str) -> Result<DetailsOpt, InvalidOptValue> {
+        match s {
+            "keep" => Ok(DetailsOpt::Keep),
+            "unwrap" => Ok(DetailsOpt::Unwrap),
+            "drop" => Ok(DetailsOpt::Drop),
+            v => Err(InvalidOptValue { value: v.to_owned() }),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum MermaidOpt {
+    Keep,
+    Image,
+    Note,
+}
+
+impl FromStr for MermaidOpt {
+    type Err = InvalidOptValue;
+
+    fn from_str(s: &str) -> Result<MermaidOpt, InvalidOptValue> {
+        match s {
+            "keep" => Ok(MermaidOpt::Keep),
+            "image" => Ok(MermaidOpt::Image),
+            "note" => Ok(MermaidOpt::Note),
+            v => Err(InvalidOptValue { value: v.to_owned() }),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum MathOpt {
+    Keep,
+    Code,
+    Image,
+}
+
+impl FromStr for MathOpt {
+    type Err = InvalidOptValue;
+
+    fn from_str(s: &str) -> Result<MathOpt, InvalidOptValue> {
+        match s {
+            "keep" => Ok(MathOpt::Keep),
+            "code" => Ok(MathOpt::Code),
+            "image" => Ok(MathOpt::Image),
+            v => Err(InvalidOptValue { value: v.to_owned() }),
+        }
+    }
+}
+
+/// The tool to print the configuration for, in `cargo rdme hook-config`.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum HookConfigOpt {
+    PreCommit,
+    GitLabCi,
+}
+
+impl FromStr for HookConfigOpt {
+    type Err = InvalidOptValue;
+
+    fn from_str(s: &str) -> Result<HookConfigOpt, InvalidOptValue> {
+        match s {
+            "pre-commit" => Ok(HookConfigOpt::PreCommit),
+            "gitlab-ci" => Ok(HookConfigOpt::GitLabCi),
+            v => Err(InvalidOptValue { value: v.to_owned() }),
+        }
+    }
+}
+
+/// Where to preview the README, in `cargo rdme preview`.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum PreviewTargetOpt {
+    CratesIo,
+    GitHub,
+}
+
+impl FromStr for PreviewTargetOpt {
+    type Err = InvalidOptValue;
+
+    fn from_str(s: &str) -> Result<PreviewTargetOpt, InvalidOptValue> {
+        match s {
+            "crates-io" => Ok(PreviewTargetOpt::CratesIo),
+            "github" => Ok(PreviewTargetOpt::GitHub),
+            v => Err(InvalidOptValue { value: v.to_owned() }),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum BlankLinesOpt {
+    Keep,
+    Trim,
+}
+
+impl FromStr for BlankLinesOpt {
+    type Err = InvalidOptValue;
+
+    fn from_str(s: &str) -> Result<BlankLinesOpt, InvalidOptValue> {
+        match s {
+            "keep" => Ok(BlankLinesOpt::Keep),
+            "trim" => Ok(BlankLinesOpt::Trim),
+            v => Err(InvalidOptValue { value: v.to_owned() }),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum SetextHeadingsOpt {
+    Keep,
+    Atx,
+}
+
+impl FromStr for SetextHeadingsOpt {
+    type Err = InvalidOptValue;
+
+    fn from_str(s: &str) -> Result<SetextHeadingsOpt, InvalidOptValue> {
+        match s {
+            "keep" => Ok(SetextHeadingsOpt::Keep),
+            "atx" => Ok(SetextHeadingsOpt::Atx),
+            v => Err(InvalidOptValue { value: v.to_owned() }),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum ListIndentationOpt {
+    Keep,
+    Normalize,
+}
+
+impl FromStr for ListIndentationOpt {
+    type Err = InvalidOptValue;
+
+    fn from_str(s: &str) -> Result<ListIndentationOpt, InvalidOptValue> {
+        match s {
+            "keep" => Ok(ListIndentationOpt::Keep),
+            "normalize" => Ok(ListIndentationOpt::Normalize),
+            v => Err(InvalidOptValue { value: v.to_owned() }),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum HardBreaksOpt {
+    Keep,
+    Html,
+    Collapse,
+}
+
+impl FromStr for HardBreaksOpt {
+    type Err = InvalidOptValue;
+
+    fn from_str(s: &str) -> Result<HardBreaksOpt, InvalidOptValue> {
+        match s {
+            "keep" => Ok(HardBreaksOpt::Keep),
+            "html" => Ok(HardBreaksOpt::Html),
+            "collapse" => Ok(HardBreaksOpt::Collapse),
+            v => Err(InvalidOptValue { value: v.to_owned() }),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum LinkStyleOpt {
+    Keep,
+    Inline,
+    Reference,
+}
+
+impl FromStr for LinkStyleOpt {
+    type Err = InvalidOptValue;
+
+    fn from_str(s: &str) -> Result<LinkStyleOpt, InvalidOptValue> {
+        match s {
+            "keep" => Ok(LinkStyleOpt::Keep),
+            "inline" => Ok(LinkStyleOpt::Inline),
+            "reference" => Ok(LinkStyleOpt::Reference),
+            v => Err(InvalidOptValue { value: v.to_owned() }),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum UsageVersionOpt {
+    Exact,
+    Major,
+}
+
+impl FromStr for UsageVersionOpt {
+    type Err = InvalidOptValue;
+
+    fn from_str(s: &str) -> Result<UsageVersionOpt, InvalidOptValue> {
+        match s {
+            "exact" => Ok(UsageVersionOpt::Exact),
+            "major" => Ok(UsageVersionOpt::Major),
+            v => Err(InvalidOptValue { value: v.to_owned() }),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum UsageStyleOpt {
+    Toml,
+    CargoAdd,
+    Both,
+}
+
+impl FromStr for UsageStyleOpt {
+    type Err = InvalidOptValue;
+
+    fn from_str(s: &str) -> Result<UsageStyleOpt, InvalidOptValue> {
+        match s {
+            "toml" => Ok(UsageStyleOpt::Toml),
+            "cargo-add" => Ok(UsageStyleOpt::CargoAdd),
+            "both" => Ok(UsageStyleOpt::Both),
+            v => Err(InvalidOptValue { value: v.to_owned() }),
+        }
+    }
+}
+
+/// The capitalization the headings of the README are checked against.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum HeadingCaseOpt {
+    Sentence,
+    Title,
+    /// The capitalization of most headings.
+    Consistent,
+}
+
+impl FromStr for HeadingCaseOpt {
+    type Err = InvalidOptValue;
+
+    fn from_str(s: &str) -> Result<HeadingCaseOpt, InvalidOptValue> {
+        match s {
+            "sentence" => Ok(HeadingCaseOpt::Sentence),
+            "title" => Ok(HeadingCaseOpt::Title),
+            "consistent" => Ok(HeadingCaseOpt::Consistent),
             v => Err(InvalidOptValue { value: v.to_owned() }),
         }
     }
@@ -57,6 +357,7 @@ pub enum EntrypointOpt {
     Lib,
     BinDefault,
     BinName(String),
+    Example(String),
 }
 
 impl FromStr for EntrypointOpt {
@@ -71,44 +372,281 @@ impl FromStr for EntrypointOpt {
                 let name = v["bin:".len()..].to_owned();
                 Ok(EntrypointOpt::BinName(name))
             }
+            v if v.starts_with("example:") && v.len() > "example:".len() => {
+                let name = v["example:".len()..].to_owned();
+                Ok(EntrypointOpt::Example(name))
+            }
             v => Err(InvalidOptValue { value: v.to_owned() }),
         }
     }
 }
 
-#[derive(Debug)]
+/// An additional README, e.g. in another language, configured in `[[translations]]`.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Translation {
+    pub readme_path: PathBuf,
+    /// The documentation to inject in the README.  If not set the crate's documentation is used.
+    pub doc_source: Option<PathBuf>,
+    /// Whether the code blocks of `doc_source` are replaced by the ones of the crate's
+    /// documentation.
+    pub shared_code_blocks: bool,
+    /// Whether only the code blocks of the README are updated, since its text is translated by
+    /// hand.
+    pub code_blocks_only: bool,
+}
+
+#[derive(Debug, Clone)]
 pub struct CmdOptions {
+    workspace: bool,
     workspace_project: Option<String>,
+    package_dirs: Vec<PathBuf>,
+    package_dir_list: Option<PathBuf>,
+    jobs: Option<NonZeroUsize>,
+    progress: bool,
+    cargo_flags: CargoFlags,
     entrypoint: Option<EntrypointOpt>,
     line_terminator: Option<LineTerminatorOpt>,
+    final_newline: Option<FinalNewlineOpt>,
     check: bool,
+    stdout: bool,
     no_fail_on_warnings: bool,
     intralinks_strip_links: bool,
     force: bool,
-    readme_path: Option<PathBuf>,
+    fix: bool,
+    readme_paths: Vec<PathBuf>,
+    doc_source: Option<PathBuf>,
+    manifest_path: Option<PathBuf>,
     heading_base_level: Option<u8>,
+    heading: Option<String>,
+    cookbook: Option<String>,
+    readme_section: Option<String>,
+    content_hash: bool,
+    merge_hand_edits: bool,
+    heading_numbering: bool,
+    format_tables: bool,
+    sort_reference_links: bool,
+    strict: bool,
+    deterministic: bool,
+    build_script: bool,
+    emoji: Option<EmojiOpt>,
+    details: Option<DetailsOpt>,
+    mermaid: Option<MermaidOpt>,
+    math: Option<MathOpt>,
+    blank_lines: Option<BlankLinesOpt>,
+    setext_headings: Option<SetextHeadingsOpt>,
+    list_indentation: Option<ListIndentationOpt>,
+    hard_breaks: Option<HardBreaksOpt>,
+    link_style: Option<LinkStyleOpt>,
+    usage_version: Option<UsageVersionOpt>,
+    usage_style: Option<UsageStyleOpt>,
+    usage_features: Option<Vec<String>>,
+    logo: bool,
+    verify_examples: bool,
+    allow_missing_docs: bool,
+    features: Option<Vec<String>>,
+    all_features: bool,
+    no_default_features: bool,
+    heading_case: Option<HeadingCaseOpt>,
+    description_similarity: Option<u8>,
+    freeze: bool,
+    preview: Option<PreviewTargetOpt>,
+    preview_no_open: bool,
+    preview_serve: Option<String>,
+    config_schema: bool,
+    explain: bool,
+    explain_code: Option<String>,
+    selftest: bool,
+    hook_config: Option<HookConfigOpt>,
+    stats: bool,
+    undo: bool,
+    stats_json: bool,
+    timings: bool,
+    summary_path: Option<PathBuf>,
+    report_path: Option<PathBuf>,
+    #[cfg(feature = "mdbook")]
+    mdbook: bool,
+    #[cfg(feature = "mdbook")]
+    mdbook_supports: Option<String>,
 }
 
-fn get_cmd_args() -> Vec<OsString> {
-    let mut args: Vec<OsString> = std::env::args_os().collect();
-    let subcommand: &str = {
-        let package_name = env!("CARGO_PKG_NAME");
+impl CmdOptions {
+    pub fn manifest_path(&self) -> Option<&Path> {
+        self.manifest_path.as_deref()
+    }
 
-        assert!(package_name.starts_with("cargo-"), "package name does not start with `cargo-`");
+    pub fn workspace_project(&self) -> Option<&str> {
+        self.workspace_project.as_deref()
+    }
 
-        &package_name["cargo-".len()..]
-    };
+    pub fn workspace(&self) -> bool {
+        self.workspace
+    }
+
+    pub fn package_dirs(&self) -> &[PathBuf] {
+        &self.package_dirs
+    }
+
+    pub fn package_dir_list(&self) -> Option<&Path> {
+        self.package_dir_list.as_deref()
+    }
+
+    pub fn progress(&self) -> bool {
+        self.progress
+    }
+
+    /// The number of packages to process in parallel with `--workspace` or `--package-dir`.
+    /// Defaults to the number of logical CPUs.
+    pub fn jobs(&self) -> usize {
+        self.jobs.or_else(|| std::thread::available_parallelism().ok()).map_or(1, NonZeroUsize::get)
+    }
+
+    pub fn cargo_flags(&self) -> CargoFlags {
+        self.cargo_flags
+    }
+
+    pub fn config_schema(&self) -> bool {
+        self.config_schema
+    }
 
-    // When cargo executes an external subcommand it passes the name of the command itself as the
-    // second argument.  Here we remove that so that we can simply run `cargo run` instead of
-    // `cargo run -- rdme` for local development.
-    if args.len() >= 2 && args[1] == subcommand {
-        args.remove(1);
+    pub fn explain(&self) -> bool {
+        self.explain
+    }
+
+    pub fn explain_code(&self) -> Option<&str> {
+        self.explain_code.as_deref()
+    }
+
+    pub fn preview_no_open(&self) -> bool {
+        self.preview_no_open
+    }
+
+    /// The address to serve the preview at, with `cargo rdme preview --serve`.
+    pub fn preview_serve(&self) -> Option<&str> {
+        self.preview_serve.as_deref()
+    }
+
+    pub fn selftest(&self) -> bool {
+        self.selftest
+    }
+
+    pub fn hook_config(&self) -> Option<HookConfigOpt> {
+        self.hook_config
+    }
+
+    pub fn undo(&self) -> bool {
+        self.undo
+    }
+
+    #[cfg(feature = "mdbook")]
+    pub fn mdbook(&self) -> bool {
+        self.mdbook
+    }
+
+    #[cfg(feature = "mdbook")]
+    pub fn mdbook_supports(&self) -> Option<&str> {
+        self.mdbook_supports.as_deref()
+    }
+
+    /// Sets the options given in the `[preprocessor.rdme]` table of the `book.toml` of an mdBook,
+    /// which take the place of the command line options.
+    #[cfg(feature = "mdbook")]
+    pub fn with_book_options(
+        self,
+        manifest_path: Option<PathBuf>,
+        workspace_project: Option<String>,
+        heading_base_level: Option<u8>,
+    ) -> CmdOptions {
+        CmdOptions {
+            manifest_path: manifest_path.or(self.manifest_path),
+            workspace_project: workspace_project.or(self.workspace_project),
+            heading_base_level: heading_base_level.or(self.heading_base_level),
+            ..self
+        }
+    }
+
+    pub fn stats(&self) -> bool {
+        self.stats
+    }
+
+    pub fn stats_json(&self) -> bool {
+        self.stats_json
+    }
+
+    pub fn strict(&self) -> bool {
+        self.strict
+    }
+
+    pub fn deterministic(&self) -> bool {
+        self.deterministic
+    }
+
+    pub fn force(&self) -> bool {
+        self.force
+    }
+
+    pub fn timings(&self) -> bool {
+        self.timings
+    }
+
+    pub fn summary_path(&self) -> Option<&Path> {
+        self.summary_path.as_deref()
+    }
+
+    pub fn report_path(&self) -> Option<&Path> {
+        self.report_path.as_deref()
+    }
+}
+
+/// How the program was invoked.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum InvocationStyle {
+    /// Invoked by cargo as `cargo rdme`.
+    CargoSubcommand,
+    /// Invoked directly, e.g. as `cargo-rdme` or `rdme`.
+    Standalone,
+}
+
+impl InvocationStyle {
+    pub fn detect(args: &[OsString]) -> InvocationStyle {
+        let subcommand: &str = {
+            let package_name = env!("CARGO_PKG_NAME");
+
+            assert!(
+                package_name.starts_with("cargo-"),
+                "package name does not start with `cargo-`"
+            );
+
+            &package_name["cargo-".len()..]
+        };
+
+        // When cargo executes an external subcommand it passes the name of the command itself as
+        // the second argument.
+        match args.get(1) {
+            Some(arg) if arg == subcommand => InvocationStyle::CargoSubcommand,
+            _ => InvocationStyle::Standalone,
+        }
+    }
+}
+
+/// Removes the extra argument cargo passes to subcommands, so that the arguments can be parsed
+/// the same way regardless of the invocation style.  This also allows us to simply run
+/// `cargo run` instead of `cargo run -- rdme` for local development.
+fn normalize_cmd_args(mut args: Vec<OsString>) -> Vec<OsString> {
+    match InvocationStyle::detect(&args) {
+        InvocationStyle::CargoSubcommand => {
+            args.remove(1);
+        }
+        InvocationStyle::Standalone => (),
     }
 
     args
 }
 
+fn get_cmd_args() -> Vec<OsString> {
+    normalize_cmd_args(std::env::args_os().collect())
+}
+
+#[allow(clippy::too_many_lines)]
 pub fn cmd_options() -> CmdOptions {
     use clap::{Arg, Command};
 
@@ -116,63 +654,444 @@ pub fn cmd_options() -> CmdOptions {
         .version(VERSION)
         .about("Create the README from your crate’s documentation.")
         .arg(
-            Arg::new("entrypoint")
-                .long("entrypoint")
-                .help("selects the source code entrypoint of the crate (e.g. auto, lib, bin, bin:<name>)")
-                .value_parser(EntrypointOpt::from_str),
+            Arg::new("entrypoint")
+                .long("entrypoint")
+                .help("selects the source code entrypoint of the crate (e.g. auto, lib, bin, bin:<name>, example:<name>)")
+                .value_parser(EntrypointOpt::from_str),
+        )
+        .arg(
+            Arg::new("line-terminator")
+                .long("line-terminator")
+                .help("line terminator to use when writing the README file")
+                .value_parser(LineTerminatorOpt::from_str)
+                .global(true),
+        )
+        .arg(
+            Arg::new("final-newline")
+                .long("final-newline")
+                .help("whether the README file ends with a line terminator (auto, always, never)")
+                .value_parser(FinalNewlineOpt::from_str)
+                .global(true),
+        )
+        .arg(
+            Arg::new("readme-path")
+                .long("readme-path")
+                .short('r')
+                .help("README file path to use (overrides of what is specified in the project `Cargo.toml`), which can be given more than once to update several READMEs")
+                .value_parser(value_parser!(PathBuf))
+                .action(ArgAction::Append)
+                .global(true),
+        )
+        .arg(
+            Arg::new("doc-source")
+                .long("doc-source")
+                .help("rust or markdown file to extract the crate documentation from, instead of the crate entrypoint")
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("manifest-path")
+                .long("manifest-path")
+                .help("path to the `Cargo.toml` of the project (defaults to the project of the current directory)")
+                .value_parser(value_parser!(PathBuf))
+                .global(true),
+        )
+        .arg(
+            Arg::new("workspace")
+                .long("workspace")
+                .help("process every package of the workspace, skipping the ones without a README or crate documentation")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("workspace-project")
+                .global(true),
+        )
+        .arg(
+            Arg::new("workspace-project")
+                .long("workspace-project")
+                .short('w')
+                .help("project to get the documentation from if your are using workspaces")
+                .global(true),
+        )
+        .arg(
+            Arg::new("package-dir")
+                .long("package-dir")
+                .help("process the package of the directory, independently of the others (can be used multiple times)")
+                .value_parser(value_parser!(PathBuf))
+                .action(ArgAction::Append)
+                .conflicts_with_all(["manifest-path", "workspace", "workspace-project"])
+                .global(true),
+        )
+        .arg(
+            Arg::new("package-dir-list")
+                .long("package-dir-list")
+                .help("file with the directories of the packages to process, one per line (like `--package-dir`)")
+                .value_parser(value_parser!(PathBuf))
+                .conflicts_with_all(["manifest-path", "workspace", "workspace-project"])
+                .global(true),
+        )
+        .arg(
+            Arg::new("jobs")
+                .long("jobs")
+                .short('j')
+                .value_name("N")
+                .help("number of packages to process in parallel with `--workspace` or `--package-dir` (defaults to the number of logical CPUs)")
+                .value_parser(value_parser!(NonZeroUsize))
+                .global(true),
+        )
+        .arg(
+            Arg::new("progress")
+                .long("progress")
+                .help("show the progress of `--workspace` or `--package-dir` when stderr is a terminal")
+                .action(ArgAction::SetTrue)
+                .global(true),
+        )
+        .arg(
+            Arg::new("locked")
+                .long("locked")
+                .help("passed to cargo: require `Cargo.lock` to be up to date")
+                .action(ArgAction::SetTrue)
+                .global(true),
+        )
+        .arg(
+            Arg::new("frozen")
+                .long("frozen")
+                .help("passed to cargo: require `Cargo.lock` to be up to date, and do not access the network")
+                .action(ArgAction::SetTrue)
+                .global(true),
+        )
+        .arg(
+            Arg::new("offline")
+                .long("offline")
+                .help("passed to cargo: do not access the network")
+                .action(ArgAction::SetTrue)
+                .global(true),
+        )
+        .arg(
+            Arg::new("check")
+                .long("check")
+                .short('c')
+                .help("checks if the README is up to date (exit code 3 if there’s a mismatch, 4 if warnings were emitted)")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("stdout")
+                .long("stdout")
+                .help("print the updated README to stdout instead of writing it")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("check"),
+        )
+        .arg(
+        Arg::new("no-fail-on-warnings")
+            .long("no-fail-on-warnings")
+            .help("do not exit with a error status code when checking if the README is up to date")
+            .action(ArgAction::SetTrue),
+        )
+        .arg(
+        Arg::new("intralinks-strip-links")
+            .long("intralinks-strip-links")
+            .help("remove the intralinks")
+            .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("heading-base-level")
+                .long("heading-base-level")
+                .help("heading level to be added to the heading level in the rust documentation")
+                .value_parser(value_parser!(u8)),
+        )
+        .arg(
+            Arg::new("heading")
+                .long("heading")
+                .help("heading to add at the top of the documentation inserted in the README"),
+        )
+        .arg(
+            Arg::new("cookbook")
+                .long("cookbook")
+                .help("integration test (e.g. `cookbook` for `tests/cookbook.rs`) whose documentation is added as a “Cookbook” section of the documentation"),
+        )
+        .arg(
+            Arg::new("readme-section")
+                .long("readme-section")
+                .help("heading of the README section whose content is replaced with the documentation, instead of the content between markers"),
+        )
+        .arg(
+            Arg::new("content-hash")
+                .long("content-hash")
+                .help("add a hash of the generated content to the end marker, to detect when it is edited by hand")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("merge-hand-edits")
+                .long("merge-hand-edits")
+                .help("(experimental) keep hand edits of the generated documentation by merging them with the new documentation (requires `--content-hash`)")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("heading-numbering")
+                .long("heading-numbering")
+                .help("number the headings of the documentation inserted in the README (1., 1.1., …), updating links to them")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("format-tables")
+                .long("format-tables")
+                .help("align the columns of the tables of the documentation inserted in the README")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("sort-reference-links")
+                .long("sort-reference-links")
+                .help("move the reference link definitions of the documentation inserted in the README to its end, sorted and without duplicates")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("strict")
+                .long("strict")
+                .help("turn every warning into an error, and fail if a package of the workspace is skipped")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("deterministic")
+                .long("deterministic")
+                .help("ignore the configuration of the user and of the environment, and link to dependencies by their version requirement, so that the README is the same on every machine")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("build-script")
+                .long("build-script")
+                .help("run `cargo check` first, so that the documentation can be read from the output of the build script")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("emoji")
+                .long("emoji")
+                .help("convert emoji shortcodes such as `:crab:` to unicode emoji (unicode), or the reverse (shortcodes)")
+                .value_parser(EmojiOpt::from_str),
+        )
+        .arg(
+            Arg::new("details")
+                .long("details")
+                .help("what to do with collapsible `<details>` blocks: keep them, unwrap them (the summary becomes a heading), or drop them")
+                .value_parser(DetailsOpt::from_str),
+        )
+        .arg(
+            Arg::new("mermaid")
+                .long("mermaid")
+                .help("what to do with mermaid diagrams, which crates.io does not render: keep them, replace them with an image rendered by mermaid.ink, or replace them with a note linking to the documentation")
+                .value_parser(MermaidOpt::from_str),
+        )
+        .arg(
+            Arg::new("math")
+                .long("math")
+                .help("what to do with math, which crates.io does not render: keep it, replace it with code, or replace it with images rendered by latex.codecogs.com")
+                .value_parser(MathOpt::from_str),
+        )
+        .arg(
+            Arg::new("blank-lines")
+                .long("blank-lines")
+                .help("what to do with blank lines at the start and end of the crate’s documentation, and with consecutive blank lines: trim them (the default) or keep them")
+                .value_parser(BlankLinesOpt::from_str),
+        )
+        .arg(
+            Arg::new("setext-headings")
+                .long("setext-headings")
+                .help("what to do with setext headings (underlined with `===` or `---`) of the crate’s documentation: keep them (the default) or convert them to atx headings (`#`)")
+                .value_parser(SetextHeadingsOpt::from_str),
+        )
+        .arg(
+            Arg::new("list-indentation")
+                .long("list-indentation")
+                .help("indentation of nested lists: keep it (the default), or normalize it to four spaces per level, which crates.io needs in some cases")
+                .value_parser(ListIndentationOpt::from_str),
+        )
+        .arg(
+            Arg::new("hard-breaks")
+                .long("hard-breaks")
+                .help("what to do with hard line breaks (lines ending in two spaces or a backslash): keep them (the default), replace them with `<br>`, or collapse them into the paragraph")
+                .value_parser(HardBreaksOpt::from_str),
+        )
+        .arg(
+            Arg::new("link-style")
+                .long("link-style")
+                .help("style of the links of the documentation inserted in the README: keep them as they are (the default), or make them all inline or all reference links")
+                .value_parser(LinkStyleOpt::from_str),
+        )
+        .arg(
+            Arg::new("usage-version")
+                .long("usage-version")
+                .help("version requirement of the dependency snippet generated in the `<!-- cargo-rdme usage -->` marker: the versions compatible with the crate’s version (major, the default), or its exact version (exact)")
+                .value_parser(UsageVersionOpt::from_str),
+        )
+        .arg(
+            Arg::new("usage-style")
+                .long("usage-style")
+                .help("style of the dependency snippet generated in the `<!-- cargo-rdme usage -->` marker: a toml code block (toml, the default), a `cargo add` command (cargo-add), or both")
+                .value_parser(UsageStyleOpt::from_str),
+        )
+        .arg(
+            Arg::new("usage-features")
+                .long("usage-features")
+                .help("comma separated features of the crate to enable in the dependency snippet generated in the `<!-- cargo-rdme usage -->` marker")
+                .value_delimiter(','),
+        )
+        .arg(
+            Arg::new("logo")
+                .long("logo")
+                .help("put the logo of the crate, from `#![doc(html_logo_url = \"…\")]`, at the top of the README")
+                .action(ArgAction::SetTrue),
         )
         .arg(
-            Arg::new("line-terminator")
-                .long("line-terminator")
-                .help("line terminator to use when writing the README file")
-                .value_parser(LineTerminatorOpt::from_str),
+            Arg::new("verify-examples")
+                .long("verify-examples")
+                .help("check that the rust examples of the documentation compile")
+                .action(ArgAction::SetTrue),
         )
         .arg(
-            Arg::new("readme-path")
-                .long("readme-path")
-                .short('r')
-                .help("README file path to use (overrides of what is specified in the project `Cargo.toml`)")
-                .value_parser(value_parser!(PathBuf)),
+            Arg::new("allow-missing-docs")
+                .long("allow-missing-docs")
+                .help("do nothing, instead of failing, if the crate has no documentation")
+                .action(ArgAction::SetTrue),
         )
         .arg(
-            Arg::new("workspace-project")
-                .long("workspace-project")
-                .short('w')
-                .help("project to get the documentation from if your are using workspaces"),
+            Arg::new("features")
+                .long("features")
+                .short('F')
+                .help("comma separated features of the crate to enable when evaluating `cfg_attr` doc attributes and `rdme:if` directives")
+                .value_delimiter(',')
+                .action(ArgAction::Append),
         )
         .arg(
-            Arg::new("check")
-                .long("check")
-                .short('c')
-                .help("checks if the README is up to date (exit code 3 if there’s a mismatch, 4 if warnings were emitted)")
+            Arg::new("all-features")
+                .long("all-features")
+                .help("enable all features of the crate when evaluating `cfg_attr` doc attributes and `rdme:if` directives")
                 .action(ArgAction::SetTrue),
         )
         .arg(
-        Arg::new("no-fail-on-warnings")
-            .long("no-fail-on-warnings")
-            .help("do not exit with a error status code when checking if the README is up to date")
-            .action(ArgAction::SetTrue),
+            Arg::new("no-default-features")
+                .long("no-default-features")
+                .help("do not enable the `default` feature of the crate when evaluating `cfg_attr` doc attributes and `rdme:if` directives")
+                .action(ArgAction::SetTrue),
         )
         .arg(
-        Arg::new("intralinks-strip-links")
-            .long("intralinks-strip-links")
-            .help("remove the intralinks")
-            .action(ArgAction::SetTrue),
+            Arg::new("heading-case")
+                .long("heading-case")
+                .help("warn about headings of the README that are not in sentence case, title case, or the case of most headings (consistent)")
+                .value_parser(HeadingCaseOpt::from_str),
         )
         .arg(
-            Arg::new("heading-base-level")
-                .long("heading-base-level")
-                .help("heading level to be added to the heading level in the rust documentation")
-                .value_parser(value_parser!(u8)),
+            Arg::new("description-similarity")
+                .long("description-similarity")
+                .help("in a workspace, warn about packages whose description is less similar than this (in percentage) to the first sentence of their crate documentation")
+                .value_parser(value_parser!(u8).range(0..=100)),
         )
         .arg(
             Arg::new("force")
                 .long("force")
                 .short('f')
                 .help("force README update, even when there are uncommitted changes")
+                .action(ArgAction::SetTrue)
+                .global(true),
+        )
+        .arg(
+            Arg::new("fix")
+                .long("fix")
+                .help("fix README lints (duplicate title, tabs, trailing whitespace, code block languages, and outdated versions of the crate), also outside the crate’s documentation")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("timings")
+                .long("timings")
+                .help("report how long each step took for every package")
                 .action(ArgAction::SetTrue),
         )
-        .get_matches_from(get_cmd_args());
+        .arg(
+            Arg::new("summary")
+                .long("summary")
+                .value_name("FILE")
+                .help("write a markdown summary of the changes to the READMEs, with their diffs, e.g. to post as a comment of a pull request (`-` prints it)")
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("report")
+                .long("report")
+                .value_name("FILE")
+                .help("write a JSON report of the run, with the status of every package, the files written, and the warnings and errors, e.g. for auditing in release pipelines (`-` prints it)")
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .subcommand(
+            Command::new("freeze")
+                .about("Pins the README to the current version of the crate, for releases.  Links to the `latest` docs.rs documentation of the crate will point to the current version, and so will dependency snippets in toml code blocks."),
+        )
+        .subcommand(
+            Command::new("preview")
+                .about("Renders the README, as it would be generated, the way the target renders it, to a local html file in `target/cargo-rdme/preview/`, and opens it in the browser.  This shows what will appear on crates.io or GitHub before publishing.")
+                .arg(
+                    Arg::new("target")
+                        .long("target")
+                        .help("where to preview the README: `crates-io` or `github`")
+                        .value_parser(PreviewTargetOpt::from_str)
+                        .default_value("crates-io"),
+                )
+                .arg(
+                    Arg::new("no-open")
+                        .long("no-open")
+                        .help("only write the html file, without opening it in the browser")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("serve")
+                        .long("serve")
+                        .value_name("ADDRESS")
+                        .help("serve the preview at ADDRESS (by default 127.0.0.1:8000), rendering it again when the package changes")
+                        .num_args(0..=1)
+                        .default_missing_value("127.0.0.1:8000"),
+                ),
+        )
+        .subcommand(
+            Command::new("config-schema")
+                .about("Prints the JSON schema of the configuration, i.e. of `.cargo-rdme.toml`, `[package.metadata.rdme]`, and `[workspace.metadata.rdme]`."),
+        )
+        .subcommand(
+            Command::new("explain")
+                .about("Explains an error or warning code, such as `RDME0003`, with examples and possible fixes.  Without a code every code is listed.")
+                .arg(Arg::new("code").help("the code to explain")),
+        )
+        .subcommand(
+            Command::new("selftest")
+                .about("Runs cargo rdme on bundled example projects and compares the generated READMEs with the expected ones, to verify that this installation behaves as released."),
+        )
+        .subcommand(
+            Command::new("hook-config")
+                .about("Prints the configuration to check that the README is up to date with the pre-commit framework (the `.pre-commit-hooks.yaml` of cargo rdme) or with GitLab CI (a job to add to `.gitlab-ci.yml`).")
+                .arg(
+                    Arg::new("tool")
+                        .help("the tool to print the configuration for: `pre-commit` or `gitlab-ci`")
+                        .value_parser(HookConfigOpt::from_str)
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            Command::new("stats")
+                .about("Reports the state of the documentation of every package of the workspace: the length of the crate documentation and of the README, whether the README has cargo rdme markers, and how many intralinks there are and how many of them are unresolved.")
+                .arg(
+                    Arg::new("json")
+                        .long("json")
+                        .help("print the report as JSON")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("undo")
+                .about("Restores the READMEs written by the last run of cargo rdme to what they were before, from the history kept in `target/cargo-rdme/history/`.  READMEs changed since then are not restored, unless `--force` is used."),
+        );
+    #[cfg(feature = "mdbook")]
+    let cmd_opts = cmd_opts.subcommand(
+        Command::new("mdbook")
+            .about("Runs as an mdBook preprocessor, which injects the crate’s documentation in the chapters of the book with cargo rdme markers.  This is what the `mdbook-rdme` preprocessor runs.")
+            .subcommand(
+                Command::new("supports")
+                    .about("Tells mdBook whether the renderer is supported.")
+                    .arg(Arg::new("renderer").required(true)),
+            ),
+    );
+    let cmd_opts = cmd_opts.get_matches_from(get_cmd_args());
 
     let workspace_project = cmd_opts.get_one::<String>("workspace-project").cloned();
 
@@ -181,20 +1100,120 @@ pub fn cmd_options() -> CmdOptions {
 
     let entrypoint = cmd_opts.get_one::<EntrypointOpt>("entrypoint").cloned();
 
-    let readme_path = cmd_opts.get_one::<PathBuf>("readme-path").cloned();
+    let readme_paths = cmd_opts
+        .get_many::<PathBuf>("readme-path")
+        .map(|paths| paths.cloned().collect())
+        .unwrap_or_default();
+
+    let doc_source = cmd_opts.get_one::<PathBuf>("doc-source").cloned();
+
+    // Like cargo does for build scripts, `CARGO_MANIFEST_DIR` locates the project if it is set.
+    let manifest_path = cmd_opts.get_one::<PathBuf>("manifest-path").cloned().or_else(|| {
+        std::env::var_os("CARGO_MANIFEST_DIR").map(|dir| PathBuf::from(dir).join("Cargo.toml"))
+    });
 
     let heading_base_level = cmd_opts.get_one::<u8>("heading-base-level").copied();
 
+    let heading = cmd_opts.get_one::<String>("heading").cloned();
+    let cookbook = cmd_opts.get_one::<String>("cookbook").cloned();
+    let readme_section = cmd_opts.get_one::<String>("readme-section").cloned();
+
     CmdOptions {
+        workspace: cmd_opts.get_flag("workspace"),
         workspace_project,
+        package_dirs: cmd_opts
+            .get_many::<PathBuf>("package-dir")
+            .map(|dirs| dirs.cloned().collect())
+            .unwrap_or_default(),
+        package_dir_list: cmd_opts.get_one::<PathBuf>("package-dir-list").cloned(),
+        jobs: cmd_opts.get_one::<NonZeroUsize>("jobs").copied(),
+        progress: cmd_opts.get_flag("progress"),
+        cargo_flags: CargoFlags::default()
+            .with_locked(cmd_opts.get_flag("locked"))
+            .with_frozen(cmd_opts.get_flag("frozen"))
+            .with_offline(cmd_opts.get_flag("offline")),
         entrypoint,
         line_terminator,
+        final_newline: cmd_opts.get_one::<FinalNewlineOpt>("final-newline").copied(),
         check: cmd_opts.get_flag("check"),
+        stdout: cmd_opts.get_flag("stdout"),
         no_fail_on_warnings: cmd_opts.get_flag("no-fail-on-warnings"),
         intralinks_strip_links: cmd_opts.get_flag("intralinks-strip-links"),
         force: cmd_opts.get_flag("force"),
-        readme_path,
+        fix: cmd_opts.get_flag("fix"),
+        readme_paths,
+        doc_source,
+        manifest_path,
         heading_base_level,
+        heading,
+        cookbook,
+        readme_section,
+        content_hash: cmd_opts.get_flag("content-hash"),
+        merge_hand_edits: cmd_opts.get_flag("merge-hand-edits"),
+        heading_numbering: cmd_opts.get_flag("heading-numbering"),
+        format_tables: cmd_opts.get_flag("format-tables"),
+        sort_reference_links: cmd_opts.get_flag("sort-reference-links"),
+        strict: cmd_opts.get_flag("strict"),
+        deterministic: cmd_opts.get_flag("deterministic"),
+        build_script: cmd_opts.get_flag("build-script"),
+        emoji: cmd_opts.get_one::<EmojiOpt>("emoji").copied(),
+        details: cmd_opts.get_one::<DetailsOpt>("details").copied(),
+        mermaid: cmd_opts.get_one::<MermaidOpt>("mermaid").copied(),
+        math: cmd_opts.get_one::<MathOpt>("math").copied(),
+        blank_lines: cmd_opts.get_one::<BlankLinesOpt>("blank-lines").copied(),
+        setext_headings: cmd_opts.get_one::<SetextHeadingsOpt>("setext-headings").copied(),
+        list_indentation: cmd_opts.get_one::<ListIndentationOpt>("list-indentation").copied(),
+        hard_breaks: cmd_opts.get_one::<HardBreaksOpt>("hard-breaks").copied(),
+        link_style: cmd_opts.get_one::<LinkStyleOpt>("link-style").copied(),
+        usage_version: cmd_opts.get_one::<UsageVersionOpt>("usage-version").copied(),
+        usage_style: cmd_opts.get_one::<UsageStyleOpt>("usage-style").copied(),
+        usage_features: cmd_opts
+            .get_many::<String>("usage-features")
+            .map(|features| features.cloned().collect()),
+        logo: cmd_opts.get_flag("logo"),
+        verify_examples: cmd_opts.get_flag("verify-examples"),
+        allow_missing_docs: cmd_opts.get_flag("allow-missing-docs"),
+        features: cmd_opts
+            .get_many::<String>("features")
+            .map(|features| features.cloned().collect()),
+        all_features: cmd_opts.get_flag("all-features"),
+        no_default_features: cmd_opts.get_flag("no-default-features"),
+        heading_case: cmd_opts.get_one::<HeadingCaseOpt>("heading-case").copied(),
+        description_similarity: cmd_opts.get_one::<u8>("description-similarity").copied(),
+        freeze: cmd_opts.subcommand_matches("freeze").is_some(),
+        preview: cmd_opts
+            .subcommand_matches("preview")
+            .and_then(|preview| preview.get_one::<PreviewTargetOpt>("target").copied()),
+        preview_no_open: cmd_opts
+            .subcommand_matches("preview")
+            .is_some_and(|preview| preview.get_flag("no-open")),
+        preview_serve: cmd_opts
+            .subcommand_matches("preview")
+            .and_then(|preview| preview.get_one::<String>("serve").cloned()),
+        config_schema: cmd_opts.subcommand_matches("config-schema").is_some(),
+        explain: cmd_opts.subcommand_matches("explain").is_some(),
+        explain_code: cmd_opts
+            .subcommand_matches("explain")
+            .and_then(|explain| explain.get_one::<String>("code").cloned()),
+        selftest: cmd_opts.subcommand_matches("selftest").is_some(),
+        hook_config: cmd_opts
+            .subcommand_matches("hook-config")
+            .and_then(|hook_config| hook_config.get_one::<HookConfigOpt>("tool").copied()),
+        stats: cmd_opts.subcommand_matches("stats").is_some(),
+        undo: cmd_opts.subcommand_matches("undo").is_some(),
+        stats_json: cmd_opts
+            .subcommand_matches("stats")
+            .is_some_and(|stats| stats.get_flag("json")),
+        timings: cmd_opts.get_flag("timings"),
+        summary_path: cmd_opts.get_one::<PathBuf>("summary").cloned(),
+        report_path: cmd_opts.get_one::<PathBuf>("report").cloned(),
+        #[cfg(feature = "mdbook")]
+        mdbook: cmd_opts.subcommand_matches("mdbook").is_some(),
+        #[cfg(feature = "mdbook")]
+        mdbook_supports: cmd_opts
+            .subcommand_matches("mdbook")
+            .and_then(|mdbook| mdbook.subcommand_matches("supports"))
+            .and_then(|supports| supports.get_one::<String>("renderer").cloned()),
     }
 }
 
@@ -208,16 +1227,120 @@ pub enum ConfigFileOptionsError {
     InvalidField(&'static str),
     #[error("invalid entrypoint table")]
     InvalidEntrypointTable,
+    #[error("invalid workspace configuration in `[workspace.metadata.rdme]`: {0}")]
+    InvalidWorkspaceConfig(Box<ConfigFileOptionsError>),
+    #[error("invalid package configuration in `[package.metadata.rdme]`: {0}")]
+    InvalidPackageConfig(Box<ConfigFileOptionsError>),
+    #[error("invalid user configuration in \"{}\": {}", .0.display(), .1)]
+    InvalidUserConfig(PathBuf, Box<ConfigFileOptionsError>),
+    #[error("invalid configuration in the `CARGO_RDME_*` environment variables: {0}")]
+    InvalidEnvConfig(Box<ConfigFileOptionsError>),
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub struct ConfigFileOptions {
     line_terminator: Option<LineTerminatorOpt>,
+    final_newline: Option<FinalNewlineOpt>,
     workspace_project: Option<String>,
     entrypoint: Option<EntrypointOpt>,
     readme_path: Option<PathBuf>,
+    doc_source: Option<PathBuf>,
     intralinks: Option<IntralinksConfig>,
     heading_base_level: Option<u8>,
+    heading: Option<String>,
+    cookbook: Option<String>,
+    readme_section: Option<String>,
+    content_hash: Option<bool>,
+    merge_hand_edits: Option<bool>,
+    heading_numbering: Option<bool>,
+    format_tables: Option<bool>,
+    sort_reference_links: Option<bool>,
+    strict: Option<bool>,
+    build_script: Option<bool>,
+    emoji: Option<EmojiOpt>,
+    details: Option<DetailsOpt>,
+    mermaid: Option<MermaidOpt>,
+    math: Option<MathOpt>,
+    blank_lines: Option<BlankLinesOpt>,
+    setext_headings: Option<SetextHeadingsOpt>,
+    list_indentation: Option<ListIndentationOpt>,
+    hard_breaks: Option<HardBreaksOpt>,
+    link_style: Option<LinkStyleOpt>,
+    usage_version: Option<UsageVersionOpt>,
+    usage_style: Option<UsageStyleOpt>,
+    usage_features: Option<Vec<String>>,
+    logo: Option<bool>,
+    verify_examples: Option<bool>,
+    allow_missing_docs: Option<bool>,
+    features: Option<Vec<String>>,
+    all_features: Option<bool>,
+    no_default_features: Option<bool>,
+    heading_case: Option<HeadingCaseOpt>,
+    description_similarity: Option<u8>,
+    translations: Option<Vec<Translation>>,
+    disabled: Option<bool>,
+}
+
+impl ConfigFileOptions {
+    pub fn workspace_project(&self) -> Option<&str> {
+        self.workspace_project.as_deref()
+    }
+
+    pub fn strict(&self) -> Option<bool> {
+        self.strict
+    }
+
+    /// Uses the options of `fallback` for the options that are not set in `self`.
+    #[must_use]
+    pub fn or(self, fallback: ConfigFileOptions) -> ConfigFileOptions {
+        let intralinks = match (self.intralinks, fallback.intralinks) {
+            (Some(intralinks), Some(fallback)) => Some(intralinks.or(fallback)),
+            (intralinks, fallback) => intralinks.or(fallback),
+        };
+
+        ConfigFileOptions {
+            line_terminator: self.line_terminator.or(fallback.line_terminator),
+            final_newline: self.final_newline.or(fallback.final_newline),
+            workspace_project: self.workspace_project.or(fallback.workspace_project),
+            entrypoint: self.entrypoint.or(fallback.entrypoint),
+            readme_path: self.readme_path.or(fallback.readme_path),
+            doc_source: self.doc_source.or(fallback.doc_source),
+            intralinks,
+            heading_base_level: self.heading_base_level.or(fallback.heading_base_level),
+            heading: self.heading.or(fallback.heading),
+            cookbook: self.cookbook.or(fallback.cookbook),
+            readme_section: self.readme_section.or(fallback.readme_section),
+            content_hash: self.content_hash.or(fallback.content_hash),
+            merge_hand_edits: self.merge_hand_edits.or(fallback.merge_hand_edits),
+            heading_numbering: self.heading_numbering.or(fallback.heading_numbering),
+            format_tables: self.format_tables.or(fallback.format_tables),
+            sort_reference_links: self.sort_reference_links.or(fallback.sort_reference_links),
+            strict: self.strict.or(fallback.strict),
+            build_script: self.build_script.or(fallback.build_script),
+            emoji: self.emoji.or(fallback.emoji),
+            details: self.details.or(fallback.details),
+            mermaid: self.mermaid.or(fallback.mermaid),
+            math: self.math.or(fallback.math),
+            blank_lines: self.blank_lines.or(fallback.blank_lines),
+            setext_headings: self.setext_headings.or(fallback.setext_headings),
+            list_indentation: self.list_indentation.or(fallback.list_indentation),
+            hard_breaks: self.hard_breaks.or(fallback.hard_breaks),
+            link_style: self.link_style.or(fallback.link_style),
+            usage_version: self.usage_version.or(fallback.usage_version),
+            usage_style: self.usage_style.or(fallback.usage_style),
+            usage_features: self.usage_features.or(fallback.usage_features),
+            logo: self.logo.or(fallback.logo),
+            verify_examples: self.verify_examples.or(fallback.verify_examples),
+            allow_missing_docs: self.allow_missing_docs.or(fallback.allow_missing_docs),
+            features: self.features.or(fallback.features),
+            all_features: self.all_features.or(fallback.all_features),
+            no_default_features: self.no_default_features.or(fallback.no_default_features),
+            heading_case: self.heading_case.or(fallback.heading_case),
+            description_similarity: self.description_similarity.or(fallback.description_similarity),
+            translations: self.translations.or(fallback.translations),
+            disabled: self.disabled.or(fallback.disabled),
+        }
+    }
 }
 
 fn config_file_options_from_str(
@@ -226,6 +1349,160 @@ fn config_file_options_from_str(
     let config_toml: toml::Value =
         toml::from_str(config_str).map_err(ConfigFileOptionsError::ErrorParsingToml)?;
 
+    config_options_from_toml(&config_toml)
+}
+
+fn get_bool(
+    config_toml: &toml::Value,
+    field: &'static str,
+) -> Result<Option<bool>, ConfigFileOptionsError> {
+    config_toml
+        .get(field)
+        .map(|v| v.as_bool().ok_or(ConfigFileOptionsError::InvalidField(field)))
+        .transpose()
+}
+
+fn get_strings(
+    config_toml: &toml::Value,
+    field: &'static str,
+) -> Result<Option<Vec<String>>, ConfigFileOptionsError> {
+    config_toml
+        .get(field)
+        .map(|v| {
+            v.as_array()
+                .ok_or(ConfigFileOptionsError::InvalidField(field))?
+                .iter()
+                .map(|item| match item.as_str() {
+                    Some(item) => Ok(item.to_owned()),
+                    None => Err(ConfigFileOptionsError::InvalidField(field)),
+                })
+                .collect()
+        })
+        .transpose()
+}
+
+fn get_parsed<T: FromStr>(
+    config_toml: &toml::Value,
+    field: &'static str,
+) -> Result<Option<T>, ConfigFileOptionsError> {
+    config_toml
+        .get(field)
+        .map(|v| {
+            v.as_str()
+                .and_then(|str| T::from_str(str).ok())
+                .ok_or(ConfigFileOptionsError::InvalidField(field))
+        })
+        .transpose()
+}
+
+fn translations_from_toml(
+    config_toml: &toml::Value,
+) -> Result<Option<Vec<Translation>>, ConfigFileOptionsError> {
+    let Some(translations) = config_toml.get("translations") else {
+        return Ok(None);
+    };
+
+    translations
+        .as_array()
+        .ok_or(ConfigFileOptionsError::InvalidField("translations"))?
+        .iter()
+        .map(|translation| {
+            let readme_path = translation
+                .get("readme-path")
+                .and_then(toml::Value::as_str)
+                .ok_or(ConfigFileOptionsError::InvalidField("translations.readme-path"))?;
+            let doc_source = translation
+                .get("doc-source")
+                .map(|v| {
+                    v.as_str()
+                        .ok_or(ConfigFileOptionsError::InvalidField("translations.doc-source"))
+                })
+                .transpose()?;
+            let code_blocks_only = get_bool(translation, "code-blocks-only")
+                .map_err(|_| ConfigFileOptionsError::InvalidField("translations.code-blocks-only"))?
+                .unwrap_or(false);
+            let shared_code_blocks = get_bool(translation, "shared-code-blocks")
+                .map_err(|_| {
+                    ConfigFileOptionsError::InvalidField("translations.shared-code-blocks")
+                })?
+                .unwrap_or(false);
+
+            Ok(Translation {
+                readme_path: PathBuf::from(readme_path),
+                doc_source: doc_source.map(PathBuf::from),
+                shared_code_blocks,
+                code_blocks_only,
+            })
+        })
+        .collect::<Result<Vec<Translation>, ConfigFileOptionsError>>()
+        .map(Some)
+}
+
+fn intralinks_config_from_toml(
+    config_toml: &toml::Value,
+) -> Result<Option<IntralinksConfig>, ConfigFileOptionsError> {
+    let intralinks_table = config_toml.get("intralinks").and_then(toml::Value::as_table);
+
+    let intralinks_docs_rs_base_url =
+        intralinks_table.and_then(|t| t.get("docs-rs-base-url")).and_then(toml::Value::as_str);
+    let intralinks_docs_rs_version =
+        intralinks_table.and_then(|t| t.get("docs-rs-version")).and_then(toml::Value::as_str);
+    let intralinks_strip_links =
+        intralinks_table.and_then(|t| t.get("strip-links")).and_then(toml::Value::as_bool);
+    let intralinks_docs_url_template =
+        intralinks_table.and_then(|t| t.get("docs-url-template")).and_then(toml::Value::as_str);
+    let intralinks_self_type =
+        intralinks_table.and_then(|t| t.get("self-type")).and_then(toml::Value::as_str);
+    let intralinks_docs_url_templates: BTreeMap<String, String> = intralinks_table
+        .and_then(|t| t.get("docs-url-templates"))
+        .map(|v| {
+            v.as_table()
+                .ok_or(ConfigFileOptionsError::InvalidField("intralinks.docs-url-templates"))?
+                .iter()
+                .map(|(crate_name, template)| match template.as_str() {
+                    Some(template) => Ok((crate_name.clone(), template.to_owned())),
+                    None => {
+                        Err(ConfigFileOptionsError::InvalidField("intralinks.docs-url-templates"))
+                    }
+                })
+                .collect()
+        })
+        .transpose()?
+        .unwrap_or_default();
+
+    let intralinks_ignore: Vec<String> = intralinks_table
+        .and_then(|t| t.get("ignore"))
+        .map(|v| {
+            v.as_array()
+                .ok_or(ConfigFileOptionsError::InvalidField("intralinks.ignore"))?
+                .iter()
+                .map(|pattern| match pattern.as_str() {
+                    Some(pattern) => Ok(pattern.to_owned()),
+                    None => Err(ConfigFileOptionsError::InvalidField("intralinks.ignore")),
+                })
+                .collect()
+        })
+        .transpose()?
+        .unwrap_or_default();
+
+    Ok(intralinks_table.map(|_| {
+        let mut intralinks = IntralinksConfig::default().with_ignore(intralinks_ignore);
+
+        intralinks.docs_rs.docs_rs_base_url = intralinks_docs_rs_base_url.map(ToOwned::to_owned);
+        intralinks.docs_rs.docs_rs_version = intralinks_docs_rs_version.map(ToOwned::to_owned);
+        intralinks.docs_rs.docs_url_template = intralinks_docs_url_template.map(ToOwned::to_owned);
+        intralinks.docs_rs.docs_url_templates = intralinks_docs_url_templates;
+        intralinks.strip_links = intralinks_strip_links;
+        intralinks.self_type = intralinks_self_type.map(ToOwned::to_owned);
+
+        intralinks
+    }))
+}
+
+#[allow(clippy::too_many_lines)]
+fn config_options_from_toml(
+    config_toml: &toml::Value,
+) -> Result<ConfigFileOptions, ConfigFileOptionsError> {
     let workspace_project =
         config_toml.get("workspace-project").and_then(toml::Value::as_str).map(ToOwned::to_owned);
 
@@ -241,24 +1518,31 @@ fn config_file_options_from_str(
         })
         .transpose()?;
 
+    let final_newline = get_parsed(config_toml, "final-newline")?;
+
     let entrypoint_table = config_toml.get("entrypoint").and_then(toml::Value::as_table);
 
     let entrypoint_type =
         entrypoint_table.and_then(|t| t.get("type")).and_then(toml::Value::as_str);
     let entrypoint_bin_name =
         entrypoint_table.and_then(|t| t.get("bin-name")).and_then(toml::Value::as_str);
+    let entrypoint_example_name =
+        entrypoint_table.and_then(|t| t.get("example-name")).and_then(toml::Value::as_str);
 
-    let entrypoint = match (entrypoint_type, entrypoint_bin_name) {
-        (Some("lib"), None) => Some(EntrypointOpt::Lib),
-        (Some("bin"), None) => Some(EntrypointOpt::BinDefault),
-        (Some("bin"), Some(name)) => Some(EntrypointOpt::BinName(name.to_owned())),
-        (None, None) => None,
+    let entrypoint = match (entrypoint_type, entrypoint_bin_name, entrypoint_example_name) {
+        (Some("lib"), None, None) => Some(EntrypointOpt::Lib),
+        (Some("bin"), None, None) => Some(EntrypointOpt::BinDefault),
+        (Some("bin"), Some(name), None) => Some(EntrypointOpt::BinName(name.to_owned())),
+        (Some("example"), None, Some(name)) => Some(EntrypointOpt::Example(name.to_owned())),
+        (None, None, None) => None,
         _ => return Err(ConfigFileOptionsError::InvalidEntrypointTable),
     };
 
     let readme_path =
         config_toml.get("readme-path").and_then(toml::Value::as_str).map(PathBuf::from);
 
+    let doc_source = config_toml.get("doc-source").and_then(toml::Value::as_str).map(PathBuf::from);
+
     let heading_base_level: Option<u8> = match config_toml
         .get("heading-base-level")
         .and_then(toml::Value::as_integer)
@@ -269,60 +1553,363 @@ fn config_file_options_from_str(
         Some(Err(_)) => return Err(ConfigFileOptionsError::InvalidField("heading-base-level")),
     };
 
-    let intralinks_table = config_toml.get("intralinks").and_then(toml::Value::as_table);
+    let heading = config_toml.get("heading").and_then(toml::Value::as_str).map(ToOwned::to_owned);
+    let cookbook = config_toml.get("cookbook").and_then(toml::Value::as_str).map(ToOwned::to_owned);
+    let readme_section =
+        config_toml.get("readme-section").and_then(toml::Value::as_str).map(ToOwned::to_owned);
 
-    let intralinks_docs_rs_base_url =
-        intralinks_table.and_then(|t| t.get("docs-rs-base-url")).and_then(toml::Value::as_str);
-    let intralinks_docs_rs_version =
-        intralinks_table.and_then(|t| t.get("docs-rs-version")).and_then(toml::Value::as_str);
-    let intralinks_strip_links =
-        intralinks_table.and_then(|t| t.get("strip-links")).and_then(toml::Value::as_bool);
+    let content_hash = get_bool(config_toml, "content-hash")?;
+    let merge_hand_edits = get_bool(config_toml, "merge-hand-edits")?;
+    let heading_numbering = get_bool(config_toml, "heading-numbering")?;
+    let format_tables = get_bool(config_toml, "format-tables")?;
+    let sort_reference_links = get_bool(config_toml, "sort-reference-links")?;
+    let strict = get_bool(config_toml, "strict")?;
+    let build_script = get_bool(config_toml, "build-script")?;
+    let emoji = get_parsed(config_toml, "emoji")?;
+    let details = get_parsed(config_toml, "details")?;
+    let mermaid = get_parsed(config_toml, "mermaid")?;
+    let math = get_parsed(config_toml, "math")?;
+    let blank_lines = get_parsed(config_toml, "blank-lines")?;
+    let setext_headings = get_parsed(config_toml, "setext-headings")?;
+    let list_indentation = get_parsed(config_toml, "list-indentation")?;
+    let hard_breaks = get_parsed(config_toml, "hard-breaks")?;
+    let link_style = get_parsed(config_toml, "link-style")?;
+    let usage_version = get_parsed(config_toml, "usage-version")?;
+    let usage_style = get_parsed(config_toml, "usage-style")?;
+    let usage_features = get_strings(config_toml, "usage-features")?;
+    let logo = get_bool(config_toml, "logo")?;
+    let verify_examples = get_bool(config_toml, "verify-examples")?;
+    let allow_missing_docs = get_bool(config_toml, "allow-missing-docs")?;
+    let features = get_strings(config_toml, "features")?;
+    let all_features = get_bool(config_toml, "all-features")?;
+    let no_default_features = get_bool(config_toml, "no-default-features")?;
+    let heading_case = get_parsed(config_toml, "heading-case")?;
+    let description_similarity: Option<u8> = match config_toml
+        .get("description-similarity")
+        .and_then(toml::Value::as_integer)
+        .map(TryInto::try_into)
+    {
+        None => None,
+        Some(Ok(s)) if s <= 100 => Some(s),
+        Some(_) => return Err(ConfigFileOptionsError::InvalidField("description-similarity")),
+    };
+    let translations = translations_from_toml(config_toml)?;
+    let disabled = get_bool(config_toml, "disabled")?;
 
-    let intralinks = intralinks_table.map(|_| IntralinksConfig {
-        docs_rs: IntralinksDocsRsConfig {
-            docs_rs_base_url: intralinks_docs_rs_base_url.map(ToOwned::to_owned),
-            docs_rs_version: intralinks_docs_rs_version.map(ToOwned::to_owned),
-        },
-        strip_links: intralinks_strip_links,
-    });
+    let intralinks = intralinks_config_from_toml(config_toml)?;
 
     Ok(ConfigFileOptions {
         line_terminator,
+        final_newline,
         workspace_project,
         entrypoint,
         readme_path,
+        doc_source,
         intralinks,
         heading_base_level,
+        heading,
+        cookbook,
+        readme_section,
+        content_hash,
+        merge_hand_edits,
+        heading_numbering,
+        format_tables,
+        sort_reference_links,
+        strict,
+        build_script,
+        emoji,
+        details,
+        mermaid,
+        math,
+        blank_lines,
+        setext_headings,
+        list_indentation,
+        hard_breaks,
+        link_style,
+        usage_version,
+        usage_style,
+        usage_features,
+        logo,
+        verify_examples,
+        allow_missing_docs,
+        features,
+        all_features,
+        no_default_features,
+        heading_case,
+        description_similarity,
+        translations,
+        disabled,
     })
 }
 
+fn workspace_config_options_from_toml(
+    workspace_metadata: &toml::Value,
+    package_name: &str,
+) -> Result<Option<ConfigFileOptions>, ConfigFileOptionsError> {
+    let Some(config_toml) = workspace_metadata.get("rdme") else {
+        return Ok(None);
+    };
+
+    if !config_toml.is_table() {
+        return Err(ConfigFileOptionsError::InvalidField("rdme"));
+    }
+
+    let default_options = config_options_from_toml(config_toml)?;
+
+    let package_options = config_toml
+        .get("packages")
+        .map(|packages| {
+            packages
+                .as_table()
+                .ok_or(ConfigFileOptionsError::InvalidField("packages"))
+                .map(|packages| packages.get(package_name))
+        })
+        .transpose()?
+        .flatten()
+        .map(|package_toml| match package_toml.is_table() {
+            true => config_options_from_toml(package_toml),
+            false => Err(ConfigFileOptionsError::InvalidField("packages")),
+        })
+        .transpose()?;
+
+    Ok(Some(match package_options {
+        Some(package_options) => package_options.or(default_options),
+        None => default_options,
+    }))
+}
+
+/// Reads the configuration in `[workspace.metadata.rdme]` of the workspace `project` belongs to.
+/// The configuration in that table is the default of every package of the workspace, and can be
+/// overridden per package in `[workspace.metadata.rdme.packages.<package-name>]`.
+///
+/// Relative paths are resolved from the directory of the project.
+pub fn workspace_config_options(
+    project: &Project,
+) -> Result<Option<ConfigFileOptions>, ConfigFileOptionsError> {
+    let Some(workspace_metadata) = project.get_workspace_metadata() else {
+        return Ok(None);
+    };
+
+    let options =
+        workspace_config_options_from_toml(workspace_metadata, project.get_package_name())
+            .map_err(|e| ConfigFileOptionsError::InvalidWorkspaceConfig(Box::new(e)))?;
+
+    Ok(options.map(|options| ConfigFileOptions {
+        readme_path: options.readme_path.map(|p| project.get_directory().join(p)),
+        doc_source: options.doc_source.map(|p| project.get_directory().join(p)),
+        translations: options.translations.map(|translations| {
+            translations
+                .into_iter()
+                .map(|translation| translation_relative_to(translation, project.get_directory()))
+                .collect()
+        }),
+        ..options
+    }))
+}
+
+/// Reads the configuration in `[package.metadata.rdme]` of `project`.
+///
+/// Relative paths are resolved from the directory of the project.
+pub fn package_config_options(
+    project: &Project,
+) -> Result<Option<ConfigFileOptions>, ConfigFileOptionsError> {
+    let Some(config_toml) = project.get_package_metadata().and_then(|m| m.get("rdme")) else {
+        return Ok(None);
+    };
+
+    let options = match config_toml.is_table() {
+        true => config_options_from_toml(config_toml),
+        false => Err(ConfigFileOptionsError::InvalidField("rdme")),
+    }
+    .map_err(|e| ConfigFileOptionsError::InvalidPackageConfig(Box::new(e)))?;
+
+    Ok(Some(ConfigFileOptions {
+        readme_path: options.readme_path.map(|p| project.get_directory().join(p)),
+        doc_source: options.doc_source.map(|p| project.get_directory().join(p)),
+        translations: options.translations.map(|translations| {
+            translations
+                .into_iter()
+                .map(|translation| translation_relative_to(translation, project.get_directory()))
+                .collect()
+        }),
+        ..options
+    }))
+}
+
+fn translation_relative_to(translation: Translation, dir: &Path) -> Translation {
+    Translation {
+        readme_path: dir.join(translation.readme_path),
+        doc_source: translation.doc_source.map(|p| dir.join(p)),
+        ..translation
+    }
+}
+
+/// The user configuration file: `cargo-rdme/config.toml` in `$XDG_CONFIG_HOME`, or in
+/// `~/.config` if that is not set.
+fn user_config_path() -> Option<PathBuf> {
+    let non_empty = |dir: std::ffi::OsString| Some(dir).filter(|dir| !dir.is_empty());
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .and_then(non_empty)
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME")
+                .and_then(non_empty)
+                .map(|home| Path::new(&home).join(".config"))
+        })?;
+
+    Some(config_home.join("cargo-rdme").join("config.toml"))
+}
+
+/// Reads the user configuration, with the defaults of the user for every project, e.g. the line
+/// terminator they prefer.
+pub fn user_config_options() -> Result<Option<ConfigFileOptions>, ConfigFileOptionsError> {
+    let Some(file_path) = user_config_path().filter(|path| path.is_file()) else {
+        return Ok(None);
+    };
+
+    std::fs::read_to_string(&file_path)
+        .map_err(|_| ConfigFileOptionsError::ErrorReadingConfigFile(file_path.clone()))
+        .and_then(|config_str| config_file_options_from_str(&config_str))
+        .map(Some)
+        .map_err(|e| ConfigFileOptionsError::InvalidUserConfig(file_path, Box::new(e)))
+}
+
+/// Prefix of the environment variables that set configuration options, e.g.
+/// `CARGO_RDME_LINE_TERMINATOR` for `line-terminator`.
+const ENV_PREFIX: &str = "CARGO_RDME_";
+
+/// The configuration set by environment variables, given as `(name, value)`.  Values are toml
+/// values, e.g. `true` or `["std", "serde"]`, or else strings, so that `lf` does not need quotes.
+fn env_config_toml(vars: impl Iterator<Item = (String, String)>) -> toml::Value {
+    let table: toml::Table = vars
+        .filter_map(|(name, value)| {
+            let key = name.strip_prefix(ENV_PREFIX)?.to_lowercase().replace('_', "-");
+            let value = toml::from_str::<toml::Table>(&format!("value = {value}"))
+                .ok()
+                .and_then(|mut table| table.remove("value"))
+                .unwrap_or(toml::Value::String(value));
+
+            Some((key, value))
+        })
+        .collect();
+
+    toml::Value::Table(table)
+}
+
+/// Reads the configuration set by `CARGO_RDME_*` environment variables.
+fn env_config_options() -> Result<Option<ConfigFileOptions>, ConfigFileOptionsError> {
+    let vars = std::env::vars_os()
+        .filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)));
+    let config_toml = env_config_toml(vars);
+
+    match config_toml.as_table().is_some_and(toml::Table::is_empty) {
+        true => Ok(None),
+        false => config_options_from_toml(&config_toml)
+            .map(Some)
+            .map_err(|e| ConfigFileOptionsError::InvalidEnvConfig(Box::new(e))),
+    }
+}
+
+/// Reads `.cargo-rdme.toml`, with its options overridden by the `CARGO_RDME_*` environment
+/// variables, unless `ignore_env` is set.
 pub fn config_file_options(
     current_dir: impl AsRef<Path>,
+    ignore_env: bool,
 ) -> Result<Option<ConfigFileOptions>, ConfigFileOptionsError> {
-    find_first_file_in_ancestors(current_dir, ".cargo-rdme.toml")
+    let file_options = find_first_file_in_ancestors(current_dir, ".cargo-rdme.toml")
         .map(|file_path| {
             let config_str = std::fs::read_to_string(&file_path)
                 .map_err(|_| ConfigFileOptionsError::ErrorReadingConfigFile(file_path))?;
 
             config_file_options_from_str(&config_str)
         })
-        .transpose()
+        .transpose()?;
+
+    let env_options = match ignore_env {
+        true => None,
+        false => env_config_options()?,
+    };
+
+    Ok(match (env_options, file_options) {
+        (Some(env_options), Some(file_options)) => Some(env_options.or(file_options)),
+        (env_options, file_options) => env_options.or(file_options),
+    })
 }
 
-#[derive(PartialEq, Eq, Debug)]
+#[derive(PartialEq, Eq, Debug, Clone)]
 pub struct Options {
     pub workspace_project: Option<String>,
     pub entrypoint: EntrypointOpt,
     pub line_terminator: LineTerminatorOpt,
+    pub final_newline: FinalNewlineOpt,
     pub check: bool,
+    /// Print the README instead of writing it.
+    pub stdout: bool,
     pub no_fail_on_warnings: bool,
     pub force: bool,
+    pub fix: bool,
     pub readme_path: Option<PathBuf>,
+    /// READMEs given on the command line after the first one, which are updated like it.
+    pub extra_readme_paths: Vec<PathBuf>,
+    pub doc_source: Option<PathBuf>,
+    pub manifest_path: Option<PathBuf>,
     pub intralinks: Option<IntralinksConfig>,
     pub heading_base_level: Option<u8>,
+    pub heading: Option<String>,
+    pub cookbook: Option<String>,
+    pub readme_section: Option<String>,
+    pub content_hash: bool,
+    pub merge_hand_edits: bool,
+    pub heading_numbering: bool,
+    pub format_tables: bool,
+    pub sort_reference_links: bool,
+    pub strict: bool,
+    pub deterministic: bool,
+    pub build_script: bool,
+    pub emoji: Option<EmojiOpt>,
+    pub details: Option<DetailsOpt>,
+    pub mermaid: Option<MermaidOpt>,
+    pub math: Option<MathOpt>,
+    pub blank_lines: Option<BlankLinesOpt>,
+    pub setext_headings: Option<SetextHeadingsOpt>,
+    pub list_indentation: Option<ListIndentationOpt>,
+    pub hard_breaks: Option<HardBreaksOpt>,
+    pub link_style: Option<LinkStyleOpt>,
+    pub usage_version: Option<UsageVersionOpt>,
+    pub usage_style: Option<UsageStyleOpt>,
+    pub usage_features: Option<Vec<String>>,
+    pub logo: bool,
+    pub verify_examples: bool,
+    pub allow_missing_docs: bool,
+    pub features: Option<Vec<String>>,
+    pub all_features: bool,
+    pub no_default_features: bool,
+    pub heading_case: Option<HeadingCaseOpt>,
+    pub description_similarity: Option<u8>,
+    pub translations: Vec<Translation>,
+    /// Only update the code blocks of the README, which is a translation.
+    pub code_blocks_only: bool,
+    pub freeze: bool,
+    /// Render the README to html, as the target would, instead of updating it.
+    pub preview: Option<PreviewTargetOpt>,
+    pub preview_no_open: bool,
+    pub disabled: bool,
 }
 
-#[allow(clippy::needless_pass_by_value)]
+impl Options {
+    /// The options of the injection of the documentation in the README.
+    pub fn inject_options(&self) -> InjectOptions {
+        let mut inject_options = InjectOptions::default();
+
+        inject_options.heading_base_level = self.heading_base_level;
+        inject_options.heading.clone_from(&self.heading);
+        inject_options.content_hash = self.content_hash;
+
+        inject_options
+    }
+}
+
+#[allow(clippy::needless_pass_by_value, clippy::too_many_lines)]
 pub fn merge_options(
     cmd_options: CmdOptions,
     config_file_options: Option<ConfigFileOptions>,
@@ -341,34 +1928,154 @@ pub fn merge_options(
             .line_terminator
             .or_else(|| config_file_options.as_ref().and_then(|c| c.line_terminator))
             .unwrap_or_default(),
+        final_newline: cmd_options
+            .final_newline
+            .or_else(|| config_file_options.as_ref().and_then(|c| c.final_newline))
+            .unwrap_or_default(),
         check: cmd_options.check,
+        stdout: cmd_options.stdout,
         no_fail_on_warnings: cmd_options.no_fail_on_warnings,
         force: cmd_options.force,
+        fix: cmd_options.fix,
         readme_path: cmd_options
-            .readme_path
+            .readme_paths
+            .first()
+            .cloned()
             .or_else(|| config_file_options.as_mut().and_then(|c| c.readme_path.take())),
-        intralinks: Some(IntralinksConfig {
-            docs_rs: IntralinksDocsRsConfig {
-                docs_rs_base_url: config_file_options
-                    .as_mut()
-                    .and_then(|c| c.intralinks.as_mut())
-                    .and_then(|il| il.docs_rs.docs_rs_base_url.take()),
-                docs_rs_version: config_file_options
-                    .as_mut()
-                    .and_then(|c| c.intralinks.as_mut())
-                    .and_then(|il| il.docs_rs.docs_rs_version.take()),
-            },
-            strip_links: match cmd_options.intralinks_strip_links {
-                true => Some(true),
-                false => config_file_options
-                    .as_ref()
-                    .and_then(|c| c.intralinks.as_ref())
-                    .and_then(|il| il.strip_links),
-            },
+        extra_readme_paths: cmd_options.readme_paths.iter().skip(1).cloned().collect(),
+        doc_source: cmd_options
+            .doc_source
+            .or_else(|| config_file_options.as_mut().and_then(|c| c.doc_source.take())),
+        manifest_path: cmd_options.manifest_path,
+        intralinks: Some({
+            let intralinks =
+                config_file_options.as_mut().and_then(|c| c.intralinks.take()).unwrap_or_default();
+
+            match cmd_options.intralinks_strip_links {
+                true => intralinks.with_strip_links(true),
+                false => intralinks,
+            }
         }),
         heading_base_level: cmd_options
             .heading_base_level
-            .or_else(|| config_file_options.and_then(|c| c.heading_base_level)),
+            .or_else(|| config_file_options.as_ref().and_then(|c| c.heading_base_level)),
+        heading: cmd_options
+            .heading
+            .or_else(|| config_file_options.as_mut().and_then(|c| c.heading.take())),
+        cookbook: cmd_options
+            .cookbook
+            .or_else(|| config_file_options.as_mut().and_then(|c| c.cookbook.take())),
+        readme_section: cmd_options
+            .readme_section
+            .or_else(|| config_file_options.as_mut().and_then(|c| c.readme_section.take())),
+        content_hash: match cmd_options.content_hash {
+            true => true,
+            false => config_file_options.as_ref().and_then(|c| c.content_hash).unwrap_or(false),
+        },
+        merge_hand_edits: match cmd_options.merge_hand_edits {
+            true => true,
+            false => config_file_options.as_ref().and_then(|c| c.merge_hand_edits).unwrap_or(false),
+        },
+        heading_numbering: match cmd_options.heading_numbering {
+            true => true,
+            false => {
+                config_file_options.as_ref().and_then(|c| c.heading_numbering).unwrap_or(false)
+            }
+        },
+        format_tables: match cmd_options.format_tables {
+            true => true,
+            false => config_file_options.as_ref().and_then(|c| c.format_tables).unwrap_or(false),
+        },
+        sort_reference_links: match cmd_options.sort_reference_links {
+            true => true,
+            false => {
+                config_file_options.as_ref().and_then(|c| c.sort_reference_links).unwrap_or(false)
+            }
+        },
+        strict: match cmd_options.strict {
+            true => true,
+            false => config_file_options.as_ref().and_then(|c| c.strict).unwrap_or(false),
+        },
+        deterministic: cmd_options.deterministic,
+        build_script: match cmd_options.build_script {
+            true => true,
+            false => config_file_options.as_ref().and_then(|c| c.build_script).unwrap_or(false),
+        },
+        emoji: cmd_options.emoji.or_else(|| config_file_options.as_ref().and_then(|c| c.emoji)),
+        details: cmd_options
+            .details
+            .or_else(|| config_file_options.as_ref().and_then(|c| c.details)),
+        mermaid: cmd_options
+            .mermaid
+            .or_else(|| config_file_options.as_ref().and_then(|c| c.mermaid)),
+        math: cmd_options.math.or_else(|| config_file_options.as_ref().and_then(|c| c.math)),
+        blank_lines: cmd_options
+            .blank_lines
+            .or_else(|| config_file_options.as_ref().and_then(|c| c.blank_lines)),
+        setext_headings: cmd_options
+            .setext_headings
+            .or_else(|| config_file_options.as_ref().and_then(|c| c.setext_headings)),
+        list_indentation: cmd_options
+            .list_indentation
+            .or_else(|| config_file_options.as_ref().and_then(|c| c.list_indentation)),
+        hard_breaks: cmd_options
+            .hard_breaks
+            .or_else(|| config_file_options.as_ref().and_then(|c| c.hard_breaks)),
+        link_style: cmd_options
+            .link_style
+            .or_else(|| config_file_options.as_ref().and_then(|c| c.link_style)),
+        usage_version: cmd_options
+            .usage_version
+            .or_else(|| config_file_options.as_ref().and_then(|c| c.usage_version)),
+        usage_style: cmd_options
+            .usage_style
+            .or_else(|| config_file_options.as_ref().and_then(|c| c.usage_style)),
+        usage_features: cmd_options
+            .usage_features
+            .or_else(|| config_file_options.as_ref().and_then(|c| c.usage_features.clone())),
+        logo: match cmd_options.logo {
+            true => true,
+            false => config_file_options.as_ref().and_then(|c| c.logo).unwrap_or(false),
+        },
+        verify_examples: match cmd_options.verify_examples {
+            true => true,
+            false => config_file_options.as_ref().and_then(|c| c.verify_examples).unwrap_or(false),
+        },
+        allow_missing_docs: match cmd_options.allow_missing_docs {
+            true => true,
+            false => {
+                config_file_options.as_ref().and_then(|c| c.allow_missing_docs).unwrap_or(false)
+            }
+        },
+        features: cmd_options
+            .features
+            .or_else(|| config_file_options.as_ref().and_then(|c| c.features.clone())),
+        all_features: match cmd_options.all_features {
+            true => true,
+            false => config_file_options.as_ref().and_then(|c| c.all_features).unwrap_or(false),
+        },
+        no_default_features: match cmd_options.no_default_features {
+            true => true,
+            false => {
+                config_file_options.as_ref().and_then(|c| c.no_default_features).unwrap_or(false)
+            }
+        },
+        heading_case: cmd_options
+            .heading_case
+            .or_else(|| config_file_options.as_ref().and_then(|c| c.heading_case)),
+        description_similarity: cmd_options
+            .description_similarity
+            .or_else(|| config_file_options.as_ref().and_then(|c| c.description_similarity)),
+        translations: config_file_options
+            .as_mut()
+            .and_then(|c| c.translations.take())
+            .unwrap_or_default(),
+        code_blocks_only: false,
+        freeze: cmd_options.freeze,
+        preview: cmd_options.preview,
+        // When serving the preview, the browser opens the server instead of the files.
+        preview_no_open: cmd_options.preview_no_open || cmd_options.preview_serve.is_some(),
+        disabled: config_file_options.as_ref().and_then(|c| c.disabled).unwrap_or(false),
     }
 }
 
@@ -380,12 +2087,46 @@ mod tests {
     use pretty_assertions::assert_eq;
 
     #[test]
+    #[allow(clippy::too_many_lines)]
     fn test_config_file_options_from_str() {
         let str = indoc! { r#"
             readme-path = "ReAdMe.md"
+            doc-source = "src/docs.rs"
             workspace-project = "myproj"
             line-terminator = "crlf"
+            final-newline = "always"
             heading-base-level = 3
+            heading = "Documentation"
+            cookbook = "cookbook"
+            readme-section = "Documentation"
+            content-hash = true
+            merge-hand-edits = true
+            heading-numbering = true
+            format-tables = true
+            sort-reference-links = true
+            strict = true
+            build-script = true
+            emoji = "unicode"
+            details = "unwrap"
+            mermaid = "image"
+            math = "code"
+            blank-lines = "keep"
+            setext-headings = "atx"
+            list-indentation = "normalize"
+            hard-breaks = "html"
+            link-style = "reference"
+            usage-version = "exact"
+            usage-style = "both"
+            usage-features = ["serde"]
+            logo = true
+            verify-examples = true
+            allow-missing-docs = true
+            features = ["std"]
+            all-features = false
+            no-default-features = true
+            heading-case = "sentence"
+            description-similarity = 50
+            disabled = false
 
             [entrypoint]
             type = "bin"
@@ -395,6 +2136,21 @@ mod tests {
             docs-rs-base-url = "https://internaldocs.rs"
             docs-rs-version = "1.0.0"
             strip-links = true
+            docs-url-template = "https://docs.internal/{crate}/{version}/{path}"
+            self-type = "Parser"
+            ignore = ["__private::*", "Internal"]
+
+            [intralinks.docs-url-templates]
+            std = "https://std.internal/{path}"
+
+            [[translations]]
+            readme-path = "README.zh-CN.md"
+            doc-source = "docs/lib.zh-CN.md"
+            shared-code-blocks = true
+
+            [[translations]]
+            readme-path = "README.pt.md"
+            code-blocks-only = true
             "#
         };
 
@@ -404,46 +2160,268 @@ mod tests {
             workspace_project: Some("myproj".to_owned()),
             entrypoint: Some(EntrypointOpt::BinName("baz".to_owned())),
             line_terminator: Some(LineTerminatorOpt::CrLf),
+            final_newline: Some(FinalNewlineOpt::Always),
             readme_path: Some(PathBuf::from("ReAdMe.md")),
-            intralinks: Some(IntralinksConfig {
-                docs_rs: IntralinksDocsRsConfig {
-                    docs_rs_base_url: Some("https://internaldocs.rs".to_owned()),
-                    docs_rs_version: Some("1.0.0".to_owned()),
-                },
-                strip_links: Some(true),
-            }),
+            doc_source: Some(PathBuf::from("src/docs.rs")),
+            intralinks: Some(
+                IntralinksConfig::default()
+                    .with_docs_rs(
+                        IntralinksDocsRsConfig::default()
+                            .with_docs_rs_base_url("https://internaldocs.rs")
+                            .with_docs_rs_version("1.0.0")
+                            .with_docs_url_template(
+                                "https://docs.internal/{crate}/{version}/{path}",
+                            )
+                            .with_crate_docs_url_template("std", "https://std.internal/{path}"),
+                    )
+                    .with_strip_links(true)
+                    .with_self_type("Parser")
+                    .with_ignore(vec!["__private::*".to_owned(), "Internal".to_owned()]),
+            ),
             heading_base_level: Some(3),
+            heading: Some("Documentation".to_owned()),
+            cookbook: Some("cookbook".to_owned()),
+            readme_section: Some("Documentation".to_owned()),
+            content_hash: Some(true),
+            merge_hand_edits: Some(true),
+            heading_numbering: Some(true),
+            format_tables: Some(true),
+            sort_reference_links: Some(true),
+            strict: Some(true),
+            build_script: Some(true),
+            emoji: Some(EmojiOpt::Unicode),
+            details: Some(DetailsOpt::Unwrap),
+            mermaid: Some(MermaidOpt::Image),
+            math: Some(MathOpt::Code),
+            blank_lines: Some(BlankLinesOpt::Keep),
+            setext_headings: Some(SetextHeadingsOpt::Atx),
+            list_indentation: Some(ListIndentationOpt::Normalize),
+            hard_breaks: Some(HardBreaksOpt::Html),
+            link_style: Some(LinkStyleOpt::Reference),
+            usage_version: Some(UsageVersionOpt::Exact),
+            usage_style: Some(UsageStyleOpt::Both),
+            usage_features: Some(vec!["serde".to_owned()]),
+            logo: Some(true),
+            verify_examples: Some(true),
+            allow_missing_docs: Some(true),
+            features: Some(vec!["std".to_owned()]),
+            all_features: Some(false),
+            no_default_features: Some(true),
+            heading_case: Some(HeadingCaseOpt::Sentence),
+            description_similarity: Some(50),
+            translations: Some(vec![
+                Translation {
+                    readme_path: PathBuf::from("README.zh-CN.md"),
+                    doc_source: Some(PathBuf::from("docs/lib.zh-CN.md")),
+                    shared_code_blocks: true,
+                    code_blocks_only: false,
+                },
+                Translation {
+                    readme_path: PathBuf::from("README.pt.md"),
+                    doc_source: None,
+                    shared_code_blocks: false,
+                    code_blocks_only: true,
+                },
+            ]),
+            disabled: Some(false),
         };
 
         assert_eq!(config_file_opts, expected);
     }
 
     #[test]
+    fn test_config_schema_has_every_field() {
+        let fields = [
+            "readme-path",
+            "doc-source",
+            "workspace-project",
+            "line-terminator",
+            "final-newline",
+            "heading-base-level",
+            "heading",
+            "cookbook",
+            "readme-section",
+            "content-hash",
+            "merge-hand-edits",
+            "heading-numbering",
+            "format-tables",
+            "sort-reference-links",
+            "strict",
+            "build-script",
+            "emoji",
+            "details",
+            "mermaid",
+            "math",
+            "blank-lines",
+            "setext-headings",
+            "list-indentation",
+            "hard-breaks",
+            "link-style",
+            "usage-version",
+            "usage-style",
+            "usage-features",
+            "logo",
+            "verify-examples",
+            "allow-missing-docs",
+            "features",
+            "all-features",
+            "no-default-features",
+            "heading-case",
+            "description-similarity",
+            "translations",
+            "shared-code-blocks",
+            "code-blocks-only",
+            "disabled",
+            "entrypoint",
+            "type",
+            "bin-name",
+            "example-name",
+            "intralinks",
+            "docs-rs-base-url",
+            "docs-rs-version",
+            "strip-links",
+            "docs-url-template",
+            "docs-url-templates",
+            "ignore",
+            "packages",
+        ];
+
+        for field in fields {
+            assert!(CONFIG_SCHEMA.contains(&format!("\"{field}\": {{")), "missing field {field}");
+        }
+    }
+
+    #[test]
+    #[allow(clippy::too_many_lines)]
     fn test_merge_cmd_wins_over_config_file() {
         let cmd_options = CmdOptions {
+            workspace: false,
             workspace_project: Some("myproj".to_owned()),
+            package_dirs: Vec::new(),
+            package_dir_list: None,
+            jobs: None,
+            progress: false,
+            cargo_flags: CargoFlags::default(),
             entrypoint: Some(EntrypointOpt::BinDefault),
             line_terminator: Some(LineTerminatorOpt::CrLf),
+            final_newline: Some(FinalNewlineOpt::Never),
             check: true,
+            stdout: false,
             no_fail_on_warnings: true,
             intralinks_strip_links: true,
             force: true,
-            readme_path: Some(PathBuf::from("rEaDmE.md")),
+            fix: true,
+            readme_paths: vec![PathBuf::from("rEaDmE.md"), PathBuf::from("docs/PACKAGING.md")],
+            doc_source: Some(PathBuf::from("src/dOcS.rs")),
+            manifest_path: Some(PathBuf::from("proj/Cargo.toml")),
             heading_base_level: Some(4),
+            heading: Some("Docs".to_owned()),
+            cookbook: Some("recipes".to_owned()),
+            readme_section: Some("Docs".to_owned()),
+            content_hash: true,
+            merge_hand_edits: false,
+            heading_numbering: false,
+            format_tables: false,
+            sort_reference_links: false,
+            strict: false,
+            deterministic: true,
+            build_script: false,
+            emoji: None,
+            details: None,
+            mermaid: None,
+            math: None,
+            blank_lines: None,
+            setext_headings: None,
+            list_indentation: None,
+            hard_breaks: None,
+            link_style: None,
+            usage_version: None,
+            usage_style: None,
+            usage_features: None,
+            logo: true,
+            verify_examples: false,
+            allow_missing_docs: false,
+            features: None,
+            all_features: false,
+            no_default_features: false,
+            heading_case: None,
+            description_similarity: None,
+            freeze: false,
+            preview: None,
+            preview_no_open: false,
+            preview_serve: None,
+            config_schema: false,
+            explain: false,
+            explain_code: None,
+            selftest: false,
+            hook_config: None,
+            stats: false,
+            undo: false,
+            stats_json: false,
+            timings: false,
+            summary_path: None,
+            report_path: None,
+            #[cfg(feature = "mdbook")]
+            mdbook: false,
+            #[cfg(feature = "mdbook")]
+            mdbook_supports: None,
         };
         let config_file_options = ConfigFileOptions {
             workspace_project: Some("aproj".to_owned()),
             entrypoint: Some(EntrypointOpt::Lib),
             line_terminator: Some(LineTerminatorOpt::Lf),
+            final_newline: Some(FinalNewlineOpt::Always),
             readme_path: Some(PathBuf::from("ReAdMe.md")),
-            intralinks: Some(IntralinksConfig {
-                docs_rs: IntralinksDocsRsConfig {
-                    docs_rs_base_url: Some("https://internaldocs.rs".to_owned()),
-                    docs_rs_version: Some("1.0.0".to_owned()),
-                },
-                strip_links: Some(false),
-            }),
+            doc_source: Some(PathBuf::from("src/docs.rs")),
+            intralinks: Some(
+                IntralinksConfig::default()
+                    .with_docs_rs(
+                        IntralinksDocsRsConfig::default()
+                            .with_docs_rs_base_url("https://internaldocs.rs")
+                            .with_docs_rs_version("1.0.0")
+                            .with_docs_url_template("https://docs.internal/{path}"),
+                    )
+                    .with_strip_links(false)
+                    .with_ignore(vec!["__private::*".to_owned()]),
+            ),
             heading_base_level: Some(3),
+            heading: Some("Documentation".to_owned()),
+            cookbook: Some("cookbook".to_owned()),
+            readme_section: Some("Documentation".to_owned()),
+            content_hash: Some(false),
+            merge_hand_edits: Some(true),
+            heading_numbering: Some(true),
+            format_tables: Some(true),
+            sort_reference_links: Some(true),
+            strict: Some(true),
+            build_script: Some(true),
+            emoji: Some(EmojiOpt::Unicode),
+            details: Some(DetailsOpt::Unwrap),
+            mermaid: Some(MermaidOpt::Image),
+            math: Some(MathOpt::Code),
+            blank_lines: Some(BlankLinesOpt::Keep),
+            setext_headings: Some(SetextHeadingsOpt::Atx),
+            list_indentation: Some(ListIndentationOpt::Normalize),
+            hard_breaks: Some(HardBreaksOpt::Html),
+            link_style: Some(LinkStyleOpt::Reference),
+            usage_version: Some(UsageVersionOpt::Exact),
+            usage_style: Some(UsageStyleOpt::Both),
+            usage_features: Some(vec!["serde".to_owned()]),
+            logo: None,
+            verify_examples: None,
+            allow_missing_docs: None,
+            features: Some(vec!["std".to_owned()]),
+            all_features: Some(false),
+            no_default_features: Some(true),
+            heading_case: Some(HeadingCaseOpt::Sentence),
+            description_similarity: Some(50),
+            translations: Some(vec![Translation {
+                readme_path: PathBuf::from("README.pt.md"),
+                doc_source: None,
+                shared_code_blocks: false,
+                code_blocks_only: false,
+            }]),
+            disabled: None,
         };
 
         let options = merge_options(cmd_options, Some(config_file_options));
@@ -452,20 +2430,208 @@ mod tests {
             workspace_project: Some("myproj".to_owned()),
             entrypoint: EntrypointOpt::BinDefault,
             line_terminator: LineTerminatorOpt::CrLf,
+            final_newline: FinalNewlineOpt::Never,
             check: true,
+            stdout: false,
             no_fail_on_warnings: true,
             force: true,
+            fix: true,
             readme_path: Some(PathBuf::from("rEaDmE.md")),
-            intralinks: Some(IntralinksConfig {
-                docs_rs: IntralinksDocsRsConfig {
-                    docs_rs_base_url: Some("https://internaldocs.rs".to_owned()),
-                    docs_rs_version: Some("1.0.0".to_owned()),
-                },
-                strip_links: Some(true),
-            }),
+            extra_readme_paths: vec![PathBuf::from("docs/PACKAGING.md")],
+            doc_source: Some(PathBuf::from("src/dOcS.rs")),
+            manifest_path: Some(PathBuf::from("proj/Cargo.toml")),
+            intralinks: Some(
+                IntralinksConfig::default()
+                    .with_docs_rs(
+                        IntralinksDocsRsConfig::default()
+                            .with_docs_rs_base_url("https://internaldocs.rs")
+                            .with_docs_rs_version("1.0.0")
+                            .with_docs_url_template("https://docs.internal/{path}"),
+                    )
+                    .with_strip_links(true)
+                    .with_ignore(vec!["__private::*".to_owned()]),
+            ),
             heading_base_level: Some(4),
+            heading: Some("Docs".to_owned()),
+            cookbook: Some("recipes".to_owned()),
+            readme_section: Some("Docs".to_owned()),
+            content_hash: true,
+            merge_hand_edits: true,
+            heading_numbering: true,
+            format_tables: true,
+            sort_reference_links: true,
+            strict: true,
+            deterministic: true,
+            build_script: true,
+            emoji: Some(EmojiOpt::Unicode),
+            details: Some(DetailsOpt::Unwrap),
+            mermaid: Some(MermaidOpt::Image),
+            math: Some(MathOpt::Code),
+            blank_lines: Some(BlankLinesOpt::Keep),
+            setext_headings: Some(SetextHeadingsOpt::Atx),
+            list_indentation: Some(ListIndentationOpt::Normalize),
+            hard_breaks: Some(HardBreaksOpt::Html),
+            link_style: Some(LinkStyleOpt::Reference),
+            usage_version: Some(UsageVersionOpt::Exact),
+            usage_style: Some(UsageStyleOpt::Both),
+            usage_features: Some(vec!["serde".to_owned()]),
+            logo: true,
+            verify_examples: false,
+            allow_missing_docs: false,
+            features: Some(vec!["std".to_owned()]),
+            all_features: false,
+            no_default_features: true,
+            heading_case: Some(HeadingCaseOpt::Sentence),
+            description_similarity: Some(50),
+            translations: vec![Translation {
+                readme_path: PathBuf::from("README.pt.md"),
+                doc_source: None,
+                shared_code_blocks: false,
+                code_blocks_only: false,
+            }],
+            code_blocks_only: false,
+            freeze: false,
+            preview: None,
+            preview_no_open: false,
+            disabled: false,
         };
 
         assert_eq!(options, expected);
     }
+
+    #[test]
+    fn test_env_config_toml() {
+        let vars = [
+            ("CARGO_RDME_LINE_TERMINATOR", "crlf"),
+            ("CARGO_RDME_HEADING_BASE_LEVEL", "3"),
+            ("CARGO_RDME_FEATURES", r#"["std", "serde"]"#),
+            ("CARGO_RDME_HEADING", r#""1984""#),
+            ("CARGO_HOME", "/home/user/.cargo"),
+        ];
+        let expected = indoc! { r#"
+            line-terminator = "crlf"
+            heading-base-level = 3
+            features = ["std", "serde"]
+            heading = "1984"
+            "#
+        };
+
+        assert_eq!(
+            env_config_toml(
+                vars.into_iter().map(|(name, value)| (name.to_owned(), value.to_owned()))
+            ),
+            toml::from_str::<toml::Value>(expected).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_workspace_config_options_from_toml() {
+        let str = indoc! { r#"
+            [rdme]
+            heading = "Documentation"
+            line-terminator = "lf"
+
+            [rdme.intralinks]
+            docs-rs-version = "1.0.0"
+
+            [rdme.packages.foo]
+            readme-path = "../README.md"
+            line-terminator = "crlf"
+
+            [rdme.packages.foo.entrypoint]
+            type = "bin"
+
+            [rdme.packages.foo.intralinks]
+            strip-links = true
+
+            [rdme.packages.bar]
+            disabled = true
+            "#
+        };
+        let workspace_metadata: toml::Value = toml::from_str(str).unwrap();
+
+        let foo_options = workspace_config_options_from_toml(&workspace_metadata, "foo").unwrap();
+
+        let expected = ConfigFileOptions {
+            workspace_project: None,
+            entrypoint: Some(EntrypointOpt::BinDefault),
+            line_terminator: Some(LineTerminatorOpt::CrLf),
+            final_newline: None,
+            readme_path: Some(PathBuf::from("../README.md")),
+            doc_source: None,
+            intralinks: Some(
+                IntralinksConfig::default()
+                    .with_docs_rs(IntralinksDocsRsConfig::default().with_docs_rs_version("1.0.0"))
+                    .with_strip_links(true),
+            ),
+            heading_base_level: None,
+            heading: Some("Documentation".to_owned()),
+            cookbook: None,
+            readme_section: None,
+            content_hash: None,
+            merge_hand_edits: None,
+            heading_numbering: None,
+            format_tables: None,
+            sort_reference_links: None,
+            strict: None,
+            build_script: None,
+            emoji: None,
+            details: None,
+            mermaid: None,
+            math: None,
+            blank_lines: None,
+            setext_headings: None,
+            list_indentation: None,
+            hard_breaks: None,
+            link_style: None,
+            usage_version: None,
+            usage_style: None,
+            usage_features: None,
+            logo: None,
+            verify_examples: None,
+            allow_missing_docs: None,
+            features: None,
+            all_features: None,
+            no_default_features: None,
+            heading_case: None,
+            description_similarity: None,
+            translations: None,
+            disabled: None,
+        };
+
+        assert_eq!(foo_options, Some(expected));
+
+        let bar_options = workspace_config_options_from_toml(&workspace_metadata, "bar").unwrap();
+
+        assert_eq!(bar_options.and_then(|o| o.disabled), Some(true));
+
+        let baz_options = workspace_config_options_from_toml(&workspace_metadata, "baz").unwrap();
+
+        assert_eq!(baz_options.and_then(|o| o.line_terminator), Some(LineTerminatorOpt::Lf));
+
+        let no_rdme: toml::Value = toml::from_str("[other-tool]").unwrap();
+
+        assert_eq!(workspace_config_options_from_toml(&no_rdme, "foo").unwrap(), None);
+    }
+
+    #[test]
+    fn test_invocation_style() {
+        let args = |args: &[&str]| -> Vec<OsString> { args.iter().map(OsString::from).collect() };
+
+        assert_eq!(
+            InvocationStyle::detect(&args(&["cargo-rdme", "rdme", "--check"])),
+            InvocationStyle::CargoSubcommand
+        );
+        assert_eq!(
+            InvocationStyle::detect(&args(&["rdme", "--check"])),
+            InvocationStyle::Standalone
+        );
+        assert_eq!(InvocationStyle::detect(&args(&["cargo-rdme"])), InvocationStyle::Standalone);
+
+        assert_eq!(
+            normalize_cmd_args(args(&["cargo-rdme", "rdme", "--check"])),
+            args(&["cargo-rdme", "--check"])
+        );
+        assert_eq!(normalize_cmd_args(args(&["rdme", "--check"])), args(&["rdme", "--check"]));
+    }
 }
@@ -0,0 +1,108 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::console::Progress;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Calls `process` on every item, with up to `jobs` items processed in parallel.  What `process`
+/// prints is held back, so that the output, as well as the calls to `done`, follow the order of the
+/// items regardless of how they were scheduled.  This keeps the output comparable between runs.
+/// The progress is updated as items finish, and kept below the output.
+pub fn for_each_parallel<T: Sync, R: Send>(
+    items: &[T],
+    jobs: usize,
+    progress: &Progress,
+    process: impl Fn(&T) -> R + Sync,
+    mut done: impl FnMut(&T, R),
+) {
+    let next_item = AtomicUsize::new(0);
+    let (sender, receiver) = std::sync::mpsc::channel();
+
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, items.len().max(1)) {
+            let sender = sender.clone();
+            let next_item = &next_item;
+            let process = &process;
+
+            scope.spawn(move || loop {
+                let i = next_item.fetch_add(1, Ordering::Relaxed);
+                let Some(item) = items.get(i) else {
+                    break;
+                };
+                let (result, output) = crate::console::capture(|| process(item));
+
+                if sender.send((i, result, output)).is_err() {
+                    break;
+                }
+            });
+        }
+
+        drop(sender);
+
+        // Results that arrived before the ones of the items preceding them.
+        let mut pending = BTreeMap::new();
+        let mut next_done = 0;
+
+        progress.show(0);
+
+        for (processed, (i, result, output)) in receiver.into_iter().enumerate() {
+            pending.insert(i, (result, output));
+            progress.clear();
+
+            while let Some((result, output)) = pending.remove(&next_done) {
+                output.print();
+                done(&items[next_done], result);
+                next_done += 1;
+            }
+
+            progress.show(processed + 1);
+        }
+
+        progress.clear();
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::time::Duration;
+
+    #[test]
+    fn test_for_each_parallel_order() {
+        let items: Vec<u64> = (0..16).collect();
+        let mut done = Vec::new();
+
+        // Earlier items take longer, so they finish after the later ones.
+        for_each_parallel(
+            &items,
+            4,
+            &Progress::new("items", items.len(), false),
+            |&i| {
+                std::thread::sleep(Duration::from_millis(16 - i));
+                i * 2
+            },
+            |&i, result| done.push((i, result)),
+        );
+
+        assert_eq!(done, items.iter().map(|&i| (i, i * 2)).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_for_each_parallel_no_items() {
+        let mut done = 0;
+
+        for_each_parallel(
+            &[] as &[()],
+            4,
+            &Progress::new("items", 0, false),
+            |()| (),
+            |(), ()| done += 1,
+        );
+
+        assert_eq!(done, 0);
+    }
+}
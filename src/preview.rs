@@ -0,0 +1,431 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+//! Renders the README as crates.io or GitHub would show it, to preview it before publishing.
+//!
+//! Like those sites, only GitHub flavored markdown is rendered (no math, no heading attributes), the
+//! html is sanitized to an allowlist of tags and attributes, and relative links and images point
+//! to the repository of the crate.
+
+use crate::Readme;
+use std::fmt::Write;
+
+/// Tags kept by the sanitizer.  Every other tag is removed, but not its content.
+const ALLOWED_TAGS: &str = "a abbr b blockquote br code dd del div dl dt em h1 h2 h3 h4 h5 h6 hr i \
+    img ins kbd li ol p picture pre q s samp source span strike strong sub sup table tbody td tfoot th \
+    thead tr tt ul var";
+
+/// Tags kept by the sanitizer for GitHub only.  crates.io does not support them.
+const GITHUB_ALLOWED_TAGS: &str = "details summary";
+
+/// Tags removed along with their content.
+const DROPPED_TAGS: &[&str] =
+    &["script", "style", "iframe", "object", "embed", "template", "textarea", "noscript"];
+
+/// Attributes kept by the sanitizer, in the allowed tags.
+const ALLOWED_ATTRIBUTES: &[&str] = &[
+    "align", "alt", "colspan", "height", "href", "media", "rowspan", "src", "srcset", "start",
+    "title", "type", "width",
+];
+
+/// Where the README is previewed, which decides how it is rendered.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[non_exhaustive]
+pub enum PreviewTarget {
+    CratesIo,
+    /// GitHub also renders alerts (`> [!NOTE]`) and collapsible `<details>` blocks.
+    GitHub,
+}
+
+impl PreviewTarget {
+    fn name(self) -> &'static str {
+        match self {
+            PreviewTarget::CratesIo => "crates.io",
+            PreviewTarget::GitHub => "GitHub",
+        }
+    }
+
+    fn markdown_options(self) -> pulldown_cmark::Options {
+        use pulldown_cmark::Options;
+
+        let options = Options::ENABLE_TABLES
+            | Options::ENABLE_STRIKETHROUGH
+            | Options::ENABLE_TASKLISTS
+            | Options::ENABLE_FOOTNOTES;
+
+        match self {
+            PreviewTarget::CratesIo => options,
+            PreviewTarget::GitHub => options | Options::ENABLE_GFM,
+        }
+    }
+
+    fn allows_tag(self, name: &str) -> bool {
+        let github_only = match self {
+            PreviewTarget::CratesIo => "",
+            PreviewTarget::GitHub => GITHUB_ALLOWED_TAGS,
+        };
+
+        ALLOWED_TAGS.split_whitespace().chain(github_only.split_whitespace()).any(|tag| tag == name)
+    }
+
+    fn style(self) -> &'static str {
+        match self {
+            PreviewTarget::CratesIo => indoc::indoc! { r#"
+                body { margin: 0; background: #f9f7ec; color: #383838; font-family: "Fira Sans", Helvetica, Arial, sans-serif; line-height: 1.5; }
+                main { max-width: 760px; margin: 2em auto; padding: 1.5em 2em; background: #fff; border-radius: 5px; }
+                a { color: #007c6a; }
+                pre { background: #f6f8fa; padding: 1em; overflow: auto; border-radius: 5px; }
+                code { font-family: "Source Code Pro", Menlo, Consolas, monospace; font-size: 0.9em; }
+                "#
+            },
+            PreviewTarget::GitHub => indoc::indoc! { r#"
+                body { margin: 0; background: #fff; color: #1f2328; font-family: -apple-system, "Segoe UI", Helvetica, Arial, sans-serif; line-height: 1.5; }
+                main { max-width: 830px; margin: 2em auto; padding: 2em; border: 1px solid #d1d9e0; border-radius: 6px; }
+                a { color: #0969da; }
+                h1, h2 { padding-bottom: 0.3em; border-bottom: 1px solid #d1d9e0; }
+                pre { background: #f6f8fa; padding: 1em; overflow: auto; border-radius: 6px; }
+                code { font-family: ui-monospace, SFMono-Regular, Menlo, Consolas, monospace; font-size: 0.85em; }
+                blockquote[class^="markdown-alert"] { color: inherit; border-left-color: #0969da; }
+                "#
+            },
+        }
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Resolves a relative url against the repository, as crates.io does: links go to the file in the
+/// repository, and images to its raw content.
+fn resolve_url(url: &str, repository: Option<&str>, is_image: bool) -> String {
+    let is_relative = !url.is_empty()
+        && !url.starts_with(['#', '?'])
+        && !url.starts_with("//")
+        && !url.split(['/', '?', '#']).next().is_some_and(|first| first.contains(':'));
+
+    match repository {
+        Some(repository) if is_relative => {
+            let repository = repository.trim_end_matches('/');
+            let repository = repository.strip_suffix(".git").unwrap_or(repository);
+            let kind = if is_image { "raw" } else { "blob" };
+            let path = url.trim_start_matches("./").trim_start_matches('/');
+
+            format!("{repository}/{kind}/HEAD/{path}")
+        }
+        _ => url.to_owned(),
+    }
+}
+
+/// Parses the attributes of a tag, e.g. `href="foo" hidden`.
+fn parse_attributes(mut source: &str) -> Vec<(String, String)> {
+    let mut attributes = Vec::new();
+
+    loop {
+        source = source.trim_start();
+
+        let name_end = source.find(|c: char| c.is_whitespace() || c == '=').unwrap_or(source.len());
+
+        if name_end == 0 {
+            return attributes;
+        }
+
+        let name = source[..name_end].to_ascii_lowercase();
+
+        source = source[name_end..].trim_start();
+
+        let value = match source.strip_prefix('=') {
+            None => String::new(),
+            Some(rest) => {
+                let rest = rest.trim_start();
+                let (value, rest) = match rest.chars().next() {
+                    Some(quote @ ('"' | '\'')) => {
+                        let end = rest[1..].find(quote).map_or(rest.len(), |i| i + 1);
+
+                        (&rest[1..end], rest.get(end + 1..).unwrap_or(""))
+                    }
+                    _ => {
+                        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+
+                        rest.split_at(end)
+                    }
+                };
+
+                source = rest;
+                value.to_owned()
+            }
+        };
+
+        attributes.push((name, value));
+    }
+}
+
+/// Sanitizes the html of the README, which can span several markdown events, e.g. a `<script>`
+/// block with blank lines.
+struct Sanitizer {
+    target: PreviewTarget,
+    /// The tag whose content is being removed.
+    dropping: Option<String>,
+}
+
+impl Sanitizer {
+    fn sanitize_tag(&mut self, tag: &str, repository: Option<&str>, output: &mut String) {
+        let is_closing = tag.starts_with('/');
+        let tag = tag.trim_start_matches('/').trim_end_matches('/');
+        let name_end = tag.find(char::is_whitespace).unwrap_or(tag.len());
+        let name = tag[..name_end].to_ascii_lowercase();
+
+        if let Some(dropping) = &self.dropping {
+            if is_closing && *dropping == name {
+                self.dropping = None;
+            }
+            return;
+        }
+
+        if DROPPED_TAGS.contains(&name.as_str()) {
+            if !is_closing {
+                self.dropping = Some(name);
+            }
+            return;
+        }
+
+        if !self.target.allows_tag(&name) {
+            return;
+        }
+
+        if is_closing {
+            let _ = write!(output, "</{name}>");
+            return;
+        }
+
+        output.push('<');
+        output.push_str(&name);
+
+        for (attribute, value) in parse_attributes(&tag[name_end..]) {
+            let is_url = matches!(attribute.as_str(), "href" | "src" | "srcset");
+
+            if !ALLOWED_ATTRIBUTES.contains(&attribute.as_str())
+                || (is_url && value.trim_start().to_ascii_lowercase().starts_with("javascript:"))
+            {
+                continue;
+            }
+
+            let value = match attribute.as_str() {
+                "href" => resolve_url(&value, repository, false),
+                "src" => resolve_url(&value, repository, true),
+                _ => value,
+            };
+
+            let _ = write!(output, " {attribute}=\"{}\"", value.replace('"', "&quot;"));
+        }
+
+        output.push('>');
+    }
+
+    fn sanitize(&mut self, html: &str, repository: Option<&str>) -> String {
+        let mut output = String::with_capacity(html.len());
+        let mut rest = html;
+
+        while !rest.is_empty() {
+            let (text, tag_start) = match rest.find('<') {
+                Some(i) => (&rest[..i], &rest[i..]),
+                None => (rest, ""),
+            };
+
+            if self.dropping.is_none() {
+                output.push_str(text);
+            }
+
+            if tag_start.is_empty() {
+                break;
+            }
+
+            if let Some(comment) = tag_start.strip_prefix("<!--") {
+                rest = comment.find("-->").map_or("", |i| &comment[i + 3..]);
+                continue;
+            }
+
+            match tag_start.find('>') {
+                Some(end) => {
+                    self.sanitize_tag(&tag_start[1..end], repository, &mut output);
+                    rest = &tag_start[end + 1..];
+                }
+                None => {
+                    output.push_str("&lt;");
+                    rest = &tag_start[1..];
+                }
+            }
+        }
+
+        output
+    }
+}
+
+/// Renders the README to html as `target` would.  Relative links and images are resolved against
+/// `repository`, the repository url of the crate, if any.
+#[must_use]
+pub fn preview_html(readme: &Readme, target: PreviewTarget, repository: Option<&str>) -> String {
+    use pulldown_cmark::{CowStr, Event, Parser, Tag};
+
+    let mut sanitizer = Sanitizer { target, dropping: None };
+    let events =
+        Parser::new_ext(readme.as_string(), target.markdown_options()).map(|event| match event {
+            Event::Html(html) => Event::Html(CowStr::from(sanitizer.sanitize(&html, repository))),
+            Event::InlineHtml(html) => {
+                Event::InlineHtml(CowStr::from(sanitizer.sanitize(&html, repository)))
+            }
+            Event::Start(Tag::Link { link_type, dest_url, title, id }) => Event::Start(Tag::Link {
+                link_type,
+                dest_url: CowStr::from(resolve_url(&dest_url, repository, false)),
+                title,
+                id,
+            }),
+            Event::Start(Tag::Image { link_type, dest_url, title, id }) => {
+                Event::Start(Tag::Image {
+                    link_type,
+                    dest_url: CowStr::from(resolve_url(&dest_url, repository, true)),
+                    title,
+                    id,
+                })
+            }
+            event => event,
+        });
+    let mut html = String::with_capacity(readme.as_string().len() * 2);
+
+    pulldown_cmark::html::push_html(&mut html, events);
+
+    html
+}
+
+/// A standalone html page with the README as `target` would render it, styled after it.
+#[must_use]
+pub fn preview_page(
+    readme: &Readme,
+    target: PreviewTarget,
+    package_name: &str,
+    repository: Option<&str>,
+) -> String {
+    indoc::formatdoc! { r#"
+        <!DOCTYPE html>
+        <html lang="en">
+        <head>
+        <meta charset="utf-8">
+        <meta name="viewport" content="width=device-width, initial-scale=1">
+        <title>{title} - {target} preview</title>
+        <style>
+        {style}img {{ max-width: 100%; }}
+        table {{ border-collapse: collapse; }}
+        th, td {{ border: 1px solid #ddd; padding: 0.3em 0.6em; }}
+        blockquote {{ margin-left: 0; padding-left: 1em; border-left: 4px solid #ddd; color: #666; }}
+        </style>
+        </head>
+        <body>
+        <main>
+        {readme}</main>
+        </body>
+        </html>
+        "#,
+        title = escape_html(package_name),
+        target = target.name(),
+        style = target.style(),
+        readme = preview_html(readme, target, repository),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_resolve_url() {
+        let repository = Some("https://github.com/foo/bar.git");
+
+        assert_eq!(
+            resolve_url("docs/guide.md", repository, false),
+            "https://github.com/foo/bar/blob/HEAD/docs/guide.md"
+        );
+        assert_eq!(
+            resolve_url("./logo.png", repository, true),
+            "https://github.com/foo/bar/raw/HEAD/logo.png"
+        );
+        assert_eq!(resolve_url("#usage", repository, false), "#usage");
+        assert_eq!(resolve_url("https://docs.rs/bar", repository, false), "https://docs.rs/bar");
+        assert_eq!(
+            resolve_url("mailto:foo@example.com", repository, false),
+            "mailto:foo@example.com"
+        );
+        assert_eq!(resolve_url("docs/guide.md", None, false), "docs/guide.md");
+    }
+
+    #[test]
+    fn test_preview_html_crates_io() {
+        let readme = indoc! { r#"
+            # Foo
+
+            <p align="center" style="color: red"><img src="logo.png" onerror="alert(1)"></p>
+
+            <script>
+            alert(1);
+
+            </script>
+
+            <details><summary>More</summary>
+
+            See [the guide](docs/guide.md) and <a href="javascript:alert(1)">this</a>.
+
+            </details>
+
+            $x$ <!-- cargo-rdme -->
+            "#
+        };
+        let expected = indoc! { r#"
+            <h1>Foo</h1>
+            <p align="center"><img src="https://github.com/foo/bar/raw/HEAD/logo.png"></p>
+
+            More
+            <p>See <a href="https://github.com/foo/bar/blob/HEAD/docs/guide.md">the guide</a> and <a>this</a>.</p>
+
+            <p>$x$ </p>
+            "#
+        };
+
+        assert_eq!(
+            preview_html(
+                &Readme::from_str(readme),
+                PreviewTarget::CratesIo,
+                Some("https://github.com/foo/bar")
+            ),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_preview_html_github() {
+        let readme = indoc! { r#"
+            > [!NOTE]
+            > Foo.
+
+            <details><summary>More</summary>
+
+            Bar.
+
+            </details>
+            "#
+        };
+        let expected = indoc! { r#"
+            <blockquote class="markdown-alert-note">
+            <p>Foo.</p>
+            </blockquote>
+            <details><summary>More</summary>
+            <p>Bar.</p>
+            </details>
+            "#
+        };
+
+        assert_eq!(preview_html(&Readme::from_str(readme), PreviewTarget::GitHub, None), expected);
+    }
+}
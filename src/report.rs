@@ -0,0 +1,198 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+//! The report of `--report`: a JSON record of what a run did, i.e. the status of every package,
+//! the files written, and the warnings and errors, for release pipelines to audit.  Unlike the
+//! output on the console, its format does not change with the terminal or the options.
+
+use crate::console::{EmittedDiagnostic, Level};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize, PartialEq, Eq, Clone, Debug)]
+#[serde(tag = "status", content = "message", rename_all = "kebab-case")]
+pub enum PackageStatus {
+    Updated,
+    UpToDate,
+    Skipped(String),
+    Failed(String),
+}
+
+#[derive(Serialize, PartialEq, Eq, Clone, Debug)]
+pub struct PackageReport {
+    pub name: String,
+    #[serde(flatten)]
+    pub status: PackageStatus,
+    pub files_written: Vec<PathBuf>,
+}
+
+/// A warning or an error emitted during the run.
+#[derive(Serialize, PartialEq, Eq, Clone, Debug)]
+pub struct ReportDiagnostic {
+    /// `warning` or `error`.
+    pub level: &'static str,
+    pub code: Option<String>,
+    pub message: String,
+    pub path: Option<PathBuf>,
+}
+
+#[derive(Serialize, PartialEq, Eq, Clone, Debug)]
+pub struct ReportStats {
+    pub packages: usize,
+    pub updated: usize,
+    pub up_to_date: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    pub warnings: usize,
+    pub errors: usize,
+}
+
+#[derive(Serialize, PartialEq, Eq, Clone, Debug)]
+pub struct Report {
+    pub version: &'static str,
+    pub exit_code: i32,
+    pub stats: ReportStats,
+    pub packages: Vec<PackageReport>,
+    pub diagnostics: Vec<ReportDiagnostic>,
+}
+
+impl Report {
+    /// Creates the report.  Paths are shown relative to `base_dir`, if possible.
+    pub fn new(
+        exit_code: i32,
+        packages: Vec<PackageReport>,
+        diagnostics: Vec<EmittedDiagnostic>,
+        base_dir: &Path,
+    ) -> Report {
+        let relative = |path: PathBuf| match path.strip_prefix(base_dir) {
+            Ok(relative) => relative.to_path_buf(),
+            Err(_) => path,
+        };
+        let packages: Vec<PackageReport> = packages
+            .into_iter()
+            .map(|package| PackageReport {
+                files_written: package.files_written.into_iter().map(relative).collect(),
+                ..package
+            })
+            .collect();
+        let count = |f: fn(&PackageStatus) -> bool| {
+            packages.iter().filter(|package| f(&package.status)).count()
+        };
+        let count_level = |level: Level| {
+            diagnostics.iter().filter(|diagnostic| diagnostic.level == level).count()
+        };
+        let stats = ReportStats {
+            packages: packages.len(),
+            updated: count(|status| *status == PackageStatus::Updated),
+            up_to_date: count(|status| *status == PackageStatus::UpToDate),
+            skipped: count(|status| matches!(status, PackageStatus::Skipped(_))),
+            failed: count(|status| matches!(status, PackageStatus::Failed(_))),
+            warnings: count_level(Level::Warning),
+            errors: count_level(Level::Error),
+        };
+        let diagnostics = diagnostics
+            .into_iter()
+            .map(|diagnostic| ReportDiagnostic {
+                level: diagnostic.level.as_str(),
+                code: diagnostic.code.map(|code| code.to_string()),
+                message: diagnostic.message,
+                path: diagnostic.path.map(relative),
+            })
+            .collect();
+
+        Report { version: env!("CARGO_PKG_VERSION"), exit_code, stats, packages, diagnostics }
+    }
+
+    /// Renders the report as JSON.
+    pub fn to_json(&self) -> String {
+        let mut json = serde_json::to_string_pretty(self).expect("the report is always valid json");
+
+        json.push('\n');
+
+        json
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::explain::DiagnosticCode;
+    use indoc::formatdoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_report_json() {
+        let packages = vec![
+            PackageReport {
+                name: "foo".to_owned(),
+                status: PackageStatus::Updated,
+                files_written: vec![PathBuf::from("/repo/foo/README.md")],
+            },
+            PackageReport {
+                name: "bar".to_owned(),
+                status: PackageStatus::Failed("README is not up to date".to_owned()),
+                files_written: Vec::new(),
+            },
+        ];
+        let diagnostics = vec![EmittedDiagnostic {
+            level: Level::Warning,
+            code: Some(DiagnosticCode::UnresolvedIntralink),
+            message: "Could not resolve definition of `Foo`.\n\"quoted\"".to_owned(),
+            path: Some(PathBuf::from("/repo/foo/src/lib.rs")),
+        }];
+        let report = Report::new(1, packages, diagnostics, Path::new("/repo"));
+
+        let expected = formatdoc! { r#"
+            {{
+              "version": "{}",
+              "exit_code": 1,
+              "stats": {{
+                "packages": 2,
+                "updated": 1,
+                "up_to_date": 0,
+                "skipped": 0,
+                "failed": 1,
+                "warnings": 1,
+                "errors": 0
+              }},
+              "packages": [
+                {{
+                  "name": "foo",
+                  "status": "updated",
+                  "files_written": [
+                    "foo/README.md"
+                  ]
+                }},
+                {{
+                  "name": "bar",
+                  "status": "failed",
+                  "message": "README is not up to date",
+                  "files_written": []
+                }}
+              ],
+              "diagnostics": [
+                {{
+                  "level": "warning",
+                  "code": "{}",
+                  "message": "Could not resolve definition of `Foo`.\n\"quoted\"",
+                  "path": "foo/src/lib.rs"
+                }}
+              ]
+            }}
+            "#,
+            env!("CARGO_PKG_VERSION"),
+            DiagnosticCode::UnresolvedIntralink,
+        };
+
+        assert_eq!(report.to_json(), expected);
+    }
+
+    #[test]
+    fn test_report_json_empty() {
+        let report = Report::new(0, Vec::new(), Vec::new(), Path::new("/repo"));
+
+        assert!(report.to_json().ends_with("\"packages\": [],\n  \"diagnostics\": []\n}\n"));
+    }
+}
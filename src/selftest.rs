@@ -0,0 +1,269 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+//! Implementation of `cargo rdme selftest`, which runs cargo rdme on bundled example projects and
+//! compares the READMEs it generates with the expected ones, to verify that an installation
+//! behaves as released.
+
+use indoc::indoc;
+use std::path::Path;
+use std::process::Command;
+use thiserror::Error;
+
+const MANIFEST: &str = indoc! { r#"
+    [package]
+    name = "selftest"
+    version = "0.1.0"
+    edition = "2021"
+
+    # Keeps the project out of the workspace of a parent directory, if any.
+    [workspace]
+    "#
+};
+
+struct Fixture {
+    name: &'static str,
+    args: &'static [&'static str],
+    lib: &'static str,
+    readme: &'static str,
+    expected_readme: &'static str,
+}
+
+const FIXTURES: &[Fixture] = &[
+    Fixture {
+        name: "single_marker",
+        args: &[],
+        lib: indoc! { r#"
+            //! # Selftest
+            //!
+            //! This crate does nothing.
+            "#
+        },
+        readme: indoc! { r#"
+            # Selftest
+
+            <!-- cargo-rdme -->
+
+            License: MPL-2.0
+            "#
+        },
+        expected_readme: indoc! { r#"
+            # Selftest
+
+            <!-- cargo-rdme start -->
+
+            ## Selftest
+
+            This crate does nothing.
+
+            <!-- cargo-rdme end -->
+
+            License: MPL-2.0
+            "#
+        },
+    },
+    Fixture {
+        name: "rust_code_blocks",
+        args: &[],
+        lib: indoc! { r#"
+            //! ```
+            //! # fn main() {
+            //! let answer = 42;
+            //! # }
+            //! ```
+            //!
+            //! ```rust,no_run
+            //! loop {}
+            //! ```
+            "#
+        },
+        readme: indoc! { r#"
+            <!-- cargo-rdme start -->
+            <!-- cargo-rdme end -->
+            "#
+        },
+        expected_readme: indoc! { r#"
+            <!-- cargo-rdme start -->
+
+            ```rust
+            let answer = 42;
+            ```
+
+            ```rust
+            loop {}
+            ```
+
+            <!-- cargo-rdme end -->
+            "#
+        },
+    },
+    Fixture {
+        name: "intralinks",
+        args: &[],
+        lib: indoc! { r#"
+            //! Use [`Foo`] or [its method](Foo::bar).
+
+            pub struct Foo;
+
+            impl Foo {
+                pub fn bar(&self) {}
+            }
+            "#
+        },
+        readme: indoc! { r#"
+            <!-- cargo-rdme -->
+            "#
+        },
+        expected_readme: indoc! { r#"
+            <!-- cargo-rdme start -->
+
+            Use [`Foo`](https://docs.rs/selftest/latest/selftest/struct.Foo.html) or [its method](https://docs.rs/selftest/latest/selftest/struct.Foo.html#method.bar).
+
+            <!-- cargo-rdme end -->
+            "#
+        },
+    },
+    Fixture {
+        name: "heading_line_terminator_crlf",
+        args: &["--heading", "Documentation", "--line-terminator", "crlf"],
+        lib: indoc! { r#"
+            //! # Usage
+            //!
+            //! Just use it.
+            "#
+        },
+        readme: indoc! { r#"
+            # Selftest
+
+            <!-- cargo-rdme -->
+            "#
+        },
+        expected_readme: "# Selftest\r\n\
+                          \r\n\
+                          <!-- cargo-rdme start -->\r\n\
+                          \r\n\
+                          ## Documentation\r\n\
+                          \r\n\
+                          ### Usage\r\n\
+                          \r\n\
+                          Just use it.\r\n\
+                          \r\n\
+                          <!-- cargo-rdme end -->\r\n",
+    },
+];
+
+#[derive(Error, Debug)]
+enum SelftestError {
+    #[error("failed to set up the project: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("cargo rdme failed:\n{stderr}")]
+    CargoRdmeFailed { stderr: String },
+    #[error(
+        "line {line_number} of the README differs\n  expected: {expected:?}\n       got: {got:?}"
+    )]
+    ReadmeMismatch { line_number: usize, expected: String, got: String },
+}
+
+fn readme_mismatch(expected: &str, got: &str) -> Option<SelftestError> {
+    let expected_lines: Vec<&str> = expected.split_inclusive('\n').collect();
+    let got_lines: Vec<&str> = got.split_inclusive('\n').collect();
+    let line_count = expected_lines.len().max(got_lines.len());
+
+    (0..line_count).find_map(|i| {
+        let expected = expected_lines.get(i).copied().unwrap_or_default();
+        let got = got_lines.get(i).copied().unwrap_or_default();
+
+        (expected != got).then(|| SelftestError::ReadmeMismatch {
+            line_number: i + 1,
+            expected: expected.to_owned(),
+            got: got.to_owned(),
+        })
+    })
+}
+
+fn run_fixture(bin_path: &Path, dir: &Path, fixture: &Fixture) -> Result<(), SelftestError> {
+    std::fs::create_dir_all(dir.join("src"))?;
+    std::fs::write(dir.join("Cargo.toml"), MANIFEST)?;
+    std::fs::write(dir.join("src").join("lib.rs"), fixture.lib)?;
+    std::fs::write(dir.join("README.md"), fixture.readme)?;
+
+    let output = Command::new(bin_path)
+        .arg("--manifest-path")
+        .arg(dir.join("Cargo.toml"))
+        .arg("--force")
+        .args(fixture.args)
+        .output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+
+        return Err(SelftestError::CargoRdmeFailed { stderr });
+    }
+
+    let readme = std::fs::read_to_string(dir.join("README.md"))?;
+
+    match readme_mismatch(fixture.expected_readme, &readme) {
+        Some(mismatch) => Err(mismatch),
+        None => Ok(()),
+    }
+}
+
+/// Runs every bundled fixture, reporting the result of each one.  Returns whether they all passed.
+pub fn selftest() -> bool {
+    let dir = std::env::temp_dir().join(format!("cargo-rdme-selftest-{}", std::process::id()));
+    let bin_path = match std::env::current_exe() {
+        Ok(bin_path) => bin_path,
+        Err(e) => {
+            print_error!("unable to find the cargo rdme executable: {}", e);
+            return false;
+        }
+    };
+    let mut failures = 0;
+
+    for fixture in FIXTURES {
+        match run_fixture(&bin_path, &dir.join(fixture.name), fixture) {
+            Ok(()) => print_info!("selftest {} ... ok", fixture.name),
+            Err(e) => {
+                print_info!("selftest {} ... FAILED", fixture.name);
+                print_error!("selftest `{}`: {}", fixture.name, e);
+                failures += 1;
+            }
+        }
+    }
+
+    // Nothing to do if the cleanup fails, the directory is in the temporary directory anyway.
+    let _ = std::fs::remove_dir_all(&dir);
+
+    print_info!("selftest: {} passed, {} failed", FIXTURES.len() - failures, failures);
+
+    failures == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_readme_mismatch() {
+        assert!(readme_mismatch("a\nb\n", "a\nb\n").is_none());
+
+        let Some(SelftestError::ReadmeMismatch { line_number, expected, got }) =
+            readme_mismatch("a\nb\n", "a\nb\r\n")
+        else {
+            panic!("expected a mismatch");
+        };
+
+        assert_eq!((line_number, expected.as_str(), got.as_str()), (2, "b\n", "b\r\n"));
+
+        let Some(SelftestError::ReadmeMismatch { line_number, expected, got }) =
+            readme_mismatch("a\n", "a\nb\n")
+        else {
+            panic!("expected a mismatch");
+        };
+
+        assert_eq!((line_number, expected.as_str(), got.as_str()), (2, "", "b\n"));
+    }
+}
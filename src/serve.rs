@@ -0,0 +1,252 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+//! The local http server of `cargo rdme preview --serve`.  The preview is rendered again on every
+//! page load, and the page reloads itself when a file of the package changes, so that changes to
+//! the documentation show up as soon as they are saved.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// How long to wait for a request, so that idle connections, e.g. the ones browsers open in
+/// advance, are eventually closed.
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Polls the fingerprint of the package, and reloads the page when it changes.
+const RELOAD_SCRIPT: &str = r#"<script>
+let fingerprint = null;
+setInterval(async () => {
+  try {
+    const value = await (await fetch("/fingerprint")).text();
+    if (fingerprint !== null && value !== fingerprint) location.reload();
+    fingerprint = value;
+  } catch (e) {}
+}, 1000);
+</script>
+"#;
+
+/// A fingerprint of the files of the package, which changes when any of them is modified.  The
+/// target directory (`target_dir`, and any directory named `target`) and hidden files, other than
+/// the configuration file, are skipped.
+pub fn directory_fingerprint(dir: &Path, target_dir: &Path) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    let mut pending = vec![dir.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        let mut entries: Vec<std::fs::DirEntry> = entries.filter_map(Result::ok).collect();
+
+        entries.sort_by_key(std::fs::DirEntry::file_name);
+
+        for entry in entries {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+
+            if name == "target"
+                || entry.path() == target_dir
+                || (name.starts_with('.') && name != ".cargo-rdme.toml")
+            {
+                continue;
+            }
+
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+
+            match metadata.is_dir() {
+                true => pending.push(entry.path()),
+                false => {
+                    entry.path().hash(&mut hasher);
+                    metadata.len().hash(&mut hasher);
+                    metadata.modified().ok().hash(&mut hasher);
+                }
+            }
+        }
+    }
+
+    hasher.finish()
+}
+
+fn with_reload_script(page: &str) -> String {
+    match page.rfind("</body>") {
+        Some(i) => format!("{}{RELOAD_SCRIPT}{}", &page[..i], &page[i..]),
+        None => format!("{page}{RELOAD_SCRIPT}"),
+    }
+}
+
+fn error_page(error: &str) -> String {
+    let error = error.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>cargo rdme preview</title>\n</head>\n<body>\n<pre>{error}</pre>\n</body>\n</html>\n"
+    )
+}
+
+fn handle_connection(
+    mut stream: TcpStream,
+    render: &impl Fn() -> Result<String, String>,
+    fingerprint: &impl Fn() -> u64,
+) -> std::io::Result<()> {
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    let mut header = String::new();
+
+    reader.read_line(&mut request_line)?;
+
+    // The headers are not needed, but they must be read before answering.
+    loop {
+        header.clear();
+
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+    }
+
+    let html = "text/html; charset=utf-8";
+    let text = "text/plain; charset=utf-8";
+    let (status, content_type, body) = match request_line.split_whitespace().nth(1) {
+        Some("/" | "/index.html") => match render() {
+            Ok(page) => ("200 OK", html, with_reload_script(&page)),
+            Err(e) => ("500 Internal Server Error", html, with_reload_script(&error_page(&e))),
+        },
+        Some("/fingerprint") => ("200 OK", text, format!("{:016x}", fingerprint())),
+        _ => ("404 Not Found", text, "not found\n".to_owned()),
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\n\
+         Cache-Control: no-store\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+
+    stream.flush()
+}
+
+/// Answers the requests to `listener`, forever.  `render` returns the page of the preview, or the
+/// error that prevented rendering it.
+///
+/// Every connection is handled in its own thread, so that a client that sends nothing does not
+/// block the others.  The preview is rendered by one connection at a time, since it is written to
+/// the same file.
+pub fn serve(
+    listener: &TcpListener,
+    render: impl Fn() -> Result<String, String> + Sync,
+    fingerprint: impl Fn() -> u64 + Sync,
+) {
+    let render_lock = Mutex::new(());
+    let render = || {
+        let _guard = render_lock.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        render()
+    };
+
+    std::thread::scope(|scope| {
+        for stream in listener.incoming().filter_map(Result::ok) {
+            let (render, fingerprint) = (&render, &fingerprint);
+
+            // A failed connection, e.g. a request cancelled by the browser or a client that timed
+            // out, only concerns that request.
+            scope.spawn(move || {
+                let _ = stream
+                    .set_read_timeout(Some(READ_TIMEOUT))
+                    .and_then(|()| handle_connection(stream, render, fingerprint));
+            });
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::io::Read;
+
+    fn request(path: &'static str, render: impl Fn() -> Result<String, String>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let client = std::thread::spawn(move || {
+            let mut stream = TcpStream::connect(address).unwrap();
+            let mut response = String::new();
+
+            write!(stream, "GET {path} HTTP/1.1\r\nHost: {address}\r\n\r\n").unwrap();
+            stream.read_to_string(&mut response).unwrap();
+
+            response
+        });
+        let (stream, _) = listener.accept().unwrap();
+
+        handle_connection(stream, &render, &|| 0x2a).unwrap();
+
+        client.join().unwrap()
+    }
+
+    #[test]
+    fn test_handle_connection() {
+        let page = || Ok("<html><body>Foo</body></html>".to_owned());
+        let response = request("/", page);
+
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.ends_with(&format!("<html><body>Foo{RELOAD_SCRIPT}</body></html>")));
+
+        let response = request("/", || Err("no crate documentation <here>".to_owned()));
+
+        assert!(response.starts_with("HTTP/1.1 500 Internal Server Error\r\n"));
+        assert!(response.contains("<pre>no crate documentation &lt;here&gt;</pre>"));
+
+        let response = request("/fingerprint", page);
+
+        assert_eq!(response.split("\r\n\r\n").nth(1), Some("000000000000002a"));
+        assert!(request("/favicon.ico", page).starts_with("HTTP/1.1 404 Not Found\r\n"));
+    }
+
+    #[test]
+    fn test_serve_idle_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        // The server runs until the end of the tests.
+        std::thread::spawn(move || serve(&listener, || Ok(String::new()), || 0x2a));
+
+        // A connection that sends nothing must not block the next one.
+        let _idle = TcpStream::connect(address).unwrap();
+        let mut stream = TcpStream::connect(address).unwrap();
+        let mut response = String::new();
+
+        write!(stream, "GET /fingerprint HTTP/1.1\r\nHost: {address}\r\n\r\n").unwrap();
+        stream.read_to_string(&mut response).unwrap();
+
+        assert!(response.ends_with("\r\n\r\n000000000000002a"));
+    }
+
+    #[test]
+    fn test_directory_fingerprint() {
+        let dir =
+            std::env::temp_dir().join(format!("cargo-rdme-fingerprint-{}", std::process::id()));
+        let target_dir = dir.join("build");
+
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        std::fs::create_dir_all(&target_dir).unwrap();
+        std::fs::write(dir.join("src").join("lib.rs"), "//! Foo.\n").unwrap();
+
+        let fingerprint = directory_fingerprint(&dir, &target_dir);
+
+        std::fs::write(target_dir.join("artifact"), "ignored").unwrap();
+        assert_eq!(directory_fingerprint(&dir, &target_dir), fingerprint);
+
+        std::fs::write(dir.join("src").join("lib.rs"), "//! Bar, which is longer.\n").unwrap();
+        assert_ne!(directory_fingerprint(&dir, &target_dir), fingerprint);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
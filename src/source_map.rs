@@ -0,0 +1,197 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+//! Maps lines of the generated documentation back to the file the documentation was extracted
+//! from, so that problems found in the README can be fixed at their source.
+
+use crate::extract_doc::ExtractDocError;
+#[cfg(feature = "rust-source")]
+use crate::extract_doc::{doc_line_numbers, extract_doc_from_source_str};
+use crate::inject_doc::generated_regions;
+use crate::Readme;
+use std::path::{Path, PathBuf};
+
+/// How many lines we look ahead for a matching line.  This keeps lines that were changed by the
+/// transformations from being matched with some unrelated line far away.
+const LOOKAHEAD_LINES: usize = 16;
+
+#[derive(Debug)]
+pub struct DocSourceMap {
+    file: PathBuf,
+    /// The lines of the documentation, as extracted, and their line number in `file`.
+    lines: Vec<(String, usize)>,
+}
+
+impl DocSourceMap {
+    /// Creates the map of the documentation extracted from a rust source file.  This is `None` if
+    /// the file has no documentation, or the documentation lines cannot be mapped to the file.
+    #[cfg(feature = "rust-source")]
+    pub fn from_source_file(
+        file: impl AsRef<Path>,
+    ) -> Result<Option<DocSourceMap>, ExtractDocError> {
+        let file = file.as_ref();
+        let source = std::fs::read_to_string(file)
+            .map_err(|_| ExtractDocError::ErrorReadingSourceFile(file.to_path_buf()))?;
+        let Some(doc) = extract_doc_from_source_str(&source)? else {
+            return Ok(None);
+        };
+        let line_numbers = doc_line_numbers(&source).unwrap_or_default();
+
+        if line_numbers.len() != doc.lines().count() {
+            return Ok(None);
+        }
+
+        let lines = doc.lines().map(ToOwned::to_owned).zip(line_numbers).collect();
+
+        Ok(Some(DocSourceMap { file: file.to_path_buf(), lines }))
+    }
+
+    /// Creates the map of the documentation of a markdown file.
+    pub fn from_markdown_file(file: impl AsRef<Path>) -> Result<DocSourceMap, ExtractDocError> {
+        let file = file.as_ref();
+        let source = std::fs::read_to_string(file)
+            .map_err(|_| ExtractDocError::ErrorReadingMarkdownFile(file.to_path_buf()))?;
+        let lines = source.lines().enumerate().map(|(i, line)| (line.to_owned(), i + 1)).collect();
+
+        Ok(DocSourceMap { file: file.to_path_buf(), lines })
+    }
+
+    /// The file the documentation was extracted from.
+    #[must_use]
+    pub fn file(&self) -> &Path {
+        &self.file
+    }
+
+    /// Returns the line of the source file of each of `lines`, which are lines of the
+    /// documentation after it was transformed.  Lines are matched, in order, with the lines of
+    /// the original documentation, so lines added or changed by the transformations (and blank
+    /// lines) are not mapped.
+    pub fn source_lines<'a>(&self, lines: impl IntoIterator<Item = &'a str>) -> Vec<Option<usize>> {
+        // Headings may have had their level changed.
+        fn normalize(line: &str) -> &str {
+            line.trim().trim_start_matches('#').trim()
+        }
+
+        let mut next = 0;
+
+        lines
+            .into_iter()
+            .map(|line| {
+                let line = normalize(line);
+
+                if line.is_empty() {
+                    return None;
+                }
+
+                let found = self.lines[next.min(self.lines.len())..]
+                    .iter()
+                    .take(LOOKAHEAD_LINES)
+                    .position(|(original, _)| normalize(original) == line)?;
+                let (_, line_number) = self.lines[next + found];
+
+                next += found + 1;
+
+                Some(line_number)
+            })
+            .collect()
+    }
+
+    /// Returns the line of the source file of each line of the README (in the regions generated
+    /// by cargo rdme).
+    #[must_use]
+    pub fn readme_source_lines(&self, readme: &Readme) -> Vec<Option<usize>> {
+        let source = readme.as_string();
+        let regions = generated_regions(readme);
+        let mut offset = 0;
+
+        let lines = source.split_inclusive('\n').map(|line| {
+            let line_start = offset;
+
+            offset += line.len();
+
+            match regions.iter().any(|region| region.contains(&line_start)) {
+                true => line,
+                // Blank lines are never mapped.
+                false => "",
+            }
+        });
+
+        self.source_lines(lines)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[cfg(feature = "rust-source")]
+    #[test]
+    fn test_doc_line_numbers() {
+        let source = indoc! { r#"
+            #![cfg_attr(not(feature = "std"), no_std)]
+            //! # My crate
+            //!
+            #![doc = "Some docs."]
+            /*!
+            Block docs.
+            */
+
+            // Not docs.
+            fn main() {}
+            "#
+        };
+
+        assert_eq!(doc_line_numbers(source), Some(vec![2, 3, 4, 6]));
+        assert_eq!(
+            extract_doc_from_source_str(source).unwrap().unwrap().lines().count(),
+            doc_line_numbers(source).unwrap().len()
+        );
+        assert_eq!(doc_line_numbers("#![doc = include_str!(\"../README.md\")]"), None);
+    }
+
+    #[test]
+    fn test_source_lines() {
+        let map = DocSourceMap {
+            file: PathBuf::from("src/lib.rs"),
+            lines: vec![
+                ("# My crate".to_owned(), 3),
+                (String::new(), 4),
+                ("```".to_owned(), 5),
+                ("# let hidden = 1;".to_owned(), 6),
+                ("let a = 1;".to_owned(), 7),
+                ("```".to_owned(), 8),
+            ],
+        };
+
+        let doc = indoc! { "
+            ## My crate
+
+            ```rust
+            let a = 1;
+            ```
+            "
+        };
+
+        assert_eq!(map.source_lines(doc.lines()), vec![Some(3), None, None, Some(7), Some(8)]);
+
+        let readme = indoc! { "
+            # My crate
+
+            <!-- cargo-rdme start -->
+
+            ## My crate
+
+            <!-- cargo-rdme end -->
+            "
+        };
+
+        assert_eq!(
+            map.readme_source_lines(&Readme::from_str(readme)),
+            vec![None, None, None, None, Some(3), None, None]
+        );
+    }
+}
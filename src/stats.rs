@@ -0,0 +1,154 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+//! The report of `cargo rdme stats`, with the state of the documentation of every package of the
+//! workspace.
+
+use std::fmt::Write;
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct PackageStats {
+    pub package_name: String,
+    /// Number of lines of the crate documentation, if the crate has documentation.
+    pub doc_lines: Option<usize>,
+    /// Number of lines of the README, if the package has a README.
+    pub readme_lines: Option<usize>,
+    /// Whether the README has cargo rdme markers, i.e. it is updated by cargo rdme.
+    pub has_markers: bool,
+    /// Number of distinct items linked to by intralinks.
+    pub intralinks: usize,
+    /// Number of intralinks that could not be resolved, if resolving them was possible at all.
+    pub unresolved_intralinks: Option<usize>,
+}
+
+fn count_or_dash(count: Option<usize>) -> String {
+    count.map_or_else(|| "-".to_owned(), |count| count.to_string())
+}
+
+fn count_or_null(count: Option<usize>) -> String {
+    count.map_or_else(|| "null".to_owned(), |count| count.to_string())
+}
+
+/// Renders the statistics as a table, with a line per package.
+pub fn stats_table(stats: &[PackageStats]) -> String {
+    const HEADER: [&str; 6] =
+        ["package", "doc lines", "readme lines", "markers", "intralinks", "unresolved"];
+
+    let rows: Vec<[String; 6]> = stats
+        .iter()
+        .map(|s| {
+            [
+                s.package_name.clone(),
+                count_or_dash(s.doc_lines),
+                count_or_dash(s.readme_lines),
+                if s.has_markers { "yes" } else { "no" }.to_owned(),
+                s.intralinks.to_string(),
+                count_or_dash(s.unresolved_intralinks),
+            ]
+        })
+        .collect();
+    let widths: Vec<usize> = (0..HEADER.len())
+        .map(|i| rows.iter().map(|row| row[i].len()).chain([HEADER[i].len()]).max().unwrap_or(0))
+        .collect();
+    let mut table = String::new();
+
+    for row in std::iter::once(HEADER.map(ToOwned::to_owned)).chain(rows) {
+        let line = row
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .map(|(i, (cell, &width))| match i {
+                // The package name is aligned to the left, the numbers to the right.
+                0 => format!("{cell:width$}"),
+                _ => format!("{cell:>width$}"),
+            })
+            .collect::<Vec<_>>()
+            .join("  ");
+
+        let _ = writeln!(table, "{}", line.trim_end());
+    }
+
+    table
+}
+
+/// Renders the statistics as JSON, to be consumed by other tools, e.g. documentation dashboards.
+pub fn stats_json(stats: &[PackageStats]) -> String {
+    let packages: Vec<String> = stats
+        .iter()
+        .map(|s| {
+            // Package names only have alphanumeric characters, `-`, and `_`, so they need no
+            // escaping.
+            format!(
+                "  {{\"package\": \"{}\", \"doc_lines\": {}, \"readme_lines\": {}, \"markers\": {}, \
+                 \"intralinks\": {}, \"unresolved_intralinks\": {}}}",
+                s.package_name,
+                count_or_null(s.doc_lines),
+                count_or_null(s.readme_lines),
+                s.has_markers,
+                s.intralinks,
+                count_or_null(s.unresolved_intralinks),
+            )
+        })
+        .collect();
+
+    match packages.is_empty() {
+        true => "[]\n".to_owned(),
+        false => format!("[\n{}\n]\n", packages.join(",\n")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    fn stats() -> Vec<PackageStats> {
+        vec![
+            PackageStats {
+                package_name: "foo".to_owned(),
+                doc_lines: Some(120),
+                readme_lines: Some(140),
+                has_markers: true,
+                intralinks: 7,
+                unresolved_intralinks: Some(1),
+            },
+            PackageStats {
+                package_name: "foo-macros".to_owned(),
+                doc_lines: None,
+                readme_lines: None,
+                has_markers: false,
+                intralinks: 0,
+                unresolved_intralinks: None,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_stats_table() {
+        let expected = indoc! { "
+            package     doc lines  readme lines  markers  intralinks  unresolved
+            foo               120           140      yes           7           1
+            foo-macros          -             -       no           0           -
+            "
+        };
+
+        assert_eq!(stats_table(&stats()), expected);
+    }
+
+    #[test]
+    fn test_stats_json() {
+        let expected = indoc! { r#"
+            [
+              {"package": "foo", "doc_lines": 120, "readme_lines": 140, "markers": true, "intralinks": 7, "unresolved_intralinks": 1},
+              {"package": "foo-macros", "doc_lines": null, "readme_lines": null, "markers": false, "intralinks": 0, "unresolved_intralinks": null}
+            ]
+            "#
+        };
+
+        assert_eq!(stats_json(&stats()), expected);
+        assert_eq!(stats_json(&[]), "[]\n");
+    }
+}
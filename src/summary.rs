@@ -0,0 +1,120 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+//! The summary of `--summary`: a markdown report of what changed in the README of every package,
+//! with collapsed diffs, to be posted by CI as a comment of a pull request.
+
+use cargo_rdme::unified_diff;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/// A README that changed, or that would change if it was updated.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct ReadmeChange {
+    pub readme_path: PathBuf,
+    pub old: String,
+    pub new: String,
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct PackageSummary {
+    pub name: String,
+    pub status: String,
+    pub changes: Vec<ReadmeChange>,
+}
+
+/// A code fence longer than any sequence of backticks in `text`, so that it can be put in a code
+/// block.
+fn code_fence(text: &str) -> String {
+    let longest = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+
+    "`".repeat(longest.max(2) + 1)
+}
+
+/// Renders the summary as markdown.  Paths are shown relative to `base_dir`, if possible.
+pub fn summary_markdown(packages: &[PackageSummary], base_dir: &Path) -> String {
+    let mut summary = String::from("### cargo rdme\n\n| package | status |\n| --- | --- |\n");
+
+    for package in packages {
+        // Pipes would end the table cell.
+        let status = package.status.replace('|', "\\|");
+        let _ = writeln!(summary, "| `{}` | {} |", package.name, status);
+    }
+
+    for package in packages {
+        for change in &package.changes {
+            let readme_path =
+                change.readme_path.strip_prefix(base_dir).unwrap_or(&change.readme_path);
+            let diff = unified_diff(&change.old, &change.new);
+            let fence = code_fence(&diff);
+
+            let _ = write!(
+                summary,
+                "\n<details>\n<summary><code>{}</code>: <code>{}</code></summary>\n\n{fence}diff\n{diff}{fence}\n\n</details>\n",
+                package.name,
+                readme_path.display(),
+            );
+        }
+    }
+
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_summary_markdown() {
+        let packages = vec![
+            PackageSummary {
+                name: "foo".to_owned(),
+                status: "README is not up to date".to_owned(),
+                changes: vec![ReadmeChange {
+                    readme_path: PathBuf::from("/repo/foo/README.md"),
+                    old: "# Foo\nOld.\n".to_owned(),
+                    new: "# Foo\nNew `code`.\n".to_owned(),
+                }],
+            },
+            PackageSummary {
+                name: "bar".to_owned(),
+                status: "up-to-date".to_owned(),
+                changes: Vec::new(),
+            },
+        ];
+
+        let expected = indoc! { "
+            ### cargo rdme
+
+            | package | status |
+            | --- | --- |
+            | `foo` | README is not up to date |
+            | `bar` | up-to-date |
+
+            <details>
+            <summary><code>foo</code>: <code>foo/README.md</code></summary>
+
+            ```diff
+            @@ -1,2 +1,2 @@
+             # Foo
+            -Old.
+            +New `code`.
+            ```
+
+            </details>
+            "
+        };
+
+        assert_eq!(summary_markdown(&packages, Path::new("/repo")), expected);
+    }
+
+    #[test]
+    fn test_code_fence() {
+        assert_eq!(code_fence("a `b`"), "```");
+        assert_eq!(code_fence("```rust\n```"), "````");
+    }
+}
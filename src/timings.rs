@@ -0,0 +1,69 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+//! How long the steps of processing a package took, as reported with `--timings`.
+
+use std::fmt::{Display, Formatter};
+use std::time::{Duration, Instant};
+
+#[derive(Default, Debug)]
+pub struct Timings {
+    /// Duration of each step, in the order the steps first ran.
+    steps: Vec<(&'static str, Duration)>,
+}
+
+impl Timings {
+    /// Runs `f`, adding the time it took to `step`.
+    pub fn time<T>(&mut self, step: &'static str, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+
+        self.add(step, start.elapsed());
+
+        result
+    }
+
+    fn add(&mut self, step: &'static str, duration: Duration) {
+        match self.steps.iter_mut().find(|(s, _)| *s == step) {
+            Some((_, total)) => *total += duration,
+            None => self.steps.push((step, duration)),
+        }
+    }
+
+    fn total(&self) -> Duration {
+        self.steps.iter().map(|(_, duration)| *duration).sum()
+    }
+}
+
+impl Display for Timings {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (step, duration) in &self.steps {
+            write!(f, "{step} {duration:.2?}, ")?;
+        }
+
+        write!(f, "total {:.2?}", self.total())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_timings_display() {
+        let mut timings = Timings::default();
+
+        timings.add("manifest", Duration::from_micros(1500));
+        timings.add("doc", Duration::from_micros(250));
+        timings.add("manifest", Duration::from_micros(500));
+        timings.add("write", Duration::from_millis(12));
+
+        assert_eq!(
+            timings.to_string(),
+            "manifest 2.00ms, doc 250.00µs, write 12.00ms, total 14.25ms"
+        );
+    }
+}
@@ -0,0 +1,84 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::transform::{code_block_ranges, DocTransform};
+use crate::Doc;
+use std::convert::Infallible;
+
+/// Removes the blank lines at the start and at the end of the documentation, which doc comments
+/// often have, and collapses consecutive blank lines into one.  Blank lines inside code blocks are
+/// kept.
+pub struct DocTransformBlankLines;
+
+impl DocTransformBlankLines {
+    #[must_use]
+    pub fn new() -> DocTransformBlankLines {
+        DocTransformBlankLines
+    }
+}
+
+impl DocTransform for DocTransformBlankLines {
+    type E = Infallible;
+
+    fn transform(&self, doc: &Doc) -> Result<Doc, Infallible> {
+        let source = doc.as_string();
+        let code_blocks = code_block_ranges(source);
+        let mut offset = 0;
+        // Each line, with its line terminator, and whether it is a blank line outside of code
+        // blocks.
+        let lines: Vec<(&str, bool)> = source
+            .split_inclusive('\n')
+            .map(|line| {
+                let line_start = offset;
+
+                offset += line.len();
+
+                let in_code_block = code_blocks.iter().any(|block| block.contains(&line_start));
+
+                (line, !in_code_block && line.trim().is_empty())
+            })
+            .collect();
+
+        let start = lines.iter().position(|(_, blank)| !blank).unwrap_or(lines.len());
+        let end = lines.iter().rposition(|(_, blank)| !blank).map_or(start, |i| i + 1);
+        let mut new_source = String::with_capacity(source.len());
+        let mut previous_blank = false;
+
+        for (line, blank) in &lines[start..end] {
+            if !(*blank && previous_blank) {
+                new_source.push_str(line);
+            }
+
+            previous_blank = *blank;
+        }
+
+        Ok(Doc::from_str(new_source))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_blank_lines() {
+        let doc = "\n  \nHello.\n\n\n\nWorld.\n\n```\na\n\n\nb\n```\n\n\n";
+        let expected = "Hello.\n\nWorld.\n\n```\na\n\n\nb\n```\n";
+
+        let transform = DocTransformBlankLines::new();
+        let new_doc = transform.transform(&Doc::from_str(doc)).unwrap();
+
+        assert_eq!(new_doc.as_string(), expected);
+    }
+
+    #[test]
+    fn test_blank_lines_only_blank_lines() {
+        let transform = DocTransformBlankLines::new();
+        let new_doc = transform.transform(&Doc::from_str("\n\n")).unwrap();
+
+        assert_eq!(new_doc.as_string(), "\n");
+    }
+}
@@ -0,0 +1,176 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::cfg::CfgContext;
+use crate::transform::code_block_ranges;
+use crate::transform::DocTransform;
+use crate::Doc;
+use thiserror::Error;
+
+#[derive(Error, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub enum CfgDirectiveError {
+    #[error("invalid predicate in `rdme:if` directive in line {line_number}: `{predicate}`")]
+    InvalidPredicate { predicate: String, line_number: usize },
+    #[error("`rdme:endif` directive without a matching `rdme:if` in line {line_number}")]
+    UnexpectedEndif { line_number: usize },
+    #[error("`rdme:if` directive without a matching `rdme:endif` in line {line_number}")]
+    UnmatchedIf { line_number: usize },
+}
+
+enum Directive<'a> {
+    If(&'a str),
+    EndIf,
+}
+
+fn directive(line: &str) -> Option<Directive<'_>> {
+    let comment = line.trim().strip_prefix("<!--")?.strip_suffix("-->")?.trim();
+
+    match comment.strip_prefix("rdme:if") {
+        Some(predicate) if predicate.starts_with(char::is_whitespace) => {
+            Some(Directive::If(predicate.trim()))
+        }
+        _ => (comment == "rdme:endif").then_some(Directive::EndIf),
+    }
+}
+
+/// Keeps the lines between `<!-- rdme:if <predicate> -->` and `<!-- rdme:endif -->` only if the
+/// `cfg` predicate holds, e.g. `<!-- rdme:if feature = "std" -->`.  The directives themselves are
+/// removed.  Directives can be nested, and are ignored inside code blocks.
+pub struct DocTransformCfgDirectives {
+    cfg: CfgContext,
+}
+
+impl DocTransformCfgDirectives {
+    #[must_use]
+    pub fn new(cfg: CfgContext) -> DocTransformCfgDirectives {
+        DocTransformCfgDirectives { cfg }
+    }
+}
+
+impl DocTransform for DocTransformCfgDirectives {
+    type E = CfgDirectiveError;
+
+    fn transform(&self, doc: &Doc) -> Result<Doc, CfgDirectiveError> {
+        let source = doc.as_string();
+        let code_blocks = code_block_ranges(source);
+        let mut new_doc = String::with_capacity(source.len());
+        // The value of the predicate of each open `rdme:if`, with its line number.
+        let mut open: Vec<(bool, usize)> = Vec::new();
+        let mut removed_lines = false;
+        let mut offset = 0;
+
+        for (i, line) in source.split_inclusive('\n').enumerate() {
+            let line_number = i + 1;
+            let in_code_block = code_blocks.iter().any(|r| r.contains(&offset));
+
+            offset += line.len();
+
+            match directive(line).filter(|_| !in_code_block) {
+                Some(Directive::If(predicate)) => {
+                    let value = self.cfg.evaluate(predicate).ok_or_else(|| {
+                        CfgDirectiveError::InvalidPredicate {
+                            predicate: predicate.to_owned(),
+                            line_number,
+                        }
+                    })?;
+
+                    open.push((value, line_number));
+                    removed_lines = true;
+                }
+                Some(Directive::EndIf) => {
+                    open.pop().ok_or(CfgDirectiveError::UnexpectedEndif { line_number })?;
+                    removed_lines = true;
+                }
+                None if open.iter().all(|(value, _)| *value) => {
+                    // Avoid two blank lines where the removed lines were between blank lines.
+                    let double_blank_line =
+                        line.trim().is_empty() && (new_doc.is_empty() || new_doc.ends_with("\n\n"));
+
+                    if !(removed_lines && double_blank_line) {
+                        new_doc.push_str(line);
+                    }
+
+                    removed_lines = false;
+                }
+                None => removed_lines = true,
+            }
+        }
+
+        if let Some((_, line_number)) = open.first() {
+            return Err(CfgDirectiveError::UnmatchedIf { line_number: *line_number });
+        }
+
+        Ok(Doc::from_str(new_doc))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    fn transform(doc: &str) -> Result<Doc, CfgDirectiveError> {
+        let cfg = CfgContext::new(["std".to_owned()].into_iter().collect());
+
+        DocTransformCfgDirectives::new(cfg).transform(&Doc::from_str(doc))
+    }
+
+    #[test]
+    fn test_cfg_directives() {
+        let doc = indoc! { r#"
+            # Foo
+
+            <!-- rdme:if feature = "std" -->
+            Uses the standard library.
+            <!-- rdme:if feature = "nightly" -->
+            And nightly.
+            <!-- rdme:endif -->
+            <!-- rdme:endif -->
+
+            <!-- rdme:if not(feature = "std") -->
+            Does not use the standard library.
+
+            <!-- rdme:endif -->
+
+            ```markdown
+            <!-- rdme:if feature = "nightly" -->
+            ```
+            "#
+        };
+        let expected = indoc! { r#"
+            # Foo
+
+            Uses the standard library.
+
+            ```markdown
+            <!-- rdme:if feature = "nightly" -->
+            ```
+            "#
+        };
+
+        assert_eq!(transform(doc).unwrap().as_string(), expected);
+    }
+
+    #[test]
+    fn test_cfg_directives_errors() {
+        assert_eq!(
+            transform("Foo\n<!-- rdme:if feature -->\n").err(),
+            Some(CfgDirectiveError::UnmatchedIf { line_number: 2 })
+        );
+        assert_eq!(
+            transform("<!-- rdme:endif -->\n").err(),
+            Some(CfgDirectiveError::UnexpectedEndif { line_number: 1 })
+        );
+        assert_eq!(
+            transform("<!-- rdme:if foo(bar) -->\n<!-- rdme:endif -->\n").err(),
+            Some(CfgDirectiveError::InvalidPredicate {
+                predicate: "foo(bar)".to_owned(),
+                line_number: 1,
+            })
+        );
+    }
+}
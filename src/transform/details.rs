@@ -0,0 +1,279 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::transform::utils::apply_edits;
+use crate::transform::DocTransform;
+use crate::Doc;
+use std::convert::Infallible;
+use std::ops::Range;
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[non_exhaustive]
+pub enum DetailsAction {
+    /// Removes the `<details>` tags, keeping their content.  The `<summary>` becomes a heading of
+    /// the content.
+    Unwrap,
+    /// Removes the `<details>` blocks and their content.
+    Drop,
+}
+
+/// Handles collapsible `<details>` blocks, which are not supported by every markdown renderer
+/// (e.g. crates.io strips them).
+pub struct DocTransformDetails {
+    action: DetailsAction,
+}
+
+impl DocTransformDetails {
+    #[must_use]
+    pub fn new(action: DetailsAction) -> DocTransformDetails {
+        DocTransformDetails { action }
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+enum Tag {
+    DetailsOpen,
+    DetailsClose,
+    Summary { text: String },
+}
+
+/// Finds the `<details>`, `</details>`, and `<summary>…</summary>` tags in the html of
+/// `source[range]`.
+fn find_tags(source: &str, range: Range<usize>, tags: &mut Vec<(Range<usize>, Tag)>) {
+    let html = &source[range.clone()];
+    let mut i = 0;
+
+    while let Some(offset) = html[i..].find('<') {
+        let start = i + offset;
+        let rest = &html[start..];
+        let rest_lowercase = rest.to_ascii_lowercase();
+        let tag_end = |from: usize| rest[from..].find('>').map(|end| from + end + 1);
+
+        let tag = if rest_lowercase.starts_with("<details")
+            && rest[8..].starts_with(|c: char| c == '>' || c.is_whitespace())
+        {
+            tag_end(8).map(|end| (end, Tag::DetailsOpen))
+        } else if rest_lowercase.starts_with("</details>") {
+            Some((10, Tag::DetailsClose))
+        } else if rest_lowercase.starts_with("<summary>") {
+            rest_lowercase.find("</summary>").map(|end| {
+                let text = rest[9..end].trim().to_owned();
+
+                (end + 10, Tag::Summary { text })
+            })
+        } else {
+            None
+        };
+
+        match tag {
+            Some((len, tag)) => {
+                tags.push(((range.start + start)..(range.start + start + len), tag));
+                i = start + len;
+            }
+            None => i = start + 1,
+        }
+    }
+}
+
+/// Extends the range to the whole line, including the line terminator, if there is nothing else
+/// in the line.
+fn whole_line(source: &str, range: Range<usize>) -> Range<usize> {
+    let line_start = source[..range.start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = source[range.end..].find('\n').map_or(source.len(), |i| range.end + i + 1);
+
+    let alone_in_line = source[line_start..range.start].trim().is_empty()
+        && source[range.end..line_end].trim().is_empty();
+
+    match alone_in_line {
+        true => line_start..line_end,
+        false => range,
+    }
+}
+
+/// Merges adjacent removals and extends them so that they do not leave two blank lines where the
+/// removed text was.
+fn avoid_double_blank_lines(
+    source: &str,
+    mut edits: Vec<(Range<usize>, String)>,
+) -> Vec<(Range<usize>, String)> {
+    edits.sort_by_key(|(range, _)| range.start);
+
+    let mut merged: Vec<(Range<usize>, String)> = Vec::with_capacity(edits.len());
+
+    for (range, replacement) in edits {
+        match merged.last_mut() {
+            Some((last, last_replacement))
+                if last.end == range.start
+                    && last_replacement.is_empty()
+                    && replacement.is_empty() =>
+            {
+                last.end = range.end;
+            }
+            _ => merged.push((range, replacement)),
+        }
+    }
+
+    for (range, replacement) in &mut merged {
+        let preceded_by_blank_line = range.start == 0 || source[..range.start].ends_with("\n\n");
+
+        if replacement.is_empty() && preceded_by_blank_line && source[range.end..].starts_with('\n')
+        {
+            range.end += 1;
+        } else if replacement.is_empty() && range.end == source.len() && range.start > 0 {
+            // At the end of the documentation we remove the blank line before the removed text.
+            range.start -= usize::from(source[..range.start].ends_with("\n\n"));
+        }
+    }
+
+    merged
+}
+
+impl DocTransform for DocTransformDetails {
+    type E = Infallible;
+
+    fn transform(&self, doc: &Doc) -> Result<Doc, Infallible> {
+        use pulldown_cmark::{Event, Options, Parser};
+
+        let source = doc.as_string();
+        let mut tags: Vec<(Range<usize>, Tag)> = Vec::new();
+        // Start of every heading and its level.
+        let mut headings: Vec<(usize, usize)> = Vec::new();
+
+        for (event, range) in Parser::new_ext(source, Options::all()).into_offset_iter() {
+            match event {
+                Event::Html(_) | Event::InlineHtml(_) => find_tags(source, range, &mut tags),
+                Event::Start(pulldown_cmark::Tag::Heading { level, .. }) => {
+                    headings.push((range.start, level as usize));
+                }
+                _ => (),
+            }
+        }
+
+        let mut edits: Vec<(Range<usize>, String)> = Vec::new();
+        // Start of the open `<details>` blocks.
+        let mut open: Vec<usize> = Vec::new();
+
+        for (range, tag) in tags {
+            match (self.action, tag) {
+                (DetailsAction::Drop, Tag::DetailsOpen) => {
+                    open.push(whole_line(source, range).start);
+                }
+                (DetailsAction::Drop, Tag::DetailsClose) => {
+                    let end = whole_line(source, range).end;
+
+                    // Only the outermost block needs to be removed.
+                    if let (Some(start), true) = (open.pop(), open.is_empty()) {
+                        edits.push((start..end, String::new()));
+                    }
+                }
+                (DetailsAction::Drop, Tag::Summary { .. }) => (),
+                (DetailsAction::Unwrap, Tag::DetailsOpen) => {
+                    open.push(range.start);
+                    edits.push((whole_line(source, range), String::new()));
+                }
+                (DetailsAction::Unwrap, Tag::DetailsClose) => {
+                    open.pop();
+                    edits.push((whole_line(source, range), String::new()));
+                }
+                (DetailsAction::Unwrap, Tag::Summary { text }) => {
+                    let section_level = headings
+                        .iter()
+                        .take_while(|(start, _)| *start < range.start)
+                        .last()
+                        .map_or(0, |(_, level)| *level);
+                    let level = (section_level + open.len()).clamp(1, 6);
+                    let heading = format!("{} {text}\n", "#".repeat(level));
+
+                    edits.push((whole_line(source, range), heading));
+                }
+            }
+        }
+
+        Ok(Doc::from_str(apply_edits(source, avoid_double_blank_lines(source, edits))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    const DOC: &str = indoc! { r#"
+        # Usage
+
+        Use it.
+
+        <details>
+        <summary>Advanced usage</summary>
+
+        Use it *harder*.
+
+        <details open>
+        <summary>Expert usage</summary>
+
+        Don't.
+
+        </details>
+        </details>
+
+        ```html
+        <details>
+        ```
+
+        The end.
+        "#
+    };
+
+    #[test]
+    fn test_details_unwrap() {
+        let expected = indoc! { r#"
+            # Usage
+
+            Use it.
+
+            ## Advanced usage
+
+            Use it *harder*.
+
+            ### Expert usage
+
+            Don't.
+
+            ```html
+            <details>
+            ```
+
+            The end.
+            "#
+        };
+
+        let transform = DocTransformDetails::new(DetailsAction::Unwrap);
+        let new_doc = transform.transform(&Doc::from_str(DOC)).unwrap();
+
+        assert_eq!(new_doc.as_string(), expected);
+    }
+
+    #[test]
+    fn test_details_drop() {
+        let expected = indoc! { r#"
+            # Usage
+
+            Use it.
+
+            ```html
+            <details>
+            ```
+
+            The end.
+            "#
+        };
+
+        let transform = DocTransformDetails::new(DetailsAction::Drop);
+        let new_doc = transform.transform(&Doc::from_str(DOC)).unwrap();
+
+        assert_eq!(new_doc.as_string(), expected);
+    }
+}
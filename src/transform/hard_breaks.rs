@@ -0,0 +1,120 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::transform::utils::apply_edits;
+use crate::transform::DocTransform;
+use crate::Doc;
+use std::convert::Infallible;
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[non_exhaustive]
+pub enum HardBreakAction {
+    /// Replaces hard breaks with a `<br>`, which survives editors that strip trailing whitespace.
+    Html,
+    /// Replaces hard breaks with a plain line break, so the lines are joined in the same
+    /// paragraph.
+    Collapse,
+}
+
+/// Handles hard line breaks, i.e. a line ending in two or more spaces or in a backslash.  Trailing
+/// spaces are invisible and easily lost, e.g. by editors that trim them.
+pub struct DocTransformHardBreaks {
+    action: HardBreakAction,
+}
+
+impl DocTransformHardBreaks {
+    #[must_use]
+    pub fn new(action: HardBreakAction) -> DocTransformHardBreaks {
+        DocTransformHardBreaks { action }
+    }
+}
+
+impl DocTransform for DocTransformHardBreaks {
+    type E = Infallible;
+
+    fn transform(&self, doc: &Doc) -> Result<Doc, Infallible> {
+        use pulldown_cmark::{Event, Options, Parser};
+
+        let replacement = match self.action {
+            HardBreakAction::Html => "<br>\n",
+            HardBreakAction::Collapse => "\n",
+        };
+        let source = doc.as_string();
+        let edits = Parser::new_ext(source, Options::all())
+            .into_offset_iter()
+            .filter_map(|(event, range)| match event {
+                Event::HardBreak => Some((range, replacement.to_owned())),
+                _ => None,
+            })
+            .collect();
+
+        Ok(Doc::from_str(apply_edits(source, edits)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    const DOC: &str = indoc! { "
+        Roses are red,  
+        violets are blue,\\
+        the end.
+
+        ```
+        code  
+        ```
+
+        `code
+        span`
+        "
+    };
+
+    #[test]
+    fn test_hard_breaks_html() {
+        let expected = indoc! { "
+            Roses are red,<br>
+            violets are blue,<br>
+            the end.
+
+            ```
+            code  
+            ```
+
+            `code
+            span`
+            "
+        };
+
+        let transform = DocTransformHardBreaks::new(HardBreakAction::Html);
+        let new_doc = transform.transform(&Doc::from_str(DOC)).unwrap();
+
+        assert_eq!(new_doc.as_string(), expected);
+    }
+
+    #[test]
+    fn test_hard_breaks_collapse() {
+        let expected = indoc! { "
+            Roses are red,
+            violets are blue,
+            the end.
+
+            ```
+            code  
+            ```
+
+            `code
+            span`
+            "
+        };
+
+        let transform = DocTransformHardBreaks::new(HardBreakAction::Collapse);
+        let new_doc = transform.transform(&Doc::from_str(DOC)).unwrap();
+
+        assert_eq!(new_doc.as_string(), expected);
+    }
+}
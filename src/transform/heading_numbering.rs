@@ -0,0 +1,286 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::transform::utils::apply_edits;
+use crate::transform::DocTransform;
+use crate::Doc;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::fmt::Write;
+use std::ops::Range;
+
+/// Numbers the headings of the documentation (`1.`, `1.1.`, `1.2.`, `2.`, …).  Links to the
+/// headings, such as the ones in a table of contents, are updated to the new anchors.
+pub struct DocTransformHeadingNumbering;
+
+impl DocTransformHeadingNumbering {
+    #[must_use]
+    pub fn new() -> DocTransformHeadingNumbering {
+        DocTransformHeadingNumbering
+    }
+}
+
+struct Heading {
+    level: usize,
+    text: String,
+    /// Where the text of the heading starts in the source.
+    text_start: usize,
+}
+
+/// The anchor GitHub generates for a heading.
+fn heading_slug(text: &str) -> String {
+    text.to_lowercase()
+        .chars()
+        .filter(|&c| c.is_alphanumeric() || c == ' ' || c == '-' || c == '_')
+        .map(|c| if c == ' ' { '-' } else { c })
+        .collect()
+}
+
+/// Computes the anchors of the headings, where repeated anchors get a `-<n>` suffix.
+fn heading_anchors<'a>(texts: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+
+    texts
+        .map(|text| {
+            let slug = heading_slug(text);
+            let count = seen.entry(slug.clone()).or_insert(0);
+            let anchor = match *count {
+                0 => slug,
+                n => format!("{slug}-{n}"),
+            };
+
+            *count += 1;
+
+            anchor
+        })
+        .collect()
+}
+
+fn heading_numbers(headings: &[Heading]) -> Vec<String> {
+    let min_level = headings.iter().map(|h| h.level).min().unwrap_or(1);
+    let mut counters: Vec<usize> = Vec::with_capacity(6);
+
+    headings
+        .iter()
+        .map(|heading| {
+            let depth = heading.level - min_level + 1;
+
+            counters.resize(depth, 0);
+            counters[depth - 1] += 1;
+
+            counters.iter().fold(String::new(), |mut number, n| {
+                let _ = write!(number, "{n}.");
+                number
+            })
+        })
+        .collect()
+}
+
+impl DocTransform for DocTransformHeadingNumbering {
+    type E = Infallible;
+
+    fn transform(&self, doc: &Doc) -> Result<Doc, Infallible> {
+        use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+
+        let source = doc.as_string();
+        let mut iter = Parser::new_ext(source, Options::all()).into_offset_iter();
+
+        let mut headings: Vec<Heading> = Vec::new();
+        let mut current_heading: Option<(usize, String, Option<usize>)> = None;
+        // Links to anchors and the range in the source where the anchor can be found.
+        let mut anchor_links: Vec<(Range<usize>, String)> = Vec::new();
+
+        for (event, range) in iter.by_ref() {
+            match (event, &mut current_heading) {
+                (Event::Start(Tag::Heading { level, .. }), _) => {
+                    current_heading = Some((level as usize, String::new(), None));
+                }
+                (Event::End(TagEnd::Heading(_)), Some((level, text, Some(text_start)))) => {
+                    headings.push(Heading {
+                        level: *level,
+                        text: std::mem::take(text),
+                        text_start: *text_start,
+                    });
+                    current_heading = None;
+                }
+                (Event::End(TagEnd::Heading(_)), _) => current_heading = None,
+                (Event::Text(str) | Event::Code(str), Some((_, text, text_start))) => {
+                    text_start.get_or_insert(range.start);
+                    text.push_str(&str);
+                }
+                (_, Some((_, _, text_start))) => {
+                    text_start.get_or_insert(range.start);
+                }
+                (Event::Start(Tag::Link { dest_url, .. }), None) => {
+                    // Only inline links have the destination in their range.  Reference links
+                    // are updated in their definition.
+                    if let Some(anchor) = dest_url.strip_prefix('#') {
+                        if let Some(dest_start) = source[range.clone()].rfind("](") {
+                            anchor_links
+                                .push(((range.start + dest_start)..range.end, anchor.to_owned()));
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        for (_, link_def) in iter.reference_definitions().iter() {
+            if let Some(anchor) = link_def.dest.strip_prefix('#') {
+                anchor_links.push((link_def.span.clone(), anchor.to_owned()));
+            }
+        }
+
+        let numbers = heading_numbers(&headings);
+        let old_anchors = heading_anchors(headings.iter().map(|h| h.text.as_str()));
+        let new_texts: Vec<String> = headings
+            .iter()
+            .zip(&numbers)
+            .map(|(h, number)| format!("{number} {}", h.text))
+            .collect();
+        let new_anchors = heading_anchors(new_texts.iter().map(String::as_str));
+        let anchor_map: HashMap<&str, &str> = old_anchors
+            .iter()
+            .map(String::as_str)
+            .zip(new_anchors.iter().map(String::as_str))
+            .collect();
+
+        let mut edits: Vec<(Range<usize>, String)> = headings
+            .iter()
+            .zip(&numbers)
+            .map(|(heading, number)| (heading.text_start..heading.text_start, format!("{number} ")))
+            .collect();
+
+        for (range, anchor) in anchor_links {
+            let Some(new_anchor) = anchor_map.get(anchor.as_str()) else {
+                continue;
+            };
+            let old_anchor = format!("#{anchor}");
+
+            if let Some(i) = source[range.clone()].find(&old_anchor) {
+                let start = range.start + i;
+
+                edits.push((start..(start + old_anchor.len()), format!("#{new_anchor}")));
+            }
+        }
+
+        let new_doc = apply_edits(source, edits);
+
+        Ok(Doc::from_str(new_doc))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_heading_numbering() {
+        let doc = indoc! { r#"
+            # Contents
+
+            * [Usage](#usage)
+            * [Configuration][config]
+            * [Configuration file](#configuration-file)
+
+            # Usage
+
+            Run it.
+
+            ```rust
+            # fn main() {}
+            ```
+
+            # Configuration
+
+            ## Command line
+
+            ## Configuration file
+
+            ### `Cargo.toml`
+
+            ## Command line
+
+            # Other
+
+            [config]: #configuration
+            "#
+        };
+
+        let expected = indoc! { r#"
+            # 1. Contents
+
+            * [Usage](#2-usage)
+            * [Configuration][config]
+            * [Configuration file](#32-configuration-file)
+
+            # 2. Usage
+
+            Run it.
+
+            ```rust
+            # fn main() {}
+            ```
+
+            # 3. Configuration
+
+            ## 3.1. Command line
+
+            ## 3.2. Configuration file
+
+            ### 3.2.1. `Cargo.toml`
+
+            ## 3.3. Command line
+
+            # 4. Other
+
+            [config]: #3-configuration
+            "#
+        };
+
+        let transform = DocTransformHeadingNumbering::new();
+        let new_doc = transform.transform(&Doc::from_str(doc)).unwrap();
+
+        assert_eq!(new_doc.as_string(), expected);
+    }
+
+    #[test]
+    fn test_heading_numbering_setext() {
+        let doc = indoc! { r#"
+            See [the details](#details).
+
+            Usage
+            =====
+
+            Details
+            -------
+            "#
+        };
+        let expected = indoc! { r#"
+            See [the details](#11-details).
+
+            1. Usage
+            =====
+
+            1.1. Details
+            -------
+            "#
+        };
+
+        let transform = DocTransformHeadingNumbering::new();
+        let new_doc = transform.transform(&Doc::from_str(doc)).unwrap();
+
+        assert_eq!(new_doc.as_string(), expected);
+    }
+
+    #[test]
+    fn test_heading_anchors() {
+        let anchors = heading_anchors(["Usage", "1.2. Foo `bar`!", "Usage"].into_iter());
+
+        assert_eq!(anchors, vec!["usage", "12-foo-bar", "usage-1"]);
+    }
+}
@@ -4,7 +4,7 @@
  */
 
 use crate::markdown::Markdown;
-use crate::transform::intralinks::ItemPath;
+use crate::transform::intralinks::IntralinkTarget;
 use crate::utils::{MarkdownItemIterator, Span};
 use itertools::Itertools;
 use pulldown_cmark::{CowStr, TagEnd};
@@ -78,10 +78,11 @@ pub struct Link {
 }
 
 impl Link {
-    pub fn link_as_item_path(&self) -> Option<ItemPath> {
+    pub fn intralink_target(&self) -> Option<IntralinkTarget> {
         let link = self.split_link_fragment().0;
+        let backticked = self.raw_link.starts_with('`');
 
-        ItemPath::from_string(link)
+        IntralinkTarget::from_string(link, backticked)
     }
 
     fn split_link_fragment(&self) -> (&str, &str) {
@@ -140,11 +141,11 @@ pub enum MarkdownReferenceLink {
 }
 
 impl MarkdownReferenceLink {
-    fn new(text: String, label: String) -> MarkdownReferenceLink {
+    pub fn new(text: String, label: String) -> MarkdownReferenceLink {
         MarkdownReferenceLink::Normal { text, label: UniCase::unicode(label) }
     }
 
-    fn new_shortcut(text: String) -> MarkdownReferenceLink {
+    pub fn new_shortcut(text: String) -> MarkdownReferenceLink {
         MarkdownReferenceLink::Shortcut { text: UniCase::unicode(text) }
     }
 
@@ -172,7 +173,7 @@ impl Display for MarkdownReferenceLink {
     }
 }
 
-pub fn markdown_link_iterator(markdown: &Markdown) -> MarkdownItemIterator<MarkdownLink> {
+pub fn markdown_link_iterator(markdown: &Markdown) -> MarkdownItemIterator<'_, MarkdownLink> {
     use pulldown_cmark::{Event, LinkType, Options, Parser, Tag};
 
     let source = markdown.as_string();
@@ -296,7 +297,7 @@ fn parse_raw_reference_link_definition(
 
 pub fn markdown_reference_link_definition_iterator(
     markdown: &Markdown,
-) -> MarkdownItemIterator<MarkdownReferenceLinkDefinition> {
+) -> MarkdownItemIterator<'_, MarkdownReferenceLinkDefinition> {
     use pulldown_cmark::{Options, Parser};
 
     let source = markdown.as_string();
@@ -4,12 +4,14 @@
  */
 
 use crate::transform::intralinks::links::{
-    markdown_link_iterator, markdown_reference_link_definition_iterator, Link, MarkdownLink,
+    markdown_link_iterator, markdown_reference_link_definition_iterator, Link, MarkdownInlineLink,
+    MarkdownLink, MarkdownReferenceLink,
 };
 use crate::transform::DocTransform;
-use crate::Doc;
+use crate::{Dependency, Doc};
 use module_walker::walk_module_file;
-use std::collections::{HashMap, HashSet};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt;
 use std::hash::{Hash, Hasher};
 use std::path::{Path, PathBuf};
@@ -22,6 +24,7 @@ mod links;
 mod module_walker;
 
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum IntralinkError {
     #[error("IO error: {0}")]
     IOError(std::io::Error),
@@ -44,15 +47,197 @@ impl From<module_walker::ModuleWalkError> for IntralinkError {
 }
 
 #[derive(Default, Debug, PartialEq, Eq, Clone)]
+#[non_exhaustive]
 pub struct IntralinksDocsRsConfig {
     pub docs_rs_base_url: Option<String>,
     pub docs_rs_version: Option<String>,
+    /// Template of the documentation url of the crate, used instead of docs.rs.  It supports the
+    /// `{crate}`, `{version}`, and `{path}` placeholders.
+    pub docs_url_template: Option<String>,
+    /// Templates of the documentation url by crate name.  These take precedence over
+    /// `docs_url_template`.
+    pub docs_url_templates: BTreeMap<String, String>,
+    /// Dependencies of the crate, whose items can be linked to (e.g. `[serde::Serialize]`).  The
+    /// links point to the documentation of the version of the dependency the crate resolves to.
+    pub dependencies: Vec<Dependency>,
+}
+
+impl IntralinksDocsRsConfig {
+    #[must_use]
+    pub fn with_docs_rs_base_url(mut self, docs_rs_base_url: impl Into<String>) -> Self {
+        self.docs_rs_base_url = Some(docs_rs_base_url.into());
+        self
+    }
+
+    #[must_use]
+    pub fn with_docs_rs_version(mut self, docs_rs_version: impl Into<String>) -> Self {
+        self.docs_rs_version = Some(docs_rs_version.into());
+        self
+    }
+
+    #[must_use]
+    pub fn with_docs_url_template(mut self, docs_url_template: impl Into<String>) -> Self {
+        self.docs_url_template = Some(docs_url_template.into());
+        self
+    }
+
+    /// Adds a template of the documentation url of the crate `crate_name`.
+    #[must_use]
+    pub fn with_crate_docs_url_template(
+        mut self,
+        crate_name: impl Into<String>,
+        docs_url_template: impl Into<String>,
+    ) -> Self {
+        self.docs_url_templates.insert(crate_name.into(), docs_url_template.into());
+        self
+    }
+
+    #[must_use]
+    pub fn with_dependencies(mut self, dependencies: Vec<Dependency>) -> Self {
+        self.dependencies = dependencies;
+        self
+    }
+
+    fn explicitly_configured(&self, crate_name: &str) -> bool {
+        self.docs_rs_base_url.is_some()
+            || self.docs_url_template.is_some()
+            || self.docs_url_templates.contains_key(crate_name)
+    }
+
+    /// Uses the `html_root_url` of the crate (`#![doc(html_root_url = "...")]`) as the base of the
+    /// links to the crate's items, like rustdoc does for links from other crates.  This does nothing
+    /// if the docs url is explicitly configured.
+    pub fn set_html_root_url(&mut self, html_root_url: &str, crate_name: &str) {
+        let url = html_root_url.trim().trim_end_matches('/');
+
+        if !url.is_empty() && !self.explicitly_configured(crate_name) {
+            self.docs_url_template = Some(format!("{url}/{{path}}"));
+        }
+    }
+
+    /// Uses the documentation url of the package (`package.documentation` in `Cargo.toml`) as the
+    /// base of the links to the crate's items.  This does nothing if the docs url is explicitly
+    /// configured (or set by [`Self::set_html_root_url()`]) or if the documentation is hosted in
+    /// docs.rs.
+    pub fn set_package_documentation_url(&mut self, documentation_url: &str, crate_name: &str) {
+        if !self.explicitly_configured(crate_name) {
+            self.docs_url_template =
+                docs_url_template_from_documentation_url(documentation_url, crate_name);
+        }
+    }
+}
+
+/// Creates a docs url template from the url of the crate's documentation.
+///
+/// The url is expected to point to the crate's documentation page (e.g.
+/// `https://example.com/docs/foobini/`), in which case the links are relative to its parent.
+/// Otherwise we consider the url to be the root of the rustdoc output.
+fn docs_url_template_from_documentation_url(
+    documentation_url: &str,
+    crate_name: &str,
+) -> Option<String> {
+    let url = documentation_url.trim();
+
+    if url.is_empty() || url.starts_with("https://docs.rs") || url.starts_with("http://docs.rs") {
+        return None;
+    }
+
+    let package_name = crate_name.replace('-', "_");
+    let url = url.strip_suffix("index.html").unwrap_or(url).trim_end_matches('/');
+    let root = url.strip_suffix(&format!("/{package_name}")).unwrap_or(url);
+
+    Some(format!("{root}/{{path}}"))
 }
 
 #[derive(Default, Debug, PartialEq, Eq, Clone)]
+#[non_exhaustive]
 pub struct IntralinksConfig {
     pub docs_rs: IntralinksDocsRsConfig,
     pub strip_links: Option<bool>,
+    /// Type that `Self` refers to in links such as `[Self::new]`.  If this is not set these links
+    /// become code spans.
+    pub self_type: Option<String>,
+    /// Patterns of link targets, e.g. `__private::*`, that are left as they are.  A `*` matches any
+    /// sequence of characters.
+    pub ignore: Vec<String>,
+}
+
+impl IntralinksConfig {
+    #[must_use]
+    pub fn with_docs_rs(mut self, docs_rs: IntralinksDocsRsConfig) -> Self {
+        self.docs_rs = docs_rs;
+        self
+    }
+
+    #[must_use]
+    pub fn with_strip_links(mut self, strip_links: bool) -> Self {
+        self.strip_links = Some(strip_links);
+        self
+    }
+
+    #[must_use]
+    pub fn with_self_type(mut self, self_type: impl Into<String>) -> Self {
+        self.self_type = Some(self_type.into());
+        self
+    }
+
+    #[must_use]
+    pub fn with_ignore(mut self, ignore: Vec<String>) -> Self {
+        self.ignore = ignore;
+        self
+    }
+
+    /// Uses the options of `fallback` for the options that are not set in `self`.  The docs url
+    /// templates by crate and the ignored patterns of both are kept, with the ones of `self` taking
+    /// precedence.  Dependencies are not merged, since they come from the project.
+    #[must_use]
+    pub fn or(self, fallback: IntralinksConfig) -> IntralinksConfig {
+        let mut docs_url_templates = fallback.docs_rs.docs_url_templates;
+        docs_url_templates.extend(self.docs_rs.docs_url_templates);
+
+        let mut ignore = fallback.ignore;
+        ignore.extend(self.ignore);
+
+        IntralinksConfig {
+            docs_rs: IntralinksDocsRsConfig {
+                docs_rs_base_url: self
+                    .docs_rs
+                    .docs_rs_base_url
+                    .or(fallback.docs_rs.docs_rs_base_url),
+                docs_rs_version: self.docs_rs.docs_rs_version.or(fallback.docs_rs.docs_rs_version),
+                docs_url_template: self
+                    .docs_rs
+                    .docs_url_template
+                    .or(fallback.docs_rs.docs_url_template),
+                docs_url_templates,
+                dependencies: self.docs_rs.dependencies,
+            },
+            strip_links: self.strip_links.or(fallback.strip_links),
+            self_type: self.self_type.or(fallback.self_type),
+            ignore,
+        }
+    }
+}
+
+/// Whether `text` matches the `pattern`, where `*` matches any sequence of characters.
+fn matches_pattern(pattern: &str, text: &str) -> bool {
+    let Some((prefix, rest)) = pattern.split_once('*') else {
+        return pattern == text;
+    };
+    let Some(mut text) = text.strip_prefix(prefix) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = rest.split('*').collect();
+    let suffix = parts.pop().unwrap_or_default();
+
+    for part in parts {
+        match text.find(part) {
+            Some(i) => text = &text[i + part.len()..],
+            None => return false,
+        }
+    }
+
+    text.len() >= suffix.len() && text.ends_with(suffix)
 }
 
 pub struct DocTransformIntralinks<F> {
@@ -87,42 +272,145 @@ where
 {
     type E = IntralinkError;
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "intralinks",
+            level = "debug",
+            skip_all,
+            fields(crate_name = %self.crate_name)
+        )
+    )]
     fn transform(&self, doc: &Doc) -> Result<Doc, IntralinkError> {
-        let symbols: HashSet<ItemPath> = extract_markdown_intralink_symbols(doc);
+        let doc = &rewrite_self_links(doc, self.config.self_type.as_deref());
+        let dependencies = &self.config.docs_rs.dependencies;
+        let targets: HashSet<IntralinkTarget> = extract_markdown_intralink_targets(doc)
+            .into_iter()
+            .map(|target| target.with_dependencies(dependencies))
+            .collect();
 
         // If there are no intralinks in the doc don't even bother doing anything else.
-        if symbols.is_empty() {
+        if targets.is_empty() {
             return Ok(doc.clone());
         }
 
-        // We only load symbols type information when we need them.
-        let symbols_type = match self.config.strip_links.unwrap_or(false) {
-            false => load_symbols_type(&self.entrypoint, &symbols, &self.emit_warning)?,
-            true => HashMap::new(),
+        // We only load symbols type information when we need them.  When stripping links we still
+        // need them to know if a link that might not be an intralink (e.g. `[foo](Foo)`) refers to
+        // an item.
+        let need_symbols_type = match self.config.strip_links.unwrap_or(false) {
+            false => true,
+            true => targets.iter().any(|target| !target.explicit),
+        };
+        let (symbols_type, aliases) = match need_symbols_type {
+            true => {
+                let symbols: HashSet<ItemPath> = intralink_symbols(&targets);
+
+                load_symbols_type(&self.entrypoint, &symbols, dependencies, &self.emit_warning)?
+            }
+            false => (HashMap::new(), HashMap::new()),
         };
 
-        let doc =
-            rewrite_links(doc, &symbols_type, &self.crate_name, &self.emit_warning, &self.config);
+        let doc = rewrite_links(
+            doc,
+            &symbols_type,
+            &aliases,
+            &self.crate_name,
+            &self.emit_warning,
+            &self.config,
+        );
 
         Ok(doc)
     }
 }
 
+/// What is needed to resolve intralinks of markdown that is not a crate's documentation, e.g. the
+/// chapters of a book or a changelog, with [`transform_intra_doc_links()`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[non_exhaustive]
+pub struct LinkContext {
+    /// Name of the crate the links refer to.
+    pub crate_name: String,
+    /// Entrypoint of the crate, e.g. `src/lib.rs`, where the items linked to are looked up.
+    pub entrypoint: PathBuf,
+    pub config: IntralinksConfig,
+}
+
+impl LinkContext {
+    #[must_use]
+    pub fn new(crate_name: impl Into<String>, entrypoint: impl Into<PathBuf>) -> LinkContext {
+        LinkContext {
+            crate_name: crate_name.into(),
+            entrypoint: entrypoint.into(),
+            config: IntralinksConfig::default(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_config(mut self, config: IntralinksConfig) -> LinkContext {
+        self.config = config;
+        self
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[non_exhaustive]
+pub struct TransformResult {
+    pub markdown: String,
+    /// Warnings about the links that could not be resolved.
+    pub warnings: Vec<String>,
+}
+
+/// Rewrites the intralinks of `markdown` into links to the crate's documentation, just like
+/// cargo rdme does for the crate's documentation, without any other transformation.  This lets
+/// other tools, like mdBook preprocessors or changelog generators, reuse the resolution of links.
+///
+/// Line terminators are normalized to `\n`.
+pub fn transform_intra_doc_links(
+    markdown: &str,
+    ctx: &LinkContext,
+) -> Result<TransformResult, IntralinkError> {
+    let warnings: RefCell<Vec<String>> = RefCell::new(Vec::new());
+    let transform = DocTransformIntralinks::new(
+        ctx.crate_name.as_str(),
+        &ctx.entrypoint,
+        |warning| warnings.borrow_mut().push(warning.to_owned()),
+        Some(ctx.config.clone()),
+    );
+    let doc = transform.transform(&Doc::from_str(markdown))?;
+    let mut markdown_out = doc.as_string().to_owned();
+
+    // A newline is added to the end of the markdown if it has none.
+    if !markdown.ends_with(['\n', '\r']) {
+        markdown_out.pop();
+    }
+
+    Ok(TransformResult { markdown: markdown_out, warnings: warnings.into_inner() })
+}
+
 fn rewrite_links(
     doc: &Doc,
     symbols_type: &HashMap<ItemPath, SymbolType>,
+    aliases: &DocAliases,
     crate_name: &str,
     emit_warning: &impl Fn(&str),
     config: &IntralinksConfig,
 ) -> Doc {
     let RewriteReferenceLinksResult { doc, reference_links_to_remove } =
-        rewrite_reference_links_definitions(doc, symbols_type, crate_name, emit_warning, config);
+        rewrite_reference_links_definitions(
+            doc,
+            symbols_type,
+            aliases,
+            crate_name,
+            emit_warning,
+            config,
+        );
 
     // TODO Refactor link removal code so that it all happens in a new phase and not inside the
     //      functions above.
     rewrite_markdown_links(
         &doc,
         symbols_type,
+        aliases,
         crate_name,
         emit_warning,
         config,
@@ -216,6 +504,18 @@ impl ItemPath {
         self
     }
 
+    fn crate_root(&self) -> ItemPath {
+        match self.anchor {
+            ItemPathAnchor::Root => {
+                let crate_name =
+                    self.path_components().next().expect("a root path should not be empty");
+
+                ItemPath::root(crate_name)
+            }
+            ItemPathAnchor::Crate => ItemPath::new(ItemPathAnchor::Crate),
+        }
+    }
+
     fn all_ancestors(&self) -> impl Iterator<Item = ItemPath> {
         let first_ancestor = self.clone().parent();
 
@@ -260,9 +560,334 @@ impl fmt::Debug for ItemPath {
     }
 }
 
+/// Crates of the standard library that can be referred to by name, e.g. `std::fs::copy`.
+const STD_LIB_CRATES: [&str; 5] = ["std", "core", "alloc", "proc_macro", "test"];
+
+/// Items of the standard prelude, i.e. the items that are in scope without an explicit `use`.
+/// These are mapped to the module where they are defined.
+const PRELUDE: [(&str, &str, SymbolType); 47] = [
+    ("Copy", "::core::marker", SymbolType::Trait),
+    ("Send", "::core::marker", SymbolType::Trait),
+    ("Sized", "::core::marker", SymbolType::Trait),
+    ("Sync", "::core::marker", SymbolType::Trait),
+    ("Unpin", "::core::marker", SymbolType::Trait),
+    ("Drop", "::core::ops", SymbolType::Trait),
+    ("Fn", "::core::ops", SymbolType::Trait),
+    ("FnMut", "::core::ops", SymbolType::Trait),
+    ("FnOnce", "::core::ops", SymbolType::Trait),
+    ("drop", "::core::mem", SymbolType::Fn),
+    ("Box", "::alloc::boxed", SymbolType::Struct),
+    ("ToOwned", "::alloc::borrow", SymbolType::Trait),
+    ("Clone", "::core::clone", SymbolType::Trait),
+    ("PartialEq", "::core::cmp", SymbolType::Trait),
+    ("PartialOrd", "::core::cmp", SymbolType::Trait),
+    ("Eq", "::core::cmp", SymbolType::Trait),
+    ("Ord", "::core::cmp", SymbolType::Trait),
+    ("AsRef", "::core::convert", SymbolType::Trait),
+    ("AsMut", "::core::convert", SymbolType::Trait),
+    ("Into", "::core::convert", SymbolType::Trait),
+    ("From", "::core::convert", SymbolType::Trait),
+    ("TryFrom", "::core::convert", SymbolType::Trait),
+    ("TryInto", "::core::convert", SymbolType::Trait),
+    ("Default", "::core::default", SymbolType::Trait),
+    ("Iterator", "::core::iter", SymbolType::Trait),
+    ("Extend", "::core::iter", SymbolType::Trait),
+    ("IntoIterator", "::core::iter", SymbolType::Trait),
+    ("DoubleEndedIterator", "::core::iter", SymbolType::Trait),
+    ("ExactSizeIterator", "::core::iter", SymbolType::Trait),
+    ("FromIterator", "::core::iter", SymbolType::Trait),
+    ("Option", "::core::option", SymbolType::Enum),
+    ("Result", "::core::result", SymbolType::Enum),
+    ("String", "::alloc::string", SymbolType::Struct),
+    ("ToString", "::alloc::string", SymbolType::Trait),
+    ("Vec", "::alloc::vec", SymbolType::Struct),
+    ("assert", "::std", SymbolType::Macro),
+    ("assert_eq", "::std", SymbolType::Macro),
+    ("assert_ne", "::std", SymbolType::Macro),
+    ("dbg", "::std", SymbolType::Macro),
+    ("eprintln", "::std", SymbolType::Macro),
+    ("format", "::std", SymbolType::Macro),
+    ("matches", "::std", SymbolType::Macro),
+    ("panic", "::std", SymbolType::Macro),
+    ("println", "::std", SymbolType::Macro),
+    ("todo", "::std", SymbolType::Macro),
+    ("vec", "::std", SymbolType::Macro),
+    ("write", "::std", SymbolType::Macro),
+];
+
+/// Primitive types, documented in `https://doc.rust-lang.org/stable/std/primitive.<name>.html`.
+const PRIMITIVES: [&str; 27] = [
+    "array",
+    "bool",
+    "char",
+    "f16",
+    "f32",
+    "f64",
+    "f128",
+    "fn",
+    "i8",
+    "i16",
+    "i32",
+    "i64",
+    "i128",
+    "isize",
+    "never",
+    "pointer",
+    "reference",
+    "slice",
+    "str",
+    "tuple",
+    "u8",
+    "u16",
+    "u32",
+    "u64",
+    "u128",
+    "unit",
+    "usize",
+];
+
+/// Keywords, documented in `https://doc.rust-lang.org/stable/std/keyword.<name>.html`.
+const KEYWORDS: [&str; 38] = [
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "type", "union",
+    "unsafe", "use", "where",
+];
+
+/// Disambiguators rustdoc accepts as a prefix of an intralink, e.g. `struct@Foo`.
+const DISAMBIGUATORS: [&str; 23] = [
+    "struct",
+    "enum",
+    "trait",
+    "union",
+    "type",
+    "mod",
+    "module",
+    "macro",
+    "const",
+    "constant",
+    "fn",
+    "function",
+    "method",
+    "static",
+    "value",
+    "field",
+    "variant",
+    "derive",
+    "attr",
+    "prim",
+    "primitive",
+    "keyword",
+    "tyalias",
+];
+
+fn prelude_item_path(name: &str) -> Option<ItemPath> {
+    PRELUDE
+        .iter()
+        .find(|(item_name, _, _)| *item_name == name)
+        .and_then(|(item_name, module, _)| ItemPath::from_string(module).map(|m| m.join(item_name)))
+}
+
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// Removes generic arguments from a path, e.g. `Vec<T>::new` becomes `Vec::new`.
+///
+/// Returns `None` if the generic arguments are not balanced.
+fn strip_generics(path: &str) -> Option<String> {
+    let mut stripped = String::with_capacity(path.len());
+    let mut depth: usize = 0;
+
+    for c in path.chars() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth = depth.checked_sub(1)?,
+            c if depth == 0 => stripped.push(c),
+            _ => (),
+        }
+    }
+
+    (depth == 0).then_some(stripped)
+}
+
+/// A language builtin that is documented in the standard library but is not an item.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+enum Builtin {
+    Primitive(&'static str),
+    Keyword(&'static str),
+}
+
+impl Builtin {
+    fn from_name(name: &str, disambiguator: Option<&str>) -> Option<Builtin> {
+        let primitive = PRIMITIVES.iter().find(|p| **p == name).map(|p| Builtin::Primitive(p));
+        let keyword = KEYWORDS.iter().find(|k| **k == name).map(|k| Builtin::Keyword(k));
+
+        match disambiguator {
+            None => primitive.or(keyword),
+            Some("prim" | "primitive") => primitive,
+            Some("keyword") => keyword,
+            Some(_) => None,
+        }
+    }
+
+    fn documentation_url(self, fragment: Option<&str>) -> String {
+        let page = match self {
+            Builtin::Primitive(name) => format!("primitive.{name}.html"),
+            Builtin::Keyword(name) => format!("keyword.{name}.html"),
+        };
+
+        format!("https://doc.rust-lang.org/stable/std/{page}{}", fragment.unwrap_or(""))
+    }
+}
+
+/// The target of an intralink, such as `crate::foo::Bar`, `Bar`, or `Vec::push`.
+///
+/// A link can refer to different items depending on what is in scope, so we keep all the paths the
+/// link can resolve to.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct IntralinkTarget {
+    /// Paths the link can refer to, by order of precedence.
+    candidates: Vec<ItemPath>,
+    /// Whether the link is unmistakably an intralink (e.g. `crate::Foo` or `` `Foo` ``), as opposed
+    /// to something that can also be a relative url (e.g. `LICENSE`).
+    explicit: bool,
+    /// The primitive type or keyword the link refers to, if no item matches the path.
+    builtin: Option<Builtin>,
+    /// The path as written in the documentation.
+    path: String,
+}
+
+impl IntralinkTarget {
+    fn from_string(s: &str, backticked: bool) -> Option<IntralinkTarget> {
+        let mut explicit = backticked;
+        let mut path = s.trim();
+        let mut disambiguator = None;
+
+        if let Some((d, rest)) = path.split_once('@') {
+            if !DISAMBIGUATORS.contains(&d) {
+                return None;
+            }
+
+            path = rest;
+            disambiguator = Some(d);
+            explicit = true;
+        }
+
+        // Primitives and keywords are not items, so there is nothing else to resolve.
+        if let Some("prim" | "primitive" | "keyword") = disambiguator {
+            let builtin = Some(Builtin::from_name(path, disambiguator)?);
+
+            return Some(IntralinkTarget {
+                candidates: Vec::new(),
+                explicit,
+                builtin,
+                path: path.to_owned(),
+            });
+        }
+
+        if let Some(p) = path.strip_suffix("()").or_else(|| path.strip_suffix('!')) {
+            path = p;
+            explicit = true;
+        }
+
+        let path: String = strip_generics(path)?;
+
+        if let Some(item_path) = ItemPath::from_string(&path) {
+            let candidates = vec![item_path];
+
+            return Some(IntralinkTarget { candidates, explicit: true, builtin: None, path });
+        }
+
+        // Crate level documentation lives in the crate root, so `self` is the crate itself.
+        let relative_path = path.strip_prefix("self::").unwrap_or(&path);
+        let components: Vec<&str> = relative_path.split("::").collect();
+
+        if !components.iter().all(|c| is_identifier(c)) {
+            return None;
+        }
+
+        let join_components = |item_path: ItemPath, components: &[&str]| {
+            components.iter().fold(item_path, ItemPath::join)
+        };
+
+        // Items defined in the crate take precedence, since they shadow the prelude.
+        let mut candidates =
+            vec![join_components(ItemPath::new(ItemPathAnchor::Crate), &components)];
+
+        if STD_LIB_CRATES.contains(&components[0]) {
+            candidates.push(join_components(ItemPath::new(ItemPathAnchor::Root), &components));
+        }
+
+        if let Some(prelude_item) = prelude_item_path(components[0]) {
+            candidates.push(join_components(prelude_item, &components[1..]));
+        }
+
+        let builtin = match components.as_slice() {
+            [name] => Builtin::from_name(name, disambiguator),
+            _ => None,
+        };
+
+        Some(IntralinkTarget {
+            candidates,
+            explicit: explicit || components.len() > 1,
+            builtin,
+            path,
+        })
+    }
+
+    /// Adds the item of a dependency the path can refer to as a candidate, e.g. `serde::Serialize`.
+    /// Items defined in the crate take precedence, since they shadow the dependencies.
+    fn with_dependencies(mut self, dependencies: &[Dependency]) -> IntralinkTarget {
+        let relative_path = self.path.strip_prefix("self::").unwrap_or(&self.path);
+        let mut components = relative_path.split("::");
+        let dependency_name = components.next().unwrap_or_default();
+
+        if dependencies.iter().any(|dependency| dependency.name == dependency_name) {
+            let candidate =
+                components.fold(ItemPath::root(dependency_name), |path, c| path.join(&c));
+
+            if !self.candidates.contains(&candidate) {
+                let position = usize::from(!self.candidates.is_empty());
+
+                self.candidates.insert(position, candidate);
+                self.explicit = true;
+            }
+        }
+
+        self
+    }
+
+    /// Adds the item a doc alias stands for after each candidate that is an alias, e.g.
+    /// `crate::Foo` after `crate::Bar` for `#[doc(alias = "Bar")] struct Foo`.  Items named like the
+    /// alias take precedence.
+    fn with_aliases(mut self, aliases: &DocAliases) -> IntralinkTarget {
+        self.candidates = self
+            .candidates
+            .into_iter()
+            .flat_map(|candidate| {
+                let item = aliases.get(&candidate).cloned();
+
+                std::iter::once(candidate).chain(item)
+            })
+            .collect();
+
+        self
+    }
+}
+
+/// Doc aliases, e.g. `crate::Bar` for `#[doc(alias = "Bar")] struct Foo`, with the path of the item
+/// they stand for.
+type DocAliases = HashMap<ItemPath, ItemPath>;
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum ImplSymbolType {
     Method,
+    /// A trait method without a default implementation.
+    RequiredMethod,
     Const,
     Type,
 }
@@ -277,10 +902,14 @@ pub enum SymbolType {
     Type,
     Mod,
     Macro,
+    Derive,
+    Attr,
     Const,
     Fn,
     Static,
     ImplItem(ImplSymbolType),
+    StructField,
+    EnumVariant,
 }
 
 impl SymbolType {
@@ -301,6 +930,8 @@ impl SymbolType {
             | SymbolType::Type
             | SymbolType::Mod
             | SymbolType::Macro
+            | SymbolType::Derive
+            | SymbolType::Attr
             | SymbolType::Const
             | SymbolType::Fn
             | SymbolType::Static => {
@@ -309,7 +940,7 @@ impl SymbolType {
                 });
                 Some(p)
             }
-            SymbolType::ImplItem(_) => {
+            SymbolType::ImplItem(_) | SymbolType::StructField | SymbolType::EnumVariant => {
                 let p = path
                     .clone()
                     .parent()
@@ -333,7 +964,13 @@ fn symbols_type_impl_block(
     use syn::{ImplItem, Type, TypePath};
 
     if let Type::Path(TypePath { qself: None, path }) = &*impl_block.self_ty {
-        if let Some(self_ident) = path.get_ident().map(ToString::to_string) {
+        // We ignore the generic arguments of the type, e.g. `impl<T> Foo<T>` is an impl of `Foo`.
+        let self_ident = match path.segments.len() {
+            1 => Some(path.segments[0].ident.to_string()),
+            _ => None,
+        };
+
+        if let Some(self_ident) = self_ident {
             let self_path = module.clone().join(&self_ident);
 
             return impl_block
@@ -365,21 +1002,114 @@ fn symbols_type_impl_block(
     Vec::new()
 }
 
+fn symbols_type_with_fields(
+    path: ItemPath,
+    symbol_type: SymbolType,
+    fields: &syn::Fields,
+) -> Vec<(ItemPath, SymbolType)> {
+    let fields = fields
+        .iter()
+        .filter_map(|field| field.ident.as_ref())
+        .map(|ident| (path.clone().join(ident), SymbolType::StructField))
+        .collect::<Vec<_>>();
+
+    std::iter::once((path, symbol_type)).chain(fields).collect()
+}
+
+fn symbols_type_trait(path: ItemPath, item_trait: &syn::ItemTrait) -> Vec<(ItemPath, SymbolType)> {
+    use syn::TraitItem;
+
+    let items = item_trait
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            TraitItem::Fn(f) => {
+                let typ = match f.default {
+                    Some(_) => ImplSymbolType::Method,
+                    None => ImplSymbolType::RequiredMethod,
+                };
+
+                Some((f.sig.ident.to_string(), typ))
+            }
+            TraitItem::Const(c) => Some((c.ident.to_string(), ImplSymbolType::Const)),
+            TraitItem::Type(t) => Some((t.ident.to_string(), ImplSymbolType::Type)),
+            _ => None,
+        })
+        .map(|(ident, typ)| (path.clone().join(&ident), SymbolType::ImplItem(typ)))
+        .collect::<Vec<_>>();
+
+    std::iter::once((path, SymbolType::Trait)).chain(items).collect()
+}
+
+fn has_attribute(attributes: &[syn::Attribute], name: &str) -> bool {
+    attributes.iter().any(|attribute| attribute.path().is_ident(name))
+}
+
+/// Returns the name and type of the macro defined by a procedural macro function, if it is one.
+fn proc_macro_symbol_type(f: &syn::ItemFn) -> Option<(String, SymbolType)> {
+    f.attrs.iter().find_map(|attribute| {
+        let path = attribute.path();
+
+        if path.is_ident("proc_macro") {
+            Some((f.sig.ident.to_string(), SymbolType::Macro))
+        } else if path.is_ident("proc_macro_attribute") {
+            Some((f.sig.ident.to_string(), SymbolType::Attr))
+        } else if path.is_ident("proc_macro_derive") {
+            // The derive name is the first argument, e.g. `#[proc_macro_derive(Foo, attributes(foo))]`.
+            let args = attribute
+                .parse_args_with(
+                    syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated,
+                )
+                .ok()?;
+            let name =
+                args.first().and_then(|meta| meta.path().get_ident()).map(ToString::to_string);
+
+            name.map(|name| (name, SymbolType::Derive))
+        } else {
+            None
+        }
+    })
+}
+
 fn item_symbols_type(module: &ItemPath, item: &Item) -> Vec<(ItemPath, SymbolType)> {
     let item_path = |ident: &syn::Ident| module.clone().join(ident);
 
     let (path, symbol_type) = match item {
-        Item::Enum(e) => (item_path(&e.ident), SymbolType::Enum),
-        Item::Struct(s) => (item_path(&s.ident), SymbolType::Struct),
-        Item::Trait(t) => (item_path(&t.ident), SymbolType::Trait),
-        Item::Union(u) => (item_path(&u.ident), SymbolType::Union),
+        Item::Enum(e) => {
+            let path = item_path(&e.ident);
+            let variants = e
+                .variants
+                .iter()
+                .map(|variant| (path.clone().join(&variant.ident), SymbolType::EnumVariant))
+                .collect::<Vec<_>>();
+
+            return std::iter::once((path, SymbolType::Enum)).chain(variants).collect();
+        }
+        Item::Struct(s) => {
+            return symbols_type_with_fields(item_path(&s.ident), SymbolType::Struct, &s.fields);
+        }
+        Item::Trait(t) => {
+            return symbols_type_trait(item_path(&t.ident), t);
+        }
+        Item::Union(u) => {
+            let fields = syn::Fields::Named(u.fields.clone());
+
+            return symbols_type_with_fields(item_path(&u.ident), SymbolType::Union, &fields);
+        }
         Item::Type(t) => (item_path(&t.ident), SymbolType::Type),
         Item::Mod(m) => (item_path(&m.ident), SymbolType::Mod),
-        Item::Macro(syn::ItemMacro { ident: Some(ident), .. }) => {
-            (item_path(ident), SymbolType::Macro)
+        Item::Macro(syn::ItemMacro { ident: Some(ident), attrs, .. }) => {
+            // Exported `macro_rules!` macros live in the root of the crate.
+            match has_attribute(attrs, "macro_export") {
+                true => (module.crate_root().join(ident), SymbolType::Macro),
+                false => (item_path(ident), SymbolType::Macro),
+            }
         }
         Item::Const(c) => (item_path(&c.ident), SymbolType::Const),
-        Item::Fn(f) => (item_path(&f.sig.ident), SymbolType::Fn),
+        Item::Fn(f) => match proc_macro_symbol_type(f) {
+            Some((name, symbol_type)) => (module.clone().join(&name), symbol_type),
+            None => (item_path(&f.sig.ident), SymbolType::Fn),
+        },
         Item::Static(s) => (item_path(&s.ident), SymbolType::Static),
         Item::Impl(impl_block) => {
             return symbols_type_impl_block(module, impl_block);
@@ -391,6 +1121,123 @@ fn item_symbols_type(module: &ItemPath, item: &Item) -> Vec<(ItemPath, SymbolTyp
     vec![(path, symbol_type)]
 }
 
+/// The aliases of `#[doc(alias = "…")]` and `#[doc(alias("…", "…"))]` attributes.
+fn doc_aliases(attributes: &[syn::Attribute]) -> Vec<String> {
+    let mut aliases = Vec::new();
+
+    for attribute in attributes.iter().filter(|attribute| attribute.path().is_ident("doc")) {
+        // Doc comments (`#[doc = "…"]`) are not lists, so they fail to parse and are skipped.
+        let _ = attribute.parse_nested_meta(|meta| {
+            if meta.path.is_ident("alias") {
+                match meta.input.peek(syn::Token![=]) {
+                    true => aliases.push(meta.value()?.parse::<syn::LitStr>()?.value()),
+                    false => {
+                        let content;
+                        syn::parenthesized!(content in meta.input);
+                        let names = content.parse_terminated(
+                            <syn::LitStr as syn::parse::Parse>::parse,
+                            syn::Token![,],
+                        )?;
+
+                        aliases.extend(names.iter().map(syn::LitStr::value));
+                    }
+                }
+            } else if meta.input.peek(syn::Token![=]) {
+                meta.value()?.parse::<syn::Expr>()?;
+            } else if meta.input.peek(syn::token::Paren) {
+                let _content;
+                syn::parenthesized!(_content in meta.input);
+            }
+
+            Ok(())
+        });
+    }
+
+    aliases
+}
+
+/// The attributes of an item, if it is one we can link to.
+fn item_attributes(item: &Item) -> Option<&[syn::Attribute]> {
+    let attributes = match item {
+        Item::Enum(e) => &e.attrs,
+        Item::Struct(s) => &s.attrs,
+        Item::Trait(t) => &t.attrs,
+        Item::Union(u) => &u.attrs,
+        Item::Type(t) => &t.attrs,
+        Item::Mod(m) => &m.attrs,
+        Item::Macro(m) => &m.attrs,
+        Item::Const(c) => &c.attrs,
+        Item::Fn(f) => &f.attrs,
+        Item::Static(s) => &s.attrs,
+        _ => return None,
+    };
+
+    Some(attributes)
+}
+
+/// The doc aliases of an item and of its members (e.g. `#[doc(alias = "Bar")]`), with the path of
+/// the item each alias stands for.
+fn item_aliases(module: &ItemPath, item: &Item) -> Vec<(ItemPath, ItemPath)> {
+    use syn::{ImplItem, TraitItem};
+
+    let Some((first_symbol, _)) = item_symbols_type(module, item).into_iter().next() else {
+        return Vec::new();
+    };
+    let aliases_of = |path: ItemPath, attributes: &[syn::Attribute]| {
+        let parent = path.clone().parent();
+
+        doc_aliases(attributes)
+            .into_iter()
+            .filter_map(|alias| Some((parent.clone()?.join(&alias), path.clone())))
+            .collect::<Vec<_>>()
+    };
+
+    // The symbols of an impl block are its members, so its first symbol is a member of the type.
+    let (item_path, mut aliases) = match item_attributes(item) {
+        Some(attributes) => (Some(first_symbol.clone()), aliases_of(first_symbol, attributes)),
+        None => (first_symbol.parent(), Vec::new()),
+    };
+    let Some(item_path) = item_path else {
+        return aliases;
+    };
+    let members: Vec<(String, &[syn::Attribute])> = match item {
+        Item::Enum(e) => {
+            e.variants.iter().map(|v| (v.ident.to_string(), v.attrs.as_slice())).collect()
+        }
+        Item::Struct(syn::ItemStruct { fields, .. }) => fields
+            .iter()
+            .filter_map(|f| Some((f.ident.as_ref()?.to_string(), f.attrs.as_slice())))
+            .collect(),
+        Item::Trait(t) => t
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                TraitItem::Fn(f) => Some((f.sig.ident.to_string(), f.attrs.as_slice())),
+                TraitItem::Const(c) => Some((c.ident.to_string(), c.attrs.as_slice())),
+                TraitItem::Type(t) => Some((t.ident.to_string(), t.attrs.as_slice())),
+                _ => None,
+            })
+            .collect(),
+        Item::Impl(impl_block) => impl_block
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                ImplItem::Fn(f) => Some((f.sig.ident.to_string(), f.attrs.as_slice())),
+                ImplItem::Const(c) => Some((c.ident.to_string(), c.attrs.as_slice())),
+                ImplItem::Type(t) => Some((t.ident.to_string(), t.attrs.as_slice())),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    for (name, attributes) in members {
+        aliases.extend(aliases_of(item_path.clone().join(&name), attributes));
+    }
+
+    aliases
+}
+
 fn is_cfg_test(attribute: &syn::Attribute) -> bool {
     let test_attribute: syn::Attribute = syn::parse_quote!(#[cfg(test)]);
 
@@ -400,11 +1247,22 @@ fn is_cfg_test(attribute: &syn::Attribute) -> bool {
 fn visit_module_item(
     save_symbol: impl Fn(&ItemPath) -> bool,
     symbols_type: &mut HashMap<ItemPath, SymbolType>,
+    aliases: &mut DocAliases,
     module: &ItemPath,
     item: &Item,
 ) {
+    // The items that aliases used in the documentation stand for are needed as well.
+    let mut aliased: HashSet<ItemPath> = HashSet::new();
+
+    for (alias, symbol) in item_aliases(module, item) {
+        if save_symbol(&alias) {
+            aliased.insert(symbol.clone());
+            aliases.entry(alias).or_insert(symbol);
+        }
+    }
+
     for (symbol, symbol_type) in item_symbols_type(module, item) {
-        if save_symbol(&symbol) {
+        if save_symbol(&symbol) || aliased.contains(&symbol) {
             symbols_type.insert(symbol, symbol_type);
         }
     }
@@ -452,6 +1310,7 @@ fn explore_crate<P: AsRef<Path>>(
     symbols: &HashSet<ItemPath>,
     paths_to_explore: &HashSet<ItemPath>,
     symbols_type: &mut HashMap<ItemPath, SymbolType>,
+    aliases: &mut DocAliases,
     emit_warning: &impl Fn(&str),
 ) -> Result<(), module_walker::ModuleWalkError> {
     let mut modules_visited: HashSet<ItemPath> = HashSet::new();
@@ -467,7 +1326,7 @@ fn explore_crate<P: AsRef<Path>>(
             symbols.contains(symbol) || paths_to_explore.contains(symbol)
         };
 
-        visit_module_item(save_symbol, symbols_type, module, item);
+        visit_module_item(save_symbol, symbols_type, aliases, module, item);
     };
 
     let mut explore_module = |mod_symbol: &ItemPath, mod_item: &ItemMod| -> bool {
@@ -485,24 +1344,59 @@ fn explore_crate<P: AsRef<Path>>(
 fn load_symbols_type<P: AsRef<Path>>(
     entry_point: P,
     symbols: &HashSet<ItemPath>,
+    dependencies: &[Dependency],
     emit_warning: &impl Fn(&str),
-) -> Result<HashMap<ItemPath, SymbolType>, IntralinkError> {
+) -> Result<(HashMap<ItemPath, SymbolType>, DocAliases), IntralinkError> {
     let paths_to_explore: HashSet<ItemPath> = all_ancestor_paths(symbols.iter());
     let mut symbols_type: HashMap<ItemPath, SymbolType> = HashMap::new();
+    let mut aliases: DocAliases = HashMap::new();
+
+    // Some items of the prelude are re-exported, so we would not find them by walking the modules.
+    for (name, module, symbol_type) in PRELUDE {
+        if let Some(module) = ItemPath::from_string(module) {
+            let symbol = module.join(&name);
+
+            if symbols.contains(&symbol) || paths_to_explore.contains(&symbol) {
+                symbols_type.insert(symbol, symbol_type);
+            }
+        }
+    }
+
+    let unresolved_symbols: HashSet<ItemPath> =
+        symbols.iter().filter(|symbol| !symbols_type.contains_key(symbol)).cloned().collect();
+    let dependency_crates: Vec<Crate> = dependencies
+        .iter()
+        .filter(|dependency| {
+            unresolved_symbols
+                .iter()
+                .any(|symbol| root_crate_name(symbol) == Some(&dependency.name))
+        })
+        .map(|dependency| Crate {
+            name: dependency.name.clone(),
+            entrypoint: dependency.entryfile.clone(),
+        })
+        .collect();
+    let unresolved_symbols: HashSet<ItemPath> = unresolved_symbols
+        .into_iter()
+        .filter(|symbol| {
+            !dependency_crates.iter().any(|c| root_crate_name(symbol) == Some(&c.name))
+        })
+        .collect();
 
     // Only load standard library information if needed.
-    let std_lib_crates = match references_standard_library(symbols) {
+    let std_lib_crates = match references_standard_library(&unresolved_symbols) {
         true => get_standard_libraries()?,
         false => Vec::new(),
     };
 
-    for Crate { name, entrypoint } in std_lib_crates {
+    for Crate { name, entrypoint } in dependency_crates.into_iter().chain(std_lib_crates) {
         explore_crate(
             entrypoint,
             &ItemPath::root(&name),
             symbols,
             &paths_to_explore,
             &mut symbols_type,
+            &mut aliases,
             emit_warning,
         )?;
     }
@@ -513,10 +1407,11 @@ fn load_symbols_type<P: AsRef<Path>>(
         symbols,
         &paths_to_explore,
         &mut symbols_type,
+        &mut aliases,
         emit_warning,
     )?;
 
-    Ok(symbols_type)
+    Ok((symbols_type, aliases))
 }
 
 /// Create a set with all ancestor paths of `symbols`.  For instance, if `symbols` is
@@ -526,18 +1421,36 @@ fn all_ancestor_paths<'a>(symbols: impl Iterator<Item = &'a ItemPath>) -> HashSe
     symbols.into_iter().flat_map(ItemPath::all_ancestors).collect()
 }
 
-fn extract_markdown_intralink_symbols(doc: &Doc) -> HashSet<ItemPath> {
-    let item_paths_inline_links =
-        markdown_link_iterator(&doc.markdown).items().filter_map(|l| match l {
-            MarkdownLink::Inline { link: inline_link } => inline_link.link.link_as_item_path(),
-            MarkdownLink::Reference { .. } => None,
-        });
+fn extract_markdown_intralink_targets(doc: &Doc) -> HashSet<IntralinkTarget> {
+    let defined_labels: HashSet<UniCase<String>> =
+        markdown_reference_link_definition_iterator(&doc.markdown)
+            .items()
+            .map(|l| l.label)
+            .collect();
+
+    let targets_links = markdown_link_iterator(&doc.markdown).items().filter_map(|l| match l {
+        MarkdownLink::Inline { link: inline_link } => inline_link.link.intralink_target(),
+        MarkdownLink::Reference { link } => match defined_labels.contains(link.label()) {
+            true => None,
+            false => Link::from(link.label().as_str()).intralink_target(),
+        },
+    });
 
-    let item_paths_reference_link_def = markdown_reference_link_definition_iterator(&doc.markdown)
+    let targets_reference_link_def = markdown_reference_link_definition_iterator(&doc.markdown)
         .items()
-        .filter_map(|l| l.link.link_as_item_path());
+        .filter_map(|l| l.link.intralink_target());
+
+    targets_links.chain(targets_reference_link_def).collect()
+}
+
+/// Number of distinct items the documentation links to with intralinks.
+#[must_use]
+pub fn intralink_count(doc: &Doc) -> usize {
+    extract_markdown_intralink_targets(doc).len()
+}
 
-    item_paths_inline_links.chain(item_paths_reference_link_def).collect()
+fn intralink_symbols(targets: &HashSet<IntralinkTarget>) -> HashSet<ItemPath> {
+    targets.iter().flat_map(|target| target.candidates.iter().cloned()).collect()
 }
 
 /// Returns the url for the item.
@@ -553,23 +1466,22 @@ fn documentation_url(
     let package_name = crate_name.replace('-', "_");
     let typ = *symbols_type.get(item_path)?;
 
+    // This is the path of the item relative to the documentation root, e.g.
+    // `foobini/amodule/struct.Foo.html`.
     let mut link = match item_path.anchor {
         ItemPathAnchor::Root => {
             let std_crate_name =
                 item_path.path_components().next().expect("a root path should not be empty");
-            format!("https://doc.rust-lang.org/stable/{std_crate_name}/")
-        }
-        ItemPathAnchor::Crate => {
-            let base_url =
-                config.docs_rs_base_url.as_ref().map_or("https://docs.rs", String::as_str);
-            let version = config.docs_rs_version.as_ref().map_or("latest", String::as_str);
-
-            format!("{base_url}/{crate_name}/{version}/{package_name}/")
+            format!("{std_crate_name}/")
         }
+        ItemPathAnchor::Crate => format!("{package_name}/"),
     };
 
     if typ == SymbolType::Crate {
-        return Some(format!("{}{}", link, fragment.unwrap_or("")));
+        let url = docs_url(item_path, crate_name, &link, config)
+            .or_else(|| dependency(item_path, config)?.repository.clone())?;
+
+        return Some(format!("{}{}", url, fragment.unwrap_or("")));
     }
 
     let skip_components = match item_path.anchor {
@@ -587,19 +1499,21 @@ fn documentation_url(
     let name =
         item_path.name().unwrap_or_else(|| panic!("failed to get last component of {item_path}"));
 
-    match typ {
+    let item_url_component = match typ {
         SymbolType::Crate => unreachable!(),
-        SymbolType::Struct => link.push_str(&format!("struct.{name}.html")),
-        SymbolType::Trait => link.push_str(&format!("trait.{name}.html")),
-        SymbolType::Enum => link.push_str(&format!("enum.{name}.html")),
-        SymbolType::Union => link.push_str(&format!("union.{name}.html")),
-        SymbolType::Type => link.push_str(&format!("type.{name}.html")),
-        SymbolType::Mod => link.push_str(&format!("{name}/")),
-        SymbolType::Macro => link.push_str(&format!("macro.{name}.html")),
-        SymbolType::Const => link.push_str(&format!("const.{name}.html")),
-        SymbolType::Fn => link.push_str(&format!("fn.{name}.html")),
-        SymbolType::Static => link.push_str(&format!("static.{name}.html")),
-        SymbolType::ImplItem(typ) => {
+        SymbolType::Struct => format!("struct.{name}.html"),
+        SymbolType::Trait => format!("trait.{name}.html"),
+        SymbolType::Enum => format!("enum.{name}.html"),
+        SymbolType::Union => format!("union.{name}.html"),
+        SymbolType::Type => format!("type.{name}.html"),
+        SymbolType::Mod => format!("{name}/"),
+        SymbolType::Macro => format!("macro.{name}.html"),
+        SymbolType::Derive => format!("derive.{name}.html"),
+        SymbolType::Attr => format!("attr.{name}.html"),
+        SymbolType::Const => format!("const.{name}.html"),
+        SymbolType::Fn => format!("fn.{name}.html"),
+        SymbolType::Static => format!("static.{name}.html"),
+        SymbolType::ImplItem(_) | SymbolType::StructField | SymbolType::EnumVariant => {
             let parent_path = item_path
                 .clone()
                 .parent()
@@ -614,59 +1528,182 @@ fn documentation_url(
                 config,
             )?;
 
-            let impl_item_fragment_str = match typ {
-                ImplSymbolType::Method => "method",
-                ImplSymbolType::Const => "associatedconstant",
-                ImplSymbolType::Type => "associatedtype",
+            let item_fragment_str = match typ {
+                SymbolType::ImplItem(ImplSymbolType::Method) => "method",
+                SymbolType::ImplItem(ImplSymbolType::RequiredMethod) => "tymethod",
+                SymbolType::ImplItem(ImplSymbolType::Const) => "associatedconstant",
+                SymbolType::ImplItem(ImplSymbolType::Type) => "associatedtype",
+                SymbolType::StructField => "structfield",
+                SymbolType::EnumVariant => "variant",
+                _ => unreachable!(),
             };
 
-            return Some(format!("{link}#{impl_item_fragment_str}.{name}"));
+            return Some(format!("{link}#{item_fragment_str}.{name}"));
         }
-    }
+    };
+
+    link.push_str(&item_url_component);
+
+    let url = docs_url(item_path, crate_name, &link, config)?;
 
-    Some(format!("{}{}", link, fragment.unwrap_or("")))
+    Some(format!("{}{}", url, fragment.unwrap_or("")))
 }
 
-enum MarkdownLinkAction {
-    Link(Link),
-    Preserve,
-    Strip,
+/// The dependency the item belongs to, if any.
+fn dependency<'a>(
+    item_path: &ItemPath,
+    config: &'a IntralinksDocsRsConfig,
+) -> Option<&'a Dependency> {
+    let root_crate_name = root_crate_name(item_path)?;
+
+    config.dependencies.iter().find(|dependency| dependency.name == root_crate_name)
 }
 
-fn markdown_link(
-    link: &Link,
-    symbols_type: &HashMap<ItemPath, SymbolType>,
-    crate_name: &str,
-    emit_warning: &impl Fn(&str),
-    config: &IntralinksConfig,
+/// Returns the url of the documentation page at `path` of a dependency.  Dependencies that do not
+/// come from crates.io (e.g. path and git dependencies) have no documentation in docs.rs, so we use
+/// the documentation url of their manifest, if they have one.
+fn dependency_docs_url(dependency: &Dependency, path: &str) -> Option<String> {
+    if dependency.published {
+        return Some(format!(
+            "https://docs.rs/{}/{}/{path}",
+            dependency.package_name, dependency.version
+        ));
+    }
+
+    let documentation_url = dependency.documentation_url.as_deref()?;
+
+    match docs_url_template_from_documentation_url(documentation_url, &dependency.package_name) {
+        Some(template) => Some(docs_url_from_template(
+            &template,
+            &dependency.package_name,
+            &dependency.version,
+            path,
+        )),
+        // The documentation is in docs.rs, but this version might not be published.
+        None => Some(format!("https://docs.rs/{}/latest/{path}", dependency.package_name)),
+    }
+}
+
+/// Returns the url of the documentation page at `path`, which is relative to the documentation root
+/// of the crate the item belongs to.
+///
+/// This returns `None` if the crate has no known documentation url.
+fn docs_url(
+    item_path: &ItemPath,
+    crate_name: &str,
+    path: &str,
+    config: &IntralinksDocsRsConfig,
+) -> Option<String> {
+    let url = match item_path.anchor {
+        ItemPathAnchor::Root => {
+            let root_crate_name =
+                item_path.path_components().next().expect("a root path should not be empty");
+
+            match (config.docs_url_templates.get(root_crate_name), dependency(item_path, config)) {
+                (Some(template), Some(dependency)) => {
+                    docs_url_from_template(template, root_crate_name, &dependency.version, path)
+                }
+                (Some(template), None) => {
+                    docs_url_from_template(template, root_crate_name, "stable", path)
+                }
+                (None, Some(dependency)) => dependency_docs_url(dependency, path)?,
+                (None, None) => format!("https://doc.rust-lang.org/stable/{path}"),
+            }
+        }
+        ItemPathAnchor::Crate => {
+            let version = config.docs_rs_version.as_ref().map_or("latest", String::as_str);
+            let template =
+                config.docs_url_templates.get(crate_name).or(config.docs_url_template.as_ref());
+
+            match template {
+                Some(template) => docs_url_from_template(template, crate_name, version, path),
+                None => {
+                    let base_url =
+                        config.docs_rs_base_url.as_ref().map_or("https://docs.rs", String::as_str);
+
+                    format!("{base_url}/{crate_name}/{version}/{path}")
+                }
+            }
+        }
+    };
+
+    Some(url)
+}
+
+/// Expands a documentation url template.  The supported placeholders are:
+///
+/// * `{crate}`: the name of the crate.
+/// * `{version}`: the version of the documentation.
+/// * `{path}`: the path of the page relative to the documentation root, as generated by rustdoc,
+///   e.g. `foobini/amodule/struct.Foo.html`.
+fn docs_url_from_template(template: &str, crate_name: &str, version: &str, path: &str) -> String {
+    template.replace("{crate}", crate_name).replace("{version}", version).replace("{path}", path)
+}
+
+enum MarkdownLinkAction {
+    Link(Link),
+    Preserve,
+    Strip,
+}
+
+fn markdown_link(
+    link: &Link,
+    symbols_type: &HashMap<ItemPath, SymbolType>,
+    aliases: &DocAliases,
+    crate_name: &str,
+    emit_warning: &impl Fn(&str),
+    config: &IntralinksConfig,
 ) -> MarkdownLinkAction {
-    match link.link_as_item_path() {
-        Some(symbol) => {
-            let link = documentation_url(
-                &symbol,
+    let Some(target) = link
+        .intralink_target()
+        .map(|target| target.with_dependencies(&config.docs_rs.dependencies).with_aliases(aliases))
+    else {
+        return MarkdownLinkAction::Preserve;
+    };
+
+    if config.ignore.iter().any(|pattern| matches_pattern(pattern, &target.path)) {
+        return MarkdownLinkAction::Preserve;
+    }
+
+    let strip_links = config.strip_links.unwrap_or(false);
+    let url = target
+        .candidates
+        .iter()
+        .find_map(|symbol| {
+            documentation_url(
+                symbol,
                 symbols_type,
                 crate_name,
                 link.link_fragment(),
                 &config.docs_rs,
-            );
-
-            match link {
-                Some(l) => MarkdownLinkAction::Link(l.into()),
-                None => {
-                    emit_warning(&format!("Could not resolve definition of `{symbol}`."));
+            )
+        })
+        .or_else(|| target.builtin.map(|builtin| builtin.documentation_url(link.link_fragment())));
+
+    match (url, strip_links) {
+        (Some(_), true) => MarkdownLinkAction::Strip,
+        (Some(url), false) => MarkdownLinkAction::Link(url.into()),
+        // This might not be an intralink at all, e.g. `[license](LICENSE)`.
+        (None, _) if !target.explicit => MarkdownLinkAction::Preserve,
+        (None, true) => MarkdownLinkAction::Strip,
+        // The item exists, but it has no documentation we can link to, e.g. an item of a path
+        // dependency.
+        (None, false) if target.candidates.iter().any(|c| symbols_type.contains_key(c)) => {
+            MarkdownLinkAction::Strip
+        }
+        (None, false) => {
+            emit_warning(&format!("Could not resolve definition of `{}`.", target.path));
 
-                    // This was an intralink, but we were not able to generate a link.
-                    MarkdownLinkAction::Strip
-                }
-            }
+            // This was an intralink, but we were not able to generate a link.
+            MarkdownLinkAction::Strip
         }
-        None => MarkdownLinkAction::Preserve,
     }
 }
 
 fn rewrite_markdown_links(
     doc: &Doc,
     symbols_type: &HashMap<ItemPath, SymbolType>,
+    aliases: &DocAliases,
     crate_name: &str,
     emit_warning: &impl Fn(&str),
     config: &IntralinksConfig,
@@ -674,25 +1711,24 @@ fn rewrite_markdown_links(
 ) -> Doc {
     use crate::utils::ItemOrOther;
 
-    let strip_links = config.strip_links.unwrap_or(false);
+    let defined_labels: HashSet<UniCase<String>> =
+        markdown_reference_link_definition_iterator(&doc.markdown)
+            .items()
+            .map(|l| l.label)
+            .collect();
     let mut new_doc = String::with_capacity(doc.as_string().len() + 1024);
 
     for item_or_other in markdown_link_iterator(&doc.markdown).complete() {
         match item_or_other {
             ItemOrOther::Item(MarkdownLink::Inline { link: inline_link }) => {
-                let markdown_link: MarkdownLinkAction = match strip_links {
-                    false => markdown_link(
-                        &inline_link.link,
-                        symbols_type,
-                        crate_name,
-                        emit_warning,
-                        config,
-                    ),
-                    true => match inline_link.link.link_as_item_path() {
-                        None => MarkdownLinkAction::Preserve,
-                        Some(_) => MarkdownLinkAction::Strip,
-                    },
-                };
+                let markdown_link: MarkdownLinkAction = markdown_link(
+                    &inline_link.link,
+                    symbols_type,
+                    aliases,
+                    crate_name,
+                    emit_warning,
+                    config,
+                );
 
                 match markdown_link {
                     MarkdownLinkAction::Link(markdown_link) => {
@@ -707,9 +1743,134 @@ fn rewrite_markdown_links(
                 }
             }
             ItemOrOther::Item(MarkdownLink::Reference { link }) => {
-                match reference_links_to_remove.contains(link.label()) {
-                    true => new_doc.push_str(link.text()),
-                    false => new_doc.push_str(&link.to_string()),
+                if reference_links_to_remove.contains(link.label()) {
+                    new_doc.push_str(link.text());
+                    continue;
+                }
+
+                if defined_labels.contains(link.label()) {
+                    new_doc.push_str(&link.to_string());
+                    continue;
+                }
+
+                // A reference link without a definition, like ``[`Foo`]``, is an intralink where
+                // the label is the path of the item.
+                let label_link: Link = Link::from(link.label().as_str());
+
+                match markdown_link(
+                    &label_link,
+                    symbols_type,
+                    aliases,
+                    crate_name,
+                    emit_warning,
+                    config,
+                ) {
+                    MarkdownLinkAction::Link(url) => {
+                        let inline_link =
+                            MarkdownInlineLink { text: link.text().to_owned(), link: url };
+
+                        new_doc.push_str(&inline_link.to_string());
+                    }
+                    MarkdownLinkAction::Preserve => {
+                        new_doc.push_str(&link.to_string());
+                    }
+                    MarkdownLinkAction::Strip => {
+                        new_doc.push_str(link.text());
+                    }
+                }
+            }
+            ItemOrOther::Other(other) => {
+                new_doc.push_str(other);
+            }
+        }
+    }
+
+    Doc::from_str(new_doc)
+}
+
+/// Replaces `Self` at the start of an intralink path (e.g. `Self::new` or `` `fn@Self::new()` ``)
+/// with `self_type`.  Returns `None` if the path does not start with `Self`.
+fn replace_self(path: &str, self_type: &str) -> Option<String> {
+    let start = path.len() - path.trim_start_matches('`').len();
+    let start = path[start..].find('@').map_or(start, |i| start + i + 1);
+    let rest = path[start..].strip_prefix("Self")?;
+
+    if rest.starts_with(|c: char| c.is_alphanumeric() || c == '_') {
+        return None;
+    }
+
+    Some(format!("{}{self_type}{rest}", &path[..start]))
+}
+
+/// The text of a link to `Self` that cannot be resolved.  If the text is the path itself it becomes
+/// a code span.
+fn self_link_code_span(text: &str, path: &str) -> String {
+    match text == path && !text.starts_with('`') {
+        true => format!("`{text}`"),
+        false => text.to_owned(),
+    }
+}
+
+/// Rustdoc resolves `Self` to the type being documented, but the crate documentation has no such
+/// type.  Links to `Self` are rewritten to refer to the configured type or, if there is none, are
+/// replaced by their text.
+fn rewrite_self_links(doc: &Doc, self_type: Option<&str>) -> Doc {
+    use crate::utils::ItemOrOther;
+
+    let defined_labels: HashSet<UniCase<String>> =
+        markdown_reference_link_definition_iterator(&doc.markdown)
+            .items()
+            .map(|l| l.label)
+            .collect();
+    let mut new_doc = String::with_capacity(doc.as_string().len());
+    let spell_out = |text: &str, path: &str, self_type: &str| match text == path {
+        true => replace_self(text, self_type).unwrap_or_else(|| text.to_owned()),
+        false => text.to_owned(),
+    };
+
+    for item_or_other in markdown_link_iterator(&doc.markdown).complete() {
+        match item_or_other {
+            ItemOrOther::Item(MarkdownLink::Inline { link: inline_link }) => {
+                let path = inline_link.link.raw_link.as_str();
+
+                match (replace_self(path, self_type.unwrap_or("Self")), self_type) {
+                    (None, _) => new_doc.push_str(&inline_link.to_string()),
+                    (Some(new_path), Some(self_type)) => {
+                        let inline_link = MarkdownInlineLink {
+                            text: spell_out(&inline_link.text, path, self_type),
+                            link: Link::from(new_path),
+                        };
+
+                        new_doc.push_str(&inline_link.to_string());
+                    }
+                    (Some(_), None) => {
+                        new_doc.push_str(&self_link_code_span(&inline_link.text, path));
+                    }
+                }
+            }
+            ItemOrOther::Item(MarkdownLink::Reference { link }) => {
+                let path = link.label().as_str();
+                let new_path = match defined_labels.contains(link.label()) {
+                    true => None,
+                    false => replace_self(path, self_type.unwrap_or("Self")),
+                };
+
+                match (new_path, self_type, &link) {
+                    (None, _, _) => new_doc.push_str(&link.to_string()),
+                    (Some(new_path), Some(_), MarkdownReferenceLink::Shortcut { .. }) => {
+                        new_doc
+                            .push_str(&MarkdownReferenceLink::new_shortcut(new_path).to_string());
+                    }
+                    (
+                        Some(new_path),
+                        Some(self_type),
+                        MarkdownReferenceLink::Normal { text, .. },
+                    ) => {
+                        let text = spell_out(text, path, self_type);
+
+                        new_doc.push_str(&MarkdownReferenceLink::new(text, new_path).to_string());
+                    }
+                    (Some(_), None, _) => new_doc.push_str(&self_link_code_span(link.text(), path)),
                 }
             }
             ItemOrOther::Other(other) => {
@@ -729,6 +1890,7 @@ struct RewriteReferenceLinksResult {
 fn rewrite_reference_links_definitions(
     doc: &Doc,
     symbols_type: &HashMap<ItemPath, SymbolType>,
+    aliases: &DocAliases,
     crate_name: &str,
     emit_warning: &impl Fn(&str),
     config: &IntralinksConfig,
@@ -737,26 +1899,20 @@ fn rewrite_reference_links_definitions(
     let mut reference_links_to_remove = HashSet::new();
     let mut new_doc = String::with_capacity(doc.as_string().len() + 1024);
     let mut skip_next_newline = false;
-    let strip_links = config.strip_links.unwrap_or(false);
 
     let iter = markdown_reference_link_definition_iterator(&doc.markdown);
 
     for item_or_other in iter.complete() {
         match item_or_other {
             ItemOrOther::Item(link_ref_def) => {
-                let markdown_link: MarkdownLinkAction = match strip_links {
-                    false => markdown_link(
-                        &link_ref_def.link,
-                        symbols_type,
-                        crate_name,
-                        emit_warning,
-                        config,
-                    ),
-                    true => match link_ref_def.link.link_as_item_path() {
-                        None => MarkdownLinkAction::Preserve,
-                        Some(_) => MarkdownLinkAction::Strip,
-                    },
-                };
+                let markdown_link: MarkdownLinkAction = markdown_link(
+                    &link_ref_def.link,
+                    symbols_type,
+                    aliases,
+                    crate_name,
+                    emit_warning,
+                    config,
+                );
 
                 match markdown_link {
                     MarkdownLinkAction::Link(link) => {
@@ -823,6 +1979,14 @@ struct Crate {
     entrypoint: PathBuf,
 }
 
+/// The name of the crate of a path starting with `::`, e.g. `std` in `::std::fs::read`.
+fn root_crate_name(symbol: &ItemPath) -> Option<&str> {
+    match symbol.anchor {
+        ItemPathAnchor::Root => symbol.path_components().next(),
+        ItemPathAnchor::Crate => None,
+    }
+}
+
 fn references_standard_library(symbols: &HashSet<ItemPath>) -> bool {
     // The only way to reference standard libraries that we support is with a intra-link of form `::⋯`.
     symbols.iter().any(|symbol| symbol.anchor == ItemPathAnchor::Root)
@@ -861,12 +2025,29 @@ mod tests {
     use super::*;
     use indoc::indoc;
     use module_walker::walk_module_items;
-    use std::cell::RefCell;
 
     fn item_path(id: &str) -> ItemPath {
         ItemPath::from_string(id).unwrap()
     }
 
+    #[test]
+    fn test_transform_intra_doc_links() {
+        let ctx = LinkContext {
+            crate_name: "cargo-rdme".to_owned(),
+            entrypoint: PathBuf::from("src/lib.rs"),
+            config: IntralinksConfig::default(),
+        };
+        let markdown = "See [`Doc`](crate::Doc), not [this](crate::DoesNotExist).";
+
+        let result = transform_intra_doc_links(markdown, &ctx).unwrap();
+
+        assert_eq!(
+            result.markdown,
+            "See [`Doc`](https://docs.rs/cargo-rdme/latest/cargo_rdme/struct.Doc.html), not this."
+        );
+        assert_eq!(result.warnings, vec!["Could not resolve definition of `crate::DoesNotExist`."]);
+    }
+
     #[test]
     fn test_item_path_is_toplevel() {
         assert!(!item_path("crate::baz::mumble").is_toplevel());
@@ -932,16 +2113,24 @@ mod tests {
         symbols_type.insert(crate_symbol.clone(), SymbolType::Crate);
 
         let mut visit = |module: &ItemPath, item: &Item| {
-            visit_module_item(|_| true, symbols_type, module, item);
+            visit_module_item(|_| true, symbols_type, &mut HashMap::new(), module, item);
         };
 
         let mut explore_module = |mod_symbol: &ItemPath, mod_item: &ItemMod| -> bool {
             check_explore_module(&should_explore_module, &mut modules_visited, mod_symbol, mod_item)
         };
 
-        walk_module_items(ast, dir, crate_symbol, &mut visit, &mut explore_module, &emit_warning)
-            .ok()
-            .unwrap();
+        walk_module_items(
+            ast,
+            dir,
+            dir,
+            crate_symbol,
+            &mut visit,
+            &mut explore_module,
+            &emit_warning,
+        )
+        .ok()
+        .unwrap();
     }
 
     #[test]
@@ -992,6 +2181,127 @@ mod tests {
         assert_eq!(symbols_type, expected);
     }
 
+    #[test]
+    fn test_symbols_type_items_and_members() {
+        let source = indoc! { "
+            pub struct Foo {
+                pub number: u32,
+            }
+
+            pub enum Color {
+                Red,
+                Green(u8),
+            }
+
+            pub trait Shape {
+                const SIDES: u32;
+                type Unit;
+
+                fn area(&self) -> f64;
+                fn name(&self) -> &str {
+                    \"shape\"
+                }
+            }
+
+            mod macros {
+                #[macro_export]
+                macro_rules! exported {
+                    () => {};
+                }
+
+                macro_rules! local {
+                    () => {};
+                }
+            }
+
+            #[proc_macro]
+            pub fn a_macro(input: TokenStream) -> TokenStream {
+                input
+            }
+
+            #[proc_macro_derive(MyDerive, attributes(my_attr))]
+            pub fn my_derive(input: TokenStream) -> TokenStream {
+                input
+            }
+
+            #[proc_macro_attribute]
+            pub fn an_attribute(_attr: TokenStream, item: TokenStream) -> TokenStream {
+                item
+            }
+            "
+        };
+
+        let mut symbols_type: HashMap<ItemPath, SymbolType> = HashMap::new();
+
+        explore_crate(
+            &syn::parse_file(source).unwrap().items,
+            &PathBuf::new(),
+            &item_path("crate"),
+            |_| true,
+            &mut symbols_type,
+            |_| (),
+        );
+
+        let expected: HashMap<ItemPath, SymbolType> = [
+            (item_path("crate"), SymbolType::Crate),
+            (item_path("crate::Foo"), SymbolType::Struct),
+            (item_path("crate::Foo::number"), SymbolType::StructField),
+            (item_path("crate::Color"), SymbolType::Enum),
+            (item_path("crate::Color::Red"), SymbolType::EnumVariant),
+            (item_path("crate::Color::Green"), SymbolType::EnumVariant),
+            (item_path("crate::Shape"), SymbolType::Trait),
+            (item_path("crate::Shape::SIDES"), SymbolType::ImplItem(ImplSymbolType::Const)),
+            (item_path("crate::Shape::Unit"), SymbolType::ImplItem(ImplSymbolType::Type)),
+            (item_path("crate::Shape::area"), SymbolType::ImplItem(ImplSymbolType::RequiredMethod)),
+            (item_path("crate::Shape::name"), SymbolType::ImplItem(ImplSymbolType::Method)),
+            (item_path("crate::macros"), SymbolType::Mod),
+            (item_path("crate::exported"), SymbolType::Macro),
+            (item_path("crate::macros::local"), SymbolType::Macro),
+            (item_path("crate::a_macro"), SymbolType::Macro),
+            (item_path("crate::MyDerive"), SymbolType::Derive),
+            (item_path("crate::an_attribute"), SymbolType::Attr),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(symbols_type, expected);
+    }
+
+    #[test]
+    fn test_item_aliases() {
+        let source = indoc! { r#"
+            /// A point.
+            #[doc(alias = "Coord")]
+            #[doc(hidden, alias("Vertex", "Vec2"))]
+            pub struct Point {
+                #[doc(alias = "abscissa")]
+                pub x: f64,
+            }
+
+            impl Point {
+                #[doc(alias = "len")]
+                pub fn norm(&self) -> f64 {}
+            }
+            "#
+        };
+        let file = syn::parse_file(source).unwrap();
+        let aliases: Vec<(ItemPath, ItemPath)> = file
+            .items
+            .iter()
+            .flat_map(|item| item_aliases(&item_path("crate::geometry"), item))
+            .collect();
+
+        let expected = vec![
+            (item_path("crate::geometry::Coord"), item_path("crate::geometry::Point")),
+            (item_path("crate::geometry::Vertex"), item_path("crate::geometry::Point")),
+            (item_path("crate::geometry::Vec2"), item_path("crate::geometry::Point")),
+            (item_path("crate::geometry::Point::abscissa"), item_path("crate::geometry::Point::x")),
+            (item_path("crate::geometry::Point::len"), item_path("crate::geometry::Point::norm")),
+        ];
+
+        assert_eq!(aliases, expected);
+    }
+
     #[test]
     fn test_symbols_type_with_mod_under_cfg_test() {
         let source = indoc! { "
@@ -1256,6 +2566,7 @@ mod tests {
         let config = IntralinksDocsRsConfig {
             docs_rs_base_url: Some("https://docs.company.rs".to_owned()),
             docs_rs_version: Some("1.0.0".to_owned()),
+            ..IntralinksDocsRsConfig::default()
         };
 
         let symbols_type: HashMap<ItemPath, SymbolType> =
@@ -1270,7 +2581,121 @@ mod tests {
     }
 
     #[test]
-    fn test_extract_markdown_intralink_symbols() {
+    fn test_docs_url_template_from_documentation_url() {
+        assert_eq!(
+            docs_url_template_from_documentation_url("https://docs.rs/foo-bar", "foo-bar"),
+            None
+        );
+        assert_eq!(
+            docs_url_template_from_documentation_url(
+                "https://example.com/docs/foo_bar/",
+                "foo-bar"
+            )
+            .as_deref(),
+            Some("https://example.com/docs/{path}")
+        );
+        assert_eq!(
+            docs_url_template_from_documentation_url(
+                "https://example.com/docs/foo_bar/index.html",
+                "foo-bar"
+            )
+            .as_deref(),
+            Some("https://example.com/docs/{path}")
+        );
+        assert_eq!(
+            docs_url_template_from_documentation_url("https://example.com/docs", "foo-bar")
+                .as_deref(),
+            Some("https://example.com/docs/{path}")
+        );
+
+        let mut config = IntralinksDocsRsConfig {
+            docs_rs_base_url: Some("https://mydocs.rs".to_owned()),
+            ..IntralinksDocsRsConfig::default()
+        };
+
+        config.set_package_documentation_url("https://example.com/docs/foo_bar/", "foo-bar");
+        assert_eq!(config.docs_url_template, None);
+    }
+
+    #[test]
+    fn test_html_root_url() {
+        let symbols_type: HashMap<ItemPath, SymbolType> =
+            [(item_path("crate::Foo"), SymbolType::Struct)].into_iter().collect();
+        let mut config = IntralinksDocsRsConfig::default();
+
+        config.set_html_root_url("https://docs.rs/foo-bar/1.2.3/", "foo-bar");
+        // The html root url takes precedence over the package documentation url.
+        config.set_package_documentation_url("https://example.com/docs/foo_bar/", "foo-bar");
+
+        let link =
+            documentation_url(&item_path("crate::Foo"), &symbols_type, "foo-bar", None, &config);
+        assert_eq!(link.as_deref(), Some("https://docs.rs/foo-bar/1.2.3/foo_bar/struct.Foo.html"));
+
+        let mut config = IntralinksDocsRsConfig {
+            docs_rs_version: Some("2.0.0".to_owned()),
+            docs_url_template: Some("https://docs.internal/{crate}/{version}/{path}".to_owned()),
+            ..IntralinksDocsRsConfig::default()
+        };
+
+        config.set_html_root_url("https://docs.rs/foo-bar/1.2.3", "foo-bar");
+        assert_eq!(
+            config.docs_url_template.as_deref(),
+            Some("https://docs.internal/{crate}/{version}/{path}")
+        );
+    }
+
+    #[test]
+    fn test_documentation_url_templates() {
+        let config = IntralinksDocsRsConfig {
+            docs_rs_version: Some("1.0.0".to_owned()),
+            docs_url_template: Some("https://docs.internal/{crate}/{version}/{path}".to_owned()),
+            docs_url_templates: [
+                ("other-crate".to_owned(), "https://other.internal/{path}".to_owned()),
+                ("std".to_owned(), "https://std.internal/{version}/{path}".to_owned()),
+            ]
+            .into_iter()
+            .collect(),
+            ..IntralinksDocsRsConfig::default()
+        };
+
+        let symbols_type: HashMap<ItemPath, SymbolType> = [
+            (item_path("crate"), SymbolType::Crate),
+            (item_path("crate::amod::Foo"), SymbolType::Struct),
+            (item_path("crate::amod::Foo::new"), SymbolType::ImplItem(ImplSymbolType::Method)),
+            (item_path("::std::fs::copy"), SymbolType::Fn),
+            (item_path("::core::option::Option"), SymbolType::Enum),
+        ]
+        .into_iter()
+        .collect();
+
+        let url = |path: &str, crate_name: &str| {
+            documentation_url(&item_path(path), &symbols_type, crate_name, None, &config)
+        };
+
+        assert_eq!(
+            url("crate", "foo-bar").as_deref(),
+            Some("https://docs.internal/foo-bar/1.0.0/foo_bar/")
+        );
+        assert_eq!(
+            url("crate::amod::Foo::new", "foo-bar").as_deref(),
+            Some("https://docs.internal/foo-bar/1.0.0/foo_bar/amod/struct.Foo.html#method.new")
+        );
+        assert_eq!(
+            url("crate::amod::Foo", "other-crate").as_deref(),
+            Some("https://other.internal/other_crate/amod/struct.Foo.html")
+        );
+        assert_eq!(
+            url("::std::fs::copy", "foo-bar").as_deref(),
+            Some("https://std.internal/stable/std/fs/fn.copy.html")
+        );
+        assert_eq!(
+            url("::core::option::Option", "foo-bar").as_deref(),
+            Some("https://doc.rust-lang.org/stable/core/option/enum.Option.html")
+        );
+    }
+
+    #[test]
+    fn test_extract_markdown_intralink_targets() {
         let doc = indoc! { "
             # Foobini
 
@@ -1287,7 +2712,8 @@ mod tests {
             "
         };
 
-        let symbols = extract_markdown_intralink_symbols(&Doc::from_str(doc));
+        let targets = extract_markdown_intralink_targets(&Doc::from_str(doc));
+        let symbols = intralink_symbols(&targets);
 
         let expected: HashSet<ItemPath> = [
             item_path("crate"),
@@ -1333,6 +2759,7 @@ mod tests {
         let new_readme = rewrite_markdown_links(
             &Doc::from_str(doc),
             &symbols_type,
+            &HashMap::new(),
             "foobini",
             &|_| (),
             &IntralinksConfig::default(),
@@ -1394,6 +2821,7 @@ mod tests {
         let new_readme = rewrite_links(
             &Doc::from_str(doc),
             &symbols_type,
+            &HashMap::new(),
             "foobini",
             &|_| (),
             &IntralinksConfig { strip_links: Some(true), ..Default::default() },
@@ -1422,6 +2850,49 @@ mod tests {
         assert_eq!(new_readme.as_string(), expected);
     }
 
+    #[test]
+    fn test_rewrite_markdown_links_ignore() {
+        let doc = indoc! { r"
+            Use [`Foo`], [the helper](__private::helper), [`crate::__private::Inner`], and
+            [`Expanded`].
+            "
+        };
+
+        let symbols_type: HashMap<ItemPath, SymbolType> =
+            [(item_path("crate::Foo"), SymbolType::Struct)].into_iter().collect();
+        let config = IntralinksConfig {
+            ignore: vec!["*__private::*".to_owned(), "Expanded".to_owned()],
+            ..Default::default()
+        };
+
+        let new_readme = rewrite_links(
+            &Doc::from_str(doc),
+            &symbols_type,
+            &HashMap::new(),
+            "foobini",
+            &|msg| panic!("unexpected warning: {msg}"),
+            &config,
+        );
+        let expected = indoc! { r"
+            Use [`Foo`](https://docs.rs/foobini/latest/foobini/struct.Foo.html), [the helper](__private::helper), [`crate::__private::Inner`], and
+            [`Expanded`].
+            "
+        };
+
+        assert_eq!(new_readme.as_string(), expected);
+    }
+
+    #[test]
+    fn test_matches_pattern() {
+        assert!(matches_pattern("Foo", "Foo"));
+        assert!(!matches_pattern("Foo", "FooBar"));
+        assert!(matches_pattern("__private::*", "__private::helper"));
+        assert!(!matches_pattern("__private::*", "crate::__private::helper"));
+        assert!(matches_pattern("*::__*", "crate::foo::__hidden"));
+        assert!(matches_pattern("a*b*c", "abc"));
+        assert!(!matches_pattern("a*bc*c", "abc"));
+    }
+
     #[test]
     fn test_rewrite_markdown_links_backticked() {
         let doc = indoc! { r"
@@ -1454,6 +2925,7 @@ mod tests {
         let new_readme = rewrite_markdown_links(
             &Doc::from_str(doc),
             &symbols_type,
+            &HashMap::new(),
             "foobini",
             &|_| (),
             &IntralinksConfig::default(),
@@ -1522,6 +2994,7 @@ mod tests {
         let new_readme = rewrite_links(
             &Doc::from_str(doc),
             &symbols_type,
+            &HashMap::new(),
             "foobini",
             &|_| (),
             &IntralinksConfig::default(),
@@ -1574,6 +3047,7 @@ mod tests {
         let new_readme = rewrite_links(
             &Doc::from_str(doc),
             &symbols_type,
+            &HashMap::new(),
             "foobini",
             &|_| (),
             &IntralinksConfig::default(),
@@ -1635,6 +3109,7 @@ mod tests {
         let new_readme = rewrite_links(
             &Doc::from_str(doc),
             &symbols_type,
+            &HashMap::new(),
             "foobini",
             &|_| (),
             &IntralinksConfig::default(),
@@ -1660,4 +3135,381 @@ mod tests {
 
         assert_eq!(new_readme.as_string(), expected);
     }
+
+    #[test]
+    fn test_documentation_url_item_members() {
+        let config = IntralinksDocsRsConfig::default();
+
+        let symbols_type: HashMap<ItemPath, SymbolType> = [
+            (item_path("crate::Color"), SymbolType::Enum),
+            (item_path("crate::Color::Red"), SymbolType::EnumVariant),
+            (item_path("crate::amod::Shape"), SymbolType::Trait),
+            (item_path("crate::amod::Shape::SIDES"), SymbolType::ImplItem(ImplSymbolType::Const)),
+            (item_path("crate::amod::Shape::Unit"), SymbolType::ImplItem(ImplSymbolType::Type)),
+            (
+                item_path("crate::amod::Shape::area"),
+                SymbolType::ImplItem(ImplSymbolType::RequiredMethod),
+            ),
+            (item_path("crate::amod::Shape::name"), SymbolType::ImplItem(ImplSymbolType::Method)),
+            (item_path("crate::a_macro"), SymbolType::Macro),
+            (item_path("crate::MyDerive"), SymbolType::Derive),
+            (item_path("crate::an_attribute"), SymbolType::Attr),
+            (item_path("::std::iter::Iterator"), SymbolType::Trait),
+            (
+                item_path("::std::iter::Iterator::next"),
+                SymbolType::ImplItem(ImplSymbolType::RequiredMethod),
+            ),
+        ]
+        .into_iter()
+        .collect();
+
+        let url = |path: &str| {
+            documentation_url(&item_path(path), &symbols_type, "foobini", None, &config)
+        };
+
+        assert_eq!(
+            url("crate::Color::Red").as_deref(),
+            Some("https://docs.rs/foobini/latest/foobini/enum.Color.html#variant.Red")
+        );
+        assert_eq!(
+            url("crate::amod::Shape::SIDES").as_deref(),
+            Some("https://docs.rs/foobini/latest/foobini/amod/trait.Shape.html#associatedconstant.SIDES")
+        );
+        assert_eq!(
+            url("crate::amod::Shape::Unit").as_deref(),
+            Some(
+                "https://docs.rs/foobini/latest/foobini/amod/trait.Shape.html#associatedtype.Unit"
+            )
+        );
+        assert_eq!(
+            url("crate::amod::Shape::area").as_deref(),
+            Some("https://docs.rs/foobini/latest/foobini/amod/trait.Shape.html#tymethod.area")
+        );
+        assert_eq!(
+            url("crate::amod::Shape::name").as_deref(),
+            Some("https://docs.rs/foobini/latest/foobini/amod/trait.Shape.html#method.name")
+        );
+        assert_eq!(
+            url("crate::a_macro").as_deref(),
+            Some("https://docs.rs/foobini/latest/foobini/macro.a_macro.html")
+        );
+        assert_eq!(
+            url("crate::MyDerive").as_deref(),
+            Some("https://docs.rs/foobini/latest/foobini/derive.MyDerive.html")
+        );
+        assert_eq!(
+            url("crate::an_attribute").as_deref(),
+            Some("https://docs.rs/foobini/latest/foobini/attr.an_attribute.html")
+        );
+        assert_eq!(
+            url("::std::iter::Iterator::next").as_deref(),
+            Some("https://doc.rust-lang.org/stable/std/iter/trait.Iterator.html#tymethod.next")
+        );
+    }
+
+    #[test]
+    fn test_intralink_target_from_string() {
+        let target = |s: &str| IntralinkTarget::from_string(s, false);
+        let candidates = |s: &str| target(s).map(|t| t.candidates);
+
+        assert_eq!(candidates("crate::Foo"), Some(vec![item_path("crate::Foo")]));
+        assert_eq!(candidates("self::Foo"), Some(vec![item_path("crate::Foo")]));
+        assert_eq!(candidates("Foo"), Some(vec![item_path("crate::Foo")]));
+        assert_eq!(candidates("struct@Foo"), Some(vec![item_path("crate::Foo")]));
+        assert_eq!(candidates("Foo<T>::new()"), Some(vec![item_path("crate::Foo::new")]));
+        assert_eq!(candidates("foo!"), Some(vec![item_path("crate::foo")]));
+        assert_eq!(
+            candidates("std::fs::copy"),
+            Some(vec![item_path("crate::std::fs::copy"), item_path("::std::fs::copy")])
+        );
+        assert_eq!(
+            candidates("Vec::push"),
+            Some(vec![item_path("crate::Vec::push"), item_path("::alloc::vec::Vec::push")])
+        );
+
+        assert!(target("crate::Foo").unwrap().explicit);
+        assert!(target("foo::Bar").unwrap().explicit);
+        assert!(target("fn@foo").unwrap().explicit);
+        assert!(target("foo()").unwrap().explicit);
+        assert!(IntralinkTarget::from_string("Foo", true).unwrap().explicit);
+        assert!(!target("Foo").unwrap().explicit);
+
+        assert_eq!(target("https://example.com"), None);
+        assert_eq!(target("foo.md"), None);
+        assert_eq!(target("foo@bar"), None);
+        assert_eq!(target("prim@Foo"), None);
+
+        assert_eq!(target("u32").unwrap().builtin, Some(Builtin::Primitive("u32")));
+        assert_eq!(target("match").unwrap().builtin, Some(Builtin::Keyword("match")));
+        assert_eq!(target("keyword@fn").unwrap().builtin, Some(Builtin::Keyword("fn")));
+        assert_eq!(target("prim@fn").unwrap().candidates, vec![]);
+        assert_eq!(target("std::u32").unwrap().builtin, None);
+        assert_eq!(target("Foo<T"), None);
+    }
+
+    fn dependency(name: &str, package_name: &str, version: &str) -> Dependency {
+        Dependency {
+            name: name.to_owned(),
+            package_name: package_name.to_owned(),
+            version: version.to_owned(),
+            requirement: format!("^{version}"),
+            entryfile: PathBuf::from(format!("/deps/{package_name}/src/lib.rs")),
+            published: true,
+            documentation_url: None,
+            repository: None,
+        }
+    }
+
+    #[test]
+    fn test_intralink_target_with_dependencies() {
+        let dependencies = [dependency("serde_json", "serde_json", "1.0.128")];
+        let candidates = |s: &str| {
+            IntralinkTarget::from_string(s, false).unwrap().with_dependencies(&dependencies)
+        };
+
+        assert_eq!(
+            candidates("serde_json::Value").candidates,
+            vec![item_path("crate::serde_json::Value"), item_path("::serde_json::Value")]
+        );
+        assert_eq!(
+            candidates("::serde_json::from_str()").candidates,
+            vec![item_path("::serde_json::from_str")]
+        );
+        assert_eq!(
+            candidates("crate::serde_json::Value").candidates,
+            vec![item_path("crate::serde_json::Value")]
+        );
+        assert_eq!(candidates("serde_json").candidates[1], item_path("::serde_json"));
+        assert!(candidates("serde_json").explicit);
+        assert_eq!(candidates("Value").candidates, vec![item_path("crate::Value")]);
+    }
+
+    #[test]
+    fn test_rewrite_links_dependencies() {
+        let doc = indoc! { r"
+            Parse it with [`serde_json::from_str()`] into a [`Value`](::serde_json::Value), or use
+            [`toml`].
+            "
+        };
+
+        let symbols_type: HashMap<ItemPath, SymbolType> = [
+            (item_path("::serde_json"), SymbolType::Crate),
+            (item_path("::serde_json::from_str"), SymbolType::Fn),
+            (item_path("::serde_json::Value"), SymbolType::Enum),
+            (item_path("::toml"), SymbolType::Crate),
+        ]
+        .into_iter()
+        .collect();
+        let config = IntralinksConfig {
+            docs_rs: IntralinksDocsRsConfig {
+                dependencies: vec![
+                    dependency("serde_json", "serde_json", "1.0.128"),
+                    dependency("toml", "toml", "0.8.19"),
+                ],
+                docs_url_templates: [(
+                    "toml".to_owned(),
+                    "https://toml.internal/{version}/{path}".to_owned(),
+                )]
+                .into_iter()
+                .collect(),
+                ..IntralinksDocsRsConfig::default()
+            },
+            ..IntralinksConfig::default()
+        };
+
+        let new_doc = rewrite_links(
+            &Doc::from_str(doc),
+            &symbols_type,
+            &HashMap::new(),
+            "foobini",
+            &|_| (),
+            &config,
+        );
+        let expected = indoc! { r"
+            Parse it with [`serde_json::from_str()`](https://docs.rs/serde_json/1.0.128/serde_json/fn.from_str.html) into a [`Value`](https://docs.rs/serde_json/1.0.128/serde_json/enum.Value.html), or use
+            [`toml`](https://toml.internal/0.8.19/toml/).
+            "
+        };
+
+        assert_eq!(new_doc.as_string(), expected);
+    }
+
+    #[test]
+    fn test_rewrite_links_unpublished_dependencies() {
+        let doc = indoc! { r"
+            See [`documented::Foo`], [`undocumented::Bar`], and [its repository](undocumented).
+            "
+        };
+
+        let symbols_type: HashMap<ItemPath, SymbolType> = [
+            (item_path("::documented"), SymbolType::Crate),
+            (item_path("::documented::Foo"), SymbolType::Struct),
+            (item_path("::undocumented"), SymbolType::Crate),
+            (item_path("::undocumented::Bar"), SymbolType::Struct),
+        ]
+        .into_iter()
+        .collect();
+        let config = IntralinksConfig {
+            docs_rs: IntralinksDocsRsConfig {
+                dependencies: vec![
+                    Dependency {
+                        published: false,
+                        documentation_url: Some("https://example.com/docs/documented/".to_owned()),
+                        ..dependency("documented", "documented", "0.2.0")
+                    },
+                    Dependency {
+                        published: false,
+                        repository: Some("https://example.com/undocumented".to_owned()),
+                        ..dependency("undocumented", "undocumented", "0.3.0")
+                    },
+                ],
+                ..IntralinksDocsRsConfig::default()
+            },
+            ..IntralinksConfig::default()
+        };
+
+        let new_doc = rewrite_links(
+            &Doc::from_str(doc),
+            &symbols_type,
+            &HashMap::new(),
+            "foobini",
+            &|msg| panic!("unexpected warning: {msg}"),
+            &config,
+        );
+        let expected = indoc! { r"
+            See [`documented::Foo`](https://example.com/docs/documented/struct.Foo.html), `undocumented::Bar`, and [its repository](https://example.com/undocumented).
+            "
+        };
+
+        assert_eq!(new_doc.as_string(), expected);
+    }
+
+    #[test]
+    fn test_rewrite_links_primitives_and_keywords() {
+        let doc = indoc! { r"
+            # Foobini
+
+            Works with [`u32`], [strings](str), [slices][slice], [`fn`], and [`prim@bool`].
+
+            Also keywords like [`match`], [`keyword@fn`], and [async](keyword@async).
+
+            Items shadow primitives: [`char`].
+            "
+        };
+
+        let symbols_type: HashMap<ItemPath, SymbolType> = [
+            (item_path("crate"), SymbolType::Crate),
+            (item_path("crate::char"), SymbolType::Struct),
+        ]
+        .into_iter()
+        .collect();
+
+        let new_readme = rewrite_links(
+            &Doc::from_str(doc),
+            &symbols_type,
+            &HashMap::new(),
+            "foobini",
+            &|_| (),
+            &IntralinksConfig::default(),
+        );
+        let expected = indoc! { r"
+            # Foobini
+
+            Works with [`u32`](https://doc.rust-lang.org/stable/std/primitive.u32.html), [strings](https://doc.rust-lang.org/stable/std/primitive.str.html), [slices](https://doc.rust-lang.org/stable/std/primitive.slice.html), [`fn`](https://doc.rust-lang.org/stable/std/primitive.fn.html), and [`prim@bool`](https://doc.rust-lang.org/stable/std/primitive.bool.html).
+
+            Also keywords like [`match`](https://doc.rust-lang.org/stable/std/keyword.match.html), [`keyword@fn`](https://doc.rust-lang.org/stable/std/keyword.fn.html), and [async](https://doc.rust-lang.org/stable/std/keyword.async.html).
+
+            Items shadow primitives: [`char`](https://docs.rs/foobini/latest/foobini/struct.char.html).
+            "
+        };
+
+        assert_eq!(new_readme.as_string(), expected);
+    }
+
+    #[test]
+    fn test_rewrite_links_rustdoc_forms() {
+        let doc = indoc! { r"
+            # Foobini
+
+            Check out [this struct](Foo), [this one][Foo], [`Foo`], [Foo], and
+            [`Foo`](crate::amodule::Foo).
+
+            Also [`Foo::new()`], [a field](Foo::bar), [`Vec::push`], [`Option`], and [`println!`].
+
+            Some things are not intralinks, like [the license](LICENSE), [this][unknown] or [`Nope`].
+            "
+        };
+
+        let symbols_type: HashMap<ItemPath, SymbolType> = [
+            (item_path("crate"), SymbolType::Crate),
+            (item_path("crate::Foo"), SymbolType::Struct),
+            (item_path("crate::Foo::new"), SymbolType::ImplItem(ImplSymbolType::Method)),
+            (item_path("crate::Foo::bar"), SymbolType::StructField),
+            (item_path("crate::amodule"), SymbolType::Mod),
+            (item_path("crate::amodule::Foo"), SymbolType::Struct),
+            (item_path("::alloc::vec::Vec"), SymbolType::Struct),
+            (item_path("::alloc::vec::Vec::push"), SymbolType::ImplItem(ImplSymbolType::Method)),
+            (item_path("::core::option::Option"), SymbolType::Enum),
+            (item_path("::std::println"), SymbolType::Macro),
+        ]
+        .into_iter()
+        .collect();
+
+        let warnings = RefCell::new(Vec::new());
+        let new_readme = rewrite_links(
+            &Doc::from_str(doc),
+            &symbols_type,
+            &HashMap::new(),
+            "foobini",
+            &|w| warnings.borrow_mut().push(w.to_owned()),
+            &IntralinksConfig::default(),
+        );
+        let expected = indoc! { r"
+            # Foobini
+
+            Check out [this struct](https://docs.rs/foobini/latest/foobini/struct.Foo.html), [this one](https://docs.rs/foobini/latest/foobini/struct.Foo.html), [`Foo`](https://docs.rs/foobini/latest/foobini/struct.Foo.html), [Foo](https://docs.rs/foobini/latest/foobini/struct.Foo.html), and
+            [`Foo`](https://docs.rs/foobini/latest/foobini/amodule/struct.Foo.html).
+
+            Also [`Foo::new()`](https://docs.rs/foobini/latest/foobini/struct.Foo.html#method.new), [a field](https://docs.rs/foobini/latest/foobini/struct.Foo.html#structfield.bar), [`Vec::push`](https://doc.rust-lang.org/stable/alloc/vec/struct.Vec.html#method.push), [`Option`](https://doc.rust-lang.org/stable/core/option/enum.Option.html), and [`println!`](https://doc.rust-lang.org/stable/std/macro.println.html).
+
+            Some things are not intralinks, like [the license](LICENSE), [this][unknown] or `Nope`.
+            "
+        };
+
+        assert_eq!(new_readme.as_string(), expected);
+        assert_eq!(warnings.into_inner(), vec!["Could not resolve definition of `Nope`."]);
+    }
+
+    #[test]
+    fn test_rewrite_self_links() {
+        let doc = indoc! { r"
+            Create it with [`Self::new`], [Self::default], or [the builder](Self::builder), which
+            is [`method@Self::build()`], [a link][`Self`], but not [Selfish].
+
+            [Selfish]: https://example.com
+            "
+        };
+
+        let new_doc = rewrite_self_links(&Doc::from_str(doc), Some("Parser"));
+        let expected = indoc! { r"
+            Create it with [`Parser::new`], [Parser::default], or [the builder](Parser::builder), which
+            is [`method@Parser::build()`], [a link][`Parser`], but not [Selfish].
+
+            [Selfish]: https://example.com
+            "
+        };
+
+        assert_eq!(new_doc.as_string(), expected);
+
+        let new_doc = rewrite_self_links(&Doc::from_str(doc), None);
+        let expected = indoc! { r"
+            Create it with `Self::new`, `Self::default`, or the builder, which
+            is `method@Self::build()`, a link, but not [Selfish].
+
+            [Selfish]: https://example.com
+            "
+        };
+
+        assert_eq!(new_doc.as_string(), expected);
+    }
 }
@@ -11,6 +11,7 @@ use thiserror::Error;
 use super::ItemPath;
 
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum ModuleWalkError {
     #[error("IO error: {0}")]
     IOError(std::io::Error),
@@ -36,26 +37,47 @@ fn file_ast<P: AsRef<Path>>(filepath: P) -> Result<syn::File, ModuleWalkError> {
     Ok(syn::parse_file(&src)?)
 }
 
-/// Determines the module filename, which can be `<module>.rs` or `<module>/mod.rs`.
-fn module_filename(dir: &Path, module: &Ident) -> Option<PathBuf> {
+/// The path of a `#[path = "…"]` attribute of a module.
+fn path_attribute(module: &syn::ItemMod) -> Option<PathBuf> {
+    use syn::{Expr, ExprLit, Lit, Meta};
+
+    module.attrs.iter().find_map(|attr| match &attr.meta {
+        Meta::NameValue(name_value) if name_value.path.is_ident("path") => {
+            match &name_value.value {
+                Expr::Lit(ExprLit { lit: Lit::Str(path), .. }) => Some(PathBuf::from(path.value())),
+                _ => None,
+            }
+        }
+        _ => None,
+    })
+}
+
+/// Determines the module filename, which can be `<module>.rs` or `<module>/mod.rs`, and whether
+/// it is a `mod.rs` file.
+fn module_filename(dir: &Path, module: &Ident) -> Option<(PathBuf, bool)> {
     let mod_file = dir.join(format!("{module}.rs"));
 
     if mod_file.is_file() {
-        return Some(mod_file);
+        return Some((mod_file, false));
     }
 
     let mod_file = dir.join(module.to_string()).join("mod.rs");
 
     if mod_file.is_file() {
-        return Some(mod_file);
+        return Some((mod_file, true));
     }
 
     None
 }
 
+/// Walks the items of a module.  The files of its submodules are searched in `dir`, except for
+/// the ones with a `#[path]` attribute, which are relative to `path_dir`.  These only differ in
+/// the top level of a non-`mod.rs` file: the submodules of `src/foo.rs` are in `src/foo/`, but
+/// paths are relative to `src/`.
 pub(super) fn walk_module_items(
     ast: &[Item],
     dir: &Path,
+    path_dir: &Path,
     mod_symbol: &ItemPath,
     visit: &mut impl FnMut(&ItemPath, &Item),
     explore_module: &mut impl FnMut(&ItemPath, &syn::ItemMod) -> bool,
@@ -68,25 +90,41 @@ pub(super) fn walk_module_items(
             let child_module_symbol: ItemPath = mod_symbol.clone().join(&module.ident.to_string());
 
             if explore_module(&child_module_symbol, module) {
-                match &module.content {
-                    Some((_, items)) => {
+                match (&module.content, path_attribute(module)) {
+                    (Some((_, items)), path) => {
+                        // The files of the submodules of an inline module are in its directory.
+                        let module_dir = dir
+                            .join(path.unwrap_or_else(|| PathBuf::from(module.ident.to_string())));
+
                         walk_module_items(
                             items,
-                            dir,
+                            &module_dir,
+                            &module_dir,
                             &child_module_symbol,
                             visit,
                             explore_module,
                             emit_warning,
                         )?;
                     }
-                    None => match module_filename(dir, &module.ident) {
+                    // Like `mod.rs` files, files loaded with `#[path]` have their submodules in
+                    // their own directory.
+                    (None, Some(path)) => walk_module_file_with_layout(
+                        path_dir.join(path),
+                        true,
+                        &child_module_symbol,
+                        visit,
+                        explore_module,
+                        emit_warning,
+                    )?,
+                    (None, None) => match module_filename(dir, &module.ident) {
                         None => emit_warning(&format!(
                             "Unable to find module file for module {} in directory \"{}\"",
                             child_module_symbol,
                             dir.display()
                         )),
-                        Some(mod_filename) => walk_module_file(
+                        Some((mod_filename, mod_rs)) => walk_module_file_with_layout(
                             mod_filename,
+                            mod_rs,
                             &child_module_symbol,
                             visit,
                             explore_module,
@@ -101,6 +139,7 @@ pub(super) fn walk_module_items(
     Ok(())
 }
 
+/// Walks the module of a crate's entry file, e.g. `src/lib.rs`.
 pub(super) fn walk_module_file<P: AsRef<Path>>(
     file: P,
     mod_symbol: &ItemPath,
@@ -108,11 +147,32 @@ pub(super) fn walk_module_file<P: AsRef<Path>>(
     explore_module: &mut impl FnMut(&ItemPath, &syn::ItemMod) -> bool,
     emit_warning: &impl Fn(&str),
 ) -> Result<(), ModuleWalkError> {
-    let dir: &Path = file
+    // The crate root has its submodules in its directory, like `mod.rs` files.
+    walk_module_file_with_layout(file, true, mod_symbol, visit, explore_module, emit_warning)
+}
+
+/// Walks the module of a file.  Unless it is a `mod.rs` file (`mod_rs`), the files of its
+/// submodules are in a directory named after it, e.g. `src/foo/` for `src/foo.rs`.
+fn walk_module_file_with_layout<P: AsRef<Path>>(
+    file: P,
+    mod_rs: bool,
+    mod_symbol: &ItemPath,
+    visit: &mut impl FnMut(&ItemPath, &Item),
+    explore_module: &mut impl FnMut(&ItemPath, &syn::ItemMod) -> bool,
+    emit_warning: &impl Fn(&str),
+) -> Result<(), ModuleWalkError> {
+    let file_dir: &Path = file
         .as_ref()
         .parent()
         .unwrap_or_else(|| panic!("failed to get directory of \"{}\"", file.as_ref().display()));
+    let dir: PathBuf = match (mod_rs, file.as_ref().file_stem()) {
+        (false, Some(stem)) => file_dir.join(stem),
+        _ => file_dir.to_path_buf(),
+    };
+    #[cfg(feature = "tracing")]
+    tracing::trace!(file = %file.as_ref().display(), "walking module file");
+
     let ast: syn::File = file_ast(&file)?;
 
-    walk_module_items(&ast.items, dir, mod_symbol, visit, explore_module, emit_warning)
+    walk_module_items(&ast.items, &dir, file_dir, mod_symbol, visit, explore_module, emit_warning)
 }
@@ -0,0 +1,356 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::transform::reference_links::{normalize_label, top_level_definitions};
+use crate::transform::utils::apply_edits;
+use crate::transform::DocTransform;
+use crate::Doc;
+use std::collections::{HashMap, HashSet};
+use std::convert::Infallible;
+use std::ops::Range;
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[non_exhaustive]
+pub enum LinkStyle {
+    /// Links with their destination, e.g. `[foo](https://example.com)`.
+    Inline,
+    /// Links to a definition, e.g. `[foo][1]` with `[1]: https://example.com`.
+    Reference,
+}
+
+/// Converts the links and images to the same style, as some projects enforce one, e.g. with
+/// markdownlint.  Autolinks (`<https://example.com>`) are left untouched.
+///
+/// Reference links are only made inline if their definition is outside of other blocks (e.g. block
+/// quotes or lists), like the definitions removed.  The definitions of the links made reference
+/// links are added to the end of the documentation.
+pub struct DocTransformLinkStyle {
+    style: LinkStyle,
+}
+
+impl DocTransformLinkStyle {
+    #[must_use]
+    pub fn new(style: LinkStyle) -> DocTransformLinkStyle {
+        DocTransformLinkStyle { style }
+    }
+}
+
+/// A link or image to convert, with its text as written (e.g. `[foo]` or `![foo]`).
+struct Link {
+    range: Range<usize>,
+    text: String,
+    /// The destination and title, as written in the link, if it is inline.
+    raw_target: Option<String>,
+    /// The label, if it is a reference link.
+    label: Option<String>,
+    /// The destination and title, as parsed.
+    target: (String, Option<String>),
+}
+
+fn links(source: &str) -> Vec<Link> {
+    use pulldown_cmark::{Event, LinkType, Options, Parser, Tag};
+
+    Parser::new_ext(source, Options::all())
+        .into_offset_iter()
+        .filter_map(|(event, range)| {
+            let Event::Start(
+                Tag::Link { link_type, dest_url, title, id }
+                | Tag::Image { link_type, dest_url, title, id },
+            ) = event
+            else {
+                return None;
+            };
+            let raw = &source[range.clone()];
+            let target = (dest_url.to_string(), Some(title.to_string()).filter(|t| !t.is_empty()));
+            let reference = |range: Range<usize>, text: &str| Link {
+                range,
+                text: text.to_owned(),
+                raw_target: None,
+                label: Some(id.to_string()),
+                target: target.clone(),
+            };
+
+            match link_type {
+                LinkType::Inline => {
+                    let text_end = raw.rfind("](")?;
+
+                    Some(Link {
+                        range: range.clone(),
+                        text: raw[..=text_end].to_owned(),
+                        raw_target: Some(raw[(text_end + 2)..(raw.len() - 1)].trim().to_owned()),
+                        label: None,
+                        target,
+                    })
+                }
+                LinkType::Reference => Some(reference(range.clone(), &raw[..raw.rfind('[')?])),
+                // The range of a collapsed link may not include its `[]`.
+                LinkType::Collapsed => match raw.strip_suffix("[]") {
+                    Some(text) => Some(reference(range.clone(), text)),
+                    None => source[range.end..]
+                        .starts_with("[]")
+                        .then(|| reference(range.start..(range.end + 2), raw)),
+                },
+                LinkType::Shortcut => Some(reference(range.clone(), raw)),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Keeps the links that do not contain another, e.g. an image in a link, which are converted
+/// first.
+fn innermost(links: Vec<Link>) -> Vec<Link> {
+    let ranges: Vec<Range<usize>> = links.iter().map(|link| link.range.clone()).collect();
+
+    links
+        .into_iter()
+        .filter(|link| {
+            !ranges.iter().any(|other| {
+                *other != link.range
+                    && link.range.start <= other.start
+                    && other.end <= link.range.end
+            })
+        })
+        .collect()
+}
+
+/// A label for a link with the text `text`, which is not in `used`.
+fn new_label(text: &str, used: &HashSet<String>) -> String {
+    let text = text.trim_start_matches('!').trim_start_matches('[').trim_end_matches(']');
+    let base = match text.contains(['[', ']', '\\']) || text.trim().is_empty() {
+        true => "link".to_owned(),
+        false => text.split_whitespace().collect::<Vec<_>>().join(" "),
+    };
+    let mut label = base.clone();
+    let mut n = 2;
+
+    while used.contains(&normalize_label(&label)) {
+        label = format!("{base} {n}");
+        n += 1;
+    }
+
+    label
+}
+
+fn make_inline(source: &str) -> Doc {
+    let (definitions, removals) = top_level_definitions(source);
+    let mut raw_targets: HashMap<String, &str> = HashMap::new();
+
+    for definition in &definitions {
+        raw_targets
+            .entry(normalize_label(&definition.label))
+            .or_insert(definition.raw_target.as_str());
+    }
+
+    let raw_target = |link: &Link| -> Option<&str> {
+        raw_targets.get(&normalize_label(link.label.as_ref()?)).copied()
+    };
+    let mut source = source.to_owned();
+
+    loop {
+        let links: Vec<Link> = innermost(
+            links(&source).into_iter().filter(|link| raw_target(link).is_some()).collect(),
+        );
+
+        if links.is_empty() {
+            break;
+        }
+
+        let edits: Vec<(Range<usize>, String)> = links
+            .iter()
+            .filter_map(|link| {
+                Some((link.range.clone(), format!("{}({})", link.text, raw_target(link)?)))
+            })
+            .collect();
+
+        source = apply_edits(&source, edits);
+    }
+
+    // The definitions moved with the links converted, so they are found again to be removed.
+    let removals = match removals.is_empty() {
+        true => removals,
+        false => top_level_definitions(&source).1,
+    };
+
+    Doc::from_str(apply_edits(&source, removals))
+}
+
+fn make_reference(source: &str) -> Doc {
+    use pulldown_cmark::{Options, Parser};
+
+    let (definitions, _) = top_level_definitions(source);
+    let mut used: HashSet<String> = Parser::new_ext(source, Options::all())
+        .reference_definitions()
+        .iter()
+        .map(|(label, _)| normalize_label(label))
+        .collect();
+    // Links to the target of a definition use its label.
+    let mut label_of_target: HashMap<(String, Option<String>), String> = HashMap::new();
+
+    for definition in definitions {
+        label_of_target.entry(definition.target).or_insert(definition.label);
+    }
+
+    let mut new_definitions: Vec<String> = Vec::new();
+    let mut source = source.to_owned();
+
+    loop {
+        let links: Vec<Link> = innermost(
+            links(&source).into_iter().filter(|link| link.raw_target.is_some()).collect(),
+        );
+
+        if links.is_empty() {
+            break;
+        }
+
+        let edits: Vec<(Range<usize>, String)> = links
+            .into_iter()
+            .map(|link| {
+                let label = label_of_target.entry(link.target).or_insert_with(|| {
+                    let label = new_label(&link.text, &used);
+                    let raw_target =
+                        link.raw_target.as_deref().filter(|t| !t.is_empty()).unwrap_or("<>");
+
+                    used.insert(normalize_label(&label));
+                    new_definitions.push(format!("[{label}]: {raw_target}"));
+
+                    label
+                });
+
+                (link.range, format!("{}[{label}]", link.text))
+            })
+            .collect();
+
+        source = apply_edits(&source, edits);
+    }
+
+    match new_definitions.is_empty() {
+        true => Doc::from_str(source),
+        false => Doc::concat(
+            &[Doc::from_str(source), Doc::from_str(new_definitions.join("\n") + "\n")],
+            "\n\n",
+        ),
+    }
+}
+
+impl DocTransform for DocTransformLinkStyle {
+    type E = Infallible;
+
+    fn transform(&self, doc: &Doc) -> Result<Doc, Infallible> {
+        let source = doc.as_string();
+
+        Ok(match self.style {
+            LinkStyle::Inline => make_inline(source),
+            LinkStyle::Reference => make_reference(source),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_link_style_inline() {
+        let doc = indoc! { r#"
+            # Foo
+
+            See [bar], [the docs][Docs], [baz][], and <https://example.com>.
+
+            [![logo][img]][bar]
+
+            [docs]: https://docs.rs/foo
+            [bar]: https://example.com/bar "Bar"
+
+            > [quoted]
+            >
+            > [quoted]: https://example.com/quoted
+
+            [baz]: <https://example.com/b z>
+            [img]: logo.png
+            "#
+        };
+
+        let expected = indoc! { r#"
+            # Foo
+
+            See [bar](https://example.com/bar "Bar"), [the docs](https://docs.rs/foo), [baz](<https://example.com/b z>), and <https://example.com>.
+
+            [![logo](logo.png)](https://example.com/bar "Bar")
+
+            > [quoted]
+            >
+            > [quoted]: https://example.com/quoted
+            "#
+        };
+
+        let transform = DocTransformLinkStyle::new(LinkStyle::Inline);
+        let new_doc = transform.transform(&Doc::from_str(doc)).unwrap();
+
+        assert_eq!(new_doc.as_string(), expected);
+    }
+
+    #[test]
+    fn test_link_style_reference() {
+        let doc = indoc! { r#"
+            # Foo
+
+            See [bar](https://example.com/bar "Bar"), [the docs](https://docs.rs/foo), and
+            [docs](https://docs.rs/foo/latest).
+
+            [![logo](logo.png)](https://example.com/bar "Bar")
+
+            Already a [reference][bar].
+
+            [bar]: https://example.com/bar "Bar"
+            "#
+        };
+
+        let expected = indoc! { r#"
+            # Foo
+
+            See [bar][bar], [the docs][the docs], and
+            [docs][docs].
+
+            [![logo][logo]][bar]
+
+            Already a [reference][bar].
+
+            [bar]: https://example.com/bar "Bar"
+
+            [the docs]: https://docs.rs/foo
+            [docs]: https://docs.rs/foo/latest
+            [logo]: logo.png
+            "#
+        };
+
+        let transform = DocTransformLinkStyle::new(LinkStyle::Reference);
+        let new_doc = transform.transform(&Doc::from_str(doc)).unwrap();
+
+        assert_eq!(new_doc.as_string(), expected);
+    }
+
+    #[test]
+    fn test_link_style_overlapping_event_ranges() {
+        let doc = Doc::from_str("a\n:a\n\ns");
+
+        for style in [LinkStyle::Inline, LinkStyle::Reference] {
+            let transform = DocTransformLinkStyle::new(style);
+
+            assert_eq!(transform.transform(&doc).unwrap().as_string(), doc.as_string());
+        }
+    }
+
+    #[test]
+    fn test_new_label() {
+        let used: HashSet<String> = ["foo".to_owned(), "foo 2".to_owned()].into_iter().collect();
+
+        assert_eq!(new_label("[Foo]", &used), "Foo 3");
+        assert_eq!(new_label("![The  logo]", &used), "The logo");
+        assert_eq!(new_label("[![logo][logo]]", &used), "link");
+    }
+}
@@ -0,0 +1,222 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::transform::DocTransform;
+use crate::Doc;
+use std::convert::Infallible;
+
+/// Indents nested lists with four spaces per level.  Rustdoc accepts nested lists indented to the
+/// content of their parent item (two spaces after `-`, three after `1.`), but some renderers (e.g.
+/// the one of crates.io in some cases) need four.
+pub struct DocTransformListIndentation;
+
+impl DocTransformListIndentation {
+    #[must_use]
+    pub fn new() -> DocTransformListIndentation {
+        DocTransformListIndentation
+    }
+}
+
+/// The width of the list item marker at the start of `item`, including the spaces after it,
+/// e.g. two for `- foo` and three for `1. foo`.
+fn item_marker_width(item: &str) -> usize {
+    let marker_len = match item.chars().next() {
+        Some('-' | '*' | '+') => 1,
+        _ => item.find(['.', ')']).map_or(0, |i| i + 1),
+    };
+    let spaces = item[marker_len..].chars().take_while(|&c| c == ' ').count();
+
+    // With five or more spaces the content is an indented code block that starts one space after
+    // the marker.
+    match spaces {
+        1..=4 => marker_len + spaces,
+        _ => marker_len + 1,
+    }
+}
+
+struct Item {
+    /// Column of the item marker, once reindented.
+    indent: usize,
+    marker_width: usize,
+    line: usize,
+}
+
+impl DocTransform for DocTransformListIndentation {
+    type E = Infallible;
+
+    fn transform(&self, doc: &Doc) -> Result<Doc, Infallible> {
+        use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+
+        let source = doc.as_string();
+        let line_starts: Vec<usize> = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .filter(|&i| i < source.len())
+            .collect();
+        let line_of = |offset: usize| line_starts.partition_point(|&start| start <= offset) - 1;
+        // The column of what starts at `offset`, if there are only spaces before it in its line.
+        // The parser starts the ranges of nested blocks after (part of) their indentation, so this
+        // is the indentation of the whole line.
+        let column_of = |offset: usize| {
+            let line = &source[line_starts[line_of(offset)]..];
+            let prefix = &line[..offset - line_starts[line_of(offset)]];
+            let indentation = line.len() - line.trim_start_matches(' ').len();
+
+            prefix.chars().all(|c| c == ' ').then_some(indentation)
+        };
+
+        // How many spaces to add (or remove, if negative) at the start of each line.
+        let mut shifts: Vec<isize> = vec![0; line_starts.len()];
+        let mut items: Vec<Option<Item>> = Vec::new();
+        let mut block_quote_depth = 0;
+
+        for (event, range) in Parser::new_ext(source, Options::all()).into_offset_iter() {
+            match event {
+                Event::Start(Tag::BlockQuote(_)) => block_quote_depth += 1,
+                Event::End(TagEnd::BlockQuote(_)) => block_quote_depth -= 1,
+                Event::Start(Tag::List(_)) if block_quote_depth == 0 => {
+                    let first_line = line_of(range.start);
+                    let parent = items.last().and_then(Option::as_ref);
+
+                    let (Some(parent), Some(column)) = (parent, column_of(range.start)) else {
+                        continue;
+                    };
+
+                    // A list in the same line as its parent item, e.g. `- - foo`.
+                    if parent.line == first_line {
+                        continue;
+                    }
+
+                    let indent = column.saturating_add_signed(shifts[first_line]);
+                    let expected = parent.indent + parent.marker_width.max(4);
+                    // Columns are bounded by the length of a line, so the conversions never wrap.
+                    #[allow(clippy::cast_possible_wrap)]
+                    let shift = expected as isize - indent as isize;
+                    let last_line = line_of(range.end.max(range.start + 1) - 1);
+
+                    for line_shift in &mut shifts[first_line..=last_line] {
+                        *line_shift += shift;
+                    }
+                }
+                Event::Start(Tag::Item) => {
+                    let item = match block_quote_depth {
+                        0 => column_of(range.start).map(|column| {
+                            let line = line_of(range.start);
+
+                            Item {
+                                indent: column.saturating_add_signed(shifts[line]),
+                                marker_width: item_marker_width(
+                                    source[range].trim_start_matches(' '),
+                                ),
+                                line,
+                            }
+                        }),
+                        _ => None,
+                    };
+
+                    items.push(item);
+                }
+                Event::End(TagEnd::Item) => {
+                    items.pop();
+                }
+                _ => (),
+            }
+        }
+
+        let mut new_source = String::with_capacity(source.len());
+
+        for (line, shift) in source.split_inclusive('\n').zip(shifts) {
+            let spaces = line.len() - line.trim_start_matches(' ').len();
+
+            match shift {
+                _ if line.trim().is_empty() => new_source.push_str(line),
+                0.. => {
+                    new_source.push_str(&" ".repeat(shift.unsigned_abs()));
+                    new_source.push_str(line);
+                }
+                _ => new_source.push_str(&line[shift.unsigned_abs().min(spaces)..]),
+            }
+        }
+
+        Ok(Doc::from_str(new_source))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_list_indentation() {
+        let doc = indoc! { r#"
+            - foo
+              - bar
+
+                More bar.
+                 - baz
+            - qux
+               * quux
+
+                 ```
+                 code
+                 ```
+
+            1. one
+               1. two
+                  - three
+
+            Not a list
+              - indented.
+
+            > - quote
+            >   - nested
+            "#
+        };
+        let expected = indoc! { r#"
+            - foo
+                - bar
+
+                  More bar.
+                    - baz
+            - qux
+                * quux
+
+                  ```
+                  code
+                  ```
+
+            1. one
+                1. two
+                    - three
+
+            Not a list
+              - indented.
+
+            > - quote
+            >   - nested
+            "#
+        };
+
+        let transform = DocTransformListIndentation::new();
+        let new_doc = transform.transform(&Doc::from_str(doc)).unwrap();
+
+        assert_eq!(new_doc.as_string(), expected);
+    }
+
+    #[test]
+    fn test_list_indentation_wide_marker() {
+        let doc = indoc! { r#"
+            100. foo
+                 - bar
+            "#
+        };
+
+        let transform = DocTransformListIndentation::new();
+        let new_doc = transform.transform(&Doc::from_str(doc)).unwrap();
+
+        assert_eq!(new_doc.as_string(), doc);
+    }
+}
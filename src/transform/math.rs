@@ -0,0 +1,276 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::transform::utils::apply_edits;
+use crate::transform::DocTransform;
+use crate::Doc;
+use std::convert::Infallible;
+use std::fmt::Write;
+use std::ops::Range;
+
+const CODECOGS_URL: &str = "https://latex.codecogs.com/svg.image?";
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[non_exhaustive]
+pub enum MathAction {
+    /// Replaces the math with code showing its LaTeX source.
+    Code,
+    /// Replaces the math with an image of it, rendered by <https://latex.codecogs.com>.
+    Image,
+}
+
+/// Handles math (`$…$`, `$$…$$`, and ```` ```math ```` blocks), which is rendered by GitHub but
+/// not by every markdown renderer (e.g. crates.io).  A warning lists the lines of the
+/// documentation with math that was converted.
+pub struct DocTransformMath<F> {
+    action: MathAction,
+    emit_warning: F,
+}
+
+impl<F> DocTransformMath<F>
+where
+    F: Fn(&str),
+{
+    #[must_use]
+    pub fn new(action: MathAction, emit_warning: F) -> DocTransformMath<F> {
+        DocTransformMath { action, emit_warning }
+    }
+}
+
+fn percent_encode(str: &str) -> String {
+    str.bytes().fold(String::with_capacity(str.len()), |mut encoded, b| {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(char::from(b));
+            }
+            _ => {
+                let _ = write!(encoded, "%{b:02X}");
+            }
+        }
+        encoded
+    })
+}
+
+fn code_span(code: &str) -> String {
+    match code.contains('`') {
+        true => format!("`` {code} ``"),
+        false => format!("`{code}`"),
+    }
+}
+
+fn image(latex: &str) -> String {
+    format!("![math]({CODECOGS_URL}{})", percent_encode(latex.trim()))
+}
+
+/// If the range is alone in its line(s) returns the indentation of the line.
+fn alone_in_lines<'a>(source: &'a str, range: &Range<usize>) -> Option<&'a str> {
+    let line_start = source[..range.start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = source[range.end..].find('\n').map_or(source.len(), |i| range.end + i);
+    let indent = &source[line_start..range.start];
+
+    match indent.trim().is_empty() && source[range.end..line_end].trim().is_empty() {
+        true => Some(indent),
+        false => None,
+    }
+}
+
+impl<F> DocTransformMath<F>
+where
+    F: Fn(&str),
+{
+    fn display_math(&self, source: &str, range: &Range<usize>, latex: &str) -> String {
+        match (self.action, alone_in_lines(source, range)) {
+            (MathAction::Code, Some(indent)) => {
+                let latex = latex.trim().replace('\n', &format!("\n{indent}"));
+
+                format!("```text\n{indent}{latex}\n{indent}```")
+            }
+            (MathAction::Code, None) => code_span(latex.trim()),
+            (MathAction::Image, _) => image(latex),
+        }
+    }
+
+    fn math_code_block(&self, source: &str, range: &Range<usize>, latex: &str) -> String {
+        let block = &source[range.clone()];
+
+        match self.action {
+            MathAction::Code => {
+                // We only need to replace the info string of the opening fence.
+                let fence_len =
+                    block.trim_start().len() - block.trim_start_matches(['`', '~']).len();
+                let indent = block.len() - block.trim_start().len();
+                let info_end = block.find('\n').unwrap_or(block.len());
+
+                format!("{}text{}", &block[..indent + fence_len], &block[info_end..])
+            }
+            MathAction::Image => match block.ends_with('\n') {
+                true => image(latex) + "\n",
+                false => image(latex),
+            },
+        }
+    }
+}
+
+impl<F> DocTransform for DocTransformMath<F>
+where
+    F: Fn(&str),
+{
+    type E = Infallible;
+
+    fn transform(&self, doc: &Doc) -> Result<Doc, Infallible> {
+        use pulldown_cmark::{CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
+
+        let source = doc.as_string();
+        let mut edits: Vec<(Range<usize>, String)> = Vec::new();
+        let mut math_code_block: Option<(Range<usize>, String)> = None;
+
+        for (event, range) in Parser::new_ext(source, Options::all()).into_offset_iter() {
+            match event {
+                Event::InlineMath(latex) => {
+                    let replacement = match self.action {
+                        MathAction::Code => code_span(&latex),
+                        MathAction::Image => image(&latex),
+                    };
+
+                    edits.push((range, replacement));
+                }
+                Event::DisplayMath(latex) => {
+                    let replacement = self.display_math(source, &range, &latex);
+
+                    edits.push((range, replacement));
+                }
+                Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info)))
+                    if info.split(|c: char| c == ',' || c.is_whitespace()).next()
+                        == Some("math") =>
+                {
+                    math_code_block = Some((range, String::new()));
+                }
+                Event::Text(text) => {
+                    if let Some((_, latex)) = &mut math_code_block {
+                        latex.push_str(&text);
+                    }
+                }
+                Event::End(TagEnd::CodeBlock) => {
+                    if let Some((range, latex)) = math_code_block.take() {
+                        let replacement = self.math_code_block(source, &range, &latex);
+
+                        edits.push((range, replacement));
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        if !edits.is_empty() {
+            let mut lines: Vec<usize> = edits
+                .iter()
+                .map(|(range, _)| source[..range.start].matches('\n').count() + 1)
+                .collect();
+
+            lines.dedup();
+
+            let lines = lines.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ");
+
+            (self.emit_warning)(&format!(
+                "math converted to {} in line(s) {lines} of the documentation",
+                match self.action {
+                    MathAction::Code => "code",
+                    MathAction::Image => "images",
+                }
+            ));
+        }
+
+        Ok(Doc::from_str(apply_edits(source, edits)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+    use std::cell::RefCell;
+
+    const DOC: &str = indoc! { r#"
+        # Math
+
+        Euler: $e^{i\pi} + 1 = 0$.
+
+        $$
+        \sum_{i=1}^n i = \frac{n(n+1)}{2}
+        $$
+
+        ```math
+        x^2
+        ```
+
+        Costs $5 or $6.
+        "#
+    };
+
+    #[test]
+    fn test_math_code() {
+        let expected = indoc! { r#"
+            # Math
+
+            Euler: `e^{i\pi} + 1 = 0`.
+
+            ```text
+            \sum_{i=1}^n i = \frac{n(n+1)}{2}
+            ```
+
+            ```text
+            x^2
+            ```
+
+            Costs $5 or $6.
+            "#
+        };
+
+        let warnings = RefCell::new(Vec::new());
+        let transform = DocTransformMath::new(MathAction::Code, |msg| {
+            warnings.borrow_mut().push(msg.to_owned());
+        });
+        let new_doc = transform.transform(&Doc::from_str(DOC)).unwrap();
+
+        assert_eq!(new_doc.as_string(), expected);
+        assert_eq!(
+            warnings.into_inner(),
+            vec!["math converted to code in line(s) 3, 5, 9 of the documentation"]
+        );
+    }
+
+    #[test]
+    fn test_math_image() {
+        let expected = indoc! { r#"
+            # Math
+
+            Euler: ![math](https://latex.codecogs.com/svg.image?e%5E%7Bi%5Cpi%7D%20%2B%201%20%3D%200).
+
+            ![math](https://latex.codecogs.com/svg.image?%5Csum_%7Bi%3D1%7D%5En%20i%20%3D%20%5Cfrac%7Bn%28n%2B1%29%7D%7B2%7D)
+
+            ![math](https://latex.codecogs.com/svg.image?x%5E2)
+
+            Costs $5 or $6.
+            "#
+        };
+
+        let transform = DocTransformMath::new(MathAction::Image, |_| ());
+        let new_doc = transform.transform(&Doc::from_str(DOC)).unwrap();
+
+        assert_eq!(new_doc.as_string(), expected);
+    }
+
+    #[test]
+    fn test_math_code_inline_display_math() {
+        let doc = "Sum $$a + b$$ is `` ` ``, $`a`$.\n";
+        let expected = "Sum `a + b` is `` ` ``, `` `a` ``.\n";
+
+        let transform = DocTransformMath::new(MathAction::Code, |_| ());
+        let new_doc = transform.transform(&Doc::from_str(doc)).unwrap();
+
+        assert_eq!(new_doc.as_string(), expected);
+    }
+}
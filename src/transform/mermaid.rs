@@ -0,0 +1,175 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::transform::utils::apply_edits;
+use crate::transform::DocTransform;
+use crate::Doc;
+use std::convert::Infallible;
+use std::ops::Range;
+
+const MERMAID_INK_URL: &str = "https://mermaid.ink/img/";
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[non_exhaustive]
+pub enum MermaidAction {
+    /// Replaces the diagram with an image of it, rendered by <https://mermaid.ink>.
+    Image,
+    /// Replaces the diagram with a note pointing to the documentation, where the diagram is
+    /// rendered.
+    Note { documentation_url: String },
+}
+
+/// Handles mermaid diagrams, which are rendered by GitHub but not by every markdown renderer (e.g.
+/// crates.io).
+pub struct DocTransformMermaid {
+    action: MermaidAction,
+}
+
+impl DocTransformMermaid {
+    #[must_use]
+    pub fn new(action: MermaidAction) -> DocTransformMermaid {
+        DocTransformMermaid { action }
+    }
+}
+
+/// Base64 encoding with the url safe alphabet and without padding.
+fn base64_url(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let n =
+            chunk.iter().enumerate().fold(0_u32, |n, (i, &b)| n | (u32::from(b) << (16 - 8 * i)));
+
+        for i in 0..=chunk.len() {
+            encoded.push(char::from(ALPHABET[((n >> (18 - 6 * i)) & 0x3f) as usize]));
+        }
+    }
+
+    encoded
+}
+
+fn is_mermaid_code_block(info: &str) -> bool {
+    info.split(|c: char| c == ',' || c.is_whitespace()).next() == Some("mermaid")
+}
+
+impl DocTransform for DocTransformMermaid {
+    type E = Infallible;
+
+    fn transform(&self, doc: &Doc) -> Result<Doc, Infallible> {
+        use pulldown_cmark::{CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
+
+        let source = doc.as_string();
+        let mut edits: Vec<(Range<usize>, String)> = Vec::new();
+        let mut diagram: Option<(Range<usize>, String)> = None;
+
+        for (event, range) in Parser::new_ext(source, Options::all()).into_offset_iter() {
+            match event {
+                Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info)))
+                    if is_mermaid_code_block(&info) =>
+                {
+                    diagram = Some((range, String::new()));
+                }
+                Event::Text(text) => {
+                    if let Some((_, code)) = &mut diagram {
+                        code.push_str(&text);
+                    }
+                }
+                Event::End(TagEnd::CodeBlock) => {
+                    if let Some((range, code)) = diagram.take() {
+                        let replacement = match &self.action {
+                            MermaidAction::Image => {
+                                format!("![diagram]({MERMAID_INK_URL}{})", base64_url(code.as_bytes()))
+                            }
+                            MermaidAction::Note { documentation_url } => format!(
+                                "*This diagram is available in the [documentation]({documentation_url}).*"
+                            ),
+                        };
+                        let replacement = match source[range.clone()].ends_with('\n') {
+                            true => replacement + "\n",
+                            false => replacement,
+                        };
+
+                        edits.push((range, replacement));
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        Ok(Doc::from_str(apply_edits(source, edits)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    const DOC: &str = indoc! { r#"
+        # Architecture
+
+        ```mermaid
+        graph TD;
+            A-->B;
+        ```
+
+        ```rust
+        let a = 1;
+        ```
+        "#
+    };
+
+    #[test]
+    fn test_mermaid_image() {
+        let expected = indoc! { r#"
+            # Architecture
+
+            ![diagram](https://mermaid.ink/img/Z3JhcGggVEQ7CiAgICBBLS0-QjsK)
+
+            ```rust
+            let a = 1;
+            ```
+            "#
+        };
+
+        let transform = DocTransformMermaid::new(MermaidAction::Image);
+        let new_doc = transform.transform(&Doc::from_str(DOC)).unwrap();
+
+        assert_eq!(new_doc.as_string(), expected);
+    }
+
+    #[test]
+    fn test_mermaid_note() {
+        let expected = indoc! { r#"
+            # Architecture
+
+            *This diagram is available in the [documentation](https://docs.rs/foo).*
+
+            ```rust
+            let a = 1;
+            ```
+            "#
+        };
+
+        let action = MermaidAction::Note { documentation_url: "https://docs.rs/foo".to_owned() };
+        let transform = DocTransformMermaid::new(action);
+        let new_doc = transform.transform(&Doc::from_str(DOC)).unwrap();
+
+        assert_eq!(new_doc.as_string(), expected);
+    }
+
+    #[test]
+    fn test_base64_url() {
+        assert_eq!(base64_url(b""), "");
+        assert_eq!(base64_url(b"f"), "Zg");
+        assert_eq!(base64_url(b"fo"), "Zm8");
+        assert_eq!(base64_url(b"foo"), "Zm9v");
+        assert_eq!(base64_url(b"foob"), "Zm9vYg");
+        assert_eq!(base64_url(&[0xfb, 0xff]), "-_8");
+    }
+}
@@ -5,16 +5,52 @@
 
 use crate::Doc;
 
+mod blank_lines;
+#[cfg(feature = "rust-source")]
+mod cfg_directives;
+mod details;
+mod emoji;
+mod hard_breaks;
+mod heading_numbering;
+#[cfg(feature = "rust-source")]
 mod intralinks;
+mod link_style;
+mod list_indentation;
+mod math;
+mod mermaid;
+mod reference_links;
 mod rust_markdown_tag;
 mod rust_remove_comments;
-mod utils;
+mod setext_headings;
+mod shared_code_blocks;
+mod tables;
+pub(crate) mod utils;
 
+pub use blank_lines::DocTransformBlankLines;
+#[cfg(feature = "rust-source")]
+pub use cfg_directives::{CfgDirectiveError, DocTransformCfgDirectives};
+pub use details::{DetailsAction, DocTransformDetails};
+pub use emoji::{DocTransformEmoji, EmojiConversion};
+pub use hard_breaks::{DocTransformHardBreaks, HardBreakAction};
+pub use heading_numbering::DocTransformHeadingNumbering;
+#[cfg(feature = "rust-source")]
 pub use intralinks::{
-    DocTransformIntralinks, IntralinkError, IntralinksConfig, IntralinksDocsRsConfig,
+    intralink_count, transform_intra_doc_links, DocTransformIntralinks, IntralinkError,
+    IntralinksConfig, IntralinksDocsRsConfig, LinkContext, TransformResult,
 };
+pub use link_style::{DocTransformLinkStyle, LinkStyle};
+pub use list_indentation::DocTransformListIndentation;
+pub use math::{DocTransformMath, MathAction};
+pub use mermaid::{DocTransformMermaid, MermaidAction};
+pub use reference_links::DocTransformReferenceLinks;
 pub use rust_markdown_tag::DocTransformRustMarkdownTag;
 pub use rust_remove_comments::DocTransformRustRemoveComments;
+pub use setext_headings::DocTransformSetextHeadings;
+pub use shared_code_blocks::DocTransformSharedCodeBlocks;
+
+pub(crate) use setext_headings::setext_to_atx;
+pub(crate) use shared_code_blocks::code_block_ranges;
+pub use tables::DocTransformFormatTables;
 
 pub trait DocTransform {
     type E;
@@ -0,0 +1,334 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::transform::utils::apply_edits;
+use crate::transform::DocTransform;
+use crate::Doc;
+use std::collections::{HashMap, HashSet};
+use std::convert::Infallible;
+use std::ops::Range;
+
+/// Moves the reference link definitions to the end of the documentation, sorted by label, which
+/// keeps the output tidy and diffs stable.  Definitions with the same target are merged into one,
+/// and the links that used the labels dropped are updated to use the label kept.
+///
+/// Only the definitions outside of other blocks (e.g. block quotes or lists) are moved.
+pub struct DocTransformReferenceLinks;
+
+impl DocTransformReferenceLinks {
+    #[must_use]
+    pub fn new() -> DocTransformReferenceLinks {
+        DocTransformReferenceLinks
+    }
+}
+
+/// A reference link definition, e.g. `[foo]: https://example.com "Foo"`.
+pub(super) struct Definition {
+    pub(super) range: Range<usize>,
+    pub(super) label: String,
+    /// The destination and title, as written.
+    pub(super) raw_target: String,
+    /// The destination and title, as parsed, which tell whether two definitions are the same.
+    pub(super) target: (String, Option<String>),
+}
+
+/// Labels match case-insensitively, with consecutive whitespace collapsed.
+pub(super) fn normalize_label(label: &str) -> String {
+    label.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// The parts of the source between the top-level blocks.  Reference link definitions have no
+/// events, so they are in these parts, with blank lines.
+fn top_level_gaps(source: &str) -> Vec<Range<usize>> {
+    use pulldown_cmark::{Event, Options, Parser};
+
+    let mut gaps = Vec::new();
+    let mut depth: usize = 0;
+    let mut last_end = 0;
+
+    // The ranges of some events, e.g. of definition lists, can start before the end of the
+    // previous block, so a gap is only opened if the block starts after it.
+    let push_gap = |gaps: &mut Vec<Range<usize>>, last_end: usize, start: usize| {
+        if start > last_end {
+            gaps.push(last_end..start);
+        }
+    };
+
+    for (event, range) in Parser::new_ext(source, Options::all()).into_offset_iter() {
+        match event {
+            Event::Start(_) => {
+                if depth == 0 {
+                    push_gap(&mut gaps, last_end, range.start);
+                }
+
+                depth += 1;
+            }
+            Event::End(_) => {
+                depth -= 1;
+
+                if depth == 0 {
+                    last_end = last_end.max(range.end);
+                }
+            }
+            _ if depth == 0 => {
+                push_gap(&mut gaps, last_end, range.start);
+                last_end = last_end.max(range.end);
+            }
+            _ => (),
+        }
+    }
+
+    push_gap(&mut gaps, last_end, source.len());
+    gaps.retain(|gap| !source[gap.clone()].trim().is_empty());
+
+    gaps
+}
+
+/// The definitions in `gap`, in order, or `None` if it has anything else.  The parser only reports
+/// the first definition of a label, so the ones it finds are blanked out and the rest parsed again
+/// to find the definitions that repeat a label.
+fn gap_definitions(source: &str, gap: &Range<usize>) -> Option<Vec<Definition>> {
+    use pulldown_cmark::{Options, Parser};
+
+    let mut text = source[gap.clone()].to_owned();
+    let mut definitions = Vec::new();
+
+    while !text.trim().is_empty() {
+        let parser = Parser::new_ext(&text, Options::all());
+        let found: Vec<Definition> = parser
+            .reference_definitions()
+            .iter()
+            .map(|(label, definition)| {
+                let raw = &text[definition.span.clone()];
+                let raw_target = raw.get(label.len() + 3..).unwrap_or_default();
+
+                Definition {
+                    range: (gap.start + definition.span.start)..(gap.start + definition.span.end),
+                    label: label.to_owned(),
+                    raw_target: raw_target.lines().map(str::trim).collect::<Vec<_>>().join(" "),
+                    target: (
+                        definition.dest.to_string(),
+                        definition.title.as_ref().map(ToString::to_string),
+                    ),
+                }
+            })
+            .collect();
+
+        if found.is_empty() {
+            return None;
+        }
+
+        for definition in &found {
+            let span = (definition.range.start - gap.start)..(definition.range.end - gap.start);
+            let blank: String =
+                text[span.clone()].chars().map(|c| if c == '\n' { '\n' } else { ' ' }).collect();
+
+            text.replace_range(span, &blank);
+        }
+
+        definitions.extend(found);
+    }
+
+    definitions.sort_by_key(|definition| definition.range.start);
+
+    Some(definitions)
+}
+
+/// The edits that update the links and images that use a label in `renames`.
+fn rename_edits(source: &str, renames: &HashMap<String, String>) -> Vec<(Range<usize>, String)> {
+    use pulldown_cmark::{Event, LinkType, Options, Parser, Tag};
+
+    Parser::new_ext(source, Options::all())
+        .into_offset_iter()
+        .filter_map(|(event, range)| {
+            let Event::Start(Tag::Link { link_type, id, .. } | Tag::Image { link_type, id, .. }) =
+                event
+            else {
+                return None;
+            };
+            let label = renames.get(&normalize_label(&id))?;
+            let label_ref = format!("[{label}]");
+
+            match link_type {
+                LinkType::Reference => {
+                    let start = range.start + source[range.clone()].rfind('[')?;
+
+                    Some((start..range.end, label_ref))
+                }
+                // The range of a collapsed link may not include its `[]`.
+                LinkType::Collapsed => match source[range.clone()].ends_with("[]") {
+                    true => Some(((range.end - 2)..range.end, label_ref)),
+                    false => source[range.end..]
+                        .starts_with("[]")
+                        .then(|| (range.end..(range.end + 2), label_ref)),
+                },
+                LinkType::Shortcut => Some((range.end..range.end, label_ref)),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// The range of the whole lines of `range`, with one of the blank lines around it, so that the
+/// blocks before and after it stay separated by a single blank line.
+fn removal_range(source: &str, range: &Range<usize>) -> Range<usize> {
+    let start = source[..range.start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = |i: usize| source[i..].find('\n').map_or(source.len(), |j| i + j + 1);
+    let end = line_end(range.end.saturating_sub(1).max(start));
+    let blank_before = start == 0 || source[..start - 1].ends_with('\n');
+    let blank_after = end < source.len() && source[end..line_end(end)].trim().is_empty();
+
+    match (blank_before, blank_after) {
+        (true, true) => start..line_end(end),
+        (true, false) if start > 0 && end == source.len() => (start - 1)..end,
+        _ => start..end,
+    }
+}
+
+/// The reference link definitions outside of other blocks, in order, and the edits that remove
+/// them.
+pub(super) fn top_level_definitions(
+    source: &str,
+) -> (Vec<Definition>, Vec<(Range<usize>, String)>) {
+    let gaps: Vec<Vec<Definition>> =
+        top_level_gaps(source).iter().filter_map(|gap| gap_definitions(source, gap)).collect();
+    // The gaps only have definitions, so their lines are removed altogether.
+    let removals: Vec<(Range<usize>, String)> = gaps
+        .iter()
+        .filter_map(|definitions| {
+            let range = definitions.first()?.range.start..definitions.last()?.range.end;
+
+            Some((removal_range(source, &range), String::new()))
+        })
+        .collect();
+
+    (gaps.into_iter().flatten().collect(), removals)
+}
+
+impl DocTransform for DocTransformReferenceLinks {
+    type E = Infallible;
+
+    fn transform(&self, doc: &Doc) -> Result<Doc, Infallible> {
+        let source = doc.as_string();
+        let (definitions, removals) = top_level_definitions(source);
+
+        if definitions.is_empty() {
+            return Ok(doc.clone());
+        }
+
+        // Only the first definition of a label is used, so the others are dropped.
+        let mut labels: HashSet<String> = HashSet::new();
+        let mut kept: Vec<&Definition> = definitions
+            .iter()
+            .filter(|definition| labels.insert(normalize_label(&definition.label)))
+            .collect();
+
+        kept.sort_by_cached_key(|definition| {
+            (normalize_label(&definition.label), definition.label.clone())
+        });
+
+        let mut label_of_target: HashMap<&(String, Option<String>), &str> = HashMap::new();
+        let mut renames: HashMap<String, String> = HashMap::new();
+
+        kept.retain(|definition| match label_of_target.get(&definition.target) {
+            Some(label) => {
+                renames.insert(normalize_label(&definition.label), (*label).to_owned());
+                false
+            }
+            None => {
+                label_of_target.insert(&definition.target, &definition.label);
+                true
+            }
+        });
+
+        let mut edits = rename_edits(source, &renames);
+
+        edits.extend(removals);
+
+        let new_source = apply_edits(source, edits);
+        let definitions: Vec<String> = kept
+            .iter()
+            .map(|definition| format!("[{}]: {}", definition.label, definition.raw_target))
+            .collect();
+
+        Ok(Doc::concat(
+            &[Doc::from_str(new_source), Doc::from_str(definitions.join("\n") + "\n")],
+            "\n\n",
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_reference_links() {
+        let doc = indoc! { r#"
+            # Foo
+
+            See [bar], [the docs][Docs], and [baz][].
+
+            [docs]: https://docs.rs/foo
+            [bar]: https://example.com/bar "Bar"
+
+            ## Baz
+
+            ![logo][img]
+
+            > [quoted]: https://example.com/quoted
+
+            [baz]: https://example.com/bar
+              "Bar"
+            [img]: logo.png
+            [bar]: https://example.com/ignored
+            "#
+        };
+
+        let expected = indoc! { r#"
+            # Foo
+
+            See [bar], [the docs][Docs], and [baz][bar].
+
+            ## Baz
+
+            ![logo][img]
+
+            > [quoted]: https://example.com/quoted
+
+            [bar]: https://example.com/bar "Bar"
+            [docs]: https://docs.rs/foo
+            [img]: logo.png
+            "#
+        };
+
+        let transform = DocTransformReferenceLinks::new();
+        let new_doc = transform.transform(&Doc::from_str(doc)).unwrap();
+
+        assert_eq!(new_doc.as_string(), expected);
+    }
+
+    #[test]
+    fn test_reference_links_none() {
+        let doc = Doc::from_str("# Foo\n\n[Foo](https://example.com).\n");
+        let transform = DocTransformReferenceLinks::new();
+
+        assert_eq!(transform.transform(&doc).unwrap().as_string(), doc.as_string());
+    }
+
+    #[test]
+    fn test_reference_links_overlapping_event_ranges() {
+        // Definition lists and math have event ranges that start before the end of the previous
+        // block.
+        for source in ["a\n:a\n\ns", "$\n:<!--\n$", "Usage\n: see below\n"] {
+            let doc = Doc::from_str(source);
+            let transform = DocTransformReferenceLinks::new();
+
+            assert_eq!(transform.transform(&doc).unwrap().as_string(), doc.as_string());
+        }
+    }
+}
@@ -3,7 +3,9 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/.
  */
 
-use crate::transform::utils::rust_code_block_iterator;
+use crate::transform::utils::{
+    block_quote_depth, rust_code_block_iterator, split_block_quote_prefix,
+};
 use crate::transform::DocTransform;
 use crate::utils::ItemOrOther;
 use crate::Doc;
@@ -19,30 +21,32 @@ impl DocTransformRustMarkdownTag {
 }
 
 fn process_code_block(new_doc_str: &mut String, code_block: &str) {
-    let fenced = code_block.starts_with("```");
+    let fence_char = code_block.chars().next().filter(|c| ['`', '~'].contains(c));
+    let fenced = fence_char.is_some_and(|c| code_block.starts_with(&c.to_string().repeat(3)));
+    let line_start = new_doc_str.rfind('\n').map_or(0, |i| i + 1);
+    // Inside blockquotes every line but the first starts with the `>` markers, which the fences we
+    // add need as well.
+    let depth = block_quote_depth(&new_doc_str[line_start..]);
+    let (quote_prefix, indent) = split_block_quote_prefix(&new_doc_str[line_start..], depth);
+    let quote_prefix = quote_prefix.to_owned();
     let mut base_indent = 0;
 
     if !fenced {
-        while !new_doc_str.ends_with('\n') && !new_doc_str.is_empty() {
-            new_doc_str.pop();
-            base_indent += 1;
-        }
-
+        base_indent = indent.len();
+        new_doc_str.truncate(line_start + quote_prefix.len());
         new_doc_str.push_str("```rust\n");
+        new_doc_str.push_str(&quote_prefix);
     }
 
     for (i, line) in code_block.split('\n').enumerate() {
         match i {
             0 if fenced => {
-                assert!(
-                    line.starts_with("```"),
-                    "fenced code block does not start with triple backtick"
-                );
-
                 // A fence can have more than three backticks.  We need to preserve that, since
                 // it can be used to escape triple fences inside the code block itself.
                 // See https://stackoverflow.com/a/31834381.
-                line.chars().take_while(|c| *c == '`').for_each(|c| new_doc_str.push(c));
+                line.chars()
+                    .take_while(|c| Some(*c) == fence_char)
+                    .for_each(|c| new_doc_str.push(c));
 
                 new_doc_str.push_str("rust");
             }
@@ -50,16 +54,21 @@ fn process_code_block(new_doc_str: &mut String, code_block: &str) {
                 new_doc_str.push_str(line);
             }
             _ => {
+                let (prefix, line) = split_block_quote_prefix(line, depth);
+
                 new_doc_str.push('\n');
+                new_doc_str.push_str(prefix);
 
-                if line.len() > base_indent {
-                    new_doc_str.push_str(&line[base_indent..]);
-                }
+                // Lines can be indented with tabs, or less than the first line if they are blank.
+                let indent = line.len() - line.trim_start_matches([' ', '\t']).len();
+
+                new_doc_str.push_str(&line[indent.min(base_indent)..]);
             }
         }
     }
 
     if !fenced {
+        new_doc_str.push_str(&quote_prefix);
         new_doc_str.push_str("```\n");
     }
 }
@@ -261,6 +270,50 @@ mod tests {
         assert_eq!(transform.transform(&doc).unwrap(), expected);
     }
 
+    #[test]
+    fn test_markdown_tag_block_quote() {
+        let doc_str = indoc! { r#"
+            > ```
+            > println!("Hi");
+            > ```
+            >
+            >     if true {
+            >         println!("Hi");
+            >     }
+            >
+            > > Nested:
+            > >
+            > >     println!("Hi");
+            "#
+        };
+
+        let expected_str = indoc! { r#"
+            > ```rust
+            > println!("Hi");
+            > ```
+            >
+            > ```rust
+            > if true {
+            >     println!("Hi");
+            > }
+            > ```
+            >
+            > > Nested:
+            > >
+            > > ```rust
+            > > println!("Hi");
+            > > ```
+            "#
+        };
+
+        let doc = Doc::from_str(doc_str);
+        let expected = Doc::from_str(expected_str);
+
+        let transform = DocTransformRustMarkdownTag::new();
+
+        assert_eq!(transform.transform(&doc).unwrap(), expected);
+    }
+
     #[test]
     fn test_markdown_tag_indent_code_beginning_file() {
         let doc_str = indoc! { r#"
@@ -346,4 +399,24 @@ mod tests {
             assert_eq!(transform.transform(&doc).unwrap(), expected);
         }
     }
+
+    #[test]
+    fn test_markdown_tag_tilde_fence() {
+        let doc = Doc::from_str("~~~~no_run\nloop {}\n~~~~\n");
+        let expected = Doc::from_str("~~~~rust\nloop {}\n~~~~\n");
+
+        let transform = DocTransformRustMarkdownTag::new();
+
+        assert_eq!(transform.transform(&doc).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_markdown_tag_indented_with_tabs() {
+        let doc = Doc::from_str("Foo:\n\n    let a = 1;\n\tlet b = \"😀\";\n\nEnd\n");
+        let expected = Doc::from_str("Foo:\n\n```rust\nlet a = 1;\nlet b = \"😀\";\n```\n\nEnd\n");
+
+        let transform = DocTransformRustMarkdownTag::new();
+
+        assert_eq!(transform.transform(&doc).unwrap(), expected);
+    }
 }
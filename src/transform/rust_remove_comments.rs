@@ -3,7 +3,10 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/.
  */
 
-use crate::transform::utils::rust_code_block_iterator;
+use crate::transform::utils::{
+    block_quote_depth, code_block_has_tag, is_line_commented, rust_code_block_iterator,
+    split_block_quote_prefix, unhide_line, TAG_KEEP_HIDDEN,
+};
 use crate::transform::DocTransform;
 use crate::utils::ItemOrOther;
 use crate::Doc;
@@ -18,14 +21,21 @@ impl DocTransformRustRemoveComments {
     }
 }
 
-fn is_line_commented(line: &str) -> bool {
-    line.trim_start().starts_with("# ") || line.trim() == "#"
-}
-
+/// Removes the hidden lines of the code block, or shows them if the block is tagged with
+/// `rdme-keep-hidden`.
 fn process_code_block(new_doc_str: &mut String, code_block: &str) {
+    let keep_hidden = code_block_has_tag(code_block, TAG_KEEP_HIDDEN);
+    let line_start = new_doc_str.rfind('\n').map_or(0, |i| i + 1);
+    // Inside blockquotes every line but the first starts with the `>` markers.
+    let depth = block_quote_depth(&new_doc_str[line_start..]);
     let mut first = true;
 
     for (i, line) in code_block.split('\n').enumerate() {
+        let code = match i {
+            0 => line,
+            _ => split_block_quote_prefix(line, depth).1,
+        };
+
         // If we have an indent code block and we start with a comment we need to
         // drop any indent whitespace that started this indent block, since
         // pulldown-cmark doesn't consider it part of the code block.
@@ -35,15 +45,15 @@ fn process_code_block(new_doc_str: &mut String, code_block: &str) {
             }
         }
 
-        if !is_line_commented(line) {
+        if keep_hidden || !is_line_commented(code) {
             if !first {
                 new_doc_str.push('\n');
             }
 
             // Lines starting with `##` are not comments, that is a way to intentionally start a
             // line with `#`.  See https://github.com/rust-lang/rust/pull/41785.
-            match line.trim_start().starts_with("##") {
-                true => new_doc_str.push_str(&line.replacen('#', "", 1)),
+            match code.trim_start().starts_with("##") || is_line_commented(code) {
+                true => new_doc_str.push_str(&unhide_line(line)),
                 false => new_doc_str.push_str(line),
             }
 
@@ -340,6 +350,47 @@ mod tests {
         assert_eq!(transform.transform(&doc).unwrap(), expected);
     }
 
+    #[test]
+    fn test_remove_comments_block_quote() {
+        let doc_str = indoc! { r#"
+            > ```
+            > # use foo::Foo;
+            > let foo = Foo;
+            > ## escaped
+            > ```
+            >
+            >     # A comment.
+            >     println!("Hi");
+            >
+            > > ```rust
+            > > # A comment.
+            > > println!("Hi");
+            > > ```
+            "#
+        };
+
+        let expected_str = indoc! { r#"
+            > ```
+            > let foo = Foo;
+            > # escaped
+            > ```
+            >
+            >     println!("Hi");
+            >
+            > > ```rust
+            > > println!("Hi");
+            > > ```
+            "#
+        };
+
+        let doc = Doc::from_str(doc_str);
+        let expected = Doc::from_str(expected_str);
+
+        let transform = DocTransformRustRemoveComments::new();
+
+        assert_eq!(transform.transform(&doc).unwrap(), expected);
+    }
+
     #[test]
     fn test_remove_comments_indent_code_block_empty_lines() {
         let doc_str = indoc! { r#"
@@ -548,6 +599,55 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_remove_comments_keep_hidden() {
+        let doc_str = indoc! { r#"
+            ```rust,rdme-keep-hidden
+            # use std::collections::HashMap;
+            #
+            let map: HashMap<u32, u32> = HashMap::new();
+            ## not hidden
+            ```
+
+            > ```rdme-keep-hidden
+            > # let x = 1;
+            > println!("{x}");
+            > ```
+
+            ```
+            # let x = 1;
+            println!("{x}");
+            ```
+            "#
+        };
+
+        let expected_str = indoc! { r#"
+            ```rust,rdme-keep-hidden
+            use std::collections::HashMap;
+
+            let map: HashMap<u32, u32> = HashMap::new();
+            # not hidden
+            ```
+
+            > ```rdme-keep-hidden
+            > let x = 1;
+            > println!("{x}");
+            > ```
+
+            ```
+            println!("{x}");
+            ```
+            "#
+        };
+
+        let doc = Doc::from_str(doc_str);
+        let expected = Doc::from_str(expected_str);
+
+        let transform = DocTransformRustRemoveComments::new();
+
+        assert_eq!(transform.transform(&doc).unwrap(), expected);
+    }
+
     #[test]
     fn test_remove_comments_nested_fenced_block() {
         let doc_str = indoc! { r#"
@@ -0,0 +1,131 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::transform::utils::apply_edits;
+use crate::transform::DocTransform;
+use crate::Doc;
+use std::convert::Infallible;
+
+/// Converts setext headings (`Title` underlined with `=====` or `-----`) to ATX headings
+/// (`# Title`), so that the documentation uses a single heading style.
+pub struct DocTransformSetextHeadings;
+
+impl DocTransformSetextHeadings {
+    #[must_use]
+    pub fn new() -> DocTransformSetextHeadings {
+        DocTransformSetextHeadings
+    }
+}
+
+/// If `heading` is the source of a setext heading, returns it as an ATX heading of the given
+/// level.  Setext headings can only be of level one or two, so they must be converted to have
+/// their level increased.  The line terminator at the end of the heading, if any, is kept.
+///
+/// Inside blockquotes the lines after the first one start with the `>` markers, which are dropped
+/// along with those lines.
+pub(crate) fn setext_to_atx(heading: &str, level: usize) -> Option<String> {
+    // A line of a paragraph cannot start with `>` after the markers of its blockquotes, since
+    // that would start a new blockquote.
+    fn strip_quote(line: &str) -> &str {
+        line.trim_start_matches(|c: char| c == '>' || c.is_whitespace())
+    }
+
+    let body = heading.trim_end();
+    let (text, underline) = body.rsplit_once('\n')?;
+    let underline = strip_quote(underline).trim();
+
+    if underline.is_empty()
+        || !(underline.chars().all(|c| c == '=') || underline.chars().all(|c| c == '-'))
+    {
+        return None;
+    }
+
+    let text: Vec<&str> = text.lines().map(strip_quote).map(str::trim).collect();
+
+    Some(format!("{} {}{}", "#".repeat(level), text.join(" "), &heading[body.len()..]))
+}
+
+impl DocTransform for DocTransformSetextHeadings {
+    type E = Infallible;
+
+    fn transform(&self, doc: &Doc) -> Result<Doc, Infallible> {
+        use pulldown_cmark::{Event, Options, Parser, Tag};
+
+        let source = doc.as_string();
+        let edits = Parser::new_ext(source, Options::all())
+            .into_offset_iter()
+            .filter_map(|(event, range)| match event {
+                Event::Start(Tag::Heading { level, .. }) => {
+                    let atx = setext_to_atx(&source[range.clone()], level as usize)?;
+
+                    Some((range, atx))
+                }
+                _ => None,
+            })
+            .collect();
+
+        Ok(Doc::from_str(apply_edits(source, edits)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_setext_headings() {
+        let doc = indoc! { r#"
+            Title
+            =====
+
+            Some text.
+
+            A long
+              subtitle
+            ---
+
+            ## Already ATX
+
+            > Quoted
+            > > nested
+            > > ======
+
+            ```
+            Not a heading
+            =============
+            ```
+
+            * * *
+            "#
+        };
+        let expected = indoc! { r#"
+            # Title
+
+            Some text.
+
+            ## A long subtitle
+
+            ## Already ATX
+
+            > Quoted
+            > > # nested
+
+            ```
+            Not a heading
+            =============
+            ```
+
+            * * *
+            "#
+        };
+
+        let transform = DocTransformSetextHeadings::new();
+        let new_doc = transform.transform(&Doc::from_str(doc)).unwrap();
+
+        assert_eq!(new_doc.as_string(), expected);
+    }
+}
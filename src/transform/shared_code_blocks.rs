@@ -0,0 +1,152 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::transform::utils::apply_edits;
+use crate::transform::DocTransform;
+use crate::Doc;
+use std::convert::Infallible;
+use std::ops::Range;
+
+/// Replaces the code blocks of the documentation with the ones of another documentation, in
+/// order.  This is used by translations of the documentation, so that the examples only need to
+/// be maintained in the crate's documentation.  A warning is emitted if the number of code blocks
+/// differs.
+pub struct DocTransformSharedCodeBlocks<F> {
+    code_blocks: Vec<String>,
+    emit_warning: F,
+}
+
+impl<F> DocTransformSharedCodeBlocks<F>
+where
+    F: Fn(&str),
+{
+    #[must_use]
+    pub fn new(shared_doc: &Doc, emit_warning: F) -> DocTransformSharedCodeBlocks<F> {
+        let source = shared_doc.as_string();
+        let code_blocks =
+            code_block_ranges(source).into_iter().map(|range| source[range].to_owned()).collect();
+
+        DocTransformSharedCodeBlocks { code_blocks, emit_warning }
+    }
+}
+
+/// Byte ranges of the code blocks of the markdown source, without the line terminator of their last
+/// line.
+pub fn code_block_ranges(source: &str) -> Vec<Range<usize>> {
+    use pulldown_cmark::{CodeBlockKind, Event, Options, Parser, Tag};
+
+    Parser::new_ext(source, Options::all())
+        .into_offset_iter()
+        .filter_map(|(event, range)| match event {
+            // The range of indented code blocks starts after the indentation of the first line.
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Indented)) => {
+                Some(source[..range.start].trim_end_matches([' ', '\t']).len()..range.end)
+            }
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(_))) => Some(range),
+            _ => None,
+        })
+        // Only some code blocks include the line terminator of their last line.
+        .map(|range| range.start..range.start + source[range].trim_end_matches(['\r', '\n']).len())
+        .collect()
+}
+
+impl<F> DocTransform for DocTransformSharedCodeBlocks<F>
+where
+    F: Fn(&str),
+{
+    type E = Infallible;
+
+    fn transform(&self, doc: &Doc) -> Result<Doc, Infallible> {
+        let source = doc.as_string();
+        let ranges = code_block_ranges(source);
+
+        if ranges.len() != self.code_blocks.len() {
+            (self.emit_warning)(&format!(
+                "the translation has {} code block(s) but the documentation has {}",
+                ranges.len(),
+                self.code_blocks.len()
+            ));
+        }
+
+        let edits = ranges.into_iter().zip(self.code_blocks.iter().cloned()).collect();
+
+        Ok(Doc::from_str(apply_edits(source, edits)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+    use std::cell::RefCell;
+
+    #[test]
+    fn test_shared_code_blocks() {
+        let shared_doc = indoc! { r#"
+            # Example
+
+            ```
+            let answer = 42;
+            ```
+
+            Or, in the shell:
+
+                echo 42
+            "#
+        };
+        let doc = indoc! { r#"
+            # Ejemplo
+
+            ```
+            let respuesta = 42;
+            ```
+
+            O, en la terminal:
+
+            ```sh
+            echo 42
+            ```
+            "#
+        };
+        let expected = indoc! { r#"
+            # Ejemplo
+
+            ```
+            let answer = 42;
+            ```
+
+            O, en la terminal:
+
+                echo 42
+            "#
+        };
+
+        let transform = DocTransformSharedCodeBlocks::new(&Doc::from_str(shared_doc), |_| {
+            panic!("unexpected warning");
+        });
+        let new_doc = transform.transform(&Doc::from_str(doc)).unwrap();
+
+        assert_eq!(new_doc.as_string(), expected);
+    }
+
+    #[test]
+    fn test_shared_code_blocks_count_mismatch() {
+        let shared_doc = "```\na\n```\n\n```\nb\n```\n";
+        let doc = "Uno:\n\n```\nuno\n```\n";
+
+        let warnings = RefCell::new(Vec::new());
+        let transform = DocTransformSharedCodeBlocks::new(&Doc::from_str(shared_doc), |msg| {
+            warnings.borrow_mut().push(msg.to_owned());
+        });
+        let new_doc = transform.transform(&Doc::from_str(doc)).unwrap();
+
+        assert_eq!(new_doc.as_string(), "Uno:\n\n```\na\n```\n");
+        assert_eq!(
+            warnings.into_inner(),
+            vec!["the translation has 1 code block(s) but the documentation has 2"]
+        );
+    }
+}
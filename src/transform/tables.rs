@@ -0,0 +1,223 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::transform::utils::apply_edits;
+use crate::transform::DocTransform;
+use crate::Doc;
+use pulldown_cmark::Alignment;
+use std::convert::Infallible;
+use std::ops::Range;
+
+/// Reformats tables so that their columns are aligned, which makes them readable in the raw
+/// markdown and keeps diffs stable.
+pub struct DocTransformFormatTables;
+
+impl DocTransformFormatTables {
+    #[must_use]
+    pub fn new() -> DocTransformFormatTables {
+        DocTransformFormatTables
+    }
+}
+
+/// Splits a table row in its cells.  Pipes escaped with a backslash do not separate cells, even
+/// inside code spans, as in GitHub flavored markdown.
+fn split_row(row: &str) -> Vec<&str> {
+    let row = row.trim();
+    let row = row.strip_prefix('|').unwrap_or(row);
+    let row = match row.ends_with('|') && !row.ends_with("\\|") {
+        true => &row[..row.len() - 1],
+        false => row,
+    };
+
+    let mut cells = Vec::new();
+    let mut cell_start = 0;
+    let mut escaped = false;
+
+    for (i, c) in row.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '|' => {
+                cells.push(row[cell_start..i].trim());
+                cell_start = i + 1;
+            }
+            _ => (),
+        }
+    }
+
+    cells.push(row[cell_start..].trim());
+
+    cells
+}
+
+fn width(str: &str) -> usize {
+    str.chars().count()
+}
+
+fn format_table(table: &str, alignments: &[Alignment]) -> Option<String> {
+    let lines: Vec<&str> = table.lines().collect();
+    let indent = &lines[0][..lines[0].len() - lines[0].trim_start().len()];
+
+    // Tables inside other blocks, such as block quotes, are left untouched.
+    if lines.iter().any(|line| !line.starts_with(indent) || line.trim_start().starts_with('>')) {
+        return None;
+    }
+
+    let rows: Vec<Vec<&str>> = lines
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i != 1)
+        .map(|(_, line)| split_row(line))
+        .collect();
+    let column_count = rows.iter().map(Vec::len).max().unwrap_or(0).max(alignments.len());
+    let alignment = |column: usize| alignments.get(column).copied().unwrap_or(Alignment::None);
+
+    let column_widths: Vec<usize> = (0..column_count)
+        .map(|column| {
+            let min_width = match alignment(column) {
+                Alignment::Center => 5,
+                Alignment::Left | Alignment::Right => 4,
+                Alignment::None => 3,
+            };
+
+            rows.iter()
+                .filter_map(|row| row.get(column))
+                .map(|cell| width(cell))
+                .max()
+                .unwrap_or(0)
+                .max(min_width)
+        })
+        .collect();
+
+    let format_row = |row: &[&str]| -> String {
+        let cells = column_widths.iter().enumerate().map(|(column, &column_width)| {
+            let cell = row.get(column).copied().unwrap_or("");
+            let padding = column_width - width(cell);
+
+            match alignment(column) {
+                Alignment::Right => format!("{}{cell}", " ".repeat(padding)),
+                Alignment::Center => {
+                    format!(
+                        "{}{cell}{}",
+                        " ".repeat(padding / 2),
+                        " ".repeat(padding - padding / 2)
+                    )
+                }
+                Alignment::Left | Alignment::None => format!("{cell}{}", " ".repeat(padding)),
+            }
+        });
+
+        format!("{indent}| {} |", cells.collect::<Vec<_>>().join(" | "))
+    };
+
+    let delimiter_row = column_widths
+        .iter()
+        .enumerate()
+        .map(|(column, &column_width)| match alignment(column) {
+            Alignment::Left => format!(":{}", "-".repeat(column_width - 1)),
+            Alignment::Right => format!("{}:", "-".repeat(column_width - 1)),
+            Alignment::Center => format!(":{}:", "-".repeat(column_width - 2)),
+            Alignment::None => "-".repeat(column_width),
+        })
+        .collect::<Vec<_>>()
+        .join(" | ");
+
+    let mut formatted: Vec<String> = rows.iter().map(|row| format_row(row)).collect();
+
+    formatted.insert(1, format!("{indent}| {delimiter_row} |"));
+
+    let mut formatted = formatted.join("\n");
+
+    if table.ends_with('\n') {
+        formatted.push('\n');
+    }
+
+    Some(formatted)
+}
+
+impl DocTransform for DocTransformFormatTables {
+    type E = Infallible;
+
+    fn transform(&self, doc: &Doc) -> Result<Doc, Infallible> {
+        use pulldown_cmark::{Event, Options, Parser, Tag};
+
+        let source = doc.as_string();
+        let mut edits: Vec<(Range<usize>, String)> = Vec::new();
+
+        for (event, range) in Parser::new_ext(source, Options::all()).into_offset_iter() {
+            if let Event::Start(Tag::Table(alignments)) = event {
+                // The range starts after the indentation of the first line.
+                let start = source[..range.start].rfind('\n').map_or(0, |i| i + 1);
+
+                if let Some(table) = format_table(&source[start..range.end], &alignments) {
+                    edits.push((start..range.end, table));
+                }
+            }
+        }
+
+        Ok(Doc::from_str(apply_edits(source, edits)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_format_tables() {
+        let doc = indoc! { r#"
+            # Tables
+
+            Name|Description|Size
+            :-|:-:|-:
+            `foo`|The foo|1
+            `a \| b`|Pipes `\|` are fine| 1000
+            bar
+
+            > | a | b |
+            > |-|-|
+            > | c | d |
+
+            ```
+            |a|b|
+            |-|-|
+            ```
+            "#
+        };
+
+        let expected = indoc! { r#"
+            # Tables
+
+            | Name     |     Description     | Size |
+            | :------- | :-----------------: | ---: |
+            | `foo`    |       The foo       |    1 |
+            | `a \| b` | Pipes `\|` are fine | 1000 |
+            | bar      |                     |      |
+
+            > | a | b |
+            > |-|-|
+            > | c | d |
+
+            ```
+            |a|b|
+            |-|-|
+            ```
+            "#
+        };
+
+        let transform = DocTransformFormatTables::new();
+        let new_doc = transform.transform(&Doc::from_str(doc)).unwrap();
+
+        assert_eq!(new_doc.as_string(), expected);
+    }
+
+    #[test]
+    fn test_split_row() {
+        assert_eq!(split_row("| a | `b|c` | d\\|e |"), vec!["a", "`b", "c`", "d\\|e"]);
+        assert_eq!(split_row("a|b"), vec!["a", "b"]);
+    }
+}
@@ -5,18 +5,59 @@
 
 use crate::markdown::Markdown;
 use crate::utils::MarkdownItemIterator;
+use std::ops::Range;
+
+/// Tag of rust code blocks whose hidden lines are shown in the README instead of removed.
+pub const TAG_KEEP_HIDDEN: &str = "rdme-keep-hidden";
 
 pub fn is_rust_code_block(tags: &str) -> bool {
     tags.split(',').all(|tag| match tag {
         "should_panic" | "no_run" | "ignore" | "allow_fail" | "rust" | "test_harness"
-        | "compile_fail" | "" => true,
+        | "compile_fail" | TAG_KEEP_HIDDEN | "" => true,
         tag if tag.starts_with("ignore-") => true,
         tag if tag.starts_with("edition") => true,
         _ => false,
     })
 }
 
-pub fn rust_code_block_iterator(markdown: &Markdown) -> MarkdownItemIterator<&str> {
+/// Whether the line of a rust code block is hidden by rustdoc.
+pub fn is_line_commented(line: &str) -> bool {
+    line.trim_start().starts_with("# ") || line.trim() == "#"
+}
+
+/// Removes the `#` that hides a line, and the space after it.
+pub fn unhide_line(line: &str) -> String {
+    match line.find('#') {
+        Some(i) => {
+            let rest = &line[i + 1..];
+
+            format!("{}{}", &line[..i], rest.strip_prefix(' ').unwrap_or(rest))
+        }
+        None => line.to_owned(),
+    }
+}
+
+/// Shows the hidden lines of rust code, as rustdoc does when it compiles it.  Lines starting with
+/// `##` are not hidden, but lose their first `#`.
+pub fn show_hidden_lines(code: &str) -> String {
+    code.split_inclusive('\n')
+        .map(|line| match line.trim_start().starts_with("##") || is_line_commented(line) {
+            true => unhide_line(line),
+            false => line.to_owned(),
+        })
+        .collect()
+}
+
+/// Whether the fence of a code block, given its source, has the tag.  Indented code blocks have no
+/// tags.
+pub fn code_block_has_tag(code_block: &str, tag: &str) -> bool {
+    let fenced = code_block.starts_with("```") || code_block.starts_with("~~~");
+    let info = code_block.split('\n').next().unwrap_or_default().trim_start_matches(['`', '~']);
+
+    fenced && info.split(',').any(|t| t.trim() == tag)
+}
+
+pub fn rust_code_block_iterator(markdown: &Markdown) -> MarkdownItemIterator<'_, &str> {
     use pulldown_cmark::{CodeBlockKind, Event, Options, Parser, Tag};
 
     let source = markdown.as_string();
@@ -35,12 +76,93 @@ pub fn rust_code_block_iterator(markdown: &Markdown) -> MarkdownItemIterator<&st
     MarkdownItemIterator::new(source, iter)
 }
 
+/// Ranges of the markdown source with plain text, i.e. not in code, html, or link destinations.
+/// Adjacent text is returned as a single range.
+pub fn text_ranges(markdown: &Markdown) -> Vec<Range<usize>> {
+    use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+
+    let source = markdown.as_string();
+    let parser = Parser::new_ext(source, Options::all());
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    let mut in_code_block = false;
+
+    for (event, range) in parser.into_offset_iter() {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+            Event::End(TagEnd::CodeBlock) => in_code_block = false,
+            Event::Text(_) if !in_code_block => match ranges.last_mut() {
+                Some(last) if last.end == range.start => last.end = range.end,
+                _ => ranges.push(range),
+            },
+            _ => (),
+        }
+    }
+
+    ranges
+}
+
+/// The number of nested blockquotes of a block, given the text of its first line before it.  The
+/// parser starts the range of a block after the `>` markers of the blockquotes it is in, but its
+/// other lines still have them.
+pub fn block_quote_depth(line_prefix: &str) -> usize {
+    line_prefix.matches('>').count()
+}
+
+/// Splits the `>` markers of `depth` nested blockquotes, and the space after them, from the start
+/// of `line`.
+pub fn split_block_quote_prefix(line: &str, depth: usize) -> (&str, &str) {
+    let mut len = 0;
+
+    for _ in 0..depth {
+        let rest = &line[len..];
+        let Some(after) = rest.trim_start_matches([' ', '\t']).strip_prefix('>') else {
+            break;
+        };
+
+        len += rest.len() - after.len() + usize::from(after.starts_with(' '));
+    }
+
+    line.split_at(len)
+}
+
+/// Applies edits to the source, given as `(range to replace, replacement)`.  The edits must not
+/// overlap.
+pub fn apply_edits(source: &str, mut edits: Vec<(Range<usize>, String)>) -> String {
+    edits.sort_by_key(|(range, _)| range.start);
+
+    let mut new_source = String::with_capacity(source.len());
+    let mut last = 0;
+
+    for (range, replacement) in edits {
+        new_source.push_str(&source[last..range.start]);
+        new_source.push_str(&replacement);
+        last = range.end;
+    }
+
+    new_source.push_str(&source[last..]);
+
+    new_source
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use indoc::indoc;
     use pretty_assertions::assert_eq;
 
+    #[test]
+    fn test_split_block_quote_prefix() {
+        assert_eq!(block_quote_depth("> - "), 1);
+        assert_eq!(block_quote_depth(" > > "), 2);
+        assert_eq!(split_block_quote_prefix("> # foo", 1), ("> ", "# foo"));
+        assert_eq!(split_block_quote_prefix(">     foo", 1), ("> ", "    foo"));
+        assert_eq!(split_block_quote_prefix("> >foo", 2), ("> >", "foo"));
+        assert_eq!(split_block_quote_prefix(" > > > foo", 2), (" > > ", "> foo"));
+        assert_eq!(split_block_quote_prefix(">", 1), (">", ""));
+        assert_eq!(split_block_quote_prefix("foo", 1), ("", "foo"));
+        assert_eq!(split_block_quote_prefix("> foo", 0), ("", "> foo"));
+    }
+
     #[test]
     fn test_rust_code_block_iterator_items() {
         let doc = indoc! { r#"
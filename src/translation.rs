@@ -0,0 +1,293 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+//! Synchronization of translations of the README whose text is translated by hand.  Only the code
+//! blocks of the generated regions are updated, and the sections of the documentation whose text
+//! changed since the last synchronization are reported, so that their translation can be revised.
+
+use crate::inject_doc::{generated_regions, hash_content, MARKER_RDME};
+use crate::transform::{code_block_ranges, DocTransform, DocTransformSharedCodeBlocks};
+use crate::{Doc, NewReadme, Readme};
+use std::fmt::Write;
+
+/// Line, right after the start marker, with the hashes of the sections of the documentation as of
+/// the last synchronization.
+const MARKER_SECTIONS_PREFIX: &str = "<!-- cargo-rdme sections:";
+
+#[non_exhaustive]
+pub struct SyncedTranslation {
+    pub readme: Readme,
+    /// Headings of the sections of the documentation whose text changed since the last
+    /// synchronization.  The text before the first heading has an empty heading.
+    pub changed_sections: Vec<String>,
+}
+
+/// A synchronized translation is the new README of the translation.  It always has the markers of
+/// the documentation, and has no hand edited regions since only its code blocks are generated.
+impl From<SyncedTranslation> for NewReadme {
+    fn from(synced: SyncedTranslation) -> NewReadme {
+        NewReadme { readme: synced.readme, had_marker: true, hand_edited_regions: Vec::new() }
+    }
+}
+
+struct Section {
+    heading: String,
+    /// Hash of the text of the section, without its code blocks, since those are synchronized.
+    hash: u64,
+}
+
+fn doc_sections(doc: &Doc) -> Vec<Section> {
+    use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+
+    let source = doc.as_string();
+    let code_blocks = code_block_ranges(source);
+    // The heading of each section and where the section starts.
+    let mut starts: Vec<(String, usize)> = vec![(String::new(), 0)];
+    let mut in_heading = false;
+
+    for (event, range) in Parser::new_ext(source, Options::all()).into_offset_iter() {
+        match event {
+            Event::Start(Tag::Heading { .. }) => {
+                starts.push((String::new(), range.start));
+                in_heading = true;
+            }
+            Event::End(TagEnd::Heading(_)) => in_heading = false,
+            Event::Text(text) | Event::Code(text) if in_heading => {
+                if let Some((heading, _)) = starts.last_mut() {
+                    heading.push_str(&text);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    let ends = starts.iter().skip(1).map(|(_, start)| *start).chain([source.len()]);
+
+    starts
+        .iter()
+        .zip(ends)
+        .map(|((heading, start), end)| {
+            let mut text = String::with_capacity(end - start);
+            let mut last = *start;
+
+            for block in
+                code_blocks.iter().filter(|block| block.start >= *start && block.end <= end)
+            {
+                text.push_str(&source[last..block.start]);
+                last = block.end;
+            }
+
+            text.push_str(&source[last..end]);
+
+            Section { heading: heading.clone(), hash: hash_content(&text) }
+        })
+        .collect()
+}
+
+fn parse_sections_line(line: &str) -> Option<Vec<u64>> {
+    line.trim()
+        .strip_prefix(MARKER_SECTIONS_PREFIX)?
+        .strip_suffix("-->")?
+        .split_whitespace()
+        .map(|hash| u64::from_str_radix(hash, 16).ok())
+        .collect()
+}
+
+fn sections_line(sections: &[Section]) -> String {
+    let mut line = MARKER_SECTIONS_PREFIX.to_owned();
+
+    for section in sections {
+        let _ = write!(line, " {:016x}", section.hash);
+    }
+
+    line.push_str(" -->");
+    line
+}
+
+/// Updates the code blocks of the regions of a translated README generated by cargo rdme with the
+/// ones of the documentation, leaving the (translated) text untouched.
+///
+/// Returns `None` if the README has no documentation to synchronize yet, i.e. it has no markers
+/// or has a single marker, in which case the documentation should be injected instead.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+pub fn sync_translation<F>(readme: &Readme, doc: &Doc, emit_warning: F) -> Option<SyncedTranslation>
+where
+    F: Fn(&str),
+{
+    let source = readme.as_string();
+    let regions = generated_regions(readme);
+    let sections = doc_sections(doc);
+    let transform = DocTransformSharedCodeBlocks::new(doc, emit_warning);
+    let mut new_readme = String::with_capacity(source.len());
+    let mut changed_sections: Vec<String> = Vec::new();
+    let mut last = 0;
+
+    if regions.is_empty() {
+        return None;
+    }
+
+    for region in regions {
+        let region_source = &source[region.clone()];
+        let region_source = region_source.strip_suffix('\n').unwrap_or(region_source);
+
+        if region_source.trim() == MARKER_RDME {
+            return None;
+        }
+
+        let (start_marker, rest) = region_source.split_once('\n')?;
+        let (content, end_marker) = rest.rsplit_once('\n')?;
+        let (synced_hashes, content) = match content.split_once('\n') {
+            Some((line, rest)) => match parse_sections_line(line) {
+                Some(hashes) => (Some(hashes), rest),
+                None => (None, content),
+            },
+            None => (None, content),
+        };
+
+        if let Some(synced_hashes) = synced_hashes {
+            for (i, section) in sections.iter().enumerate() {
+                let changed = synced_hashes.get(i) != Some(&section.hash);
+
+                if changed && !changed_sections.contains(&section.heading) {
+                    changed_sections.push(section.heading.clone());
+                }
+            }
+        }
+
+        // TODO Use `into_ok()` once it is stable (https://github.com/rust-lang/rust/issues/61695).
+        let Ok(content) = transform.transform(&Doc::from_str(content));
+
+        new_readme.push_str(&source[last..region.start]);
+        new_readme.push_str(start_marker);
+        new_readme.push('\n');
+        new_readme.push_str(&sections_line(&sections));
+        new_readme.push('\n');
+        new_readme.push_str(content.as_string());
+        new_readme.push('\n');
+        new_readme.push_str(end_marker);
+        last = region.start + region_source.len();
+    }
+
+    new_readme.push_str(&source[last..]);
+
+    Some(SyncedTranslation { readme: Readme::from_str(new_readme), changed_sections })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    const DOC: &str = indoc! { r#"
+        Intro.
+
+        # Usage
+
+        Add it:
+
+        ```toml
+        foo = "1.2"
+        ```
+
+        # License
+
+        MPL.
+        "#
+    };
+
+    #[test]
+    fn test_sync_translation() {
+        let readme = indoc! { r#"
+            # Foo
+
+            <!-- cargo-rdme start -->
+
+            Introducción.
+
+            ## Uso
+
+            Añádelo:
+
+            ```toml
+            foo = "1.0"
+            ```
+
+            ## Licencia
+
+            MPL.
+
+            <!-- cargo-rdme end -->
+
+            Fin.
+            "#
+        };
+        let doc = Doc::from_str(DOC);
+        let sections = sections_line(&doc_sections(&doc));
+        let expected = format!(
+            indoc! { r#"
+                # Foo
+
+                <!-- cargo-rdme start -->
+                {}
+
+                Introducción.
+
+                ## Uso
+
+                Añádelo:
+
+                ```toml
+                foo = "1.2"
+                ```
+
+                ## Licencia
+
+                MPL.
+
+                <!-- cargo-rdme end -->
+
+                Fin.
+                "#
+            },
+            sections
+        );
+
+        let synced = sync_translation(&Readme::from_str(readme), &doc, |_| {
+            panic!("unexpected warning");
+        })
+        .unwrap();
+
+        assert_eq!(synced.readme.as_string(), expected);
+        assert!(synced.changed_sections.is_empty());
+
+        // Synchronizing again changes nothing.
+        let synced_again = sync_translation(&synced.readme, &doc, |_| ()).unwrap();
+
+        assert_eq!(synced_again.readme.as_string(), expected);
+        assert!(synced_again.changed_sections.is_empty());
+
+        // Only changes to the text of a section are reported, not to its code blocks.
+        let new_doc = DOC.replace("Add it:", "Add it to `Cargo.toml`:").replace("1.2", "2.0");
+        let synced = sync_translation(&synced.readme, &Doc::from_str(new_doc), |_| ()).unwrap();
+
+        assert!(synced.readme.as_string().contains("foo = \"2.0\""));
+        assert!(synced.readme.as_string().contains("Añádelo:"));
+        assert_eq!(synced.changed_sections, vec!["Usage"]);
+    }
+
+    #[test]
+    fn test_sync_translation_needs_injection() {
+        let doc = Doc::from_str(DOC);
+
+        assert!(sync_translation(&Readme::from_str("# Foo\n"), &doc, |_| ()).is_none());
+        assert!(sync_translation(
+            &Readme::from_str("# Foo\n\n<!-- cargo-rdme -->\n"),
+            &doc,
+            |_| ()
+        )
+        .is_none());
+    }
+}
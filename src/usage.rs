@@ -0,0 +1,324 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+//! Generates the snippet to add the crate as a dependency, where the README has the
+//! `<!-- cargo-rdme usage -->` marker, so that it always has the version of the crate.
+
+use crate::inject_doc::generated_regions;
+use crate::{InjectDocError, Readme};
+use std::ops::Range;
+
+pub const MARKER_USAGE: &str = "<!-- cargo-rdme usage -->";
+const MARKER_USAGE_START: &str = "<!-- cargo-rdme usage start -->";
+const MARKER_USAGE_END: &str = "<!-- cargo-rdme usage end -->";
+
+/// The form of the version requirement in the usage snippet.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[non_exhaustive]
+pub enum UsageVersion {
+    /// The version of the crate, e.g. `1.2.3`.
+    Exact,
+    /// The versions compatible with the version of the crate, e.g. `1` for `1.2.3` or `0.2` for
+    /// `0.2.3`.
+    Major,
+}
+
+/// How the usage snippet adds the crate as a dependency.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[non_exhaustive]
+pub enum UsageStyle {
+    /// A toml code block with the dependency, to add to `Cargo.toml`.
+    Toml,
+    /// A `cargo add` command.
+    CargoAdd,
+    /// A `cargo add` command followed by the toml code block.
+    Both,
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[non_exhaustive]
+pub struct UsageConfig {
+    pub version: UsageVersion,
+    pub style: UsageStyle,
+    /// Features of the crate to enable in the snippet.
+    pub features: Vec<String>,
+}
+
+impl Default for UsageConfig {
+    fn default() -> UsageConfig {
+        UsageConfig { version: UsageVersion::Major, style: UsageStyle::Toml, features: Vec::new() }
+    }
+}
+
+impl UsageConfig {
+    #[must_use]
+    pub fn with_version(mut self, version: UsageVersion) -> UsageConfig {
+        self.version = version;
+        self
+    }
+
+    #[must_use]
+    pub fn with_style(mut self, style: UsageStyle) -> UsageConfig {
+        self.style = style;
+        self
+    }
+
+    #[must_use]
+    pub fn with_features(mut self, features: Vec<String>) -> UsageConfig {
+        self.features = features;
+        self
+    }
+}
+
+/// The version requirement of the usage snippet.  Pre-releases are only matched by requirements
+/// that name them, so they are always exact.
+fn version_requirement(version: &str, usage_version: UsageVersion) -> String {
+    if usage_version == UsageVersion::Exact || version.contains(['-', '+']) {
+        return version.to_owned();
+    }
+
+    let components: Vec<&str> = version.split('.').collect();
+    // The first non-zero component is the one that breaks compatibility.
+    let significant = components.iter().position(|c| *c != "0").unwrap_or(components.len() - 1);
+
+    components[..=significant].join(".")
+}
+
+/// The code blocks to add the crate as a dependency.
+#[must_use]
+pub fn usage_snippet(package_name: &str, version: &str, config: &UsageConfig) -> String {
+    let requirement = version_requirement(version, config.version);
+    let cargo_add = match config.features.is_empty() {
+        true => format!("```sh\ncargo add {package_name}\n```\n"),
+        false => {
+            format!(
+                "```sh\ncargo add {package_name} --features {}\n```\n",
+                config.features.join(",")
+            )
+        }
+    };
+    let dependency = match config.features.is_empty() {
+        true => format!("{package_name} = \"{requirement}\""),
+        false => {
+            let features: Vec<String> =
+                config.features.iter().map(|f| format!("\"{f}\"")).collect();
+
+            format!(
+                "{package_name} = {{ version = \"{requirement}\", features = [{}] }}",
+                features.join(", ")
+            )
+        }
+    };
+    let toml = format!("```toml\n[dependencies]\n{dependency}\n```\n");
+
+    match config.style {
+        UsageStyle::Toml => toml,
+        UsageStyle::CargoAdd => cargo_add,
+        UsageStyle::Both => format!("{cargo_add}\n{toml}"),
+    }
+}
+
+enum UsageMarker {
+    Single,
+    Start,
+    End,
+}
+
+/// The usage markers of the README, with the range of their lines.  Markers in code blocks or in
+/// the regions generated from the crate’s documentation are ignored.
+fn usage_markers(readme: &Readme) -> Vec<(Range<usize>, UsageMarker, usize)> {
+    use pulldown_cmark::{Event, Options, Parser};
+
+    let source = readme.as_string();
+    let doc_regions = generated_regions(readme);
+
+    Parser::new_ext(source, Options::all())
+        .into_offset_iter()
+        .filter_map(|(event, range)| match event {
+            Event::Html(html) if !doc_regions.iter().any(|r| r.contains(&range.start)) => {
+                let line_start = source[..range.start].rfind('\n').map_or(0, |i| i + 1);
+
+                if !source[line_start..range.start].trim().is_empty() {
+                    return None;
+                }
+
+                let marker = match html.trim() {
+                    MARKER_USAGE => UsageMarker::Single,
+                    MARKER_USAGE_START => UsageMarker::Start,
+                    MARKER_USAGE_END => UsageMarker::End,
+                    _ => return None,
+                };
+                let line_end =
+                    source[range.start..].find('\n').map_or(source.len(), |i| range.start + i + 1);
+                let line_number = source[..line_start].matches('\n').count() + 1;
+
+                Some((line_start..line_end, marker, line_number))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Puts the usage snippet in the usage markers of the README.  A `<!-- cargo-rdme usage -->`
+/// marker is replaced by start and end markers with the snippet between them.
+pub fn inject_usage_in_readme(readme: &Readme, snippet: &str) -> Result<Readme, InjectDocError> {
+    let source = readme.as_string();
+    let region = format!("{MARKER_USAGE_START}\n\n{snippet}\n{MARKER_USAGE_END}\n");
+    let mut new_readme = String::with_capacity(source.len() + snippet.len());
+    let mut last = 0;
+    let mut start: Option<usize> = None;
+
+    for (range, marker, line_number) in usage_markers(readme) {
+        match (marker, start) {
+            (UsageMarker::Single, None) => {
+                new_readme.push_str(&source[last..range.start]);
+                new_readme.push_str(&region);
+                last = range.end;
+            }
+            (UsageMarker::Start, None) => {
+                new_readme.push_str(&source[last..range.start]);
+                last = range.start;
+                start = Some(line_number);
+            }
+            (UsageMarker::End, Some(_)) => {
+                new_readme.push_str(&region);
+                last = range.end;
+                start = None;
+            }
+            (UsageMarker::End, None) => {
+                return Err(InjectDocError::UnexpectedMarkerCargoRdmeEnd { line_number });
+            }
+            // Markers inside the region are replaced with it.
+            (UsageMarker::Single | UsageMarker::Start, Some(_)) => (),
+        }
+    }
+
+    if let Some(line_number) = start {
+        return Err(InjectDocError::UnmatchedMarkerCargoRdmeStart { line_number });
+    }
+
+    new_readme.push_str(&source[last..]);
+
+    Ok(Readme::from_str(new_readme))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_usage_snippet() {
+        assert_eq!(
+            usage_snippet("my-crate", "1.2.3", &UsageConfig::default()),
+            "```toml\n[dependencies]\nmy-crate = \"1\"\n```\n"
+        );
+        assert_eq!(version_requirement("1.2.3", UsageVersion::Exact), "1.2.3");
+        assert_eq!(version_requirement("0.2.3", UsageVersion::Major), "0.2");
+        assert_eq!(version_requirement("0.0.3", UsageVersion::Major), "0.0.3");
+        assert_eq!(version_requirement("0.0.0", UsageVersion::Major), "0.0.0");
+        assert_eq!(version_requirement("2.0.0-beta.1", UsageVersion::Major), "2.0.0-beta.1");
+    }
+
+    #[test]
+    fn test_usage_snippet_styles() {
+        let config = UsageConfig {
+            version: UsageVersion::Exact,
+            style: UsageStyle::Both,
+            features: vec!["foo".to_owned(), "bar".to_owned()],
+        };
+        let expected = indoc! { r#"
+            ```sh
+            cargo add my-crate --features foo,bar
+            ```
+
+            ```toml
+            [dependencies]
+            my-crate = { version = "1.2.3", features = ["foo", "bar"] }
+            ```
+            "#
+        };
+
+        assert_eq!(usage_snippet("my-crate", "1.2.3", &config), expected);
+
+        let config = UsageConfig { style: UsageStyle::CargoAdd, ..UsageConfig::default() };
+
+        assert_eq!(usage_snippet("my-crate", "1.2.3", &config), "```sh\ncargo add my-crate\n```\n");
+    }
+
+    #[test]
+    fn test_inject_usage_in_readme() {
+        let readme = indoc! { r#"
+            # My crate
+
+            <!-- cargo-rdme usage -->
+
+            ```markdown
+            <!-- cargo-rdme usage -->
+            ```
+
+            <!-- cargo-rdme start -->
+
+            <!-- cargo-rdme usage -->
+
+            <!-- cargo-rdme end -->
+            "#
+        };
+        let expected = indoc! { r#"
+            # My crate
+
+            <!-- cargo-rdme usage start -->
+
+            ```toml
+            [dependencies]
+            my-crate = "1"
+            ```
+
+            <!-- cargo-rdme usage end -->
+
+            ```markdown
+            <!-- cargo-rdme usage -->
+            ```
+
+            <!-- cargo-rdme start -->
+
+            <!-- cargo-rdme usage -->
+
+            <!-- cargo-rdme end -->
+            "#
+        };
+
+        let snippet = usage_snippet("my-crate", "1.2.3", &UsageConfig::default());
+        let new_readme = inject_usage_in_readme(&Readme::from_str(readme), &snippet).unwrap();
+
+        assert_eq!(new_readme.as_string(), expected);
+
+        // Updating the snippet replaces the region.
+        let config = UsageConfig { version: UsageVersion::Exact, ..UsageConfig::default() };
+        let snippet = usage_snippet("my-crate", "2.0.1", &config);
+        let new_readme = inject_usage_in_readme(&new_readme, &snippet).unwrap();
+
+        assert_eq!(new_readme.as_string(), expected.replace("\"1\"", "\"2.0.1\""));
+    }
+
+    #[test]
+    fn test_inject_usage_in_readme_unmatched_markers() {
+        let snippet = usage_snippet("my-crate", "1.2.3", &UsageConfig::default());
+        let readme = Readme::from_str("Foo\n\n<!-- cargo-rdme usage start -->\n");
+
+        assert_eq!(
+            inject_usage_in_readme(&readme, &snippet).err(),
+            Some(InjectDocError::UnmatchedMarkerCargoRdmeStart { line_number: 3 })
+        );
+
+        let readme = Readme::from_str("<!-- cargo-rdme usage end -->\n");
+
+        assert_eq!(
+            inject_usage_in_readme(&readme, &snippet).err(),
+            Some(InjectDocError::UnexpectedMarkerCargoRdmeEnd { line_number: 1 })
+        );
+    }
+}
@@ -0,0 +1,362 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+//! Checks that the rust examples of the documentation compile, so that readers of the README do not
+//! copy broken code.  Like rustdoc, the examples are taken from the documentation as written, with
+//! their hidden lines, and `ignore` and `compile_fail` examples are skipped.  The examples are
+//! compiled by `cargo check` as examples of a package, in the target directory, that depends on the
+//! crate.
+
+use crate::transform::utils::{is_rust_code_block, show_hidden_lines};
+use crate::{Doc, Project};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum VerifyExamplesError {
+    #[error("failed to prepare the package of the examples: {0}")]
+    IOError(std::io::Error),
+    #[error("failed to run `cargo check`: {0}")]
+    CargoCheckError(std::io::Error),
+    #[error("`cargo check` of the examples failed before compiling them")]
+    CargoCheckFailed,
+}
+
+/// A rust code block of the documentation.
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[non_exhaustive]
+pub struct DocExample {
+    /// Line of the opening fence, or of the first line of an indented code block (starting at 1).
+    pub line_number: usize,
+    /// The attributes of the fence, e.g. `no_run` or `compile_fail`.
+    pub attributes: Vec<String>,
+    /// The code, with its hidden lines.
+    pub code: String,
+}
+
+impl DocExample {
+    /// Whether the example is expected to compile, i.e. it is not tagged with `ignore` or
+    /// `compile_fail`.
+    #[must_use]
+    pub fn is_checked(&self) -> bool {
+        !self.attributes.iter().any(|attribute| {
+            attribute == "ignore" || attribute.starts_with("ignore-") || attribute == "compile_fail"
+        })
+    }
+}
+
+/// An example that does not compile.
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[non_exhaustive]
+pub struct ExampleFailure {
+    pub line_number: usize,
+    /// The errors of the compiler, as it renders them.
+    pub errors: Vec<String>,
+}
+
+/// The rust code blocks of the documentation, as rustdoc sees them: fenced code blocks without a
+/// language other than rust, and indented code blocks.  This should be given the documentation
+/// before it is transformed, since the transformations tag code blocks and remove hidden lines.
+#[must_use]
+pub fn doc_examples(doc: &Doc) -> Vec<DocExample> {
+    use pulldown_cmark::{CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
+
+    let source = doc.as_string();
+    let mut examples = Vec::new();
+    let mut current: Option<DocExample> = None;
+
+    for (event, range) in Parser::new_ext(source, Options::all()).into_offset_iter() {
+        let attributes = match &event {
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Indented)) => Some(Vec::new()),
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(tags)))
+                if is_rust_code_block(tags) =>
+            {
+                Some(
+                    tags.split(',')
+                        .map(str::trim)
+                        .filter(|tag| !tag.is_empty())
+                        .map(ToOwned::to_owned)
+                        .collect(),
+                )
+            }
+            _ => None,
+        };
+
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => {
+                let line_number = source[..range.start].matches('\n').count() + 1;
+
+                current = attributes.map(|attributes| DocExample {
+                    line_number,
+                    attributes,
+                    code: String::new(),
+                });
+            }
+            Event::Text(text) => {
+                if let Some(example) = &mut current {
+                    example.code.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                examples.extend(current.take().map(|example| DocExample {
+                    code: show_hidden_lines(&example.code),
+                    ..example
+                }));
+            }
+            _ => (),
+        }
+    }
+
+    examples
+}
+
+fn example_name(example: &DocExample) -> String {
+    format!("doc_line_{}", example.line_number)
+}
+
+/// The source of the example as a program.  Like rustdoc, the code is put in a `main` function,
+/// unless it has one, with its crate attributes kept at the top.  In edition 2015 the crate is only
+/// in scope with `extern crate`, which rustdoc adds if the example uses the crate.
+fn example_source(code: &str, crate_name: Option<&str>, edition: &str) -> String {
+    let body_start = code
+        .split_inclusive('\n')
+        .take_while(|line| line.trim().is_empty() || line.trim_start().starts_with("#!["))
+        .map(str::len)
+        .sum();
+    let (attributes, body) = code.split_at(body_start);
+    let mut source = String::from("#![allow(unused)]\n");
+
+    source.push_str(attributes);
+
+    if let Some(crate_name) = crate_name {
+        if edition == "2015" && code.contains(crate_name) && !code.contains("extern crate") {
+            let _ = writeln!(source, "extern crate {crate_name};");
+        }
+    }
+
+    match code.contains("fn main") {
+        true => source.push_str(body),
+        false => {
+            source.push_str("fn main() {\n");
+            source.push_str(body);
+            source.push_str("}\n");
+        }
+    }
+
+    source
+}
+
+fn toml_string(str: &str) -> String {
+    toml::Value::String(str.to_owned()).to_string()
+}
+
+impl Project {
+    fn examples_package_dir(&self) -> PathBuf {
+        self.target_directory.join("cargo-rdme").join("verify-examples").join(&self.package_name)
+    }
+
+    fn write_examples_package(&self, dir: &Path, examples: &[&DocExample]) -> std::io::Result<()> {
+        let mut manifest = indoc::formatdoc! { r#"
+            [package]
+            name = "cargo-rdme-verify-examples"
+            version = "0.0.0"
+            edition = "{edition}"
+            publish = false
+
+            # Keeps the package out of the workspace of a parent directory, if any.
+            [workspace]
+
+            [dependencies]
+            "#,
+            edition = self.edition,
+        };
+
+        if self.lib_path.is_some() {
+            let _ = writeln!(
+                manifest,
+                "{} = {{ path = {} }}",
+                toml_string(&self.package_name),
+                toml_string(&self.directory.display().to_string())
+            );
+        }
+
+        let examples_dir = dir.join("examples");
+
+        match std::fs::remove_dir_all(&examples_dir) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+            _ => (),
+        }
+
+        std::fs::create_dir_all(&examples_dir)?;
+        std::fs::write(dir.join("Cargo.toml"), manifest)?;
+
+        let crate_name = self.lib_path.is_some().then(|| self.package_name.replace('-', "_"));
+
+        for example in examples {
+            let path = examples_dir.join(format!("{}.rs", example_name(example)));
+            let source = example_source(&example.code, crate_name.as_deref(), &self.edition);
+
+            std::fs::write(path, source)?;
+        }
+
+        Ok(())
+    }
+
+    /// Compiles the examples with `cargo check`, except the ones that are not expected to compile,
+    /// and returns the ones that do not compile.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(package = %self.package_name))
+    )]
+    pub fn verify_examples(
+        &self,
+        examples: &[DocExample],
+    ) -> Result<Vec<ExampleFailure>, VerifyExamplesError> {
+        use cargo_metadata::diagnostic::DiagnosticLevel;
+        use cargo_metadata::Message;
+        use std::io::BufReader;
+        use std::process::{Command, Stdio};
+
+        let examples: Vec<&DocExample> =
+            examples.iter().filter(|example| example.is_checked()).collect();
+
+        if examples.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let dir = self.examples_package_dir();
+
+        self.write_examples_package(&dir, &examples).map_err(VerifyExamplesError::IOError)?;
+
+        let cargo = std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+        let mut command = Command::new(cargo);
+
+        command
+            .args(["check", "--examples", "--keep-going", "--message-format=json", "--quiet"])
+            .arg("--manifest-path")
+            .arg(dir.join("Cargo.toml"))
+            .arg("--target-dir")
+            .arg(dir.join("target"));
+
+        // The package of the examples has its own lock file, so only `--offline` applies to it.
+        if self.cargo_flags.offline || self.cargo_flags.frozen {
+            command.arg("--offline");
+        }
+
+        let mut child = command
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(VerifyExamplesError::CargoCheckError)?;
+
+        let mut errors: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+        if let Some(stdout) = child.stdout.take() {
+            for message in Message::parse_stream(BufReader::new(stdout)).filter_map(Result::ok) {
+                if let Message::CompilerMessage(message) = message {
+                    if matches!(
+                        message.message.level,
+                        DiagnosticLevel::Error | DiagnosticLevel::Ice
+                    ) {
+                        let rendered = message.message.rendered.unwrap_or(message.message.message);
+
+                        errors.entry(message.target.name).or_default().push(rendered);
+                    }
+                }
+            }
+        }
+
+        let success = child.wait().map_err(VerifyExamplesError::CargoCheckError)?.success();
+        let failures: Vec<ExampleFailure> = examples
+            .iter()
+            .filter_map(|example| {
+                errors
+                    .remove(&example_name(example))
+                    .map(|errors| ExampleFailure { line_number: example.line_number, errors })
+            })
+            .collect();
+
+        match success || !failures.is_empty() {
+            true => Ok(failures),
+            false => Err(VerifyExamplesError::CargoCheckFailed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_doc_examples() {
+        let doc = indoc! { r#"
+            # My crate
+
+            ```
+            # use foo::bar;
+            let x = bar();
+            ```
+
+            ```toml
+            foo = "1"
+            ```
+
+            > ```rust,no_run
+            > let y = 2;
+            > ## not hidden
+            > ```
+
+            ```compile_fail
+            let z: u32 = "3";
+            ```
+
+                #
+                let w = 4;
+            "#
+        };
+        let example = |line_number: usize, attributes: &[&str], code: &str| DocExample {
+            line_number,
+            attributes: attributes.iter().map(ToString::to_string).collect(),
+            code: code.to_owned(),
+        };
+        let examples = doc_examples(&Doc::from_str(doc));
+
+        assert_eq!(
+            examples,
+            vec![
+                example(3, &[], "use foo::bar;\nlet x = bar();\n"),
+                example(12, &["rust", "no_run"], "let y = 2;\n# not hidden\n"),
+                example(17, &["compile_fail"], "let z: u32 = \"3\";\n"),
+                example(21, &[], "\nlet w = 4;\n"),
+            ]
+        );
+        assert_eq!(
+            examples.iter().map(DocExample::is_checked).collect::<Vec<_>>(),
+            vec![true, true, false, true]
+        );
+        assert!(!example(1, &["ignore-windows"], "").is_checked());
+    }
+
+    #[test]
+    fn test_example_source() {
+        assert_eq!(
+            example_source("#![no_std]\nlet x = 1;\n", None, "2021"),
+            "#![allow(unused)]\n#![no_std]\nfn main() {\nlet x = 1;\n}\n"
+        );
+        assert_eq!(
+            example_source("fn main() {}\n", Some("foo"), "2021"),
+            "#![allow(unused)]\nfn main() {}\n"
+        );
+        assert_eq!(
+            example_source("#![no_std]\nfoo::bar();\n", Some("foo"), "2015"),
+            "#![allow(unused)]\n#![no_std]\nextern crate foo;\nfn main() {\nfoo::bar();\n}\n"
+        );
+    }
+}
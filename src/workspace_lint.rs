@@ -0,0 +1,203 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+//! Checks across the packages of a workspace.
+
+use cargo_rdme::{readme_has_markers, source_includes_readme, Doc, Project, Readme};
+use std::collections::HashSet;
+
+/// How a package keeps its README and crate documentation in sync.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum DocStrategy {
+    /// The crate documentation is injected in the README between cargo rdme markers.
+    Markers,
+    /// The README is the crate documentation, e.g. with `#![doc = include_str!("../README.md")]`.
+    IncludeReadme,
+}
+
+pub fn doc_strategy(project: &Project) -> Option<DocStrategy> {
+    let has_markers = project
+        .get_readme_path()
+        .and_then(|readme_path| Readme::from_file(readme_path).ok())
+        .is_some_and(|readme| readme_has_markers(&readme));
+
+    if has_markers {
+        return Some(DocStrategy::Markers);
+    }
+
+    let entryfile =
+        project.get_lib_entryfile_path().or_else(|| project.get_bin_default_entryfile_path())?;
+    let source = std::fs::read_to_string(entryfile).ok()?;
+
+    source_includes_readme(&source).ok()?.then_some(DocStrategy::IncludeReadme)
+}
+
+/// Returns a warning if the packages do not all use the same [`DocStrategy`], since mixed setups
+/// make it hard to tell which file is the source of truth, and why CI fails.
+pub fn mixed_doc_strategies_warning(strategies: &[(&str, DocStrategy)]) -> Option<String> {
+    let packages_with = |strategy: DocStrategy| -> Vec<String> {
+        strategies
+            .iter()
+            .filter(|(_, s)| *s == strategy)
+            .map(|(package_name, _)| format!("`{package_name}`"))
+            .collect()
+    };
+
+    let markers = packages_with(DocStrategy::Markers);
+    let include_readme = packages_with(DocStrategy::IncludeReadme);
+
+    if markers.is_empty() || include_readme.is_empty() {
+        return None;
+    }
+
+    Some(format!(
+        "packages of the workspace mix ways of keeping the README in sync with the crate \
+         documentation: {} use cargo rdme markers in the README, while {} include the README in \
+         the crate documentation",
+        markers.join(", "),
+        include_readme.join(", ")
+    ))
+}
+
+/// The first sentence of the crate documentation, as plain text, which is what rustdoc shows as
+/// the summary of the crate.
+pub fn doc_first_sentence(doc: &Doc) -> Option<String> {
+    use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+
+    let mut paragraph: Option<String> = None;
+
+    for event in Parser::new_ext(doc.as_string(), Options::all()) {
+        match (event, paragraph.as_mut()) {
+            (Event::Start(Tag::Paragraph), None) => paragraph = Some(String::new()),
+            (Event::End(TagEnd::Paragraph), Some(_)) => break,
+            (Event::Text(text) | Event::Code(text), Some(paragraph)) => paragraph.push_str(&text),
+            (Event::SoftBreak | Event::HardBreak, Some(paragraph)) => paragraph.push(' '),
+            _ => (),
+        }
+    }
+
+    let paragraph = paragraph?;
+    let sentence = match paragraph.find(". ") {
+        Some(end) => &paragraph[..=end],
+        None => paragraph.as_str(),
+    };
+
+    Some(sentence.trim().to_owned()).filter(|sentence| !sentence.is_empty())
+}
+
+fn words(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// How similar two texts are, from 0 to 100, by the words they share (the Sørensen–Dice
+/// coefficient of their sets of words).
+pub fn text_similarity(a: &str, b: &str) -> u8 {
+    let (a, b) = (words(a), words(b));
+
+    if a.is_empty() && b.is_empty() {
+        return 100;
+    }
+
+    let shared = a.intersection(&b).count();
+
+    // This is at most 100, so the conversion never truncates.
+    #[allow(clippy::cast_possible_truncation)]
+    let similarity = (200 * shared / (a.len() + b.len())) as u8;
+
+    similarity
+}
+
+/// Returns a warning if the `package.description` of a package is less than `min_similarity`
+/// similar to the first sentence of its crate documentation, so that the package is not described
+/// one way in the registry and another in its documentation.
+pub fn description_mismatch_warning(
+    package_name: &str,
+    description: &str,
+    first_sentence: &str,
+    min_similarity: u8,
+) -> Option<String> {
+    let similarity = text_similarity(description, first_sentence);
+
+    (similarity < min_similarity).then(|| {
+        format!(
+            "the description of package `{package_name}` (“{}”) is only {similarity}% similar to the \
+             first sentence of its crate documentation (“{first_sentence}”)",
+            description.trim(),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_doc_first_sentence() {
+        let doc = indoc! { "
+            # Foo
+
+            A crate to parse `foo` files, fast. It also
+            writes them.
+
+            More.
+            "
+        };
+
+        assert_eq!(
+            doc_first_sentence(&Doc::from_str(doc)).as_deref(),
+            Some("A crate to parse foo files, fast.")
+        );
+        assert_eq!(
+            doc_first_sentence(&Doc::from_str("Parses foo\nfiles")).as_deref(),
+            Some("Parses foo files")
+        );
+        assert_eq!(doc_first_sentence(&Doc::from_str("# Foo\n")), None);
+    }
+
+    #[test]
+    fn test_description_mismatch_warning() {
+        let first_sentence = "A crate to parse foo files.";
+
+        assert_eq!(text_similarity("Parse foo files", first_sentence), 66);
+        assert_eq!(
+            description_mismatch_warning("foo", "Parse foo files", first_sentence, 60),
+            None
+        );
+        assert_eq!(
+            description_mismatch_warning("foo", "A tool for bar", first_sentence, 60).as_deref(),
+            Some(
+                "the description of package `foo` (“A tool for bar”) is only 20% similar to the \
+                 first sentence of its crate documentation (“A crate to parse foo files.”)"
+            )
+        );
+    }
+
+    #[test]
+    fn test_mixed_doc_strategies_warning() {
+        let strategies = [("foo", DocStrategy::Markers), ("bar", DocStrategy::Markers)];
+
+        assert_eq!(mixed_doc_strategies_warning(&strategies), None);
+
+        let strategies = [
+            ("foo", DocStrategy::Markers),
+            ("bar", DocStrategy::IncludeReadme),
+            ("baz", DocStrategy::Markers),
+        ];
+
+        assert_eq!(
+            mixed_doc_strategies_warning(&strategies).as_deref(),
+            Some(
+                "packages of the workspace mix ways of keeping the README in sync with the crate \
+                 documentation: `foo`, `baz` use cargo rdme markers in the README, while `bar` \
+                 include the README in the crate documentation"
+            )
+        );
+    }
+}
@@ -0,0 +1,5 @@
+// cargo-rdme: doc-source = "docs/README-source.md"
+#![cfg_attr(feature = "nightly", doc = "This crate needs a nightly compiler.")]
+#![cfg_attr(not(feature = "nightly"), doc = "This crate works on stable.")]
+
+fn foo() {}
@@ -0,0 +1,5 @@
+//! # My crate
+//!
+//! This crate is found with `CARGO_MANIFEST_DIR`.
+
+fn foo() {}
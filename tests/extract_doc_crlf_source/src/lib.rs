@@ -0,0 +1,14 @@
+//! # My crate
+//!
+//! This crate has its source files saved with `\r\n` line terminators.
+//!
+
+/*!
+But the README does not.
+
+```rust
+let answer = 42;
+```
+*/
+
+fn foo() {}
@@ -0,0 +1,5 @@
+//! # My crate
+//!
+//! This is a crate that does nothing.
+
+fn foo() {}
@@ -0,0 +1,3 @@
+//! A parser of TOML files.  It also validates them.
+
+fn foo() {}
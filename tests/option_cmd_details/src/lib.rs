@@ -0,0 +1,12 @@
+//! # Usage
+//!
+//! Use it.
+//!
+//! <details>
+//! <summary>Advanced usage</summary>
+//!
+//! Use it harder.
+//!
+//! </details>
+
+fn foo() {}
@@ -0,0 +1,3 @@
+//! # Foo
+//!
+//! This is the documentation of a crate that does nothing.
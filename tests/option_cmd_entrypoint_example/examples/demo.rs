@@ -0,0 +1,7 @@
+//! # Getting started
+//!
+//! Call `nothing()` and enjoy.
+
+fn main() {
+    integration_test::nothing();
+}
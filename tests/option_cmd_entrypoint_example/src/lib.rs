@@ -0,0 +1,5 @@
+//! # My crate
+//!
+//! This is a lib crate that does nothing.
+
+pub fn nothing() {}
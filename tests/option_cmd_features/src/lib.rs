@@ -0,0 +1,16 @@
+//! This crate does things.
+#![cfg_attr(feature = "alloc", doc = "", doc = "It allocates.")]
+#![cfg_attr(feature = "std", doc = "", doc = "It uses the standard library.")]
+//!
+//! <!-- rdme:if feature = "nightly" -->
+//! Some things are only available in nightly.
+//! <!-- rdme:endif -->
+//!
+//! # Usage
+//!
+//! ```rust
+//! let x = 1;
+//! ```
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub fn foo() {}
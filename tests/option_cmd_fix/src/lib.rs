@@ -0,0 +1,13 @@
+//! # Integration test
+//!
+//! Add it to your `Cargo.toml`:
+//!
+//! ```TOML
+//! integration_test = "0.0.1"
+//! ```
+//!
+//! Use it: 
+//!
+//! ```rs
+//! integration_test::run();
+//! ```
@@ -0,0 +1,3 @@
+//! Roses are red,  
+//! violets are blue,\
+//! the end.
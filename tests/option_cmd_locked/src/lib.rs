@@ -0,0 +1,3 @@
+//! # My crate
+
+fn foo() {}
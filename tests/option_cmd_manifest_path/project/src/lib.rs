@@ -0,0 +1,5 @@
+//! # My crate
+//!
+//! This crate lives in a directory other than the one cargo rdme runs in.
+
+fn foo() {}
@@ -0,0 +1,5 @@
+//! The area of a circle is $\pi r^2$.
+//!
+//! ```math
+//! A = \pi r^2
+//! ```
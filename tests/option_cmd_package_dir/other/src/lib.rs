@@ -0,0 +1,3 @@
+//! This package has no README, so it is skipped.
+
+fn foo() {}
@@ -0,0 +1,5 @@
+//! # My crate
+//!
+//! This crate is processed with `--package-dir`.
+
+fn foo() {}
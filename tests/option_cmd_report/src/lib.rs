@@ -0,0 +1,5 @@
+//! # My crate
+//!
+//! This crate has new documentation.
+
+fn foo() {}
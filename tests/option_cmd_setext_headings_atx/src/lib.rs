@@ -0,0 +1,15 @@
+//! This is the crate.
+//!
+//! Usage
+//! =====
+//!
+//! Call it.
+//!
+//! Advanced usage
+//! --------------
+//!
+//! Call it twice.
+//!
+//! # Examples
+//!
+//! Some examples.
@@ -0,0 +1,3 @@
+//! # My crate
+//!
+//! This is a crate that does nothing [doesnotexist](crate::doesnotexist).
@@ -0,0 +1,15 @@
+//! This compiles:
+//!
+//! ```
+//! let answer: u32 = mycrate::answer();
+//! ```
+//!
+//! This does not:
+//!
+//! ```
+//! let answer: String = mycrate::answer();
+//! ```
+
+pub fn answer() -> u32 {
+    42
+}
@@ -0,0 +1,10 @@
+//! In edition 2015 the crate is only in scope with `extern crate`, which is added to the examples
+//! like rustdoc does:
+//!
+//! ```
+//! let answer: u32 = mycrate::answer();
+//! ```
+
+pub fn answer() -> u32 {
+    42
+}
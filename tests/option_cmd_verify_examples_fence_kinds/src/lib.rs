@@ -0,0 +1,36 @@
+//! Examples are checked like rustdoc does, with their hidden lines:
+//!
+//! ```
+//! # use mycrate::answer;
+//! let answer: u32 = answer();
+//! ```
+//!
+//! `no_run` and `should_panic` examples only need to compile:
+//!
+//! ```no_run
+//! loop {}
+//! ```
+//!
+//! ```should_panic
+//! panic!("expected");
+//! ```
+//!
+//! `ignore` and `compile_fail` examples are not checked:
+//!
+//! ```ignore
+//! let answer: String = mycrate::answer();
+//! ```
+//!
+//! ```compile_fail
+//! let answer: String = mycrate::answer();
+//! ```
+//!
+//! Nor are code blocks in other languages:
+//!
+//! ```text
+//! let answer: String = mycrate::answer();
+//! ```
+
+pub fn answer() -> u32 {
+    42
+}
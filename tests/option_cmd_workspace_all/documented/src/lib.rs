@@ -0,0 +1,5 @@
+//! # My crate
+//!
+//! This is the only documented package.
+
+fn foo() {}
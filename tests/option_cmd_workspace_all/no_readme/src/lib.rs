@@ -0,0 +1,5 @@
+//! # My crate
+//!
+//! This package has no README.
+
+fn foo() {}
@@ -0,0 +1 @@
+//! The broken crate.
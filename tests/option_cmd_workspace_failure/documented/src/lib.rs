@@ -0,0 +1 @@
+//! The documented crate.
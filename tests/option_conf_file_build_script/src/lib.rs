@@ -0,0 +1 @@
+#![doc = include_str!(concat!(env!("OUT_DIR"), "/docs.md"))]
@@ -0,0 +1,7 @@
+//! # My crate
+//!
+//! This is a crate that adds numbers.
+
+pub fn add(a: u32, b: u32) -> u32 {
+    a + b
+}
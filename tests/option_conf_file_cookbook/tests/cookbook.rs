@@ -0,0 +1,10 @@
+//! ## Adding numbers
+//!
+//! ```rust
+//! assert_eq!(integration_test::add(1, 2), 3);
+//! ```
+
+#[test]
+fn add() {
+    assert_eq!(integration_test::add(1, 2), 3);
+}
@@ -0,0 +1,3 @@
+#![doc = include_str!("../docs/crate.md")]
+
+pub fn foo() {}
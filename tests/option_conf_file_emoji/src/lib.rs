@@ -0,0 +1,5 @@
+//! This crate is blazingly fast :rocket: and written in Rust :crab:.
+//!
+//! Use `:crab:` to crab.
+
+fn foo() {}
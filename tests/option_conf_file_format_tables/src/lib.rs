@@ -0,0 +1,6 @@
+//! Feature|Description
+//! -|-
+//! `std`|Enables the standard library.
+//! `serde`|Serde support.
+
+fn foo() {}
@@ -0,0 +1,5 @@
+//! # Foo
+//!
+//! This is a crate that does nothing.
+
+fn foo() {}
@@ -0,0 +1,9 @@
+//! This is a crate that does nothing.
+//!
+//! # Getting Started With It
+//!
+//! Do nothing.
+//!
+//! # How it works
+//!
+//! It does not.
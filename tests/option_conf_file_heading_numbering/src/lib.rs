@@ -0,0 +1,16 @@
+//! * [Usage](#usage)
+//! * [Configuration](#configuration)
+//!
+//! # Usage
+//!
+//! Use it.
+//!
+//! # Configuration
+//!
+//! ## Command line
+//!
+//! ## Configuration file
+//!
+//! Configure it.
+
+fn foo() {}
@@ -0,0 +1,11 @@
+//! Create a parser with [`Self::new`] and run it with [`Self::parse()`].
+
+pub struct Parser;
+
+impl Parser {
+    pub fn new() -> Parser {
+        Parser
+    }
+
+    pub fn parse(&self) {}
+}
@@ -0,0 +1,11 @@
+//! This crate follows the [API guidelines](https://rust-lang.github.io/api-guidelines/) and uses
+//! [serde](https://serde.rs "Serde").
+//!
+//! # Usage
+//!
+//! See the [guidelines](https://rust-lang.github.io/api-guidelines/), the [`Foo`] type, and the
+//! [serde docs][docs].
+//!
+//! [docs]: https://docs.rs/serde
+
+pub struct Foo;
@@ -0,0 +1,8 @@
+//! This crate has:
+//!
+//! - Lists:
+//!   - nested with two spaces,
+//!     - and deeper.
+//! - Numbered lists:
+//!   1. first,
+//!   2. second.
@@ -0,0 +1,4 @@
+#![doc(html_logo_url = "https://example.com/mycrate.svg")]
+#![doc(test(attr(deny(warnings))))]
+
+//! This crate has a logo.
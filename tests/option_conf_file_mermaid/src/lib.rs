@@ -0,0 +1,12 @@
+//! This crate has an architecture:
+//!
+//! ```mermaid
+//! graph LR;
+//!     parser-->renderer;
+//! ```
+//!
+//! And some code:
+//!
+//! ```
+//! let x = 1;
+//! ```
@@ -0,0 +1,7 @@
+//! This is the crate documentation.
+//!
+//! # Usage
+//!
+//! Call `foo()`.
+
+pub fn foo() {}
@@ -0,0 +1,13 @@
+//! This crate follows the [API guidelines][guidelines] and uses [serde].
+//!
+//! [serde]: https://serde.rs
+//! [guidelines]: https://rust-lang.github.io/api-guidelines/
+//!
+//! # Usage
+//!
+//! See the [guidelines][api] and the [`Foo`] type.
+//!
+//! [api]: https://rust-lang.github.io/api-guidelines/
+//! [`Foo`]: crate::Foo
+
+pub struct Foo;
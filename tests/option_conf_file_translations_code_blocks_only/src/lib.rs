@@ -0,0 +1,12 @@
+//! # Foo
+//!
+//! Computes the answer:
+//!
+//! ```
+//! # use integration_test::answer;
+//! assert_eq!(answer(), 42);
+//! ```
+
+pub fn answer() -> u32 {
+    42
+}
@@ -0,0 +1 @@
+//! This is the documentation of the crate.
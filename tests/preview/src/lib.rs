@@ -0,0 +1,5 @@
+//! This is the crate documentation, with a [guide](docs/guide.md).
+//!
+//! <script>alert("hello");</script>
+
+pub fn foo() {}
@@ -0,0 +1,11 @@
+//! # Linked
+//!
+//! Use [`Foo`], [`Foo::new()`], or [`Bar`].
+
+pub struct Foo;
+
+impl Foo {
+    pub fn new() -> Foo {
+        Foo
+    }
+}
@@ -0,0 +1,3 @@
+//! This crate does nothing.
+
+fn foo() {}
@@ -15,6 +15,16 @@ pub struct TestOptions {
     pub expected_exit_code: i32,
     pub check_readme_expected: bool,
     pub force: bool,
+    /// Run the binary the way cargo runs it as a subcommand (i.e. `cargo rdme`).
+    pub cargo_subcommand: bool,
+    /// Languages of the translations of the README, e.g. `README.es.md` for `es`, which are
+    /// created from `README-template.<language>.md` and checked against
+    /// `README-expected.<language>.md`.
+    pub translations: &'static [&'static str],
+    /// What the binary is expected to print to stdout, if it matters.
+    pub expected_stdout: Option<&'static str>,
+    /// Environment variables to set when running the binary.
+    pub env: &'static [(&'static str, &'static str)],
 }
 
 impl Default for TestOptions {
@@ -25,6 +35,10 @@ impl Default for TestOptions {
             expected_exit_code: 0,
             check_readme_expected: true,
             force: true,
+            cargo_subcommand: false,
+            translations: &[],
+            expected_stdout: None,
+            env: &[],
         }
     }
 }
@@ -45,6 +59,17 @@ pub fn test_readme_expected(test_name: &str) -> PathBuf {
     test_dir(test_name).join("README-expected.md")
 }
 
+/// The README of a translation, along with its template and expected README.
+fn test_translation_readmes(test_name: &str, language: &str) -> (PathBuf, PathBuf, PathBuf) {
+    let test_dir = test_dir(test_name);
+
+    (
+        test_dir.join(format!("README.{language}.md")),
+        test_dir.join(format!("README-template.{language}.md")),
+        test_dir.join(format!("README-expected.{language}.md")),
+    )
+}
+
 fn is_stderr_terminal() -> bool {
     use std::io::IsTerminal;
 
@@ -174,6 +199,12 @@ pub fn run_test_with_options(test_name: &str, options: &TestOptions) {
         );
 
         std::fs::copy(&template_readme, &readme).unwrap();
+
+        for language in options.translations {
+            let (readme, template_readme, _) = test_translation_readmes(test_name, language);
+
+            std::fs::copy(&template_readme, &readme).unwrap();
+        }
     }
 
     let args: Vec<&str> = {
@@ -183,13 +214,22 @@ pub fn run_test_with_options(test_name: &str, options: &TestOptions) {
             args.insert(0, "--force");
         }
 
+        if options.cargo_subcommand {
+            args.insert(0, "rdme");
+        }
+
         args
     };
 
     let output = Command::new(bin_path)
         .args(args)
-        .current_dir(test_dir)
+        .current_dir(&test_dir)
+        // Set by `cargo test`, but it would make the binary use the manifest of this crate.
+        .env_remove("CARGO_MANIFEST_DIR")
+        // Keeps the user configuration of whoever runs the tests out of them.
+        .env("XDG_CONFIG_HOME", test_dir.join("xdg-config"))
         .env("RUST_BACKTRACE", "1")
+        .envs(options.env.iter().copied())
         .output()
         .unwrap_or_else(|_| panic!("Failed to execute {}", bin_path.display()));
 
@@ -202,15 +242,31 @@ pub fn run_test_with_options(test_name: &str, options: &TestOptions) {
         panic!("Test {test_name} failed.");
     }
 
+    if let Some(expected_stdout) = options.expected_stdout {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+
+        assert_eq!(stdout, expected_stdout, "Test {test_name} failed: unexpected stdout.");
+    }
+
     if options.check_readme_expected {
-        let expected = std::fs::read_to_string(&expected_readme).unwrap();
-        let got = std::fs::read_to_string(&readme).unwrap();
-
-        if expected != got {
-            print_failure_readme_mismatch(&expected, &got, readme, expected_readme, &stderr);
-            panic!("Test {test_name} failed.");
-        } else {
-            std::fs::remove_file(readme).unwrap();
+        let translations = options
+            .translations
+            .iter()
+            .map(|language| test_translation_readmes(test_name, language))
+            .map(|(readme, _, expected_readme)| (readme, expected_readme));
+
+        for (readme, expected_readme) in
+            std::iter::once((readme, expected_readme)).chain(translations)
+        {
+            let expected = std::fs::read_to_string(&expected_readme).unwrap();
+            let got = std::fs::read_to_string(&readme).unwrap();
+
+            if expected != got {
+                print_failure_readme_mismatch(&expected, &got, readme, expected_readme, &stderr);
+                panic!("Test {test_name} failed.");
+            } else {
+                std::fs::remove_file(readme).unwrap();
+            }
         }
     }
 }
@@ -6,8 +6,10 @@
 mod testing;
 
 use crate::testing::{test_dir, test_readme_expected, test_readme_template};
-use cargo_rdme::{infer_line_terminator, LineTerminator};
+use cargo_rdme::{infer_final_newline, infer_line_terminator, LineTerminator, Project, TargetKind};
+use indoc::indoc;
 use std::io::{Read, Write};
+use std::path::PathBuf;
 use testing::{run_test, run_test_with_options, TestOptions};
 
 #[test]
@@ -15,6 +17,55 @@ fn integration_test_simple_single_marker() {
     run_test("simple_single_marker");
 }
 
+#[test]
+fn integration_test_selftest() {
+    let options = TestOptions {
+        args: &["selftest"],
+        check_readme_expected: false,
+        force: false,
+        ..TestOptions::default()
+    };
+
+    run_test_with_options("simple_single_marker", &options);
+}
+
+#[test]
+fn integration_test_stats() {
+    let options = TestOptions {
+        args: &["stats"],
+        check_readme_expected: false,
+        force: false,
+        expected_stdout: Some(indoc! { "
+            package  doc lines  readme lines  markers  intralinks  unresolved
+            linked           3             3      yes           3           1
+            plain            -             -       no           0           -
+            "
+        }),
+        ..TestOptions::default()
+    };
+
+    run_test_with_options("stats", &options);
+}
+
+#[test]
+fn integration_test_stats_json() {
+    let options = TestOptions {
+        args: &["stats", "--json"],
+        check_readme_expected: false,
+        force: false,
+        expected_stdout: Some(indoc! { r#"
+            [
+              {"package": "linked", "doc_lines": 3, "readme_lines": 3, "markers": true, "intralinks": 3, "unresolved_intralinks": 1},
+              {"package": "plain", "doc_lines": null, "readme_lines": null, "markers": false, "intralinks": 0, "unresolved_intralinks": null}
+            ]
+            "#
+        }),
+        ..TestOptions::default()
+    };
+
+    run_test_with_options("stats", &options);
+}
+
 #[test]
 fn integration_test_simple_single_marker_no_footer() {
     run_test("simple_single_marker_no_footer");
@@ -25,6 +76,67 @@ fn integration_test_simple_start_end_marker() {
     run_test("simple_start_end_marker");
 }
 
+#[test]
+fn integration_test_project_targets() {
+    let dir = test_dir("separate_bin_and_lib");
+    let project = Project::from_manifest_path(dir.join("Cargo.toml")).unwrap();
+
+    let targets: Vec<(TargetKind, PathBuf)> =
+        project.targets().map(|target| (target.kind.clone(), target.entryfile.clone())).collect();
+
+    assert_eq!(
+        targets,
+        vec![
+            (TargetKind::Lib, dir.join("src/lib/lib.rs")),
+            (TargetKind::Bin { name: "thebin".to_owned() }, dir.join("src/bin/the-binary/main.rs")),
+        ]
+    );
+}
+
+#[test]
+fn integration_test_project_manifest_fields() {
+    let project =
+        Project::from_manifest_path(test_dir("package_metadata_config").join("Cargo.toml"))
+            .unwrap();
+
+    assert_eq!(project.get_package_name(), "integration_test");
+    assert_eq!(project.get_version(), "0.1.0");
+    assert_eq!(project.get_description(), Some("A crate to test cargo rdme."));
+    assert_eq!(project.get_repository(), Some("https://github.com/orium/cargo-rdme"));
+    assert_eq!(project.get_license(), Some("MPL-2.0"));
+    assert_eq!(project.get_rust_version(), Some("1.70.0"));
+    assert_eq!(project.get_edition(), "2021");
+
+    let project =
+        Project::from_manifest_path(test_dir("simple_single_marker").join("Cargo.toml")).unwrap();
+
+    assert_eq!(project.get_description(), None);
+    assert_eq!(project.get_rust_version(), None);
+
+    let project = Project::from_manifest_path(
+        test_dir("option_cmd_verify_examples_edition_2015").join("Cargo.toml"),
+    )
+    .unwrap();
+
+    assert_eq!(project.get_edition(), "2015");
+}
+
+#[test]
+fn integration_test_project_targets_example() {
+    let dir = test_dir("option_cmd_entrypoint_example");
+    let project = Project::from_manifest_path(dir.join("Cargo.toml")).unwrap();
+
+    assert_eq!(
+        project.targets().map(|target| &target.kind).collect::<Vec<_>>(),
+        vec![&TargetKind::Lib, &TargetKind::Example { name: "demo".to_owned() }]
+    );
+    assert_eq!(
+        project.get_example_entryfile_path("demo"),
+        Some(dir.join("examples/demo.rs").as_path())
+    );
+    assert_eq!(project.get_example_entryfile_path("nope"), None);
+}
+
 #[test]
 fn integration_test_custom_lib_path() {
     run_test("custom_lib_path");
@@ -49,6 +161,46 @@ fn integration_test_line_terminator_crlf() {
     run_test(test_name);
 }
 
+#[test]
+fn integration_test_line_terminator_cr() {
+    let test_name = "line_terminator_cr";
+    let readme_template = test_readme_template(test_name);
+    let readme_expected = test_readme_expected(test_name);
+
+    assert_eq!(infer_line_terminator(readme_template).unwrap(), LineTerminator::Cr);
+    assert_eq!(infer_line_terminator(readme_expected).unwrap(), LineTerminator::Cr);
+
+    run_test(test_name);
+}
+
+#[test]
+fn integration_test_extract_doc_crlf_source() {
+    let test_name = "extract_doc_crlf_source";
+    let source = test_dir(test_name).join("src").join("lib.rs");
+
+    assert_eq!(infer_line_terminator(source).unwrap(), LineTerminator::CrLf);
+    assert_eq!(infer_line_terminator(test_readme_expected(test_name)).unwrap(), LineTerminator::Lf);
+
+    run_test(test_name);
+}
+
+#[test]
+fn integration_test_no_final_newline() {
+    let test_name = "no_final_newline";
+
+    assert!(!infer_final_newline(test_readme_template(test_name)).unwrap());
+    assert!(!infer_final_newline(test_readme_expected(test_name)).unwrap());
+
+    run_test(test_name);
+}
+
+#[test]
+fn integration_test_option_cmd_final_newline_always() {
+    let options = TestOptions { args: &["--final-newline", "always"], ..TestOptions::default() };
+
+    run_test_with_options("option_cmd_final_newline_always", &options);
+}
+
 #[test]
 fn integration_test_multiline_doc() {
     run_test("multiline_doc");
@@ -95,6 +247,40 @@ fn integration_test_option_cmd_line_terminator_crlf() {
     run_test_with_options(test_name, &options);
 }
 
+#[test]
+fn integration_test_option_cmd_setext_headings_atx() {
+    let options = TestOptions { args: &["--setext-headings", "atx"], ..TestOptions::default() };
+
+    run_test_with_options("option_cmd_setext_headings_atx", &options);
+}
+
+#[test]
+fn integration_test_option_cmd_hard_breaks_html() {
+    let options = TestOptions { args: &["--hard-breaks", "html"], ..TestOptions::default() };
+
+    run_test_with_options("option_cmd_hard_breaks_html", &options);
+}
+
+#[test]
+fn integration_test_option_cmd_usage_style_both() {
+    let options = TestOptions {
+        args: &["--usage-style", "both", "--usage-features", "serde,derive"],
+        ..TestOptions::default()
+    };
+
+    run_test_with_options("option_cmd_usage_style_both", &options);
+}
+
+#[test]
+fn integration_test_option_cmd_features() {
+    let options = TestOptions {
+        args: &["--no-default-features", "--features", "alloc,nightly"],
+        ..TestOptions::default()
+    };
+
+    run_test_with_options("option_cmd_features", &options);
+}
+
 #[test]
 fn integration_test_option_conf_file_override_readme_path() {
     let test_name = "option_conf_file_override_readme_path";
@@ -167,6 +353,19 @@ fn integration_test_option_cmd_check_fail_because_warnings() {
     run_test_with_options(test_name, &options);
 }
 
+#[test]
+fn integration_test_option_conf_file_heading_case() {
+    let test_name = "option_conf_file_heading_case";
+    let options = TestOptions {
+        args: &["--check"],
+        check_readme_expected: false,
+        expected_exit_code: 4,
+        ..TestOptions::default()
+    };
+
+    run_test_with_options(test_name, &options);
+}
+
 #[test]
 fn integration_test_option_cmd_check_no_fail_on_warnings() {
     let test_name = "option_cmd_check_no_fail_on_warnings";
@@ -180,6 +379,103 @@ fn integration_test_option_cmd_check_no_fail_on_warnings() {
     run_test_with_options(test_name, &options);
 }
 
+#[test]
+fn integration_test_option_conf_file_build_script() {
+    run_test("option_conf_file_build_script");
+}
+
+#[test]
+fn integration_test_option_cmd_strict() {
+    let test_name = "option_cmd_strict";
+    // The README is not updated because of the unresolved intralink.
+    let options =
+        TestOptions { args: &["--strict"], expected_exit_code: 1, ..TestOptions::default() };
+
+    run_test_with_options(test_name, &options);
+}
+
+#[test]
+fn integration_test_option_cmd_allow_missing_docs() {
+    let test_name = "option_cmd_allow_missing_docs";
+    let options = TestOptions {
+        expected_exit_code: 1,
+        check_readme_expected: false,
+        ..TestOptions::default()
+    };
+
+    run_test_with_options(test_name, &options);
+
+    // The crate has no documentation, so the README is left as it is.
+    let options = TestOptions { args: &["--allow-missing-docs"], ..TestOptions::default() };
+
+    run_test_with_options(test_name, &options);
+}
+
+#[test]
+fn integration_test_option_cmd_readme_paths() {
+    // The READMEs given on the command line are updated instead of the one of `Cargo.toml`.
+    let options = TestOptions {
+        args: &["--readme-path", "README.md", "--readme-path", "README.packaging.md"],
+        translations: &["packaging"],
+        ..TestOptions::default()
+    };
+
+    run_test_with_options("option_cmd_readme_paths", &options);
+}
+
+#[test]
+fn integration_test_option_cmd_stdout() {
+    let test_name = "option_cmd_stdout";
+    // The README is printed, and not written.
+    let options = TestOptions {
+        args: &["--stdout"],
+        expected_stdout: Some(indoc! { "
+            header
+
+            <!-- cargo-rdme start -->
+
+            # My crate
+
+            This is a crate that does nothing.
+
+            <!-- cargo-rdme end -->
+
+            footer
+            "
+        }),
+        ..TestOptions::default()
+    };
+
+    run_test_with_options(test_name, &options);
+}
+
+#[test]
+fn integration_test_option_cmd_verify_examples() {
+    let test_name = "option_cmd_verify_examples";
+    // The README is not updated because one of the examples does not compile.
+    let options = TestOptions {
+        args: &["--verify-examples"],
+        expected_exit_code: 1,
+        ..TestOptions::default()
+    };
+
+    run_test_with_options(test_name, &options);
+}
+
+#[test]
+fn integration_test_option_cmd_verify_examples_fence_kinds() {
+    let options = TestOptions { args: &["--verify-examples"], ..TestOptions::default() };
+
+    run_test_with_options("option_cmd_verify_examples_fence_kinds", &options);
+}
+
+#[test]
+fn integration_test_option_cmd_verify_examples_edition_2015() {
+    let options = TestOptions { args: &["--verify-examples"], ..TestOptions::default() };
+
+    run_test_with_options("option_cmd_verify_examples_edition_2015", &options);
+}
+
 #[test]
 fn integration_test_option_cmd_check_fail_line_terminator() {
     let test_name = "option_cmd_check_fail_line_terminator";
@@ -255,6 +551,15 @@ fn integration_test_option_cmd_entrypoint_select_bin() {
     run_test_with_options(test_name, &options);
 }
 
+#[test]
+fn integration_test_option_cmd_entrypoint_example() {
+    let test_name = "option_cmd_entrypoint_example";
+
+    let options = TestOptions { args: &["--entrypoint", "example:demo"], ..TestOptions::default() };
+
+    run_test_with_options(test_name, &options);
+}
+
 #[test]
 fn integration_test_option_cmd_entrypoint_select_bin_custom_path() {
     let test_name = "option_cmd_entrypoint_select_bin_custom_path";
@@ -293,7 +598,7 @@ fn integration_test_avoid_overwrite_uncommitted_readme() {
     };
 
     let mut file = File::create(&readme_path).unwrap();
-    file.write_all("A file!".as_bytes()).unwrap();
+    file.write_all("A file!\n\n<!-- cargo-rdme -->\n".as_bytes()).unwrap();
     drop(file);
 
     run_test_with_options(test_name, &options);
@@ -302,7 +607,7 @@ fn integration_test_avoid_overwrite_uncommitted_readme() {
     let mut content = String::new();
     file.read_to_string(&mut content).unwrap();
 
-    assert_eq!(content, "A file!");
+    assert_eq!(content, "A file!\n\n<!-- cargo-rdme -->\n");
 }
 
 #[test]
@@ -330,6 +635,11 @@ fn integration_test_transform_intralinks_method() {
     run_test("transform_intralinks_impl_items");
 }
 
+#[test]
+fn integration_test_transform_intralinks_doc_alias() {
+    run_test("transform_intralinks_doc_alias");
+}
+
 #[test]
 fn integration_test_transform_intralinks_reference_links() {
     run_test("transform_intralinks_reference_links");
@@ -340,6 +650,11 @@ fn integration_test_transform_intralinks_module_walk() {
     run_test("transform_intralinks_module_walk");
 }
 
+#[test]
+fn integration_test_transform_intralinks_module_layouts() {
+    run_test("transform_intralinks_module_layouts");
+}
+
 #[test]
 fn integration_test_transform_intralinks_ambiguous_module() {
     run_test("transform_intralinks_ambiguous_module");
@@ -355,11 +670,96 @@ fn integration_test_transform_intralinks_crate_name_hyphen() {
     run_test("transform_intralinks_crate_name_hyphen");
 }
 
+#[test]
+fn integration_test_usage_marker() {
+    run_test("usage_marker");
+}
+
+#[test]
+fn integration_test_option_conf_file_logo() {
+    run_test("option_conf_file_logo");
+}
+
+#[test]
+fn integration_test_undo() {
+    let test_name = "undo";
+    let readme = test_dir(test_name).join("README.md");
+    let template = std::fs::read_to_string(test_readme_template(test_name)).unwrap();
+    let expected = std::fs::read_to_string(test_readme_expected(test_name)).unwrap();
+
+    std::fs::write(&readme, &template).unwrap();
+
+    let options = TestOptions { check_readme_expected: false, ..TestOptions::default() };
+
+    run_test_with_options(test_name, &options);
+    assert_eq!(std::fs::read_to_string(&readme).unwrap(), expected);
+
+    let options = TestOptions {
+        args: &["undo"],
+        force: false,
+        check_readme_expected: false,
+        ..TestOptions::default()
+    };
+
+    run_test_with_options(test_name, &options);
+    assert_eq!(std::fs::read_to_string(&readme).unwrap(), template);
+
+    // The run was undone, so there is nothing left to undo.
+    let options = TestOptions { expected_exit_code: 1, ..options };
+
+    run_test_with_options(test_name, &options);
+    std::fs::remove_file(readme).unwrap();
+}
+
+#[test]
+fn integration_test_preview() {
+    let test_name = "preview";
+    let options = TestOptions { args: &["preview", "--no-open"], ..TestOptions::default() };
+
+    // The README is left as it is.
+    run_test_with_options(test_name, &options);
+
+    let preview_path =
+        test_dir(test_name).join("target/cargo-rdme/preview/integration_test/README.html");
+    let preview = std::fs::read_to_string(preview_path).unwrap();
+
+    assert!(preview.contains("<h1>The crate</h1>"));
+    assert!(preview.contains(
+        r#"<a href="https://github.com/foo/integration_test/blob/HEAD/docs/guide.md">guide</a>"#
+    ));
+    assert!(!preview.contains("alert"));
+}
+
+#[test]
+fn integration_test_transform_blank_lines() {
+    run_test("transform_blank_lines");
+}
+
+#[test]
+fn integration_test_transform_block_quotes() {
+    run_test("transform_block_quotes");
+}
+
 #[test]
 fn integration_test_transform_intralinks_backticked() {
     run_test("transform_intralinks_backticked");
 }
 
+#[test]
+fn integration_test_transform_intralinks_rustdoc_forms() {
+    run_test("transform_intralinks_rustdoc_forms");
+}
+
+#[test]
+fn integration_test_transform_intralinks_package_documentation() {
+    run_test("transform_intralinks_package_documentation");
+}
+
+#[test]
+fn integration_test_transform_intralinks_path_dependencies() {
+    run_test("transform_intralinks_path_dependencies");
+}
+
 #[test]
 fn integration_test_option_conf_file_workspace() {
     run_test("option_conf_file_workspace");
@@ -399,11 +799,21 @@ fn integration_test_option_conf_file_intralinks_docs_rs_version() {
     run_test("option_conf_file_intralinks_docs_rs_version");
 }
 
+#[test]
+fn integration_test_option_conf_file_intralinks_docs_url_template() {
+    run_test("option_conf_file_intralinks_docs_url_template");
+}
+
 #[test]
 fn integration_test_option_conf_file_intralinks_strip_links() {
     run_test("option_conf_file_intralinks_strip_links");
 }
 
+#[test]
+fn integration_test_option_conf_file_intralinks_self_type() {
+    run_test("option_conf_file_intralinks_self_type");
+}
+
 #[test]
 fn integration_test_option_cmd_intralinks_strip_links() {
     let test_name = "option_cmd_intralinks_strip_links";
@@ -432,6 +842,386 @@ fn integration_test_option_cmd_heading_base_level() {
     run_test_with_options(test_name, &options);
 }
 
+#[test]
+fn integration_test_option_conf_file_heading() {
+    run_test("option_conf_file_heading");
+}
+
+#[test]
+fn integration_test_option_conf_file_readme_section() {
+    run_test("option_conf_file_readme_section");
+}
+
+#[test]
+fn integration_test_option_cmd_heading() {
+    let test_name = "option_cmd_heading";
+
+    let options = TestOptions { args: &["--heading", "Documentation"], ..TestOptions::default() };
+
+    run_test_with_options(test_name, &options);
+}
+
+#[test]
+fn integration_test_option_conf_file_heading_numbering() {
+    run_test("option_conf_file_heading_numbering");
+}
+
+#[test]
+fn integration_test_option_conf_file_emoji() {
+    run_test("option_conf_file_emoji");
+}
+
+#[test]
+fn integration_test_option_conf_file_list_indentation() {
+    run_test("option_conf_file_list_indentation");
+}
+
+#[test]
+fn integration_test_option_conf_file_cookbook() {
+    run_test("option_conf_file_cookbook");
+}
+
+#[test]
+fn integration_test_option_conf_file_mermaid() {
+    run_test("option_conf_file_mermaid");
+}
+
+#[test]
+fn integration_test_option_cmd_details() {
+    let test_name = "option_cmd_details";
+
+    let options = TestOptions { args: &["--details", "unwrap"], ..TestOptions::default() };
+
+    run_test_with_options(test_name, &options);
+}
+
+#[test]
+fn integration_test_option_conf_file_format_tables() {
+    run_test("option_conf_file_format_tables");
+}
+
+#[test]
+fn integration_test_option_conf_file_sort_reference_links() {
+    run_test("option_conf_file_sort_reference_links");
+}
+
+#[test]
+fn integration_test_option_conf_file_link_style() {
+    run_test("option_conf_file_link_style");
+}
+
+#[test]
+fn integration_test_option_cmd_math() {
+    let test_name = "option_cmd_math";
+
+    let options = TestOptions { args: &["--math", "code"], ..TestOptions::default() };
+
+    run_test_with_options(test_name, &options);
+}
+
+#[test]
+fn integration_test_option_cmd_fix() {
+    let test_name = "option_cmd_fix";
+
+    let options = TestOptions { args: &["--fix"], ..TestOptions::default() };
+
+    run_test_with_options(test_name, &options);
+}
+
+#[test]
+fn integration_test_marker_content_hash_hand_edited() {
+    let test_name = "marker_content_hash_hand_edited";
+
+    let options = TestOptions { force: false, expected_exit_code: 1, ..TestOptions::default() };
+
+    run_test_with_options(test_name, &options);
+}
+
+#[test]
+fn integration_test_subcommand_freeze() {
+    let test_name = "subcommand_freeze";
+
+    let options = TestOptions { args: &["freeze"], ..TestOptions::default() };
+
+    run_test_with_options(test_name, &options);
+}
+
+#[test]
+fn integration_test_option_cmd_workspace_all() {
+    let test_name = "option_cmd_workspace_all";
+
+    let options = TestOptions { args: &["--workspace"], ..TestOptions::default() };
+
+    run_test_with_options(test_name, &options);
+}
+
+#[test]
+fn integration_test_option_cmd_workspace_failure() {
+    let test_name = "option_cmd_workspace_failure";
+
+    // The package with invalid markers fails, but the others are still processed.
+    let options = TestOptions {
+        args: &["--workspace"],
+        expected_exit_code: 1,
+        expected_stdout: Some("package     status\nbroken      failed\ndocumented  updated\n"),
+        ..TestOptions::default()
+    };
+
+    run_test_with_options(test_name, &options);
+}
+
+#[test]
+fn integration_test_option_cmd_description_similarity() {
+    let test_name = "option_cmd_description_similarity";
+
+    // The warning about the description is an error in strict mode.
+    let options = TestOptions {
+        args: &["--workspace", "--strict", "--description-similarity", "50"],
+        expected_exit_code: 1,
+        ..TestOptions::default()
+    };
+
+    run_test_with_options(test_name, &options);
+}
+
+#[test]
+fn integration_test_option_cmd_package_dir() {
+    let test_name = "option_cmd_package_dir";
+
+    let options = TestOptions {
+        args: &["--package-dir", ".", "--package-dir-list", "dirs.txt"],
+        ..TestOptions::default()
+    };
+
+    run_test_with_options(test_name, &options);
+}
+
+#[test]
+fn integration_test_env_cargo_manifest_dir() {
+    let test_name = "env_cargo_manifest_dir";
+
+    let options =
+        TestOptions { env: &[("CARGO_MANIFEST_DIR", "project")], ..TestOptions::default() };
+
+    run_test_with_options(test_name, &options);
+}
+
+#[test]
+fn integration_test_option_cmd_locked() {
+    let test_name = "option_cmd_locked";
+
+    // There is no `Cargo.lock`, and cargo is not allowed to create it.
+    let options = TestOptions {
+        args: &["--check", "--locked"],
+        expected_exit_code: 1,
+        check_readme_expected: false,
+        ..TestOptions::default()
+    };
+
+    run_test_with_options(test_name, &options);
+}
+
+#[test]
+fn integration_test_option_cmd_summary() {
+    let test_name = "option_cmd_summary";
+
+    let options = TestOptions {
+        args: &["--check", "--summary", "-"],
+        expected_exit_code: 3,
+        expected_stdout: Some(
+            "### cargo rdme\n\
+             \n\
+             | package | status |\n\
+             | --- | --- |\n\
+             | `mycrate` | failed: README is not up to date |\n\
+             \n\
+             <details>\n\
+             <summary><code>mycrate</code>: <code>README.md</code></summary>\n\
+             \n\
+             ```diff\n\
+             @@ -4,6 +4,6 @@\n\
+             \x20\n\
+             \x20## My crate\n\
+             \x20\n\
+             -This crate has old documentation.\n\
+             +This crate has new documentation.\n\
+             \x20\n\
+             \x20<!-- cargo-rdme end -->\n\
+             ```\n\
+             \n\
+             </details>\n",
+        ),
+        ..TestOptions::default()
+    };
+
+    run_test_with_options(test_name, &options);
+}
+
+#[test]
+fn integration_test_option_cmd_report() {
+    let test_name = "option_cmd_report";
+
+    let options = TestOptions {
+        args: &["--check", "--report", "-"],
+        expected_exit_code: 3,
+        expected_stdout: Some(concat!(
+            "{\n",
+            "  \"version\": \"",
+            env!("CARGO_PKG_VERSION"),
+            "\",\n",
+            indoc! { r#"
+                  "exit_code": 3,
+                  "stats": {
+                    "packages": 1,
+                    "updated": 0,
+                    "up_to_date": 0,
+                    "skipped": 0,
+                    "failed": 1,
+                    "warnings": 0,
+                    "errors": 1
+                  },
+                  "packages": [
+                    {
+                      "name": "mycrate",
+                      "status": "failed",
+                      "message": "README is not up to date",
+                      "files_written": []
+                    }
+                  ],
+                  "diagnostics": [
+                    {
+                      "level": "error",
+                      "code": "RDME0004",
+                      "message": "README is not up to date",
+                      "path": "README.md"
+                    }
+                  ]
+                }
+                "#
+            },
+        )),
+        ..TestOptions::default()
+    };
+
+    run_test_with_options(test_name, &options);
+}
+
+#[test]
+fn integration_test_workspace_metadata_config() {
+    let test_name = "workspace_metadata_config";
+
+    let options =
+        TestOptions { args: &["--workspace-project", "otherproj"], ..TestOptions::default() };
+
+    run_test_with_options(test_name, &options);
+}
+
+#[test]
+fn integration_test_workspace_metadata_config_disabled_package() {
+    let test_name = "workspace_metadata_config";
+
+    let options = TestOptions {
+        args: &["--workspace-project", "myproj"],
+        check_readme_expected: false,
+        ..TestOptions::default()
+    };
+
+    run_test_with_options(test_name, &options);
+}
+
+#[test]
+fn integration_test_package_metadata_config() {
+    run_test("package_metadata_config");
+}
+
+#[test]
+fn integration_test_invocation_cargo_subcommand() {
+    let test_name = "invocation_cargo_subcommand";
+
+    let options = TestOptions { cargo_subcommand: true, ..TestOptions::default() };
+
+    run_test_with_options(test_name, &options);
+}
+
+#[test]
+fn integration_test_option_cmd_manifest_path() {
+    let test_name = "option_cmd_manifest_path";
+
+    let options =
+        TestOptions { args: &["--manifest-path", "project/Cargo.toml"], ..TestOptions::default() };
+
+    run_test_with_options(test_name, &options);
+}
+
+#[test]
+fn integration_test_option_cmd_doc_source() {
+    let test_name = "option_cmd_doc_source";
+
+    let options = TestOptions { args: &["--doc-source", "src/docs.rs"], ..TestOptions::default() };
+
+    run_test_with_options(test_name, &options);
+}
+
+#[test]
+fn integration_test_option_conf_file_doc_source() {
+    run_test("option_conf_file_doc_source");
+}
+
+#[test]
+fn integration_test_doc_source_pragma() {
+    run_test("doc_source_pragma");
+}
+
+#[test]
+fn integration_test_option_conf_file_doc_source_markdown() {
+    run_test("option_conf_file_doc_source_markdown");
+}
+
+#[test]
+fn integration_test_option_env_vars() {
+    // The environment variables take precedence over `.cargo-rdme.toml`, and the command line over
+    // the environment variables.
+    let options = TestOptions {
+        args: &["--heading-base-level", "2"],
+        env: &[("CARGO_RDME_HEADING", "Documentation"), ("CARGO_RDME_HEADING_BASE_LEVEL", "1")],
+        ..TestOptions::default()
+    };
+
+    run_test_with_options("option_env_vars", &options);
+}
+
+#[test]
+fn integration_test_option_user_config() {
+    // The user configuration is in `xdg-config/cargo-rdme/config.toml` of the test directory.
+    run_test("option_user_config");
+}
+
+#[test]
+fn integration_test_option_cmd_deterministic() {
+    // Neither the user configuration nor the environment variables apply.
+    let options = TestOptions {
+        args: &["--deterministic"],
+        env: &[("CARGO_RDME_HEADING", "Documentation")],
+        ..TestOptions::default()
+    };
+
+    run_test_with_options("option_cmd_deterministic", &options);
+}
+
+#[test]
+fn integration_test_option_conf_file_translations() {
+    let options = TestOptions { translations: &["es", "pt"], ..TestOptions::default() };
+
+    run_test_with_options("option_conf_file_translations", &options);
+}
+
+#[test]
+fn integration_test_option_conf_file_translations_code_blocks_only() {
+    let options = TestOptions { translations: &["pt"], ..TestOptions::default() };
+
+    run_test_with_options("option_conf_file_translations_code_blocks_only", &options);
+}
+
 #[test]
 fn integration_test_crate_procmacro() {
     run_test("crate_procmacro");
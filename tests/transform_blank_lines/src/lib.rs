@@ -0,0 +1,18 @@
+//!
+//!
+//! # My crate
+//!
+//!
+//!
+//! This crate has blank lines.
+//!
+//! ```text
+//! a
+//!
+//!
+//! b
+//! ```
+//!
+//!
+
+fn foo() {}
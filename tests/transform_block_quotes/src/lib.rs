@@ -0,0 +1,30 @@
+//! Intro.
+//!
+//! > # Note
+//! >
+//! > See [`Foo`] and [the bar](Bar).
+//! >
+//! > ```
+//! > # use mycrate::Foo;
+//! > let x = Foo;
+//! > ```
+//! >
+//! >     # hidden
+//! >     let y = 2;
+//! >
+//! > > ## Nested
+//! > >
+//! > > ```rust
+//! > > # fn hidden() {}
+//! > > let z = 3;
+//! > > ```
+//! > >
+//! > > Nested [`Foo`].
+//! >
+//! > Setext
+//! > ------
+//!
+//! # Section
+
+pub struct Foo;
+pub struct Bar;
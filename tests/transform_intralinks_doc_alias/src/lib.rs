@@ -0,0 +1,25 @@
+//! Build a [`Coord`] with [`Point::new()`], and compare it with [`Point::len()`].  Items named
+//! like an alias take precedence, e.g. [`Vec2`].  See also [`shapes::Vertex`].
+
+#[doc(alias("Coord", "Vec2"))]
+pub struct Point {
+    pub x: f64,
+}
+
+impl Point {
+    pub fn new() -> Point {
+        Point { x: 0.0 }
+    }
+
+    #[doc(alias = "len")]
+    pub fn norm(&self) -> f64 {
+        self.x.abs()
+    }
+}
+
+pub struct Vec2;
+
+pub mod shapes {
+    #[doc(alias = "Vertex")]
+    pub struct Corner;
+}
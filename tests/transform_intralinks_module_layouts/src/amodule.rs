@@ -0,0 +1,3 @@
+mod baz;
+#[path = "sibling_file.rs"]
+mod sibling;
@@ -0,0 +1,12 @@
+//! [amodule baz S1](crate::amodule::baz::S1)
+//! [amodule sibling S2](crate::amodule::sibling::S2)
+//! [custom S3](crate::custom::S3)
+//! [custom nested S4](crate::custom::nested::S4)
+//! [inline deep S5](crate::inline::deep::S5)
+
+mod amodule;
+#[path = "other/custom_file.rs"]
+mod custom;
+mod inline {
+    mod deep;
+}
@@ -0,0 +1,3 @@
+struct S3 {}
+
+mod nested;
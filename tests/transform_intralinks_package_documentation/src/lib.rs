@@ -0,0 +1,6 @@
+//! This [beautiful crate](crate) is cool because it contains [modules](crate::amodule) and may use
+//! [copy](::std::fs::copy).
+
+mod amodule {}
+
+fn main() {}
@@ -0,0 +1,5 @@
+//! # Integration test
+//!
+//! Path dependencies are not in docs.rs, so this links to [`documented::Foo`] in the documentation
+//! of `documented`, while [`undocumented::Bar`] becomes a code span.  The [`undocumented`] crate
+//! links to its repository.
@@ -0,0 +1,21 @@
+//! # Rustdoc forms
+//!
+//! Check out [this struct](Foo), [this one][Foo], [`Foo`], and [`Bar`](crate::amodule::Bar).
+//!
+//! Also [`Foo::new()`] and [a field](Foo::number).
+//!
+//! Not an intralink: [the license](LICENSE).
+
+pub mod amodule {
+    pub struct Bar;
+}
+
+pub struct Foo<T> {
+    pub number: T,
+}
+
+impl<T> Foo<T> {
+    pub fn new(number: T) -> Foo<T> {
+        Foo { number }
+    }
+}
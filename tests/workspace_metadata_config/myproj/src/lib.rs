@@ -0,0 +1,5 @@
+//! # My crate
+//!
+//! This is myproj.
+
+fn foo() {}
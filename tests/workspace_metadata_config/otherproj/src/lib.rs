@@ -0,0 +1,5 @@
+//! # My crate
+//!
+//! This is otherproj.
+
+fn foo() {}
@@ -85,6 +85,19 @@ function check_msrv {
     cargo msrv verify
 }
 
+function check_features {
+    echo 'Checking the library without the default features:'
+    cargo test --lib --no-default-features --features fatal-warnings
+    cargo test --lib --no-default-features --features fatal-warnings,rust-source
+    echo 'Checking the mdBook preprocessor:'
+    cargo test --bin cargo-rdme --features fatal-warnings,mdbook
+}
+
+function check_wasm {
+    echo 'Checking the library builds for WebAssembly:'
+    cargo build --lib --no-default-features --features fatal-warnings --target wasm32-unknown-unknown
+}
+
 function check_clippy {
     assert_installed "cargo-clippy"
 
@@ -92,7 +105,7 @@ function check_clippy {
     cargo clippy --all-targets -- -D warnings
 }
 
-to_run=(basic doc_url_links unused_deps packaging fmt toml_fmt readme msrv clippy)
+to_run=(basic features doc_url_links unused_deps packaging fmt toml_fmt readme msrv wasm clippy)
 
 if [ $# -ge 1 ]; then
     to_run=("$@")